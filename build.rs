@@ -0,0 +1,22 @@
+//! Regenerates the C header for the `ffi` module when the `ffi` feature is enabled
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        use std::path::Path;
+
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo sets CARGO_MANIFEST_DIR");
+        let crate_dir = Path::new(&crate_dir);
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+
+        // Parse the module directly: `cbindgen::generate` runs `cargo metadata`, which would wait on this build
+        let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).expect("cbindgen.toml should be valid");
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(crate_dir.join("src/ffi.rs"))
+            .generate()
+            .expect("the ffi module should translate to C")
+            .write_to_file(crate_dir.join("include/jinglebells.h"));
+    }
+}
@@ -0,0 +1,75 @@
+/* Generated by cbindgen from src/ffi.rs with the `ffi` feature; do not edit. */
+
+#ifndef JINGLEBELLS_H
+#define JINGLEBELLS_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct JingleGenerator JingleGenerator;
+
+// The call succeeded
+#define JINGLE_OK 0
+
+// A pointer was null or a string was not valid UTF-8
+#define JINGLE_ERROR_INVALID_ARGUMENT -1
+
+// No built-in preset has the given name
+#define JINGLE_ERROR_UNKNOWN_PRESET -2
+
+// The file could not be encoded or written
+#define JINGLE_ERROR_EXPORT -3
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a generator whose random choices follow `seed`
+//
+// Free it with `jingle_generator_free`.
+JingleGenerator *jingle_generator_new(uint64_t seed);
+
+// Free a generator created with `jingle_generator_new`; null is ignored
+//
+// # Safety
+// `generator` must be null or a handle from `jingle_generator_new` that has not been freed.
+void jingle_generator_free(JingleGenerator *generator);
+
+// Rate (Hz) of the samples a generator renders, or 0 for a null handle
+//
+// # Safety
+// `generator` must be null or a live handle from `jingle_generator_new`.
+uint32_t jingle_sample_rate(const JingleGenerator *generator);
+
+// Render a built-in preset with its own waveform into a caller-provided buffer
+//
+// Writes at most `capacity` samples and returns the full length of the
+// jingle, so a return value above `capacity` means the buffer was too short.
+// Returns a negative `JINGLE_ERROR_*` code on failure.
+//
+// # Safety
+// `generator` must be a live handle, `name` a NUL-terminated string and
+// `buffer` valid for `capacity` writes (it may be null when `capacity` is 0).
+int64_t jingle_render_preset(JingleGenerator *generator,
+                             const char *name,
+                             float *buffer,
+                             size_t capacity);
+
+// Export `length` samples to a 16-bit WAV file at the generator's sample rate
+//
+// # Safety
+// `generator` must be a live handle, `samples` valid for `length` reads
+// and `path` a NUL-terminated string.
+int32_t jingle_export_wav(const JingleGenerator *generator,
+                          const float *samples,
+                          size_t length,
+                          const char *path);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* JINGLEBELLS_H */
@@ -1,12 +1,27 @@
 //! Audio synthesis components
 
+use alloc::{vec, vec::Vec};
+use core::f32::consts::PI;
+#[cfg(feature = "std")]
 use rodio::source::Source;
+#[cfg(feature = "std")]
 use std::time::Duration;
-use std::f32::consts::PI;
-use crate::SAMPLE_RATE;
+#[cfg(not(feature = "std"))]
+use crate::math::Float;
+use crate::{SAMPLE_RATE, Real, to_sample, math::db_to_linear, music::Melody, chiptune};
+#[cfg(feature = "std")]
+use crate::error::{Result, check_range, check_positive};
+
+/// Linear output level of the oscillators, leaving headroom so chords and layers don't clip
+pub const OSCILLATOR_HEADROOM: f32 = 0.3;
+
+/// Longest note, envelope stage or render (seconds) that validation accepts
+#[cfg(feature = "std")]
+pub(crate) const MAX_DURATION: f32 = 3600.0;
 
 /// Available waveform types for oscillator synthesis
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum WaveForm {
     Sine,
     Triangle,
@@ -15,7 +30,8 @@ pub enum WaveForm {
 }
 
 /// ADSR envelope parameters for natural-sounding audio
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ADSR {
     pub attack: f32,  // seconds
     pub decay: f32,   // seconds
@@ -34,7 +50,61 @@ impl Default for ADSR {
     }
 }
 
+impl ADSR {
+    /// Envelope level (0.0 - 1.0) at a time within a note of the given total duration
+    pub fn level(&self, time: f32, total_duration: f32) -> f32 {
+        let release_start = total_duration - self.release;
+
+        if time < self.attack {
+            // Attack phase
+            time / self.attack
+        } else if time < self.attack + self.decay {
+            // Decay phase
+            let decay_progress = (time - self.attack) / self.decay;
+            1.0 - decay_progress * (1.0 - self.sustain)
+        } else if time < release_start {
+            // Sustain phase
+            self.sustain
+        } else {
+            // Release phase
+            let release_progress = (time - release_start) / self.release;
+            self.sustain * (1.0 - release_progress)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ADSR {
+    /// Check that the stage times are finite and not negative and the sustain level is 0.0 - 1.0
+    pub fn validate(&self) -> Result<()> {
+        check_range("ADSR attack (s)", self.attack, 0.0..=MAX_DURATION)?;
+        check_range("ADSR decay (s)", self.decay, 0.0..=MAX_DURATION)?;
+        check_range("ADSR sustain level", self.sustain, 0.0..=1.0)?;
+        check_range("ADSR release (s)", self.release, 0.0..=MAX_DURATION)
+    }
+}
+
+/// Check a tone's frequency is audible below the Nyquist limit and its duration is positive
+#[cfg(feature = "std")]
+pub(crate) fn check_tone(frequency: f32, duration: f32) -> Result<()> {
+    check_positive("frequency (Hz)", frequency, SAMPLE_RATE as f32 / 2.0)?;
+    check_positive("duration (s)", duration, MAX_DURATION)
+}
+
+/// Value (-1.0 to 1.0) of a waveform at a phase in cycles (0.0 - 1.0)
+pub(crate) fn wave_at(waveform: WaveForm, phase: f32) -> f32 {
+    match waveform {
+        WaveForm::Sine => (phase * 2.0 * PI).sin(),
+        WaveForm::Triangle => if phase < 0.5 { 4.0 * phase - 1.0 } else { 3.0 - 4.0 * phase },
+        WaveForm::Sawtooth => 2.0 * phase - 1.0,
+        WaveForm::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+    }
+}
+
 /// Custom oscillator that implements Rodio's Source trait
+///
+/// The waveform is read from a phase accumulator advanced by `frequency / sample_rate`
+/// every sample, so `set_frequency` can change pitch mid-note without a click.
 pub struct Oscillator {
     frequency: f32,
     waveform: WaveForm,
@@ -42,6 +112,10 @@ pub struct Oscillator {
     sample_rate: u32,
     current_sample: usize,
     total_duration: f32,
+    gain: f32,
+    phase: f64,
+    phase_step: f64,
+    time_phase: bool,
 }
 
 impl Oscillator {
@@ -54,6 +128,10 @@ impl Oscillator {
             sample_rate: SAMPLE_RATE,
             current_sample: 0,
             total_duration: duration,
+            gain: OSCILLATOR_HEADROOM,
+            phase: 0.0,
+            phase_step: phase_step(frequency, SAMPLE_RATE),
+            time_phase: false,
         }
     }
 
@@ -63,32 +141,70 @@ impl Oscillator {
         self
     }
 
+    /// Set the output level in dB (defaults to the linear `OSCILLATOR_HEADROOM`)
+    pub fn with_gain_db(mut self, gain_db: f32) -> Self {
+        self.gain = db_to_linear(gain_db);
+        self
+    }
+
+    /// Set the linear output level
+    fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Set the rate (Hz) samples are generated at (defaults to `SAMPLE_RATE`)
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate.max(1);
+        self.phase_step = phase_step(self.frequency, self.sample_rate);
+        self
+    }
+
+    /// Compute the phase from the sample time instead of accumulating it, as the
+    /// V1 generation algorithm did
+    pub(crate) fn with_time_phase(mut self, time_phase: bool) -> Self {
+        self.time_phase = time_phase;
+        self
+    }
+
+    /// Change the frequency (Hz) from the next sample on, continuing from the current phase
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency;
+        self.phase_step = phase_step(frequency, self.sample_rate);
+    }
+
+    /// Get the frequency (Hz)
+    pub fn frequency(&self) -> f32 {
+        self.frequency
+    }
+
+    /// Write the next samples into a buffer, returning how many were written
+    ///
+    /// Fewer than `buffer.len()` are written once the sound ends.
+    pub fn fill(&mut self, buffer: &mut [f32]) -> usize {
+        fill_from(self, buffer)
+    }
+
+    /// Append the remaining samples to a buffer, reserving space for them once
+    pub fn render_into(mut self, out: &mut Vec<f32>) {
+        let remaining = self.total_duration * self.sample_rate as f32 - self.current_sample as f32;
+        out.reserve(remaining.max(0.0).ceil() as usize + 1);
+        out.extend(&mut self);
+    }
+
     /// Calculate the amplitude envelope value at a given time
     fn get_amplitude_envelope(&self, time: f32) -> f32 {
-        let attack_time = self.adsr.attack;
-        let decay_time = self.adsr.decay;
-        let release_start = self.total_duration - self.adsr.release;
-
-        if time < attack_time {
-            // Attack phase
-            time / attack_time
-        } else if time < attack_time + decay_time {
-            // Decay phase
-            let decay_progress = (time - attack_time) / decay_time;
-            1.0 - decay_progress * (1.0 - self.adsr.sustain)
-        } else if time < release_start {
-            // Sustain phase
-            self.adsr.sustain
-        } else {
-            // Release phase
-            let release_progress = (time - release_start) / self.adsr.release;
-            self.adsr.sustain * (1.0 - release_progress)
-        }
+        self.adsr.level(time, self.total_duration)
     }
 
-    /// Generate the raw waveform value at a given time
-    fn generate_wave(&self, time: f32) -> f32 {
+    /// Generate the raw waveform value at a given time, computing the phase from it (V1)
+    fn generate_wave(&self, time: Real) -> f32 {
+        // Single precision multiplies exactly as the 0.1 releases did
+        #[cfg(not(feature = "f64"))]
         let phase = time * self.frequency * 2.0 * PI;
+        // Wrap to whole cycles before dropping precision so long notes stay in tune
+        #[cfg(feature = "f64")]
+        let phase = to_sample((time * Real::from(self.frequency)).fract()) * 2.0 * PI;
         
         match self.waveform {
             WaveForm::Sine => phase.sin(),
@@ -111,25 +227,61 @@ impl Oscillator {
     }
 }
 
+#[cfg(feature = "std")]
+impl Oscillator {
+    /// Create an oscillator, failing with `InvalidParameter` for a frequency that isn't
+    /// between 0 Hz and the Nyquist limit or a duration that isn't positive
+    ///
+    /// `new` takes any values and renders silence or noise for these.
+    pub fn try_new(frequency: f32, waveform: WaveForm, duration: f32) -> Result<Self> {
+        check_tone(frequency, duration)?;
+        Ok(Self::new(frequency, waveform, duration))
+    }
+}
+
 impl Iterator for Oscillator {
     type Item = f32;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let time = self.current_sample as f32 / self.sample_rate as f32;
+        let time = self.current_sample as Real / self.sample_rate as Real;
         
-        if time >= self.total_duration {
+        if to_sample(time) >= self.total_duration {
             return None;
         }
 
-        let wave_value = self.generate_wave(time);
-        let envelope = self.get_amplitude_envelope(time);
-        let sample = wave_value * envelope * 0.3; // Reduce volume to prevent clipping
+        let wave_value = if self.time_phase {
+            self.generate_wave(time)
+        } else {
+            wave_at(self.waveform, self.phase as f32)
+        };
+        let envelope = self.get_amplitude_envelope(to_sample(time));
+        let sample = wave_value * envelope * self.gain;
 
+        self.phase = advance_phase(self.phase, self.phase_step);
         self.current_sample += 1;
         Some(sample)
     }
 }
 
+/// Cycles per sample of a frequency (Hz) at a sample rate (Hz)
+///
+/// Phases accumulate in f64 whatever `Real` is: single precision drifts out of tune within a minute.
+fn phase_step(frequency: f32, sample_rate: u32) -> f64 {
+    f64::from(frequency) / f64::from(sample_rate)
+}
+
+/// Advance a phase in cycles by a step, wrapped to 0.0 - 1.0
+fn advance_phase(phase: f64, step: f64) -> f64 {
+    let phase = phase + step;
+    if (0.0..1.0).contains(&phase) {
+        phase
+    } else {
+        let wrapped = phase % 1.0;
+        if wrapped < 0.0 { wrapped + 1.0 } else { wrapped }
+    }
+}
+
+#[cfg(feature = "std")]
 impl Source for Oscillator {
     fn current_span_len(&self) -> Option<usize> {
         None
@@ -150,6 +302,7 @@ impl Source for Oscillator {
 
 /// Layer configuration for multi-layered synthesis
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OscillatorLayer {
     pub frequency_offset: f32,  // Frequency multiplier relative to base frequency
     pub waveform: WaveForm,
@@ -169,6 +322,8 @@ impl Default for OscillatorLayer {
 }
 
 /// Multi-layered oscillator that combines multiple waveforms for richer sounds
+///
+/// Every layer advances its own phase accumulator, like `Oscillator`.
 pub struct LayeredOscillator {
     base_frequency: f32,
     layers: Vec<OscillatorLayer>,
@@ -176,6 +331,8 @@ pub struct LayeredOscillator {
     sample_rate: u32,
     current_sample: usize,
     total_duration: f32,
+    gain: f32,
+    phases: Vec<f64>,
 }
 
 impl LayeredOscillator {
@@ -195,29 +352,31 @@ impl LayeredOscillator {
             sample_rate: SAMPLE_RATE,
             current_sample: 0,
             total_duration: duration,
+            gain: OSCILLATOR_HEADROOM,
+            phases: vec![0.0],
         }
     }
 
     /// Add a new layer to the oscillator
     pub fn add_layer(mut self, layer: OscillatorLayer) -> Self {
         self.layers.push(layer);
+        self.phases.push(0.0);
         self
     }
 
     /// Add a harmonic layer (frequency multiplier)
-    pub fn add_harmonic(mut self, multiplier: f32, waveform: WaveForm, amplitude: f32) -> Self {
+    pub fn add_harmonic(self, multiplier: f32, waveform: WaveForm, amplitude: f32) -> Self {
         let layer = OscillatorLayer {
             frequency_offset: multiplier,
             waveform,
             amplitude,
             phase_offset: 0.0,
         };
-        self.layers.push(layer);
-        self
+        self.add_layer(layer)
     }
 
     /// Add a detune layer (slight frequency offset for chorus effect)
-    pub fn add_detune(mut self, cents: f32, waveform: WaveForm, amplitude: f32) -> Self {
+    pub fn add_detune(self, cents: f32, waveform: WaveForm, amplitude: f32) -> Self {
         let frequency_offset = 2.0_f32.powf(cents / 1200.0);
         let layer = OscillatorLayer {
             frequency_offset,
@@ -225,8 +384,7 @@ impl LayeredOscillator {
             amplitude,
             phase_offset: 0.0,
         };
-        self.layers.push(layer);
-        self
+        self.add_layer(layer)
     }
 
     /// Set custom ADSR envelope parameters
@@ -235,72 +393,50 @@ impl LayeredOscillator {
         self
     }
 
-    /// Calculate the amplitude envelope value at a given time
-    fn get_amplitude_envelope(&self, time: f32) -> f32 {
-        let attack_time = self.adsr.attack;
-        let decay_time = self.adsr.decay;
-        let release_start = self.total_duration - self.adsr.release;
+    /// Set the output level in dB (defaults to the linear `OSCILLATOR_HEADROOM`)
+    pub fn with_gain_db(mut self, gain_db: f32) -> Self {
+        self.gain = db_to_linear(gain_db);
+        self
+    }
 
-        if time < attack_time {
-            // Attack phase
-            time / attack_time
-        } else if time < attack_time + decay_time {
-            // Decay phase
-            let decay_progress = (time - attack_time) / decay_time;
-            1.0 - decay_progress * (1.0 - self.adsr.sustain)
-        } else if time < release_start {
-            // Sustain phase
-            self.adsr.sustain
-        } else {
-            // Release phase
-            let release_progress = (time - release_start) / self.adsr.release;
-            self.adsr.sustain * (1.0 - release_progress)
-        }
+    /// Set the rate (Hz) samples are generated at (defaults to `SAMPLE_RATE`)
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate.max(1);
+        self
     }
 
-    /// Generate the raw waveform value for a single layer at a given time
-    fn generate_layer_wave(&self, layer: &OscillatorLayer, time: f32) -> f32 {
-        let frequency = self.base_frequency * layer.frequency_offset;
-        let phase = time * frequency * 2.0 * PI + layer.phase_offset;
-        
-        let wave_value = match layer.waveform {
-            WaveForm::Sine => phase.sin(),
-            WaveForm::Triangle => {
-                let normalized_phase = (phase / (2.0 * PI)) % 1.0;
-                if normalized_phase < 0.5 {
-                    4.0 * normalized_phase - 1.0
-                } else {
-                    3.0 - 4.0 * normalized_phase
-                }
-            },
-            WaveForm::Sawtooth => {
-                let normalized_phase = (phase / (2.0 * PI)) % 1.0;
-                2.0 * normalized_phase - 1.0
-            },
-            WaveForm::Square => {
-                if phase.sin() >= 0.0 { 1.0 } else { -1.0 }
-            },
-        };
+    /// Change the base frequency (Hz) from the next sample on, continuing every layer from its current phase
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.base_frequency = frequency;
+    }
 
-        wave_value * layer.amplitude
+    /// Write the next samples into a buffer, returning how many were written
+    ///
+    /// Fewer than `buffer.len()` are written once the sound ends.
+    pub fn fill(&mut self, buffer: &mut [f32]) -> usize {
+        fill_from(self, buffer)
     }
 
-    /// Generate combined waveform from all layers
-    fn generate_combined_wave(&self, time: f32) -> f32 {
-        let mut combined = 0.0;
-        let mut total_amplitude = 0.0;
+    /// Append the remaining samples to a buffer, reserving space for them once
+    pub fn render_into(mut self, out: &mut Vec<f32>) {
+        let remaining = self.total_duration * self.sample_rate as f32 - self.current_sample as f32;
+        out.reserve(remaining.max(0.0).ceil() as usize + 1);
+        out.extend(&mut self);
+    }
 
-        for layer in &self.layers {
-            combined += self.generate_layer_wave(layer, time);
-            total_amplitude += layer.amplitude;
-        }
+    /// Calculate the amplitude envelope value at a given time
+    fn get_amplitude_envelope(&self, time: f32) -> f32 {
+        self.adsr.level(time, self.total_duration)
+    }
 
-        // Normalize by total amplitude to prevent clipping
-        if total_amplitude > 0.0 {
-            combined / total_amplitude
-        } else {
-            0.0
-        }
+}
+
+#[cfg(feature = "std")]
+impl LayeredOscillator {
+    /// Create a layered oscillator, validating the frequency and duration like `Oscillator::try_new`
+    pub fn try_new(frequency: f32, waveform: WaveForm, duration: f32) -> Result<Self> {
+        check_tone(frequency, duration)?;
+        Ok(Self::new(frequency, waveform, duration))
     }
 }
 
@@ -308,21 +444,31 @@ impl Iterator for LayeredOscillator {
     type Item = f32;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let time = self.current_sample as f32 / self.sample_rate as f32;
+        let time = self.current_sample as Real / self.sample_rate as Real;
         
-        if time >= self.total_duration {
+        if to_sample(time) >= self.total_duration {
             return None;
         }
 
-        let wave_value = self.generate_combined_wave(time);
-        let envelope = self.get_amplitude_envelope(time);
-        let sample = wave_value * envelope * 0.3; // Reduce volume to prevent clipping
+        // Mix the layers, normalized by their total amplitude to prevent clipping
+        let mut combined = 0.0;
+        let mut total_amplitude = 0.0;
+        for (layer, phase) in self.layers.iter().zip(self.phases.iter_mut()) {
+            let cycle = advance_phase(*phase, f64::from(layer.phase_offset / (2.0 * PI)));
+            combined += wave_at(layer.waveform, cycle as f32) * layer.amplitude;
+            total_amplitude += layer.amplitude;
+            *phase = advance_phase(*phase, phase_step(self.base_frequency * layer.frequency_offset, self.sample_rate));
+        }
+        let wave_value = if total_amplitude > 0.0 { combined / total_amplitude } else { 0.0 };
+        let envelope = self.get_amplitude_envelope(to_sample(time));
+        let sample = wave_value * envelope * self.gain;
 
         self.current_sample += 1;
         Some(sample)
     }
 }
 
+#[cfg(feature = "std")]
 impl Source for LayeredOscillator {
     fn current_span_len(&self) -> Option<usize> {
         None
@@ -341,6 +487,142 @@ impl Source for LayeredOscillator {
     }
 }
 
+/// Write samples from an iterator into a buffer until either runs out
+fn fill_from<I: Iterator<Item = f32>>(source: &mut I, buffer: &mut [f32]) -> usize {
+    let mut written = 0;
+    for (slot, sample) in buffer.iter_mut().zip(source) {
+        *slot = sample;
+        written += 1;
+    }
+    written
+}
+
+/// Melody rendered note by note as a rodio `Source`, without materializing the samples
+///
+/// Each note is an `Oscillator` created when the previous one finishes, so the
+/// melody can be wrapped in effects or played gaplessly as it is generated.
+pub struct MelodySource {
+    notes: vec::IntoIter<(f32, f32)>,
+    current: Option<Oscillator>,
+    waveform: WaveForm,
+    adsr: ADSR,
+    sample_rate: u32,
+    gain: f32,
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    total_duration: f32,
+    quantize: bool,
+    output_gain: f32,
+    time_phase: bool,
+}
+
+impl MelodySource {
+    /// Create a source playing a melody in the given octave
+    pub fn new(melody: &Melody, octave: i32, waveform: WaveForm) -> Self {
+        let notes = melody.notes.iter().map(|(note, duration)| (note.frequency(octave), *duration)).collect();
+        Self::from_frequencies(notes, waveform)
+    }
+
+    /// Create a source playing `(frequency, duration)` pairs in order
+    pub fn from_frequencies(notes: Vec<(f32, f32)>, waveform: WaveForm) -> Self {
+        Self {
+            total_duration: notes.iter().map(|&(_, duration)| duration.max(0.0)).sum(),
+            notes: notes.into_iter(),
+            current: None,
+            waveform,
+            adsr: ADSR::default(),
+            sample_rate: SAMPLE_RATE,
+            gain: OSCILLATOR_HEADROOM,
+            quantize: false,
+            output_gain: 1.0,
+            time_phase: false,
+        }
+    }
+
+    /// Set the envelope of every note
+    pub fn with_adsr(mut self, adsr: ADSR) -> Self {
+        self.adsr = adsr;
+        self
+    }
+
+    /// Set the output level of every note in dB (defaults to the linear `OSCILLATOR_HEADROOM`)
+    pub fn with_gain_db(mut self, gain_db: f32) -> Self {
+        self.gain = db_to_linear(gain_db);
+        self
+    }
+
+    /// Set the rate (Hz) samples are generated at (defaults to `SAMPLE_RATE`)
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate.max(1);
+        self
+    }
+
+    /// Write the next samples into a buffer, returning how many were written
+    ///
+    /// Fewer than `buffer.len()` are written once the melody ends; a real-time
+    /// callback can pass its output buffer directly.
+    pub fn fill(&mut self, buffer: &mut [f32]) -> usize {
+        fill_from(self, buffer)
+    }
+
+    /// Quantize to the chiptune DAC and then scale every sample, as `JingleGenerator::generate_tone` does
+    #[cfg(feature = "std")]
+    pub(crate) fn with_output(mut self, quantize: bool, gain: f32) -> Self {
+        self.quantize = quantize;
+        self.output_gain = gain;
+        self
+    }
+
+    /// Compute each note's phase from the sample time, as the V1 generation algorithm did
+    #[cfg(feature = "std")]
+    pub(crate) fn with_time_phase(mut self, time_phase: bool) -> Self {
+        self.time_phase = time_phase;
+        self
+    }
+}
+
+impl Iterator for MelodySource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(sample) = self.current.as_mut().and_then(|oscillator| oscillator.next()) {
+                let mut sample = [sample];
+                if self.quantize {
+                    chiptune::quantize(&mut sample);
+                }
+                return Some(sample[0] * self.output_gain);
+            }
+            let (frequency, duration) = self.notes.next()?;
+            self.current = Some(
+                Oscillator::new(frequency, self.waveform, duration)
+                    .with_adsr(self.adsr)
+                    .with_gain(self.gain)
+                    .with_sample_rate(self.sample_rate)
+                    .with_time_phase(self.time_phase),
+            );
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Source for MelodySource {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f32(self.total_duration))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -370,6 +652,89 @@ mod tests {
         assert!(envelope_attack > 0.0 && envelope_attack < 1.0);
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_oscillator_sample_rate() {
+        let samples: Vec<f32> = Oscillator::new(440.0, WaveForm::Sine, 0.5).with_sample_rate(48000).collect();
+        assert_eq!(samples.len(), 24000);
+        let layered = LayeredOscillator::new(440.0, WaveForm::Sine, 0.5).with_sample_rate(8000);
+        assert_eq!(layered.sample_rate(), 8000);
+        assert_eq!(layered.count(), 4000);
+    }
+
+    #[test]
+    fn test_fill_and_render_into() {
+        let expected: Vec<f32> = Oscillator::new(440.0, WaveForm::Sawtooth, 0.01).collect();
+        let mut oscillator = Oscillator::new(440.0, WaveForm::Sawtooth, 0.01);
+        let mut buffer = [0.0; 64];
+        let mut filled = Vec::new();
+        loop {
+            let written = oscillator.fill(&mut buffer);
+            filled.extend_from_slice(&buffer[..written]);
+            if written < buffer.len() {
+                break;
+            }
+        }
+        assert_eq!(filled, expected);
+
+        let mut out = vec![1.0];
+        LayeredOscillator::new(440.0, WaveForm::Sine, 0.01).render_into(&mut out);
+        assert_eq!(out.len(), expected.len() + 1);
+        assert!(out.capacity() <= expected.len() + 2);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_long_note_stays_in_tune() {
+        // Half a minute in, single-precision time is only accurate to a couple of microseconds
+        let start = 30 * SAMPLE_RATE as usize;
+        let late: Vec<f32> = Oscillator::new(1000.0, WaveForm::Sine, 31.0).skip(start).take(64).collect();
+        let level = OSCILLATOR_HEADROOM * ADSR::default().sustain;
+        let error = late.iter().enumerate().map(|(i, &sample)| {
+            let time = (start + i) as f64 / SAMPLE_RATE as f64;
+            (sample - (2.0 * std::f64::consts::PI * 1000.0 * time).sin() as f32 * level).abs()
+        }).fold(0.0f32, f32::max);
+        let tolerance = if cfg!(feature = "f64") { 1e-5 } else { 0.005 };
+        assert!(error < tolerance, "{}", error);
+    }
+
+    #[test]
+    fn test_phase_accumulator() {
+        let accumulated: Vec<f32> = Oscillator::new(523.25, WaveForm::Triangle, 0.2).collect();
+        let timed: Vec<f32> = Oscillator::new(523.25, WaveForm::Triangle, 0.2).with_time_phase(true).collect();
+        assert_eq!(accumulated.len(), timed.len());
+        let error = accumulated.iter().zip(&timed).map(|(a, b)| (a - b).abs()).fold(0.0f32, f32::max);
+        assert!(error < 1e-3, "{}", error);
+
+        // A frequency change mid-note moves on from the current phase instead of jumping
+        let flat = ADSR { attack: 0.0, decay: 0.0, sustain: 1.0, release: 0.0 };
+        let mut oscillator = Oscillator::new(440.0, WaveForm::Sine, 0.1).with_adsr(flat).with_gain_db(0.0);
+        let last = oscillator.by_ref().take(100).last().unwrap();
+        oscillator.set_frequency(660.0);
+        assert_eq!(oscillator.frequency(), 660.0);
+        let next = oscillator.next().unwrap();
+        assert!((next - last).abs() <= 2.0 * PI * 660.0 / SAMPLE_RATE as f32);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_melody_source_streams_notes() {
+        use crate::{music::Note, effects::Echo};
+
+        let melody = Melody { notes: vec![(Note::C, 0.1), (Note::E, 0.0), (Note::G, 0.15)] };
+        let streamed: Vec<f32> = MelodySource::new(&melody, 5, WaveForm::Triangle).collect();
+        let rendered: Vec<f32> = [(Note::C, 0.1), (Note::G, 0.15)].iter()
+            .flat_map(|&(note, duration)| Oscillator::new(note.frequency(5), WaveForm::Triangle, duration))
+            .collect();
+        assert_eq!(streamed, rendered);
+
+        let source = MelodySource::new(&melody, 5, WaveForm::Triangle);
+        assert_eq!(source.total_duration(), Some(Duration::from_secs_f32(0.25)));
+        let echoed: Vec<f32> = Echo::new(source, 50.0, 0.4, 0.5).collect();
+        assert_eq!(echoed.len(), rendered.len());
+        assert_ne!(echoed, rendered);
+    }
+
     #[test]
     fn test_layered_oscillator_creation() {
         let osc = LayeredOscillator::new(440.0, WaveForm::Sine, 1.0);
@@ -403,4 +768,22 @@ mod tests {
         assert!(first_sample.is_some());
         assert!(first_sample.unwrap().abs() <= 1.0);
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_try_new_validates() {
+        use crate::error::JingleError;
+
+        assert!(Oscillator::try_new(440.0, WaveForm::Sine, 0.5).is_ok());
+        for (frequency, duration) in [(0.0, 0.5), (-440.0, 0.5), (f32::NAN, 0.5), (30_000.0, 0.5), (440.0, -0.5), (440.0, 0.0), (440.0, f32::INFINITY)] {
+            assert!(matches!(Oscillator::try_new(frequency, WaveForm::Sine, duration), Err(JingleError::InvalidParameter(_))));
+            assert!(LayeredOscillator::try_new(frequency, WaveForm::Sine, duration).is_err());
+        }
+
+        assert!(ADSR::default().validate().is_ok());
+        assert!(ADSR { attack: f32::NAN, ..ADSR::default() }.validate().is_err());
+        assert!(ADSR { release: -0.1, ..ADSR::default() }.validate().is_err());
+        let error = ADSR { sustain: 1.5, ..ADSR::default() }.validate().unwrap_err();
+        assert!(error.to_string().contains("sustain"));
+    }
+}
@@ -0,0 +1,241 @@
+//! Parameter automation for effects
+
+use std::time::Duration;
+use rodio::source::Source;
+use crate::effects::{SampleProcessor, Effect, MixControl, impl_mix_effect};
+
+/// Breakpoint envelope describing a parameter value over time
+///
+/// Values are linearly interpolated between points and held before the first
+/// and after the last point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Automation {
+    points: Vec<(f32, f32)>,
+}
+
+impl Automation {
+    /// Create an automation holding a constant value
+    pub fn new(value: f32) -> Self {
+        Self {
+            points: vec![(0.0, value)],
+        }
+    }
+
+    /// Create a linear ramp from `start` to `end` over `duration` seconds
+    pub fn linear(start: f32, end: f32, duration: f32) -> Self {
+        Self::new(start).with_point(duration.max(0.0), end)
+    }
+
+    /// Add a breakpoint at a time in seconds
+    pub fn with_point(mut self, time: f32, value: f32) -> Self {
+        let index = self.points.partition_point(|&(point_time, _)| point_time <= time);
+        self.points.insert(index, (time, value));
+        self
+    }
+
+    /// Get the breakpoints as (time, value) pairs in time order
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    /// Get the parameter value at a time in seconds
+    pub fn value_at(&self, time: f32) -> f32 {
+        let index = self.points.partition_point(|&(point_time, _)| point_time <= time);
+        match (index.checked_sub(1).map(|i| self.points[i]), self.points.get(index)) {
+            (Some((t0, v0)), Some(&(t1, v1))) => v0 + (v1 - v0) * (time - t0) / (t1 - t0),
+            (Some((_, value)), None) | (None, Some(&(_, value))) => value,
+            (None, None) => 0.0,
+        }
+    }
+}
+
+/// Callback applying an automated value to a processor parameter
+type Setter<P> = Box<dyn Fn(&mut P, f32) + Send>;
+
+/// Per-sample parameter value source and the setter it drives
+struct Lane<P> {
+    value: Box<dyn Fn(f32) -> f32 + Send>,
+    apply: Setter<P>,
+}
+
+/// Processor whose parameters follow automation curves while it runs
+///
+/// ```rust
+/// use jinglebells::{Automated, Automation, DelayBuffer, LowPassFilter, SampleProcessor, SAMPLE_RATE};
+///
+/// // Low-pass opening from 300 Hz to 8 kHz over the first second
+/// let mut filter = Automated::new(LowPassFilter::new(300.0, SAMPLE_RATE as f32), SAMPLE_RATE as f32)
+///     .automate(Automation::linear(300.0, 8000.0, 1.0), LowPassFilter::set_cutoff);
+///
+/// // Echo feedback decaying to zero
+/// let mut echo = Automated::new(DelayBuffer::new(120.0, 0.6, 0.4, SAMPLE_RATE as f32), SAMPLE_RATE as f32)
+///     .automate(Automation::linear(0.6, 0.0, 2.0), DelayBuffer::set_feedback);
+///
+/// let samples = echo.process(filter.process(vec![0.0; 100]));
+/// assert_eq!(samples.len(), 100);
+/// ```
+pub struct Automated<P> {
+    processor: P,
+    lanes: Vec<Lane<P>>,
+    sample_rate: f32,
+    sample_index: usize,
+}
+
+impl<P: SampleProcessor> Automated<P> {
+    /// Wrap a processor with no automation yet
+    pub fn new(processor: P, sample_rate: f32) -> Self {
+        Self {
+            processor,
+            lanes: Vec::new(),
+            sample_rate,
+            sample_index: 0,
+        }
+    }
+
+    /// Drive a parameter from a breakpoint envelope
+    /// - setter: called with the envelope value before each sample, e.g. `LowPassFilter::set_cutoff`
+    pub fn automate<F>(self, automation: Automation, setter: F) -> Self
+    where
+        F: Fn(&mut P, f32) + Send + 'static,
+    {
+        self.automate_with(move |time| automation.value_at(time), setter)
+    }
+
+    /// Drive a parameter from a callback receiving the time in seconds
+    pub fn automate_with<V, F>(mut self, value: V, setter: F) -> Self
+    where
+        V: Fn(f32) -> f32 + Send + 'static,
+        F: Fn(&mut P, f32) + Send + 'static,
+    {
+        self.lanes.push(Lane {
+            value: Box::new(value),
+            apply: Box::new(setter),
+        });
+        self
+    }
+
+    /// Get the wrapped processor
+    pub fn processor(&self) -> &P {
+        &self.processor
+    }
+}
+
+impl<P: SampleProcessor> SampleProcessor for Automated<P> {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let time = self.sample_index as f32 / self.sample_rate;
+        self.sample_index += 1;
+        for lane in &self.lanes {
+            (lane.apply)(&mut self.processor, (lane.value)(time));
+        }
+        self.processor.process_sample(input)
+    }
+}
+
+/// Wrapper running any processor over a Source, e.g. an automated effect
+pub struct Processed<S, P>
+where
+    S: Source<Item = f32>,
+    P: SampleProcessor,
+{
+    source: S,
+    processor: P,
+    controls: MixControl,
+}
+
+impl<S, P> Processed<S, P>
+where
+    S: Source<Item = f32>,
+    P: SampleProcessor,
+{
+    /// Create a new processed source
+    pub fn new(source: S, processor: P) -> Self {
+        Self { source, processor, controls: MixControl::new(1.0) }
+    }
+}
+
+impl<S, P> Iterator for Processed<S, P>
+where
+    S: Source<Item = f32>,
+    P: SampleProcessor,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.processor.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S, P> Source for Processed<S, P>
+where
+    S: Source<Item = f32>,
+    P: SampleProcessor,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(Processed<S, P> where S: Source<Item = f32>, P: SampleProcessor);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+    use crate::{SAMPLE_RATE, effects::{DelayBuffer, LowPassFilter}};
+
+    #[test]
+    fn test_breakpoint_interpolation() {
+        let automation = Automation::new(0.0).with_point(2.0, 1.0).with_point(1.0, 4.0);
+        assert_eq!(automation.points(), &[(0.0, 0.0), (1.0, 4.0), (2.0, 1.0)]);
+        assert_eq!(automation.value_at(-1.0), 0.0);
+        assert_eq!(automation.value_at(0.5), 2.0);
+        assert_eq!(automation.value_at(1.5), 2.5);
+        assert_eq!(automation.value_at(10.0), 1.0);
+    }
+
+    #[test]
+    fn test_feedback_decays_to_zero() {
+        let sample_rate = SAMPLE_RATE as f32;
+        let mut echo = Automated::new(DelayBuffer::new(10.0, 0.9, 1.0, sample_rate), sample_rate)
+            .automate(Automation::linear(0.9, 0.0, 0.05), DelayBuffer::set_feedback);
+        let mut impulse = vec![0.0; SAMPLE_RATE as usize / 5];
+        impulse[0] = 1.0;
+        let output = echo.process(impulse);
+
+        // Repeats stop once the feedback has reached zero
+        let tail = &output[SAMPLE_RATE as usize / 10..];
+        assert!(tail.iter().all(|&s| s == 0.0));
+        assert!(output[..SAMPLE_RATE as usize / 20].iter().filter(|&&s| s != 0.0).count() > 1);
+    }
+
+    #[test]
+    fn test_cutoff_opens_over_time() {
+        let sample_rate = SAMPLE_RATE as f32;
+        let tone: Vec<f32> = (0..SAMPLE_RATE)
+            .map(|i| (2.0 * std::f32::consts::PI * 4000.0 * i as f32 / sample_rate).sin())
+            .collect();
+        let filter = Automated::new(LowPassFilter::new(200.0, sample_rate), sample_rate)
+            .automate_with(|time| 200.0 + time * 15000.0, LowPassFilter::set_cutoff);
+        let samples: Vec<f32> = Processed::new(SamplesBuffer::new(1, SAMPLE_RATE, tone), filter).collect();
+
+        let peak = |range: &[f32]| range.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        let step = samples.len() / 20;
+        assert!(peak(&samples[step / 2..step]) < 0.1);
+        assert!(peak(&samples[15 * step..]) > 0.9);
+    }
+}
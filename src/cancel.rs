@@ -0,0 +1,118 @@
+//! Cancelling long renders from another thread
+//!
+//! Give a generator a `CancelToken` and call `cancel` on a clone of it, e.g.
+//! from a GUI thread when the user changes parameters. Sequencer and
+//! polyphonic renders, batch exports, families and best-of searches check the
+//! token as they go and stop with `JingleError::Cancelled`.
+//!
+//! ```rust
+//! use jinglebells::{CancelToken, JingleError, JingleGenerator, JinglePreset};
+//!
+//! let token = CancelToken::new();
+//! let generator = JingleGenerator::with_seed(1).with_cancel_token(token.clone());
+//! token.cancel();
+//! assert!(matches!(generator.generate_family(JinglePreset::Success, 4), Err(JingleError::Cancelled)));
+//! ```
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::{export::JingleGenerator, error::{JingleError, Result}};
+
+/// Samples rendered between checks in per-sample loops
+pub(crate) const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+/// Shared flag asking a render to stop; clones share the flag
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every render watching this token to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether `cancel` has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Clear the flag so the token can be used for another render
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::Relaxed);
+    }
+
+    /// Fail with `JingleError::Cancelled` once cancelled
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() { Err(JingleError::Cancelled) } else { Ok(()) }
+    }
+}
+
+impl JingleGenerator {
+    /// Watch a token while rendering and exporting, stopping with `JingleError::Cancelled` once it is cancelled
+    ///
+    /// Generators derived with `derive_seed` watch the same token.
+    pub fn with_cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Stop watching the cancel token
+    pub fn clear_cancel_token(&mut self) {
+        self.cancel = None;
+    }
+
+    /// Get the token renders watch, if any
+    pub fn get_cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel.as_ref()
+    }
+
+    /// Fail with `JingleError::Cancelled` if the generator's token has been cancelled
+    pub(crate) fn check_cancelled(&self) -> Result<()> {
+        self.cancel.as_ref().map_or(Ok(()), CancelToken::check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{audio::WaveForm, presets::JinglePreset, voices::VoiceNote};
+
+    #[test]
+    fn test_token() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+        clone.cancel();
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(JingleError::Cancelled)));
+        token.reset();
+        assert!(!clone.is_cancelled());
+    }
+
+    #[test]
+    fn test_renders_stop() {
+        let token = CancelToken::new();
+        let generator = JingleGenerator::with_seed(1).with_cancel_token(token.clone());
+        let notes = [VoiceNote::new(0.0, 440.0, 0.2, WaveForm::Sine)];
+        assert!(generator.render_polyphonic(&notes, 4).is_ok());
+
+        token.cancel();
+        assert!(matches!(generator.render_polyphonic(&notes, 4), Err(JingleError::Cancelled)));
+        let dir = std::env::temp_dir().join("jinglebells_test_cancel");
+        assert!(matches!(generator.export_variations(JinglePreset::Alert, WaveForm::Sine, 2, &dir, |i, _| format!("{}.wav", i)), Err(JingleError::Cancelled)));
+        assert!(!dir.join("0.wav").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(matches!(generator.derive_seed(1).unwrap().check_cancelled(), Err(JingleError::Cancelled)));
+
+        let mut unwatched = generator.derive_seed(1).unwrap();
+        unwatched.clear_cancel_token();
+        assert!(unwatched.render_polyphonic(&notes, 4).is_ok());
+    }
+}
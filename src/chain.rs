@@ -0,0 +1,514 @@
+//! Runtime-configurable chains of audio effects
+
+use rodio::{buffer::SamplesBuffer, source::Source};
+use crate::{
+    SAMPLE_RATE,
+    audio::WaveForm,
+    effects::{Effect, Echo, Reverb, LowPass, FilterSweep, Phaser, Distortion, Shaper, Compressor, Compression, Tremolo, RingMod, Shimmer, AGC, Gain},
+    filter::{SvfMode, CutoffSweep},
+    error::{JingleError, Result, check_range, check_positive},
+};
+
+/// Boxed mono source produced by applying an effect chain
+pub type EffectSource = Box<dyn Source + Send>;
+
+/// A single configured effect in a chain
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "lowercase"))]
+pub enum EffectKind {
+    Echo { delay_ms: f32, feedback: f32, mix: f32 },
+    Reverb { room_size: f32, damping: f32, mix: f32 },
+    LowPass { cutoff: f32, q: f32 },
+    FilterSweep { mode: SvfMode, q: f32, sweep: CutoffSweep },
+    Phaser { stages: usize, rate: f32, feedback: f32, mix: f32 },
+    Distortion { shaper: Shaper, drive: f32, output: f32 },
+    Compressor(Compressor),
+    Tremolo { rate: f32, depth: f32, waveform: WaveForm },
+    RingMod { frequency: f32, mix: f32 },
+    Shimmer { room_size: f32, shimmer: f32, mix: f32 },
+    Agc { target_level: f32, attack_time: f32, release_time: f32 },
+    Limiter,
+    Gain { gain_db: f32 },
+}
+
+impl EffectKind {
+    /// Check the parameters are finite and in a usable range
+    ///
+    /// The effects clamp mixes and feedback on their own; this rejects NaN, negative
+    /// times and rates, and delays or frequencies that would allocate huge buffers
+    /// or alias, with an `InvalidParameter` error naming the parameter.
+    pub fn validate(&self) -> Result<()> {
+        let nyquist = SAMPLE_RATE as f32 / 2.0;
+        match self {
+            EffectKind::Echo { delay_ms, feedback, mix } => {
+                check_positive("echo delay (ms)", *delay_ms, 10_000.0)?;
+                check_range("echo feedback", *feedback, 0.0..=0.95)?;
+                check_range("echo mix", *mix, 0.0..=1.0)
+            }
+            EffectKind::Reverb { room_size, damping, mix } => {
+                check_range("reverb room size", *room_size, 0.0..=1.0)?;
+                check_range("reverb damping", *damping, 0.0..=1.0)?;
+                check_range("reverb mix", *mix, 0.0..=1.0)
+            }
+            EffectKind::LowPass { cutoff, q } => {
+                check_positive("low-pass cutoff (Hz)", *cutoff, nyquist)?;
+                check_positive("low-pass Q", *q, 100.0)
+            }
+            EffectKind::FilterSweep { q, sweep, .. } => {
+                check_positive("filter sweep Q", *q, 100.0)?;
+                sweep.validate()
+            }
+            EffectKind::Phaser { stages, rate, feedback, mix } => {
+                if !(1..=32).contains(stages) {
+                    return Err(JingleError::InvalidParameter(format!("phaser stages must be between 1 and 32, got {}", stages)));
+                }
+                check_range("phaser rate (Hz)", *rate, 0.0..=100.0)?;
+                check_range("phaser feedback", *feedback, 0.0..=0.9)?;
+                check_range("phaser mix", *mix, 0.0..=1.0)
+            }
+            EffectKind::Distortion { drive, output, .. } => {
+                check_range("distortion drive", *drive, 0.0..=100.0)?;
+                check_range("distortion output", *output, 0.0..=10.0)
+            }
+            EffectKind::Compressor(compressor) => compressor.validate(),
+            EffectKind::Tremolo { rate, depth, .. } => {
+                check_range("tremolo rate (Hz)", *rate, 0.0..=100.0)?;
+                check_range("tremolo depth", *depth, 0.0..=1.0)
+            }
+            EffectKind::RingMod { frequency, mix } => {
+                check_positive("ring modulator frequency (Hz)", *frequency, nyquist)?;
+                check_range("ring modulator mix", *mix, 0.0..=1.0)
+            }
+            EffectKind::Shimmer { room_size, shimmer, mix } => {
+                check_range("shimmer room size", *room_size, 0.0..=1.0)?;
+                check_range("shimmer amount", *shimmer, 0.0..=0.9)?;
+                check_range("shimmer mix", *mix, 0.0..=1.0)
+            }
+            EffectKind::Agc { target_level, attack_time, release_time } => {
+                check_range("AGC target level", *target_level, 0.0..=1.0)?;
+                check_positive("AGC attack time (s)", *attack_time, 10.0)?;
+                check_positive("AGC release time (s)", *release_time, 10.0)
+            }
+            EffectKind::Limiter => Ok(()),
+            EffectKind::Gain { gain_db } => check_range("gain (dB)", *gain_db, -120.0..=60.0),
+        }
+    }
+
+    /// Wrap a source with this effect, applying the slot's mix and bypass settings
+    fn wrap(&self, source: EffectSource, slot_mix: Option<f32>, bypass: bool) -> EffectSource {
+        match self.clone() {
+            EffectKind::Echo { delay_ms, feedback, mix } => controlled(Echo::new(source, delay_ms, feedback, mix), slot_mix, bypass),
+            EffectKind::Reverb { room_size, damping, mix } => controlled(Reverb::new(source, room_size, damping, mix), slot_mix, bypass),
+            EffectKind::LowPass { cutoff, q } => controlled(LowPass::with_resonance(source, cutoff, q), slot_mix, bypass),
+            EffectKind::FilterSweep { mode, q, sweep } => controlled(FilterSweep::new(source, mode, q, sweep), slot_mix, bypass),
+            EffectKind::Phaser { stages, rate, feedback, mix } => controlled(Phaser::new(source, stages, rate, feedback, mix), slot_mix, bypass),
+            EffectKind::Distortion { shaper, drive, output } => controlled(Distortion::new(source, shaper, drive, output), slot_mix, bypass),
+            EffectKind::Compressor(compressor) => controlled(Compression::new(source, compressor), slot_mix, bypass),
+            EffectKind::Tremolo { rate, depth, waveform } => controlled(Tremolo::new(source, rate, depth, waveform), slot_mix, bypass),
+            EffectKind::RingMod { frequency, mix } => controlled(RingMod::new(source, frequency, mix), slot_mix, bypass),
+            EffectKind::Shimmer { room_size, shimmer, mix } => controlled(Shimmer::new(source, room_size, shimmer, mix), slot_mix, bypass),
+            EffectKind::Agc { target_level, attack_time, release_time } => controlled(AGC::new(source, target_level, attack_time, release_time), slot_mix, bypass),
+            EffectKind::Limiter => controlled(AGC::limiter(source), slot_mix, bypass),
+            EffectKind::Gain { gain_db } => controlled(Gain::new(source, gain_db), slot_mix, bypass),
+        }
+    }
+}
+
+/// Box an effect after applying mix and bypass settings through the `Effect` trait
+fn controlled<E>(mut effect: E, mix: Option<f32>, bypass: bool) -> EffectSource
+where
+    E: Effect + Source + Send + 'static,
+{
+    if let Some(mix) = mix {
+        effect.set_mix(mix);
+    }
+    effect.set_bypass(bypass);
+    Box::new(effect)
+}
+
+/// An effect in a chain with its mix and bypass settings
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Slot {
+    effect: EffectKind,
+    mix: Option<f32>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+    bypass: bool,
+}
+
+/// Ordered list of effects applied one after another
+///
+/// ```rust
+/// use jinglebells::EffectChain;
+///
+/// let chain = EffectChain::new()
+///     .reverb(0.8, 0.4, 0.3)
+///     .lowpass(4000.0)
+///     .limiter();
+/// let processed = chain.process(vec![0.0; 100]);
+/// assert_eq!(processed.len(), 100);
+/// ```
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct EffectChain {
+    slots: Vec<Slot>,
+}
+
+impl EffectChain {
+    /// Create an empty effect chain
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an effect to the end of the chain
+    pub fn push(mut self, effect: EffectKind) -> Self {
+        self.slots.push(Slot { effect, mix: None, bypass: false });
+        self
+    }
+
+    /// Override the wet/dry mix of the most recently added effect (0.0 = dry only, 1.0 = wet only)
+    pub fn with_mix(mut self, mix: f32) -> Self {
+        if let Some(slot) = self.slots.last_mut() {
+            slot.mix = Some(mix.clamp(0.0, 1.0));
+        }
+        self
+    }
+
+    /// Add an echo (see `Echo::new`)
+    pub fn echo(self, delay_ms: f32, feedback: f32, mix: f32) -> Self {
+        self.push(EffectKind::Echo { delay_ms, feedback, mix })
+    }
+
+    /// Add a comb reverb (see `Reverb::new`)
+    pub fn reverb(self, room_size: f32, damping: f32, mix: f32) -> Self {
+        self.push(EffectKind::Reverb { room_size, damping, mix })
+    }
+
+    /// Add a flat low-pass filter
+    pub fn lowpass(self, cutoff: f32) -> Self {
+        self.push(EffectKind::LowPass { cutoff, q: crate::filter::BUTTERWORTH_Q })
+    }
+
+    /// Add a resonant low-pass filter
+    pub fn resonant_lowpass(self, cutoff: f32, q: f32) -> Self {
+        self.push(EffectKind::LowPass { cutoff, q })
+    }
+
+    /// Add a swept state-variable filter (see `FilterSweep::new`)
+    pub fn filter_sweep(self, mode: SvfMode, q: f32, sweep: CutoffSweep) -> Self {
+        self.push(EffectKind::FilterSweep { mode, q, sweep })
+    }
+
+    /// Add a phaser (see `Phaser::new`)
+    pub fn phaser(self, stages: usize, rate: f32, feedback: f32, mix: f32) -> Self {
+        self.push(EffectKind::Phaser { stages, rate, feedback, mix })
+    }
+
+    /// Add distortion (see `Distortion::new`)
+    pub fn distortion(self, shaper: Shaper, drive: f32, output: f32) -> Self {
+        self.push(EffectKind::Distortion { shaper, drive, output })
+    }
+
+    /// Add a configured compressor
+    pub fn compressor(self, compressor: Compressor) -> Self {
+        self.push(EffectKind::Compressor(compressor))
+    }
+
+    /// Add tremolo (see `Tremolo::new`)
+    pub fn tremolo(self, rate: f32, depth: f32, waveform: WaveForm) -> Self {
+        self.push(EffectKind::Tremolo { rate, depth, waveform })
+    }
+
+    /// Add ring modulation (see `RingMod::new`)
+    pub fn ring_mod(self, frequency: f32, mix: f32) -> Self {
+        self.push(EffectKind::RingMod { frequency, mix })
+    }
+
+    /// Add shimmer reverb (see `Shimmer::new`)
+    pub fn shimmer(self, room_size: f32, shimmer: f32, mix: f32) -> Self {
+        self.push(EffectKind::Shimmer { room_size, shimmer, mix })
+    }
+
+    /// Add automatic gain control (see `AGC::new`)
+    pub fn agc(self, target_level: f32, attack_time: f32, release_time: f32) -> Self {
+        self.push(EffectKind::Agc { target_level, attack_time, release_time })
+    }
+
+    /// Add a fast limiter to prevent clipping (see `AGC::limiter`)
+    pub fn limiter(self) -> Self {
+        self.push(EffectKind::Limiter)
+    }
+
+    /// Add a level change in decibels
+    pub fn gain(self, gain_db: f32) -> Self {
+        self.push(EffectKind::Gain { gain_db })
+    }
+
+    /// Check every effect's parameters (see `EffectKind::validate`)
+    pub fn validate(&self) -> Result<()> {
+        self.effects().try_for_each(EffectKind::validate)
+    }
+
+    /// Get the effects in processing order
+    pub fn effects(&self) -> impl Iterator<Item = &EffectKind> {
+        self.slots.iter().map(|slot| &slot.effect)
+    }
+
+    /// Number of effects in the chain
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Check whether the chain has no effects
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Bypass or re-enable the effect at an index, e.g. for A/B comparisons
+    pub fn set_bypass(&mut self, index: usize, bypass: bool) {
+        if let Some(slot) = self.slots.get_mut(index) {
+            slot.bypass = bypass;
+        }
+    }
+
+    /// Check whether the effect at an index is bypassed
+    pub fn is_bypassed(&self, index: usize) -> bool {
+        self.slots.get(index).is_some_and(|slot| slot.bypass)
+    }
+
+    /// Bypass or re-enable every effect in the chain
+    pub fn set_bypass_all(&mut self, bypass: bool) {
+        for slot in &mut self.slots {
+            slot.bypass = bypass;
+        }
+    }
+
+    /// Wrap a mono source with every effect in the chain
+    pub fn apply<S>(&self, source: S) -> EffectSource
+    where
+        S: Source + Send + 'static,
+    {
+        self.slots.iter().fold(Box::new(source), |source, slot| slot.effect.wrap(source, slot.mix, slot.bypass))
+    }
+
+    /// Run mono samples at the library sample rate through the chain
+    pub fn process(&self, samples: Vec<f32>) -> Vec<f32> {
+        self.process_at(samples, SAMPLE_RATE)
+    }
+
+    /// Run mono samples at the given sample rate through the chain
+    pub fn process_at(&self, samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+        if self.slots.is_empty() {
+            return samples;
+        }
+        self.apply(SamplesBuffer::new(1, sample_rate, samples)).collect()
+    }
+
+    /// Parse a compact chain like `"reverb:small,echo:120ms:0.3,lowpass:3000"`
+    ///
+    /// Effects are separated by commas and run in order. Each is a name followed by
+    /// `:`-separated arguments, which may be left off from the end:
+    ///
+    /// - `reverb:<small|medium|large|hall or room size>:<mix>`
+    /// - `echo:<delay, e.g. 120ms or 0.5s>:<feedback>:<mix>`
+    /// - `lowpass:<cutoff, e.g. 3000 or 3khz>:<q>`, cutoff required
+    /// - `phaser:<rate Hz>:<mix>`
+    /// - `tremolo:<rate Hz>:<depth>`
+    /// - `ringmod:<frequency>:<mix>`, frequency required
+    /// - `shimmer:<room size>:<mix>`
+    /// - `gain:<dB, e.g. -6db>`, required
+    /// - `limiter`
+    ///
+    /// Mixes, depths and feedback are fractions or percentages (`30%`). Unknown effects,
+    /// malformed values and values `validate` rejects fail with `InvalidParameter`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut chain = Self::new();
+        for item in text.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let mut parts = item.split(':').map(str::trim);
+            let name = parts.next().unwrap_or_default().to_ascii_lowercase();
+            let args: Vec<&str> = parts.collect();
+            let arg = |index: usize, default: Option<f32>, units: &[(&str, f64)]| -> Result<f32> {
+                match args.get(index) {
+                    Some(value) => parse_quantity(item, value, units),
+                    None => default.ok_or_else(|| JingleError::InvalidParameter(format!("effect `{}` needs more arguments", item))),
+                }
+            };
+            let (effect, max_args) = match name.as_str() {
+                "reverb" => {
+                    let (room_size, damping, mix) = match args.first().map(|size| size.to_ascii_lowercase()).as_deref() {
+                        None | Some("small") => (0.3, 0.5, 0.2),
+                        Some("medium") => (0.5, 0.5, 0.3),
+                        Some("large") => (0.8, 0.4, 0.35),
+                        Some("hall") => (0.95, 0.3, 0.45),
+                        Some(_) => (arg(0, None, &[])?, 0.5, 0.3),
+                    };
+                    (EffectKind::Reverb { room_size, damping, mix: arg(1, Some(mix), FRACTION)? }, 2)
+                },
+                "echo" => (EffectKind::Echo {
+                    delay_ms: arg(0, Some(250.0), &[("ms", 1.0), ("s", 1000.0)])?,
+                    feedback: arg(1, Some(0.3), FRACTION)?,
+                    mix: arg(2, Some(0.4), FRACTION)?,
+                }, 3),
+                "lowpass" => (EffectKind::LowPass {
+                    cutoff: arg(0, None, FREQUENCY)?,
+                    q: arg(1, Some(crate::filter::BUTTERWORTH_Q), &[])?,
+                }, 2),
+                "phaser" => (EffectKind::Phaser { stages: 4, rate: arg(0, Some(0.5), FREQUENCY)?, feedback: 0.5, mix: arg(1, Some(0.5), FRACTION)? }, 2),
+                "tremolo" => (EffectKind::Tremolo { rate: arg(0, Some(5.0), FREQUENCY)?, depth: arg(1, Some(0.5), FRACTION)?, waveform: WaveForm::Sine }, 2),
+                "ringmod" => (EffectKind::RingMod { frequency: arg(0, None, FREQUENCY)?, mix: arg(1, Some(0.5), FRACTION)? }, 2),
+                "shimmer" => (EffectKind::Shimmer { room_size: arg(0, Some(0.7), &[])?, shimmer: 0.5, mix: arg(1, Some(0.4), FRACTION)? }, 2),
+                "gain" => (EffectKind::Gain { gain_db: arg(0, None, &[("db", 1.0)])? }, 1),
+                "limiter" => (EffectKind::Limiter, 0),
+                _ => return Err(JingleError::InvalidParameter(format!("unknown effect `{}`", item))),
+            };
+            if args.len() > max_args {
+                return Err(JingleError::InvalidParameter(format!("effect `{}` takes at most {} arguments", item, max_args)));
+            }
+            effect.validate()?;
+            chain = chain.push(effect);
+        }
+        Ok(chain)
+    }
+}
+
+impl std::str::FromStr for EffectChain {
+    type Err = JingleError;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Self::parse(text)
+    }
+}
+
+/// Unit suffixes of mixes, depths and feedback amounts
+const FRACTION: &[(&str, f64)] = &[("%", 0.01)];
+/// Unit suffixes of frequencies
+const FREQUENCY: &[(&str, f64)] = &[("khz", 1000.0), ("hz", 1.0)];
+
+/// Parse a number with an optional unit suffix, scaled to the unit the effect takes
+fn parse_quantity(item: &str, value: &str, units: &[(&str, f64)]) -> Result<f32> {
+    let lower = value.to_ascii_lowercase();
+    let (number, scale) = units.iter()
+        .find_map(|(unit, scale)| lower.strip_suffix(unit).map(|number| (number, *scale)))
+        .unwrap_or((lower.as_str(), 1.0));
+    // Scaled in f64 so `40%` comes out as exactly 0.4
+    number.trim().parse::<f64>()
+        .map(|number| (number * scale) as f32)
+        .map_err(|_| JingleError::InvalidParameter(format!("`{}` is not a valid value in effect `{}`", value, item)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::Oscillator;
+
+    #[test]
+    fn test_empty_chain_is_identity() {
+        let samples = vec![0.1, -0.2, 0.3];
+        assert_eq!(EffectChain::new().process(samples.clone()), samples);
+    }
+
+    #[test]
+    fn test_chain_matches_nested_wrappers() {
+        let chain = EffectChain::new().echo(20.0, 0.4, 0.3).distortion(Shaper::Tanh, 2.0, 0.8);
+        let chained: Vec<f32> = chain.apply(Oscillator::new(440.0, WaveForm::Sine, 0.1)).collect();
+        let nested: Vec<f32> = Distortion::new(Echo::new(Oscillator::new(440.0, WaveForm::Sine, 0.1), 20.0, 0.4, 0.3), Shaper::Tanh, 2.0, 0.8).collect();
+        assert_eq!(chained, nested);
+    }
+
+    #[test]
+    fn test_chain_bypass_and_mix() {
+        let tone: Vec<f32> = Oscillator::new(440.0, WaveForm::Sawtooth, 0.05).collect();
+        let mut chain = EffectChain::new().lowpass(500.0).distortion(Shaper::HardClip, 8.0, 0.5).with_mix(0.5);
+        let processed = chain.process(tone.clone());
+        assert_ne!(processed, tone);
+
+        chain.set_bypass_all(true);
+        assert!(chain.is_bypassed(1));
+        assert_eq!(chain.process(tone.clone()), tone);
+
+        // Only the distortion active, at half mix
+        chain.set_bypass(1, false);
+        let half: Vec<f32> = Distortion::new(SamplesBuffer::new(1, SAMPLE_RATE, tone.clone()), Shaper::HardClip, 8.0, 0.5)
+            .zip(&tone)
+            .map(|(wet, dry)| dry * 0.5 + wet * 0.5)
+            .collect();
+        assert_eq!(chain.process(tone), half);
+    }
+
+    #[test]
+    fn test_chain_configured_at_runtime() {
+        let names = ["reverb", "lowpass", "limiter"];
+        let chain = names.iter().fold(EffectChain::new(), |chain, name| match *name {
+            "reverb" => chain.reverb(1.0, 0.5, 0.3),
+            "lowpass" => chain.lowpass(3000.0),
+            _ => chain.limiter(),
+        });
+        assert_eq!(chain.len(), 3);
+
+        let tone: Vec<f32> = Oscillator::new(440.0, WaveForm::Square, 0.1).collect();
+        let processed = chain.process(tone.clone());
+        assert_eq!(processed.len(), tone.len());
+        assert!(processed.iter().all(|s| s.is_finite()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_chain_serde_round_trip() {
+        use crate::{audio::ADSR, effects::Compressor};
+
+        let mut chain = EffectChain::new()
+            .filter_sweep(SvfMode::LowPass, 2.0, CutoffSweep::Envelope { base: 200.0, peak: 4000.0, adsr: ADSR::default(), duration: 0.1 })
+            .compressor(Compressor::new(-18.0, 4.0, SAMPLE_RATE as f32))
+            .tremolo(6.0, 0.5, WaveForm::Triangle)
+            .with_mix(0.7);
+        chain.set_bypass(1, true);
+        let json = serde_json::to_string(&chain).unwrap();
+        assert!(json.starts_with(r#"[{"effect":{"type":"filtersweep","mode":"lowpass""#));
+        let restored: EffectChain = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_bypassed(1) && !restored.is_bypassed(2));
+
+        let tone: Vec<f32> = Oscillator::new(440.0, WaveForm::Sawtooth, 0.1).collect();
+        assert_eq!(restored.process(tone.clone()), chain.process(tone));
+    }
+
+    #[test]
+    fn test_parse() {
+        let chain = EffectChain::parse("reverb:small, echo:120ms:0.3, lowpass:3khz, gain:-6dB, limiter").unwrap();
+        let effects: Vec<String> = chain.effects().map(|effect| format!("{:?}", effect)).collect();
+        assert_eq!(effects, [
+            format!("{:?}", EffectKind::Reverb { room_size: 0.3, damping: 0.5, mix: 0.2 }),
+            format!("{:?}", EffectKind::Echo { delay_ms: 120.0, feedback: 0.3, mix: 0.4 }),
+            format!("{:?}", EffectKind::LowPass { cutoff: 3000.0, q: crate::filter::BUTTERWORTH_Q }),
+            format!("{:?}", EffectKind::Gain { gain_db: -6.0 }),
+            format!("{:?}", EffectKind::Limiter),
+        ]);
+
+        let echo: EffectChain = "echo:0.5s:40%:1".parse().unwrap();
+        assert_eq!(format!("{:?}", echo.effects().next().unwrap()), format!("{:?}", EffectKind::Echo { delay_ms: 500.0, feedback: 0.4, mix: 1.0 }));
+        assert!(EffectChain::parse("").unwrap().is_empty());
+
+        for text in ["chorus", "lowpass", "gain:loud", "echo:120ms:2", "limiter:1", "reverb:huge", "reverb:small:0.2:0.5"] {
+            assert!(matches!(EffectChain::parse(text), Err(JingleError::InvalidParameter(_))), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(EffectChain::new().echo(120.0, 0.3, 0.3).reverb(0.8, 0.5, 0.2).lowpass(3000.0).limiter().validate().is_ok());
+        assert!(EffectChain::new().echo(1e9, 0.3, 0.3).validate().is_err());
+        assert!(EffectChain::new().echo(-10.0, 0.3, 0.3).validate().is_err());
+        assert!(EffectChain::new().reverb(f32::NAN, 0.5, 0.2).validate().is_err());
+        assert!(EffectChain::new().lowpass(50_000.0).validate().is_err());
+        assert!(EffectChain::new().phaser(0, 0.5, 0.3, 0.5).validate().is_err());
+        assert!(EffectChain::new().compressor(Compressor::new(6.0, 4.0, SAMPLE_RATE as f32)).validate().is_err());
+        assert!(EffectChain::new().filter_sweep(SvfMode::LowPass, 2.0, CutoffSweep::Fixed(-1.0)).validate().is_err());
+
+        let error = EffectChain::new().gain(0.0).echo(120.0, 0.3, 2.0).validate().unwrap_err();
+        assert!(error.to_string().contains("echo mix"), "{}", error);
+
+        // Every built-in preset's own chain is valid
+        for preset in crate::presets::JinglePreset::all() {
+            assert!(preset.style().effects.validate().is_ok(), "{} has invalid effects", preset.name());
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Channel layouts and helpers for multi-channel audio
+
+/// Speaker layouts supported by multi-channel export
+///
+/// Channels are ordered as they are written to WAV files: front left, front right,
+/// front center, LFE, back left, back right, side left, side right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelLayout {
+    Mono,
+    Stereo,
+    Quad,
+    Surround51,
+    Surround71,
+    /// Arbitrary number of channels without speaker assignments
+    Discrete(u16),
+}
+
+impl ChannelLayout {
+    /// Get the number of channels in this layout
+    pub fn channel_count(&self) -> u16 {
+        match self {
+            ChannelLayout::Mono => 1,
+            ChannelLayout::Stereo => 2,
+            ChannelLayout::Quad => 4,
+            ChannelLayout::Surround51 => 6,
+            ChannelLayout::Surround71 => 8,
+            ChannelLayout::Discrete(count) => *count,
+        }
+    }
+
+    /// Get the speaker name of each channel in order
+    pub fn channel_names(&self) -> Vec<&'static str> {
+        match self {
+            ChannelLayout::Mono => vec!["center"],
+            ChannelLayout::Stereo => vec!["left", "right"],
+            ChannelLayout::Quad => vec!["front_left", "front_right", "back_left", "back_right"],
+            ChannelLayout::Surround51 => vec![
+                "front_left", "front_right", "center", "lfe", "back_left", "back_right",
+            ],
+            ChannelLayout::Surround71 => vec![
+                "front_left", "front_right", "center", "lfe", "back_left", "back_right", "side_left", "side_right",
+            ],
+            ChannelLayout::Discrete(count) => vec!["discrete"; *count as usize],
+        }
+    }
+
+    /// Speaker bits of the `dwChannelMask` in WAVE_FORMAT_EXTENSIBLE headers
+    ///
+    /// `Discrete` channels have no speaker assignments and get an empty mask.
+    pub fn channel_mask(&self) -> u32 {
+        match self {
+            ChannelLayout::Mono => 0x4,         // Front center
+            ChannelLayout::Stereo => 0x3,       // Front left and right
+            ChannelLayout::Quad => 0x33,        // Front and back pairs
+            ChannelLayout::Surround51 => 0x3F,  // Front pair, center, LFE, back pair
+            ChannelLayout::Surround71 => 0x63F, // 5.1 plus the side pair
+            ChannelLayout::Discrete(_) => 0,
+        }
+    }
+
+    /// Pick the layout matching a channel count, falling back to `Discrete`
+    pub fn from_channel_count(count: u16) -> Self {
+        match count {
+            1 => ChannelLayout::Mono,
+            2 => ChannelLayout::Stereo,
+            4 => ChannelLayout::Quad,
+            6 => ChannelLayout::Surround51,
+            8 => ChannelLayout::Surround71,
+            _ => ChannelLayout::Discrete(count),
+        }
+    }
+}
+
+/// Place a mono signal into several channels with a gain per channel
+pub fn pan(samples: &[f32], gains: &[f32]) -> Vec<Vec<f32>> {
+    gains
+        .iter()
+        .map(|&gain| samples.iter().map(|&sample| sample * gain).collect())
+        .collect()
+}
+
+/// Average planar channels into a single mono signal
+pub fn mixdown(channels: &[&[f32]]) -> Vec<f32> {
+    let frames = channels.iter().map(|channel| channel.len()).max().unwrap_or(0);
+    let scale = 1.0 / channels.len().max(1) as f32;
+    (0..frames)
+        .map(|frame| {
+            channels.iter().map(|channel| channel.get(frame).copied().unwrap_or(0.0)).sum::<f32>() * scale
+        })
+        .collect()
+}
+
+/// Interleave planar channels into frames, padding shorter channels with silence
+pub fn interleave(channels: &[&[f32]]) -> Vec<f32> {
+    let frames = channels.iter().map(|channel| channel.len()).max().unwrap_or(0);
+    let mut interleaved = Vec::with_capacity(frames * channels.len());
+
+    for frame in 0..frames {
+        for channel in channels {
+            interleaved.push(channel.get(frame).copied().unwrap_or(0.0));
+        }
+    }
+
+    interleaved
+}
+
+/// Split interleaved frames into planar channels
+pub fn deinterleave(samples: &[f32], channel_count: usize) -> Vec<Vec<f32>> {
+    let channel_count = channel_count.max(1);
+    (0..channel_count)
+        .map(|channel| samples.iter().skip(channel).step_by(channel_count).copied().collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_counts() {
+        assert_eq!(ChannelLayout::Quad.channel_count(), 4);
+        assert_eq!(ChannelLayout::Surround51.channel_names().len(), 6);
+        assert_eq!(ChannelLayout::from_channel_count(6), ChannelLayout::Surround51);
+        assert_eq!(ChannelLayout::from_channel_count(3), ChannelLayout::Discrete(3));
+    }
+
+    #[test]
+    fn test_interleave_roundtrip() {
+        let left = [0.1, 0.2, 0.3];
+        let right = [0.4, 0.5];
+        let interleaved = interleave(&[&left, &right]);
+        assert_eq!(interleaved, vec![0.1, 0.4, 0.2, 0.5, 0.3, 0.0]);
+
+        let planar = deinterleave(&interleaved, 2);
+        assert_eq!(planar[0], vec![0.1, 0.2, 0.3]);
+        assert_eq!(planar[1], vec![0.4, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_mixdown() {
+        let left = [1.0, 0.5];
+        let right = [0.0, 0.5, 1.0];
+        assert_eq!(mixdown(&[&left, &right]), vec![0.5, 0.5, 0.5]);
+    }
+
+    #[test]
+    fn test_pan() {
+        let channels = pan(&[1.0, -1.0], &[1.0, 0.5, 0.0]);
+        assert_eq!(channels.len(), 3);
+        assert_eq!(channels[1], vec![0.5, -0.5]);
+        assert_eq!(channels[2], vec![0.0, 0.0]);
+    }
+}
@@ -0,0 +1,129 @@
+//! Chiptune rendering: pulse, triangle and noise voices with the limits of an 8-bit sound chip
+//!
+//! Enabled with `JingleGenerator::set_chiptune`, which applies these limits to
+//! every preset.
+
+#[cfg(not(feature = "std"))]
+use crate::math::Float;
+use crate::audio::{WaveForm, OSCILLATOR_HEADROOM};
+
+/// Tonal voices that can sound at once: two pulse channels and a triangle
+pub const CHIPTUNE_VOICES: usize = 3;
+/// Rate (Hz) at which pitch slides step, like a sound driver updated once per video frame
+pub const CHIPTUNE_FRAME_RATE: f32 = 60.0;
+/// Levels either side of silence, for the 15 levels a 4-bit DAC centred on zero can output
+const DAC_STEPS: f32 = 7.0;
+
+/// Waveform a chip channel plays in place of `waveform`
+///
+/// Smooth waveforms go to the triangle channel and bright ones to a pulse channel.
+pub fn chip_waveform(waveform: WaveForm) -> WaveForm {
+    match waveform {
+        WaveForm::Sine | WaveForm::Triangle => WaveForm::Triangle,
+        WaveForm::Square | WaveForm::Sawtooth => WaveForm::Square,
+    }
+}
+
+/// Round samples to the levels of a 4-bit DAC spanning the oscillator headroom range
+pub fn quantize(samples: &mut [f32]) {
+    let step = OSCILLATOR_HEADROOM / DAC_STEPS;
+    for sample in samples.iter_mut() {
+        *sample = (*sample / step).round().clamp(-DAC_STEPS, DAC_STEPS) * step;
+    }
+}
+
+/// Time at the start of the frame containing `time`, so slides evaluated at it move in steps
+pub fn stepped_time(time: f32) -> f32 {
+    (time * CHIPTUNE_FRAME_RATE).floor() / CHIPTUNE_FRAME_RATE
+}
+
+/// 15-bit linear feedback shift register behind the chip's noise channel
+#[derive(Clone, Debug)]
+pub struct NoiseChannel {
+    register: u16,
+    period: usize,
+    counter: usize,
+}
+
+impl NoiseChannel {
+    /// Create a noise channel
+    /// - seed: initial register contents (a zero register is replaced with 1, since it would never change)
+    /// - period: samples between register shifts; longer periods sound lower
+    pub fn new(seed: u16, period: usize) -> Self {
+        let register = seed & 0x7FFF;
+        Self {
+            register: if register == 0 { 1 } else { register },
+            period: period.max(1),
+            counter: 0,
+        }
+    }
+
+    /// Get the next sample (-1.0 or 1.0)
+    pub fn next_sample(&mut self) -> f32 {
+        let output = if self.register & 1 == 0 { 1.0 } else { -1.0 };
+        self.counter += 1;
+        if self.counter == self.period {
+            self.counter = 0;
+            let feedback = (self.register ^ (self.register >> 1)) & 1;
+            self.register = (self.register >> 1) | (feedback << 14);
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    #[cfg(feature = "std")]
+    use crate::{SAMPLE_RATE, JingleGenerator, JinglePreset};
+
+    #[test]
+    fn test_noise_channel_sequence() {
+        let mut noise = NoiseChannel::new(0, 1);
+        let first: Vec<f32> = (0..32767).map(|_| noise.next_sample()).collect();
+        let second: Vec<f32> = (0..32767).map(|_| noise.next_sample()).collect();
+        // A maximal-length register repeats every 2^15 - 1 shifts
+        assert_eq!(first, second);
+        assert!(first.iter().all(|&s| s == 1.0 || s == -1.0));
+
+        let mut slow = NoiseChannel::new(0, 4);
+        let held: Vec<f32> = (0..8).map(|_| slow.next_sample()).collect();
+        assert!(held[..4].iter().all(|&s| s == held[0]));
+    }
+
+    #[test]
+    fn test_quantize_and_steps() {
+        let mut samples: Vec<f32> = (0..1000).map(|i| (i as f32 / 100.0).sin() * 0.5).collect();
+        quantize(&mut samples);
+        let mut levels: Vec<i32> = samples.iter().map(|s| (s * 10000.0).round() as i32).collect();
+        levels.sort_unstable();
+        levels.dedup();
+        assert!(levels.len() <= 2 * DAC_STEPS as usize + 1);
+
+        assert_eq!(stepped_time(0.02), 1.0 / CHIPTUNE_FRAME_RATE);
+        assert_eq!(stepped_time(0.01), 0.0);
+        assert_eq!(chip_waveform(WaveForm::Sawtooth), WaveForm::Square);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_chiptune_mode_across_presets() {
+        let mut generator = JingleGenerator::with_seed(8);
+        generator.set_chiptune(true);
+        for preset in JinglePreset::all() {
+            let samples = preset.generate(&mut generator, WaveForm::Sine);
+            assert!(!samples.is_empty() && samples.iter().all(|s| s.is_finite()), "{} failed", preset.name());
+            let waveform = generator.last_choices().waveform;
+            assert!(matches!(waveform, None | Some(WaveForm::Triangle) | Some(WaveForm::Square)), "{} used {:?}", preset.name(), waveform);
+        }
+
+        // Tones come out of a 4-bit DAC
+        let tone = generator.generate_tone(440.0, 0.1, WaveForm::Sine);
+        let mut levels: Vec<i32> = tone.iter().map(|s| (s * 10000.0).round() as i32).collect();
+        levels.sort_unstable();
+        levels.dedup();
+        assert!(levels.len() <= 2 * DAC_STEPS as usize + 1);
+        assert_eq!(tone.len(), (0.1 * SAMPLE_RATE as f32) as usize);
+    }
+}
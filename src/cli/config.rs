@@ -0,0 +1,234 @@
+//! Shared defaults for the command line tool, read from a TOML file
+//!
+//! Teams keep one file under version control so everyone renders with the
+//! same output directory, format, sample rate and preset waveforms:
+//!
+//! ```toml
+//! output_dir = "sounds"
+//! format = "wav"
+//! sample_rate = 48000
+//! bit_depth = 24
+//!
+//! [waveforms]
+//! alert = "sawtooth"
+//!
+//! [presets.doorbell]
+//! preset = "notification"
+//! waveform = "triangle"
+//! duration = 1.5
+//! ```
+//!
+//! Named presets under `[presets.<name>]` are `JingleSpec`s written with the
+//! same keys as spec files.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use jinglebells::{BitDepth, JingleError, JinglePreset, JingleSpec, WaveForm, error::Result};
+
+/// File name of the config inside the config directory
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Defaults loaded from a config file; every setting is optional
+#[derive(Clone, Debug, Default)]
+pub struct ConfigFile {
+    /// Directory outputs are written to when no output path is given
+    pub output_dir: Option<PathBuf>,
+    /// File extension picking the output format, e.g. `wav`
+    pub format: Option<String>,
+    /// Rate (Hz) renders are produced at
+    pub sample_rate: Option<u32>,
+    /// Bitrate (kbps) of MP3 and Ogg Opus outputs
+    pub bitrate: Option<u32>,
+    /// Sample format of WAV outputs
+    pub bit_depth: Option<BitDepth>,
+    /// Waveform used for a built-in preset instead of its own default
+    pub waveforms: Vec<(JinglePreset, WaveForm)>,
+    /// Named presets in file order
+    pub presets: Vec<(String, JingleSpec)>,
+}
+
+/// The file as written, before its values are checked
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Settings {
+    output_dir: Option<PathBuf>,
+    format: Option<String>,
+    sample_rate: Option<u32>,
+    bitrate: Option<u32>,
+    bit_depth: Option<u16>,
+    waveforms: BTreeMap<String, WaveForm>,
+    presets: toml::Table,
+}
+
+impl ConfigFile {
+    /// `$XDG_CONFIG_HOME/jinglebells/config.toml`, falling back to `~/.config/jinglebells/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_dir.join("jinglebells").join(CONFIG_FILE))
+    }
+
+    /// Load the config at `default_path`, or an empty config if there is none
+    pub fn load_default() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.is_file() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load a config file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text)
+    }
+
+    /// Parse config text: top-level settings, then `[waveforms]` and `[presets.<name>]` tables
+    pub fn parse(text: &str) -> Result<Self> {
+        let settings: Settings = toml::from_str(text).map_err(|err| invalid(err.to_string()))?;
+        let format = settings.format
+            .map(|format| {
+                let format = format.trim_start_matches('.').to_ascii_lowercase();
+                if format.is_empty() || !format.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(invalid(format!("format `{}` is not a file extension", format)));
+                }
+                Ok(format)
+            })
+            .transpose()?;
+        if settings.sample_rate == Some(0) {
+            return Err(invalid("sample_rate must be a positive whole number".to_string()));
+        }
+        if let Some(kbps) = settings.bitrate.filter(|kbps| !(8..=512).contains(kbps)) {
+            return Err(invalid(format!("bitrate `{}` must be a whole number of kbps from 8 to 512", kbps)));
+        }
+        let bit_depth = settings.bit_depth
+            .map(|bits| BitDepth::from_bits(bits).ok_or_else(|| invalid(format!("bit_depth `{}` must be 16, 24 or 32", bits))))
+            .transpose()?;
+        let waveforms = settings.waveforms.into_iter()
+            .map(|(name, waveform)| {
+                let preset = JinglePreset::from_name(&name)
+                    .ok_or_else(|| invalid(format!("unknown preset `{}` in [waveforms]", name)))?;
+                Ok((preset, waveform))
+            })
+            .collect::<Result<_>>()?;
+        let presets = settings.presets.into_iter()
+            .map(|(name, table)| {
+                let spec: JingleSpec = table.try_into().map_err(|err| invalid(format!("preset `{}`: {}", name, err)))?;
+                spec.validate().map_err(|err| invalid(format!("preset `{}`: {}", name, err)))?;
+                Ok((name, spec))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            output_dir: settings.output_dir,
+            format,
+            sample_rate: settings.sample_rate,
+            bitrate: settings.bitrate,
+            bit_depth,
+            waveforms,
+            presets,
+        })
+    }
+
+    /// Waveform configured for a built-in preset
+    pub fn waveform(&self, preset: JinglePreset) -> Option<WaveForm> {
+        self.waveforms.iter().find(|(configured, _)| *configured == preset).map(|(_, waveform)| *waveform)
+    }
+
+    /// Named preset defined in the config
+    pub fn preset(&self, name: &str) -> Option<&JingleSpec> {
+        self.presets.iter().find(|(preset, _)| preset == name).map(|(_, spec)| spec)
+    }
+}
+
+/// Invalid config file error
+fn invalid(message: String) -> JingleError {
+    JingleError::InvalidParameter(format!("config file: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+# Shared team defaults
+output_dir = "sounds"
+format = ".WAV"
+sample_rate = 48000
+bitrate = 160
+bit_depth = 24
+
+[waveforms]
+alert = "sawtooth"
+success = "sine"
+
+[presets.doorbell]
+preset = "notification"
+waveform = "triangle"
+duration = 1.5
+
+[[presets.doorbell.effects]]
+effect = { type = "reverb", room_size = 0.8, damping = 0.4, mix = 0.2 }
+
+[presets.buzz]
+preset = "error"
+"#;
+
+    #[test]
+    fn test_parse() {
+        let config = ConfigFile::parse(CONFIG).unwrap();
+        assert_eq!(config.output_dir, Some(PathBuf::from("sounds")));
+        assert_eq!(config.format.as_deref(), Some("wav"));
+        assert_eq!(config.sample_rate, Some(48000));
+        assert_eq!(config.bitrate, Some(160));
+        assert_eq!(config.bit_depth, Some(BitDepth::Int24));
+        assert_eq!(config.waveform(JinglePreset::Alert), Some(WaveForm::Sawtooth));
+        assert_eq!(config.waveform(JinglePreset::Success), Some(WaveForm::Sine));
+        assert_eq!(config.waveform(JinglePreset::Error), None);
+
+        let names: Vec<&str> = config.presets.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["doorbell", "buzz"]);
+        let doorbell = config.preset("doorbell").unwrap();
+        assert_eq!(doorbell.preset, JinglePreset::Notification);
+        assert_eq!(doorbell.waveform, WaveForm::Triangle);
+        assert_eq!(doorbell.duration, Some(1.5));
+        assert!(!doorbell.effects.is_empty());
+        assert!(config.preset("chime").is_none());
+
+        let empty = ConfigFile::parse("").unwrap();
+        assert!(empty.output_dir.is_none() && empty.presets.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in [
+            "volume = 3",
+            "sample_rate = 0",
+            "bitrate = 4",
+            "bit_depth = 8",
+            "format = \"w/av\"",
+            "[colors]\nalert = \"red\"",
+            "[waveforms]\nbeep = \"sine\"",
+            "[waveforms]\nalert = \"noise\"",
+            "[presets.]\npreset = \"alert\"",
+            "[presets.doorbell\npreset = \"alert\"",
+            "[presets.doorbell]\nwaveform = \"sine\"",
+            "[presets.doorbell]\npreset = \"alert\"\nduration = -1.0",
+            "[presets.a]\npreset = \"alert\"\n[presets.a]\npreset = \"error\"",
+        ] {
+            assert!(matches!(ConfigFile::parse(text), Err(JingleError::InvalidParameter(_))), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join("jinglebells_test_config.toml");
+        std::fs::write(&path, CONFIG).unwrap();
+        let config = ConfigFile::load(&path).unwrap();
+        assert_eq!(config.presets.len(), 2);
+        std::fs::remove_file(&path).unwrap();
+        assert!(ConfigFile::load(&path).is_err());
+    }
+}
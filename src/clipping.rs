@@ -0,0 +1,127 @@
+//! Detecting samples beyond full scale and deciding what final renders do about them
+
+use crate::{export::JingleGenerator, error::{JingleError, Result}};
+
+/// What happens to a final render with samples beyond ±1.0, see `GeneratorConfig::with_output_policy`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum OutputPolicy {
+    /// Hard-limit clipped samples to ±1.0
+    #[default]
+    Clamp,
+    /// Scale the whole render down so its peak is at full scale, only when it clips
+    Normalize,
+    /// Fail with `JingleError::Clipping` describing the clipped samples
+    ErrorOnClip,
+}
+
+/// Where a render goes beyond full scale
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClippingReport {
+    /// Number of samples beyond ±1.0 (or NaN)
+    pub count: usize,
+    /// Indices of those samples, in order
+    pub positions: Vec<usize>,
+    /// Largest absolute sample value
+    pub peak: f32,
+}
+
+impl ClippingReport {
+    /// Find the clipped samples in a buffer
+    pub fn measure(samples: &[f32]) -> Self {
+        let positions: Vec<usize> = samples.iter()
+            .enumerate()
+            .filter(|(_, sample)| sample.is_nan() || sample.abs() > 1.0)
+            .map(|(i, _)| i)
+            .collect();
+        Self {
+            count: positions.len(),
+            positions,
+            peak: samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs())),
+        }
+    }
+
+    /// Check whether any sample clipped
+    pub fn is_clipping(&self) -> bool {
+        self.count > 0
+    }
+}
+
+impl JingleGenerator {
+    /// Apply the output policy to a final render in place, returning what clipped beforehand
+    ///
+    /// Exports run this on the samples handed to the encoder, after the loudness
+    /// target and resampling, so positions there count output-rate samples.
+    pub fn apply_output_policy(&self, samples: &mut [f32]) -> Result<ClippingReport> {
+        let report = ClippingReport::measure(samples);
+        if !report.is_clipping() {
+            return Ok(report);
+        }
+        match self.get_output_policy() {
+            OutputPolicy::Clamp => {
+                samples.iter_mut().for_each(|sample| *sample = if sample.is_nan() { 0.0 } else { sample.clamp(-1.0, 1.0) });
+            }
+            OutputPolicy::Normalize => {
+                if !report.peak.is_finite() {
+                    return Err(JingleError::Clipping(report));
+                }
+                let gain = 1.0 / report.peak;
+                samples.iter_mut().for_each(|sample| *sample = if sample.is_nan() { 0.0 } else { *sample * gain });
+            }
+            OutputPolicy::ErrorOnClip => return Err(JingleError::Clipping(report)),
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::GeneratorConfig;
+
+    fn generator(policy: OutputPolicy) -> JingleGenerator {
+        JingleGenerator::with_seed(1).with_config(GeneratorConfig::default().with_output_policy(policy)).unwrap()
+    }
+
+    #[test]
+    fn test_report() {
+        let report = ClippingReport::measure(&[0.5, 1.0, -1.5, 0.2, 2.0, f32::NAN]);
+        assert_eq!(report.count, 3);
+        assert_eq!(report.positions, vec![2, 4, 5]);
+        assert_eq!(report.peak, 2.0);
+        assert!(!ClippingReport::measure(&[0.5, -1.0]).is_clipping());
+    }
+
+    #[test]
+    fn test_policies() {
+        let loud = vec![0.5, -2.0, 1.0];
+
+        let mut samples = loud.clone();
+        assert_eq!(generator(OutputPolicy::Clamp).apply_output_policy(&mut samples).unwrap().count, 1);
+        assert_eq!(samples, vec![0.5, -1.0, 1.0]);
+
+        let mut samples = loud.clone();
+        generator(OutputPolicy::Normalize).apply_output_policy(&mut samples).unwrap();
+        assert_eq!(samples, vec![0.25, -1.0, 0.5]);
+
+        let mut samples = loud.clone();
+        match generator(OutputPolicy::ErrorOnClip).apply_output_policy(&mut samples) {
+            Err(JingleError::Clipping(report)) => assert_eq!(report.positions, vec![1]),
+            other => panic!("expected a clipping error, got {:?}", other),
+        }
+        assert_eq!(samples, loud);
+
+        // Quiet renders pass untouched whatever the policy
+        let mut quiet = vec![0.5, -0.25];
+        generator(OutputPolicy::Normalize).apply_output_policy(&mut quiet).unwrap();
+        assert_eq!(quiet, vec![0.5, -0.25]);
+    }
+
+    #[test]
+    fn test_export_errors_on_clip() {
+        let generator = generator(OutputPolicy::ErrorOnClip);
+        assert!(matches!(generator.encode_wav(&[0.5, 1.5]), Err(JingleError::Clipping(_))));
+        assert!(generator.encode_wav(&[0.5, -0.5]).is_ok());
+        assert!(JingleGenerator::with_seed(1).encode_wav(&[0.5, 1.5]).is_ok());
+    }
+}
@@ -0,0 +1,353 @@
+//! Hard limits on rendered jingles, such as platform notification guidelines
+
+use crate::{
+    filter::Biquad,
+    loudness::integrated_loudness,
+    effects::{db_to_linear, linear_to_db, fade_out},
+    spec::JingleSpec,
+    error::{JingleError, Result},
+};
+
+/// Fade applied when a render has to be cut to the maximum duration
+const TRIM_FADE: f32 = 0.01;
+
+/// Limits on duration, frequency content, peak level and loudness
+///
+/// Set with `JingleGenerator::set_constraints`: presets then pick durations and
+/// base frequencies inside the limits and the output is filtered, trimmed and
+/// attenuated to meet them. `JingleGenerator::try_render` also reports specs
+/// that can't meet them.
+///
+/// ```rust
+/// use jinglebells::{Constraints, JingleGenerator, JinglePreset, JingleSpec};
+///
+/// let mut generator = JingleGenerator::new();
+/// generator.set_constraints(Some(Constraints::new().with_max_duration(1.5).with_frequency_range(200.0, 8000.0)));
+/// let samples = generator.try_render(&JingleSpec::new(JinglePreset::Shutdown)).unwrap();
+/// assert!(samples.len() as f32 / 44100.0 <= 1.5);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Constraints {
+    /// Longest allowed render in seconds
+    pub max_duration: Option<f32>,
+    /// Lowest frequency in Hz allowed in the output
+    pub min_frequency: Option<f32>,
+    /// Highest frequency in Hz allowed in the output
+    pub max_frequency: Option<f32>,
+    /// Highest allowed sample peak in dBFS
+    pub max_peak_db: Option<f32>,
+    /// Highest allowed integrated loudness in LUFS
+    pub max_loudness: Option<f32>,
+    /// Band `(low, high)` in Hz to keep free, e.g. the speech band in voice apps
+    pub avoid_band: Option<(f32, f32)>,
+}
+
+impl Constraints {
+    /// Create constraints with no limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limit the render length in seconds
+    pub fn with_max_duration(mut self, seconds: f32) -> Self {
+        self.max_duration = Some(seconds);
+        self
+    }
+
+    /// Keep the output between two frequencies in Hz
+    pub fn with_frequency_range(mut self, min: f32, max: f32) -> Self {
+        self.min_frequency = Some(min);
+        self.max_frequency = Some(max);
+        self
+    }
+
+    /// Limit the sample peak in dBFS
+    pub fn with_max_peak_db(mut self, peak_db: f32) -> Self {
+        self.max_peak_db = Some(peak_db);
+        self
+    }
+
+    /// Keep a band free: melodies are placed above or below it and it is filtered out
+    pub fn with_avoided_band(mut self, low: f32, high: f32) -> Self {
+        self.avoid_band = Some((low, high));
+        self
+    }
+
+    /// Limit the integrated loudness in LUFS
+    pub fn with_max_loudness(mut self, lufs: f32) -> Self {
+        self.max_loudness = Some(lufs);
+        self
+    }
+
+    /// Check that the limits can be met at all
+    pub fn validate(&self) -> Result<()> {
+        if self.max_duration.is_some_and(|duration| duration <= 0.0) {
+            return Err(invalid("maximum duration must be positive".to_string()));
+        }
+        if self.min_frequency.is_some_and(|frequency| frequency < 0.0) {
+            return Err(invalid("minimum frequency can't be negative".to_string()));
+        }
+        if let (Some(min), Some(max)) = (self.min_frequency, self.max_frequency) && min >= max {
+            return Err(invalid(format!("frequency range {} Hz to {} Hz is empty", min, max)));
+        }
+        if let Some((low, high)) = self.avoid_band && (low <= 0.0 || low >= high) {
+            return Err(invalid(format!("avoided band {} Hz to {} Hz is empty", low, high)));
+        }
+        if self.max_peak_db.is_some_and(|peak| peak > 0.0) {
+            return Err(invalid("maximum peak can't be above 0 dBFS".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Check that a spec's explicit duration and frequency are inside the limits
+    pub fn validate_spec(&self, spec: &JingleSpec) -> Result<()> {
+        self.validate()?;
+        if let (Some(duration), Some(max)) = (spec.duration, self.max_duration) && duration > max {
+            return Err(invalid(format!("duration {} s is longer than the {} s limit", duration, max)));
+        }
+        if let Some(frequency) = spec.frequency && !self.allows_frequency(frequency) {
+            return Err(invalid(format!("base frequency {} Hz is outside the allowed range", frequency)));
+        }
+        Ok(())
+    }
+
+    /// Check rendered samples against the duration, peak and loudness limits
+    pub fn check(&self, samples: &[f32], sample_rate: u32) -> Result<()> {
+        let duration = samples.len() as f32 / sample_rate as f32;
+        if let Some(max) = self.max_duration && duration > max {
+            return Err(invalid(format!("render is {} s, longer than the {} s limit", duration, max)));
+        }
+        if let Some(max) = self.max_peak_db {
+            let peak = linear_to_db(peak(samples));
+            if peak > max + 0.01 {
+                return Err(invalid(format!("peak {:.1} dBFS is above the {} dBFS limit", peak, max)));
+            }
+        }
+        if let Some(max) = self.max_loudness {
+            let loudness = integrated_loudness(samples, sample_rate);
+            if loudness > max + 0.1 {
+                return Err(invalid(format!("loudness {:.1} LUFS is above the {} LUFS limit", loudness, max)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a frequency is inside the allowed range
+    fn allows_frequency(&self, frequency: f32) -> bool {
+        self.min_frequency.is_none_or(|min| frequency >= min)
+            && self.max_frequency.is_none_or(|max| frequency <= max)
+            && self.avoid_band.is_none_or(|(low, high)| frequency <= low || frequency >= high)
+    }
+
+    /// Move a spec's duration and base frequency inside the limits
+    ///
+    /// The base frequency is moved by octaves so the preset keeps its key, and
+    /// kept an octave below the top of the range so melodies have room to rise.
+    /// With an avoided band, the octave nearest the original whose melody range
+    /// clears the band is chosen.
+    pub(crate) fn fit_spec(&self, spec: &JingleSpec) -> JingleSpec {
+        let mut fitted = spec.clone();
+        if let Some(max) = self.max_duration {
+            fitted.duration = Some(spec.duration.unwrap_or(spec.preset.default_duration()).min(max));
+        }
+        let mut frequency = spec.frequency.unwrap_or(spec.preset.default_frequency());
+        if let Some(min) = self.min_frequency.filter(|&min| min > 0.0) {
+            while frequency < min {
+                frequency *= 2.0;
+            }
+        }
+        if let Some(max) = self.max_frequency {
+            while frequency * 2.0 > max && self.min_frequency.is_none_or(|min| frequency / 2.0 >= min) {
+                frequency /= 2.0;
+            }
+        }
+        if let Some(band) = self.avoid_band {
+            frequency = self.clear_band(frequency, band);
+        }
+        if spec.frequency.is_some() || self.min_frequency.is_some() || self.max_frequency.is_some() || self.avoid_band.is_some() {
+            fitted.frequency = Some(frequency);
+        }
+        fitted
+    }
+
+    /// Nearest octave of a base frequency whose melody range (one octave up) stays out of a band
+    fn clear_band(&self, frequency: f32, (low, high): (f32, f32)) -> f32 {
+        let overlaps = |base: f32| base < high && base * 2.0 > low;
+        if !overlaps(frequency) {
+            return frequency;
+        }
+        let mut below = frequency;
+        while overlaps(below) {
+            below /= 2.0;
+        }
+        let mut above = frequency;
+        while overlaps(above) {
+            above *= 2.0;
+        }
+        let below_allowed = self.min_frequency.is_none_or(|min| below >= min);
+        let above_allowed = self.max_frequency.is_none_or(|max| above * 2.0 <= max);
+        match (below_allowed, above_allowed) {
+            (true, true) if frequency / below <= above / frequency => below,
+            (_, true) => above,
+            (true, false) => below,
+            // Neither side fits the range; the band filter still removes what it can
+            (false, false) => frequency,
+        }
+    }
+
+    /// Filter, trim and attenuate samples to meet the limits
+    pub(crate) fn enforce(&self, mut samples: Vec<f32>, sample_rate: u32) -> Vec<f32> {
+        // Two biquads per edge give a 24 dB/octave slope
+        if let Some(min) = self.min_frequency.filter(|&min| min > 0.0) {
+            for _ in 0..2 {
+                Biquad::high_pass(min, std::f32::consts::FRAC_1_SQRT_2, sample_rate as f32).process(&mut samples);
+            }
+        }
+        if let Some(max) = self.max_frequency.filter(|&max| max < sample_rate as f32 / 2.0) {
+            for _ in 0..2 {
+                Biquad::low_pass(max, std::f32::consts::FRAC_1_SQRT_2, sample_rate as f32).process(&mut samples);
+            }
+        }
+
+        if let Some((low, high)) = self.avoid_band {
+            samples = band_stop(&samples, low, high, sample_rate);
+        }
+
+        if let Some(max) = self.max_duration {
+            let max_samples = (max * sample_rate as f32) as usize;
+            if samples.len() > max_samples {
+                samples.truncate(max_samples);
+                fade_out(&mut samples, TRIM_FADE, sample_rate as f32);
+            }
+        }
+
+        if let Some(max) = self.max_loudness {
+            let loudness = integrated_loudness(&samples, sample_rate);
+            if loudness.is_finite() && loudness > max {
+                scale(&mut samples, db_to_linear(max - loudness));
+            }
+        }
+        if let Some(max) = self.max_peak_db {
+            let limit = db_to_linear(max);
+            let peak = peak(&samples);
+            if peak > limit {
+                scale(&mut samples, limit / peak);
+            }
+        }
+        samples
+    }
+}
+
+/// Invalid constraint error
+fn invalid(message: String) -> JingleError {
+    JingleError::InvalidParameter(format!("constraints: {}", message))
+}
+
+/// Remove a band by summing the signal low-passed below it and high-passed above it
+fn band_stop(samples: &[f32], low: f32, high: f32, sample_rate: u32) -> Vec<f32> {
+    let q = std::f32::consts::FRAC_1_SQRT_2;
+    let mut below = samples.to_vec();
+    let mut above = samples.to_vec();
+    for _ in 0..2 {
+        Biquad::low_pass(low, q, sample_rate as f32).process(&mut below);
+        if high < sample_rate as f32 / 2.0 {
+            Biquad::high_pass(high, q, sample_rate as f32).process(&mut above);
+        }
+    }
+    if high >= sample_rate as f32 / 2.0 {
+        return below;
+    }
+    below.iter().zip(&above).map(|(low, high)| low + high).collect()
+}
+
+fn peak(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0f32, |acc, &sample| acc.max(sample.abs()))
+}
+
+fn scale(samples: &mut [f32], gain: f32) {
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JingleGenerator, JinglePreset, WaveForm, SAMPLE_RATE};
+
+    #[test]
+    fn test_constraints_validate() {
+        assert!(Constraints::new().validate().is_ok());
+        assert!(Constraints::new().with_max_duration(0.0).validate().is_err());
+        assert!(Constraints::new().with_frequency_range(800.0, 200.0).validate().is_err());
+        assert!(Constraints::new().with_max_peak_db(3.0).validate().is_err());
+
+        let constraints = Constraints::new().with_max_duration(1.0).with_frequency_range(200.0, 4000.0);
+        let spec = JingleSpec::new(JinglePreset::Success);
+        assert!(constraints.validate_spec(&spec).is_ok());
+        assert!(constraints.validate_spec(&spec.clone().with_duration(2.0)).is_err());
+        assert!(constraints.validate_spec(&spec.with_frequency(100.0)).is_err());
+    }
+
+    #[test]
+    fn test_fit_spec_moves_by_octaves() {
+        let constraints = Constraints::new().with_frequency_range(600.0, 8000.0).with_max_duration(1.0);
+        let fitted = constraints.fit_spec(&JingleSpec::new(JinglePreset::Shutdown));
+        // G4 moves up to G5
+        let frequency = fitted.frequency.unwrap();
+        assert!((frequency / JinglePreset::Shutdown.default_frequency() - 2.0).abs() < 1e-4);
+        assert_eq!(fitted.duration, Some(1.0));
+    }
+
+    #[test]
+    fn test_avoided_band() {
+        assert!(Constraints::new().with_avoided_band(4000.0, 1000.0).validate().is_err());
+        let constraints = Constraints::new().with_avoided_band(1000.0, 4000.0);
+        assert!(constraints.validate_spec(&JingleSpec::new(JinglePreset::Success).with_frequency(2000.0)).is_err());
+
+        // Melodies move to the nearest octave that clears the band
+        let fitted = constraints.fit_spec(&JingleSpec::new(JinglePreset::Notification));
+        let frequency = fitted.frequency.unwrap();
+        assert!(frequency * 2.0 <= 1000.0 || frequency >= 4000.0, "{}", frequency);
+        assert_eq!(constraints.fit_spec(&JingleSpec::new(JinglePreset::Shutdown)).frequency, Some(JinglePreset::Shutdown.default_frequency()));
+
+        // The band itself is filtered out
+        let band_energy = |samples: &[f32]| {
+            let mut band = samples.to_vec();
+            Biquad::band_pass(2000.0, 1.0, SAMPLE_RATE as f32).process(&mut band);
+            band.iter().map(|s| s * s).sum::<f32>()
+        };
+        let tone: Vec<f32> = (0..SAMPLE_RATE as usize / 2)
+            .map(|i| (2.0 * std::f32::consts::PI * 2000.0 * i as f32 / SAMPLE_RATE as f32).sin() * 0.5)
+            .collect();
+        let filtered = constraints.enforce(tone.clone(), SAMPLE_RATE);
+        assert!(band_energy(&filtered) < band_energy(&tone) * 0.01);
+
+        let mut generator = JingleGenerator::with_seed(2);
+        generator.set_constraints(Some(constraints));
+        let samples = generator.try_render(&JingleSpec::new(JinglePreset::Message).with_waveform(WaveForm::Square)).unwrap();
+        assert!(!samples.is_empty());
+    }
+
+    #[test]
+    fn test_constrained_presets() {
+        let constraints = Constraints::new()
+            .with_max_duration(1.5)
+            .with_frequency_range(200.0, 6000.0)
+            .with_max_peak_db(-6.0)
+            .with_max_loudness(-20.0);
+        let mut generator = JingleGenerator::with_seed(9);
+        generator.set_constraints(Some(constraints));
+        for preset in JinglePreset::all() {
+            let samples = generator.try_render(&JingleSpec::new(preset)).unwrap();
+            constraints.check(&samples, SAMPLE_RATE).unwrap();
+        }
+
+        // Infallible preset calls respect the limits too
+        let samples = generator.create_ringtone_jingle(WaveForm::Square, None, None);
+        assert!(samples.len() <= (1.5 * SAMPLE_RATE as f32) as usize);
+
+        let impossible = JingleSpec::new(JinglePreset::Alert).with_frequency(50.0);
+        assert!(generator.try_render(&impossible).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+//! Earcons: short motifs for semantic atoms composed into one jingle
+
+use std::collections::HashMap;
+use crate::{
+    audio::WaveForm,
+    music::Note,
+    export::JingleGenerator,
+    error::{JingleError, Result},
+};
+
+/// Grammatical role of an atom in an earcon, in the order the atoms are played
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum EarconRole {
+    /// What the event is about, e.g. a file or a message
+    Object,
+    /// What happened to it, e.g. created or deleted
+    Action,
+    /// How it turned out, e.g. success or failure
+    Status,
+}
+
+impl EarconRole {
+    /// Get all roles in playing order
+    pub fn all() -> Vec<EarconRole> {
+        vec![EarconRole::Object, EarconRole::Action, EarconRole::Status]
+    }
+
+    /// Get the name of this role as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            EarconRole::Object => "object",
+            EarconRole::Action => "action",
+            EarconRole::Status => "status",
+        }
+    }
+
+    /// Each role has its own timbre so listeners can tell the parts apart
+    fn waveform(&self) -> WaveForm {
+        match self {
+            EarconRole::Object => WaveForm::Triangle,
+            EarconRole::Action => WaveForm::Square,
+            EarconRole::Status => WaveForm::Sine,
+        }
+    }
+}
+
+/// A short motif as `(semitones above the key, length in beats)` steps
+#[derive(Clone, Debug, PartialEq)]
+pub struct Motif {
+    pub steps: Vec<(i32, f32)>,
+}
+
+impl Motif {
+    /// Create a motif from `(semitones, beats)` steps
+    pub fn new(steps: &[(i32, f32)]) -> Self {
+        Self { steps: steps.to_vec() }
+    }
+
+    /// Total length in beats
+    pub fn beats(&self) -> f32 {
+        self.steps.iter().map(|&(_, beats)| beats).sum()
+    }
+}
+
+/// Atom of the default vocabulary as `(role, name, motif steps)`
+type DefaultAtom = (EarconRole, &'static str, &'static [(i32, f32)]);
+
+/// Motifs assigned to named atoms, played in a shared key and tempo
+///
+/// The default vocabulary covers common objects (file, folder, message, user,
+/// download), actions (created, deleted, moved, sent, received, opened, closed)
+/// and statuses (success, failure, warning, pending).
+#[derive(Clone, Debug)]
+pub struct EarconVocabulary {
+    motifs: HashMap<(EarconRole, String), Motif>,
+    /// Key the motifs are transposed to
+    pub key: Note,
+    pub octave: i32,
+    /// Length of one beat in seconds
+    pub beat: f32,
+}
+
+impl Default for EarconVocabulary {
+    fn default() -> Self {
+        let mut vocabulary = Self::empty();
+        let atoms: [DefaultAtom; 16] = [
+            // Objects: rhythmic figures on the key note
+            (EarconRole::Object, "file", &[(0, 0.5), (0, 0.5)]),
+            (EarconRole::Object, "folder", &[(0, 1.0), (0, 0.5), (0, 0.5)]),
+            (EarconRole::Object, "message", &[(0, 0.5), (7, 0.5)]),
+            (EarconRole::Object, "user", &[(0, 1.5)]),
+            (EarconRole::Object, "download", &[(12, 0.5), (7, 0.5), (0, 0.5)]),
+            // Actions: contours, up for creating and down for removing
+            (EarconRole::Action, "created", &[(0, 0.5), (4, 0.5), (7, 0.5)]),
+            (EarconRole::Action, "deleted", &[(7, 0.5), (4, 0.5), (0, 0.5)]),
+            (EarconRole::Action, "moved", &[(0, 0.5), (5, 0.5), (0, 0.5)]),
+            (EarconRole::Action, "sent", &[(0, 0.25), (12, 0.75)]),
+            (EarconRole::Action, "received", &[(12, 0.25), (0, 0.75)]),
+            (EarconRole::Action, "opened", &[(0, 0.5), (7, 1.0)]),
+            (EarconRole::Action, "closed", &[(7, 0.5), (0, 1.0)]),
+            // Statuses: consonant resolutions for good news, dissonance for bad
+            (EarconRole::Status, "success", &[(4, 0.5), (12, 1.0)]),
+            (EarconRole::Status, "failure", &[(6, 0.5), (0, 1.0)]),
+            (EarconRole::Status, "warning", &[(1, 0.5), (0, 0.5), (1, 0.5)]),
+            (EarconRole::Status, "pending", &[(7, 0.5), (7, 0.5)]),
+        ];
+        for (role, name, steps) in atoms {
+            vocabulary.define(role, name, Motif::new(steps));
+        }
+        vocabulary
+    }
+}
+
+impl EarconVocabulary {
+    /// Create a vocabulary with no atoms, in C5 at 0.12 seconds per beat
+    pub fn empty() -> Self {
+        Self {
+            motifs: HashMap::new(),
+            key: Note::C,
+            octave: 5,
+            beat: 0.12,
+        }
+    }
+
+    /// Transpose every motif to a key and octave
+    pub fn with_key(mut self, key: Note, octave: i32) -> Self {
+        self.key = key;
+        self.octave = octave;
+        self
+    }
+
+    /// Set the beat length in seconds
+    pub fn with_beat(mut self, beat: f32) -> Self {
+        self.beat = beat.max(0.01);
+        self
+    }
+
+    /// Assign a motif to an atom, replacing any motif it already had
+    pub fn define(&mut self, role: EarconRole, name: &str, motif: Motif) {
+        self.motifs.insert((role, name.to_string()), motif);
+    }
+
+    /// Get the motif assigned to an atom
+    pub fn motif(&self, role: EarconRole, name: &str) -> Option<&Motif> {
+        self.motifs.get(&(role, name.to_string()))
+    }
+
+    /// Names of the atoms defined for a role in alphabetical order
+    pub fn names(&self, role: EarconRole) -> Vec<&str> {
+        let mut names: Vec<&str> = self.motifs.keys()
+            .filter(|(atom_role, _)| *atom_role == role)
+            .map(|(_, name)| name.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+/// A sentence of atoms such as "file" + "deleted" + "success"
+///
+/// Atoms are always played object, action, status, whichever are set.
+///
+/// ```rust
+/// use jinglebells::{Earcon, EarconVocabulary, JingleGenerator};
+///
+/// let earcon = Earcon::new().with_object("file").with_action("deleted").with_status("success");
+/// let samples = JingleGenerator::new().create_earcon(&EarconVocabulary::default(), &earcon).unwrap();
+/// assert!(!samples.is_empty());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Earcon {
+    pub object: Option<String>,
+    pub action: Option<String>,
+    pub status: Option<String>,
+}
+
+impl Earcon {
+    /// Create an earcon with no atoms
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the object atom
+    pub fn with_object(mut self, name: &str) -> Self {
+        self.object = Some(name.to_string());
+        self
+    }
+
+    /// Set the action atom
+    pub fn with_action(mut self, name: &str) -> Self {
+        self.action = Some(name.to_string());
+        self
+    }
+
+    /// Set the status atom
+    pub fn with_status(mut self, name: &str) -> Self {
+        self.status = Some(name.to_string());
+        self
+    }
+
+    /// The atoms that are set, in playing order
+    pub fn atoms(&self) -> Vec<(EarconRole, &str)> {
+        [
+            (EarconRole::Object, &self.object),
+            (EarconRole::Action, &self.action),
+            (EarconRole::Status, &self.status),
+        ]
+        .into_iter()
+        .filter_map(|(role, name)| name.as_deref().map(|name| (role, name)))
+        .collect()
+    }
+}
+
+impl JingleGenerator {
+    /// Render an earcon by playing the motif of each atom with a half-beat gap between them
+    ///
+    /// Earcons don't use the random generator, so the same sentence always sounds
+    /// the same and listeners can learn it.
+    pub fn create_earcon(&self, vocabulary: &EarconVocabulary, earcon: &Earcon) -> Result<Vec<f32>> {
+        let atoms = earcon.atoms();
+        if atoms.is_empty() {
+            return Err(JingleError::InvalidParameter("earcon has no atoms".to_string()));
+        }
+
+        let key_frequency = vocabulary.key.frequency(vocabulary.octave);
+        let mut phrases = Vec::with_capacity(atoms.len());
+        for (role, name) in atoms {
+            let motif = vocabulary.motif(role, name).ok_or_else(|| {
+                JingleError::InvalidParameter(format!("no earcon motif for {} `{}`", role.name(), name))
+            })?;
+            let mut phrase = Vec::new();
+            for &(semitones, beats) in &motif.steps {
+                let frequency = key_frequency * (2.0_f32).powf(semitones as f32 / 12.0);
+                phrase.extend(self.generate_tone(frequency, beats * vocabulary.beat, role.waveform()));
+            }
+            phrases.push(phrase);
+        }
+
+        Ok(self.combine_samples(&phrases, vocabulary.beat * 0.5))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SAMPLE_RATE;
+
+    #[test]
+    fn test_earcon_composition() {
+        let generator = JingleGenerator::new();
+        let vocabulary = EarconVocabulary::default();
+        let full = Earcon::new().with_status("success").with_action("deleted").with_object("file");
+        assert_eq!(full.atoms(), vec![(EarconRole::Object, "file"), (EarconRole::Action, "deleted"), (EarconRole::Status, "success")]);
+
+        let samples = generator.create_earcon(&vocabulary, &full).unwrap();
+        // 1 + 1.5 + 1.5 beats of motifs plus two half-beat gaps
+        let expected = (4.0 + 1.0) * vocabulary.beat * SAMPLE_RATE as f32;
+        assert!((samples.len() as f32 - expected).abs() < 10.0, "{} vs {}", samples.len(), expected);
+
+        // Changing one atom changes only its part of the sentence
+        let failed = generator.create_earcon(&vocabulary, &full.clone().with_status("failure")).unwrap();
+        let shared = ((1.0 + 0.5 + 1.5) * vocabulary.beat * SAMPLE_RATE as f32) as usize - 10;
+        assert_eq!(samples[..shared], failed[..shared]);
+        assert_ne!(samples, failed);
+    }
+
+    #[test]
+    fn test_earcon_vocabulary() {
+        let generator = JingleGenerator::new();
+        let mut vocabulary = EarconVocabulary::empty().with_key(Note::A, 4).with_beat(0.2);
+        assert!(vocabulary.names(EarconRole::Object).is_empty());
+        assert!(generator.create_earcon(&vocabulary, &Earcon::new()).is_err());
+        assert!(generator.create_earcon(&vocabulary, &Earcon::new().with_object("printer")).is_err());
+
+        vocabulary.define(EarconRole::Object, "printer", Motif::new(&[(0, 1.0), (3, 1.0)]));
+        assert_eq!(vocabulary.motif(EarconRole::Object, "printer").unwrap().beats(), 2.0);
+        let samples = generator.create_earcon(&vocabulary, &Earcon::new().with_object("printer")).unwrap();
+        assert_eq!(samples.len(), (0.4 * SAMPLE_RATE as f32) as usize);
+
+        assert_eq!(EarconVocabulary::default().names(EarconRole::Status), vec!["failure", "pending", "success", "warning"]);
+    }
+}
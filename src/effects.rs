@@ -3,16 +3,116 @@
 use rodio::source::Source;
 use std::collections::VecDeque;
 use std::time::Duration;
-use crate::SAMPLE_RATE;
+pub use crate::math::{db_to_linear, linear_to_db};
+use crate::{Real, to_sample, audio::{ADSR, WaveForm}, filter::{Biquad, BUTTERWORTH_Q, StateVariableFilter, SvfMode, CutoffSweep}, error::{Result, check_range}};
+
+/// Mono effect that can process samples directly, without a rodio Source
+///
+/// Every `Source` wrapper in this module is built on a processor implementing
+/// this trait, so offline pipelines working on `Vec<f32>` get identical output.
+pub trait SampleProcessor {
+    /// Process a single sample
+    fn process_sample(&mut self, input: f32) -> f32;
+
+    /// Process a block of samples in place
+    fn process_buffer(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+
+    /// Process samples, returning the result
+    fn process(&mut self, mut samples: Vec<f32>) -> Vec<f32> {
+        self.process_buffer(&mut samples);
+        samples
+    }
+}
+
+/// Wet/dry mix and bypass controls shared by every effect wrapper
+///
+/// A mix of 0.0 is dry only and 1.0 is wet only. Bypassed effects keep
+/// processing in the background so switching back in is seamless.
+pub trait Effect {
+    /// Get the wet/dry mix (0.0 - 1.0)
+    fn get_mix(&self) -> f32;
+
+    /// Set the wet/dry mix (0.0 = dry only, 1.0 = wet only)
+    fn set_mix(&mut self, mix: f32);
+
+    /// Check whether the effect is bypassed
+    fn is_bypassed(&self) -> bool;
+
+    /// Bypass the effect, passing the input through unchanged
+    fn set_bypass(&mut self, bypass: bool);
+}
+
+/// Mix and bypass state held by the effect wrappers
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MixControl {
+    pub(crate) mix: f32,
+    pub(crate) bypass: bool,
+}
+
+impl MixControl {
+    pub(crate) fn new(mix: f32) -> Self {
+        Self {
+            mix: mix.clamp(0.0, 1.0),
+            bypass: false,
+        }
+    }
+
+    /// Blend a dry and wet sample according to the mix and bypass state
+    pub(crate) fn output(&self, dry: f32, wet: f32) -> f32 {
+        if self.bypass {
+            dry
+        } else {
+            dry * (1.0 - self.mix) + wet * self.mix
+        }
+    }
+}
+
+/// Implement `Effect` for a wrapper keeping its mix and bypass state in a `controls: MixControl` field
+///
+/// Wrappers generic over more than the source list their parameters and bounds after the name.
+macro_rules! impl_mix_effect {
+    ($effect:ident) => {
+        impl_mix_effect!($effect<S> where S: Source<Item = f32>);
+    };
+    ($effect:ident<$($param:ident),+> where $($bounds:tt)+) => {
+        impl<$($param),+> Effect for $effect<$($param),+>
+        where
+            $($bounds)+
+        {
+            fn get_mix(&self) -> f32 {
+                self.controls.mix
+            }
+
+            fn set_mix(&mut self, mix: f32) {
+                self.controls.mix = mix.clamp(0.0, 1.0);
+            }
+
+            fn is_bypassed(&self) -> bool {
+                self.controls.bypass
+            }
+
+            fn set_bypass(&mut self, bypass: bool) {
+                self.controls.bypass = bypass;
+            }
+        }
+    };
+}
+pub(crate) use impl_mix_effect;
 
 /// Simple delay buffer for creating echo and reverb effects
 #[derive(Clone)]
 pub struct DelayBuffer {
-    buffer: VecDeque<f32>,
+    buffer: VecDeque<Real>,
     max_delay_samples: usize,
     delay_samples: usize,
     feedback: f32,
     mix: f32,
+    damping: f32,
+    damping_state: Real,
 }
 
 impl DelayBuffer {
@@ -20,8 +120,9 @@ impl DelayBuffer {
     /// - delay_ms: delay time in milliseconds
     /// - feedback: amount of delayed signal fed back (0.0 - 0.95)
     /// - mix: wet/dry mix (0.0 = dry only, 1.0 = wet only)
-    pub fn new(delay_ms: f32, feedback: f32, mix: f32) -> Self {
-        let delay_samples = ((delay_ms / 1000.0) * SAMPLE_RATE as f32) as usize;
+    /// - sample_rate: rate (Hz) of the processed samples
+    pub fn new(delay_ms: f32, feedback: f32, mix: f32, sample_rate: f32) -> Self {
+        let delay_samples = ((delay_ms / 1000.0) * sample_rate) as usize;
         let max_delay_samples = delay_samples.max(1);
         
         Self {
@@ -30,25 +131,56 @@ impl DelayBuffer {
             delay_samples,
             feedback: feedback.clamp(0.0, 0.95),
             mix: mix.clamp(0.0, 1.0),
+            damping: 0.0,
+            damping_state: 0.0,
         }
     }
 
-    /// Process a single sample through the delay buffer
+    /// Low-pass the fed back signal so each repeat is darker than the last
+    /// - damping: one-pole filter coefficient (0.0 = bright, 0.99 = very dark)
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping.clamp(0.0, 0.99);
+        self
+    }
+
+    /// Set the amount of delayed signal fed back (0.0 - 0.95)
+    pub fn set_feedback(&mut self, feedback: f32) {
+        self.feedback = feedback.clamp(0.0, 0.95);
+    }
+
+    /// Set the wet/dry mix (0.0 = dry only, 1.0 = wet only)
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// Process a single sample, without needing `SampleProcessor` in scope
     pub fn process_sample(&mut self, input: f32) -> f32 {
+        SampleProcessor::process_sample(self, input)
+    }
+}
+
+impl SampleProcessor for DelayBuffer {
+    /// Process a single sample through the delay buffer
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let dry = input;
+        let input = Real::from(input);
+        let (feedback, damping) = (Real::from(self.feedback), Real::from(self.damping));
+
         // Initialize buffer with zeros if needed
         while self.buffer.len() < self.max_delay_samples {
             self.buffer.push_back(0.0);
         }
 
-        // Get delayed sample
-        let delayed_sample = if self.delay_samples < self.buffer.len() {
-            self.buffer[self.buffer.len() - self.delay_samples - 1]
+        // Get delayed sample (the buffer holds exactly the last `delay_samples` outputs)
+        let delayed_sample = if self.delay_samples > 0 {
+            self.buffer[self.buffer.len() - self.delay_samples]
         } else {
             0.0
         };
 
-        // Calculate output with feedback
-        let output_sample = input + delayed_sample * self.feedback;
+        // Calculate output with (optionally damped) feedback
+        self.damping_state = delayed_sample * (1.0 - damping) + self.damping_state * damping;
+        let output_sample = input + self.damping_state * feedback;
         
         // Add to buffer
         self.buffer.push_back(output_sample);
@@ -57,7 +189,7 @@ impl DelayBuffer {
         }
 
         // Mix wet and dry signals
-        input * (1.0 - self.mix) + delayed_sample * self.mix
+        dry * (1.0 - self.mix) + to_sample(delayed_sample) * self.mix
     }
 }
 
@@ -68,6 +200,7 @@ where
 {
     source: S,
     delay_buffer: DelayBuffer,
+    controls: MixControl,
 }
 
 impl<S> Echo<S>
@@ -81,8 +214,9 @@ where
     /// - mix: wet/dry mix (0.0 - 1.0)
     pub fn new(source: S, delay_ms: f32, feedback: f32, mix: f32) -> Self {
         Self {
+            delay_buffer: DelayBuffer::new(delay_ms, feedback, 1.0, source.sample_rate() as f32),
             source,
-            delay_buffer: DelayBuffer::new(delay_ms, feedback, mix),
+            controls: MixControl::new(mix),
         }
     }
 }
@@ -95,7 +229,8 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         self.source.next().map(|sample| {
-            self.delay_buffer.process_sample(sample)
+            let wet = self.delay_buffer.process_sample(sample);
+            self.controls.output(sample, wet)
         })
     }
 }
@@ -121,13 +256,81 @@ where
     }
 }
 
-/// Simple reverb effect using multiple delay lines
+impl_mix_effect!(Echo);
+
+/// Simple reverb using multiple parallel delay lines
+#[derive(Clone)]
+pub struct DelayReverb {
+    delay_buffers: Vec<DelayBuffer>,
+    pre_delay: VecDeque<f32>,
+    mix: f32,
+    sample_rate: f32,
+}
+
+impl DelayReverb {
+    /// Create a new reverb with multiple delay lines
+    /// - room_size: scales the delay times and decay length (1.0 = medium room)
+    /// - damping: high-frequency absorption in the feedback paths (0.0 - 1.0)
+    /// - mix: wet/dry mix (0.0 = dry only, 1.0 = wet only)
+    /// - sample_rate: rate (Hz) of the processed samples
+    pub fn new(room_size: f32, damping: f32, mix: f32, sample_rate: f32) -> Self {
+        // Create multiple delay lines with different delays for natural reverb
+        let delays = [
+            29.0, 37.0, 41.0, 43.0, 47.0, 53.0, 59.0, 61.0
+        ];
+        
+        // Bigger rooms ring longer; damping darkens each pass instead of shortening the tail
+        let feedback = (0.4 + 0.2 * room_size).min(0.85);
+        let delay_buffers = delays.iter().map(|&delay_ms| {
+            DelayBuffer::new(delay_ms * room_size, feedback, 1.0, sample_rate).with_damping(damping.clamp(0.0, 1.0) * 0.7)
+        }).collect();
+
+        Self {
+            delay_buffers,
+            pre_delay: VecDeque::new(),
+            mix: mix.clamp(0.0, 1.0),
+            sample_rate,
+        }
+    }
+
+    /// Set the delay before the reverb starts (milliseconds)
+    pub fn with_pre_delay(mut self, pre_delay_ms: f32) -> Self {
+        let samples = (pre_delay_ms.max(0.0) / 1000.0 * self.sample_rate) as usize;
+        self.pre_delay = VecDeque::from(vec![0.0; samples]);
+        self
+    }
+
+    /// Set the wet/dry mix (0.0 = dry only, 1.0 = wet only)
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+}
+
+impl SampleProcessor for DelayReverb {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let delayed = if self.pre_delay.is_empty() {
+            input
+        } else {
+            self.pre_delay.push_back(input);
+            self.pre_delay.pop_front().unwrap_or(0.0)
+        };
+
+        // Equal-power sum of the delay lines keeps the wet level near the dry level
+        let wet: f32 = self.delay_buffers.iter_mut().map(|delay_buffer| delay_buffer.process_sample(delayed)).sum::<f32>()
+            / (self.delay_buffers.len() as f32).sqrt();
+        
+        input * (1.0 - self.mix) + wet * self.mix
+    }
+}
+
+/// Simple reverb effect wrapper for any Source
 pub struct Reverb<S>
 where
     S: Source<Item = f32>,
 {
     source: S,
-    delay_buffers: Vec<DelayBuffer>,
+    reverb: DelayReverb,
+    controls: MixControl,
 }
 
 impl<S> Reverb<S>
@@ -136,23 +339,19 @@ where
 {
     /// Create a new reverb effect with multiple delay lines
     pub fn new(source: S, room_size: f32, damping: f32, mix: f32) -> Self {
-        // Create multiple delay lines with different delays for natural reverb
-        let delays = [
-            29.0, 37.0, 41.0, 43.0, 47.0, 53.0, 59.0, 61.0
-        ];
-        
-        let delay_buffers = delays.iter().map(|&delay_ms| {
-            let actual_delay = delay_ms * room_size;
-            let feedback = damping * 0.6; // Limit feedback to prevent runaway
-            DelayBuffer::new(actual_delay, feedback, mix * 0.125) // Divide mix by number of delays
-        }).collect();
-
         Self {
+            reverb: DelayReverb::new(room_size, damping, 1.0, source.sample_rate() as f32),
             source,
-            delay_buffers,
+            controls: MixControl::new(mix),
         }
     }
 
+    /// Set the delay before the reverb starts (milliseconds)
+    pub fn with_pre_delay(mut self, pre_delay_ms: f32) -> Self {
+        self.reverb = self.reverb.with_pre_delay(pre_delay_ms);
+        self
+    }
+
     /// Create a small room reverb (quick, subtle)
     pub fn small_room(source: S) -> Self {
         Self::new(source, 0.5, 0.3, 0.2)
@@ -160,7 +359,7 @@ where
 
     /// Create a large hall reverb (long, lush)
     pub fn large_hall(source: S) -> Self {
-        Self::new(source, 1.5, 0.5, 0.4)
+        Self::new(source, 1.5, 0.5, 0.4).with_pre_delay(20.0)
     }
 }
 
@@ -172,15 +371,8 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         self.source.next().map(|sample| {
-            let mut output = sample;
-            
-            // Process through all delay buffers
-            for delay_buffer in &mut self.delay_buffers {
-                output += delay_buffer.process_sample(sample);
-            }
-            
-            // Normalize to prevent clipping
-            output * 0.7
+            let wet = self.reverb.process_sample(sample);
+            self.controls.output(sample, wet)
         })
     }
 }
@@ -206,45 +398,379 @@ where
     }
 }
 
-/// Simple low-pass filter for smoothing audio signals
+impl_mix_effect!(Reverb);
+
+/// Comb filter with a damped feedback loop (Freeverb building block)
+#[derive(Clone)]
+struct DampedComb {
+    buffer: Vec<Real>,
+    index: usize,
+    filter_store: Real,
+}
+
+impl DampedComb {
+    fn new(length: usize) -> Self {
+        Self { buffer: vec![0.0; length.max(1)], index: 0, filter_store: 0.0 }
+    }
+
+    fn process_sample(&mut self, input: Real, feedback: Real, damping: Real) -> Real {
+        let output = self.buffer[self.index];
+        // One-pole low-pass inside the loop makes high frequencies die away first
+        self.filter_store = output * (1.0 - damping) + self.filter_store * damping;
+        self.buffer[self.index] = input + self.filter_store * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+}
+
+/// Schroeder all-pass diffuser (Freeverb building block)
+#[derive(Clone)]
+struct Diffuser {
+    buffer: Vec<Real>,
+    index: usize,
+}
+
+impl Diffuser {
+    fn new(length: usize) -> Self {
+        Self { buffer: vec![0.0; length.max(1)], index: 0 }
+    }
+
+    fn process_sample(&mut self, input: Real) -> Real {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = input + delayed * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+/// Comb delays in samples at 44.1 kHz from the original Freeverb
+const FREEVERB_COMBS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+
+/// All-pass delays in samples at 44.1 kHz from the original Freeverb
+const FREEVERB_ALLPASSES: [usize; 4] = [556, 441, 341, 225];
+
+/// Extra delay on the right channel for stereo decorrelation
+const FREEVERB_STEREO_SPREAD: usize = 23;
+
+/// Freeverb-style stereo reverb: parallel damped combs into series all-pass diffusers
+#[derive(Clone)]
+pub struct Freeverb {
+    combs: [Vec<DampedComb>; 2],
+    diffusers: [Vec<Diffuser>; 2],
+    pre_delay: VecDeque<f32>,
+    pre_delay_samples: usize,
+    feedback: f32,
+    damping: f32,
+    wet: f32,
+    dry: f32,
+    width: f32,
+    sample_rate: f32,
+    frozen: bool,
+    freeze_at: Option<usize>,
+    freeze_fade_samples: usize,
+    frozen_samples: usize,
+    position: usize,
+}
+
+impl Freeverb {
+    /// Create a new reverb
+    /// - room_size: decay length (0.0 - 1.0)
+    /// - damping: high-frequency absorption (0.0 - 1.0)
+    /// - wet: level of the reverberated signal (0.0 - 1.0)
+    pub fn new(room_size: f32, damping: f32, wet: f32, sample_rate: f32) -> Self {
+        let scale = |length: usize| (length as f32 * sample_rate / 44100.0) as usize;
+        let channel = |spread: usize| {
+            (
+                FREEVERB_COMBS.iter().map(|&length| DampedComb::new(scale(length + spread))).collect::<Vec<_>>(),
+                FREEVERB_ALLPASSES.iter().map(|&length| Diffuser::new(scale(length + spread))).collect::<Vec<_>>(),
+            )
+        };
+        let (left_combs, left_diffusers) = channel(0);
+        let (right_combs, right_diffusers) = channel(FREEVERB_STEREO_SPREAD);
+
+        Self {
+            combs: [left_combs, right_combs],
+            diffusers: [left_diffusers, right_diffusers],
+            pre_delay: VecDeque::new(),
+            pre_delay_samples: 0,
+            feedback: 0.7 + room_size.clamp(0.0, 1.0) * 0.28,
+            damping: damping.clamp(0.0, 1.0) * 0.4,
+            wet: wet.clamp(0.0, 1.0),
+            dry: 1.0 - wet.clamp(0.0, 1.0),
+            width: 1.0,
+            sample_rate,
+            frozen: false,
+            freeze_at: None,
+            freeze_fade_samples: 0,
+            frozen_samples: 0,
+            position: 0,
+        }
+    }
+
+    /// Set the delay before the reverb starts (milliseconds)
+    pub fn with_pre_delay(mut self, pre_delay_ms: f32) -> Self {
+        self.pre_delay_samples = (pre_delay_ms.max(0.0) / 1000.0 * self.sample_rate) as usize;
+        self.pre_delay = VecDeque::from(vec![0.0; self.pre_delay_samples]);
+        self
+    }
+
+    /// Set the stereo width of the reverb (0.0 = mono, 1.0 = full width)
+    pub fn with_width(mut self, width: f32) -> Self {
+        self.width = width.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the level of the unprocessed signal (0.0 - 1.0)
+    pub fn with_dry(mut self, dry: f32) -> Self {
+        self.dry = dry.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Freeze the tail once this much time (seconds) has been processed
+    pub fn with_freeze_at(mut self, time: f32) -> Self {
+        self.freeze_at = Some((time.max(0.0) * self.sample_rate) as usize);
+        self
+    }
+
+    /// Fade the frozen tail out linearly over this many seconds (0.0 = hold forever)
+    pub fn with_freeze_fade(mut self, fade_time: f32) -> Self {
+        self.freeze_fade_samples = (fade_time.max(0.0) * self.sample_rate) as usize;
+        self
+    }
+
+    /// Hold the current tail indefinitely: no decay, no damping and no new input
+    pub fn set_freeze(&mut self, freeze: bool) {
+        if freeze != self.frozen {
+            self.frozen_samples = 0;
+        }
+        self.frozen = freeze;
+    }
+
+    /// Check whether the tail is frozen
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Get the level of the reverberated signal
+    pub fn wet(&self) -> f32 {
+        self.wet
+    }
+
+    /// Set the wet and dry levels together (0.0 = dry only, 1.0 = wet only)
+    pub fn set_mix(&mut self, mix: f32) {
+        self.wet = mix.clamp(0.0, 1.0);
+        self.dry = 1.0 - self.wet;
+    }
+
+    /// Process a mono sample into a (left, right) pair
+    pub fn process_sample(&mut self, input: f32) -> (f32, f32) {
+        if self.freeze_at == Some(self.position) {
+            self.set_freeze(true);
+        }
+        self.position += 1;
+
+        // Frozen combs recirculate undamped at unity gain and stop taking input
+        let (feedback, damping, input_gain, fade) = if self.frozen {
+            let fade = if self.freeze_fade_samples > 0 {
+                (1.0 - self.frozen_samples as f32 / self.freeze_fade_samples as f32).max(0.0)
+            } else {
+                1.0
+            };
+            self.frozen_samples += 1;
+            (1.0, 0.0, 0.0, fade)
+        } else {
+            // Fixed input gain from Freeverb keeps the comb bank from overloading
+            (self.feedback, self.damping, 0.015, 1.0)
+        };
+
+        let delayed = if self.pre_delay_samples > 0 {
+            self.pre_delay.push_back(input);
+            self.pre_delay.pop_front().unwrap_or(0.0)
+        } else {
+            input
+        } * input_gain;
+
+        let mut outputs = [0.0; 2];
+        for (channel, output) in outputs.iter_mut().enumerate() {
+            let mut sum: Real = self.combs[channel]
+                .iter_mut()
+                .map(|comb| comb.process_sample(Real::from(delayed), Real::from(feedback), Real::from(damping)))
+                .sum();
+            for diffuser in &mut self.diffusers[channel] {
+                sum = diffuser.process_sample(sum);
+            }
+            *output = to_sample(sum);
+        }
+
+        // Crossfeed the channels according to the width
+        let wet_main = self.wet * fade * (1.0 + self.width) / 2.0;
+        let wet_cross = self.wet * fade * (1.0 - self.width) / 2.0;
+        (
+            outputs[0] * wet_main + outputs[1] * wet_cross + input * self.dry,
+            outputs[1] * wet_main + outputs[0] * wet_cross + input * self.dry,
+        )
+    }
+
+    /// Process mono samples into interleaved stereo (left, right, left, right, ...)
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        let mut output = Vec::with_capacity(samples.len() * 2);
+        for &sample in samples {
+            let (left, right) = self.process_sample(sample);
+            output.push(left);
+            output.push(right);
+        }
+        output
+    }
+}
+
+/// Stereo Freeverb wrapper turning a mono Source into an interleaved stereo Source
+pub struct StereoReverb<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    reverb: Freeverb,
+    pending_right: Option<f32>,
+    bypass: bool,
+}
+
+impl<S> StereoReverb<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new stereo reverb effect from a configured reverb
+    pub fn new(source: S, reverb: Freeverb) -> Self {
+        Self { source, reverb, pending_right: None, bypass: false }
+    }
+
+    /// Create a small, lightly damped room
+    pub fn room(source: S) -> Self {
+        let reverb = Freeverb::new(0.5, 0.5, 0.25, source.sample_rate() as f32).with_pre_delay(5.0);
+        Self::new(source, reverb)
+    }
+
+    /// Create a large, smooth hall
+    pub fn hall(source: S) -> Self {
+        let reverb = Freeverb::new(0.85, 0.3, 0.35, source.sample_rate() as f32).with_pre_delay(20.0);
+        Self::new(source, reverb)
+    }
+}
+
+impl<S> Iterator for StereoReverb<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(right) = self.pending_right.take() {
+            return Some(right);
+        }
+        self.source.next().map(|sample| {
+            let (left, right) = self.reverb.process_sample(sample);
+            if self.bypass {
+                self.pending_right = Some(sample);
+                return sample;
+            }
+            self.pending_right = Some(right);
+            left
+        })
+    }
+}
+
+impl<S> Source for StereoReverb<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len().map(|len| len * 2)
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl<S> Effect for StereoReverb<S>
+where
+    S: Source<Item = f32>,
+{
+    fn get_mix(&self) -> f32 {
+        self.reverb.wet()
+    }
+
+    fn set_mix(&mut self, mix: f32) {
+        self.reverb.set_mix(mix);
+    }
+
+    fn is_bypassed(&self) -> bool {
+        self.bypass
+    }
+
+    fn set_bypass(&mut self, bypass: bool) {
+        self.bypass = bypass;
+    }
+}
+
+/// Low-pass filter for smoothing audio signals
 #[derive(Clone)]
 pub struct LowPassFilter {
-    #[allow(dead_code)]
+    biquad: Biquad,
     cutoff_frequency: f32,
-    #[allow(dead_code)]
+    q: f32,
     sample_rate: f32,
-    alpha: f32,
-    previous_output: f32,
 }
 
 impl LowPassFilter {
-    /// Create a new low-pass filter
+    /// Create a new low-pass filter with a flat (Butterworth) response
     /// - cutoff_frequency: frequency above which signals are attenuated (Hz)
     /// - sample_rate: audio sample rate (Hz)
     pub fn new(cutoff_frequency: f32, sample_rate: f32) -> Self {
-        // Calculate filter coefficient
-        let rc = 1.0 / (2.0 * std::f32::consts::PI * cutoff_frequency);
-        let dt = 1.0 / sample_rate;
-        let alpha = dt / (rc + dt);
+        Self::with_resonance(cutoff_frequency, BUTTERWORTH_Q, sample_rate)
+    }
 
+    /// Create a new low-pass filter with a resonant peak at the cutoff
+    /// - q: filter Q (0.707 is flat, higher values resonate)
+    pub fn with_resonance(cutoff_frequency: f32, q: f32, sample_rate: f32) -> Self {
         Self {
+            biquad: Biquad::low_pass(cutoff_frequency, q, sample_rate),
             cutoff_frequency,
+            q,
             sample_rate,
-            alpha,
-            previous_output: 0.0,
         }
     }
 
-    /// Process a single sample through the filter
-    pub fn process_sample(&mut self, input: f32) -> f32 {
-        let output = self.alpha * input + (1.0 - self.alpha) * self.previous_output;
-        self.previous_output = output;
-        output
+    /// Move the cutoff frequency (Hz) while keeping the filter state
+    pub fn set_cutoff(&mut self, cutoff_frequency: f32) {
+        if cutoff_frequency != self.cutoff_frequency {
+            self.cutoff_frequency = cutoff_frequency;
+            self.biquad.set_coefficients(&Biquad::low_pass(cutoff_frequency, self.q, self.sample_rate));
+        }
     }
 
     /// Reset the filter state
     pub fn reset(&mut self) {
-        self.previous_output = 0.0;
+        self.biquad.reset();
+    }
+
+    /// Process a single sample, without needing `SampleProcessor` in scope
+    pub fn process_sample(&mut self, input: f32) -> f32 {
+        SampleProcessor::process_sample(self, input)
+    }
+}
+
+impl SampleProcessor for LowPassFilter {
+    /// Process a single sample through the filter
+    fn process_sample(&mut self, input: f32) -> f32 {
+        self.biquad.process_sample(input)
     }
 }
 
@@ -255,6 +781,7 @@ where
 {
     source: S,
     filter: LowPassFilter,
+    controls: MixControl,
 }
 
 impl<S> LowPass<S>
@@ -264,8 +791,18 @@ where
     /// Create a new low-pass filter effect
     pub fn new(source: S, cutoff_frequency: f32) -> Self {
         Self {
-            filter: LowPassFilter::new(cutoff_frequency, SAMPLE_RATE as f32),
+            filter: LowPassFilter::new(cutoff_frequency, source.sample_rate() as f32),
+            source,
+            controls: MixControl::new(1.0),
+        }
+    }
+
+    /// Create a resonant low-pass filter effect
+    pub fn with_resonance(source: S, cutoff_frequency: f32, q: f32) -> Self {
+        Self {
+            filter: LowPassFilter::with_resonance(cutoff_frequency, q, source.sample_rate() as f32),
             source,
+            controls: MixControl::new(1.0),
         }
     }
 
@@ -288,7 +825,8 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         self.source.next().map(|sample| {
-            self.filter.process_sample(sample)
+            let wet = self.filter.process_sample(sample);
+            self.controls.output(sample, wet)
         })
     }
 }
@@ -314,101 +852,1381 @@ where
     }
 }
 
-/// Automatic Gain Control (AGC) for dynamic range compression
-#[derive(Clone)]
-pub struct AutomaticGainControl {
-    target_level: f32,
-    attack_time: f32,
-    release_time: f32,
-    current_gain: f32,
-    envelope_follower: f32,
+impl_mix_effect!(LowPass);
+
+/// State-variable filter whose cutoff follows a sweep over time
+#[derive(Clone, Debug)]
+pub struct SweptFilter {
+    filter: StateVariableFilter,
+    sweep: CutoffSweep,
     sample_rate: f32,
+    sample_index: usize,
 }
 
-impl AutomaticGainControl {
-    /// Create a new AGC
-    /// - target_level: desired output level (0.0 - 1.0)
-    /// - attack_time: time to reduce gain when signal is loud (seconds)
-    /// - release_time: time to increase gain when signal is quiet (seconds)
-    pub fn new(target_level: f32, attack_time: f32, release_time: f32, sample_rate: f32) -> Self {
+impl SweptFilter {
+    /// Create a new swept filter
+    /// - mode: which filter output to hear
+    /// - q: resonance (0.707 is flat, 4.0+ is a pronounced peak)
+    /// - sweep: how the cutoff moves over time
+    pub fn new(mode: SvfMode, q: f32, sweep: CutoffSweep, sample_rate: f32) -> Self {
         Self {
-            target_level: target_level.clamp(0.0, 1.0),
-            attack_time,
-            release_time,
+            filter: StateVariableFilter::new(mode, q, sample_rate),
+            sweep,
+            sample_rate,
+            sample_index: 0,
+        }
+    }
+}
+
+impl SampleProcessor for SweptFilter {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let time = self.sample_index as f32 / self.sample_rate;
+        self.sample_index += 1;
+        self.filter.process_sample(input, self.sweep.cutoff_at(time))
+    }
+}
+
+/// Resonant filter sweep wrapper for any Source
+pub struct FilterSweep<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    filter: SweptFilter,
+    controls: MixControl,
+}
+
+impl<S> FilterSweep<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new filter sweep effect
+    /// - mode: which filter output to hear
+    /// - q: resonance (0.707 is flat, 4.0+ is a pronounced peak)
+    /// - sweep: how the cutoff moves over time
+    pub fn new(source: S, mode: SvfMode, q: f32, sweep: CutoffSweep) -> Self {
+        let sample_rate = source.sample_rate() as f32;
+        Self {
+            source,
+            filter: SweptFilter::new(mode, q, sweep, sample_rate),
+            controls: MixControl::new(1.0),
+        }
+    }
+
+    /// Create a classic synth "zap": a resonant low-pass opening quickly then closing over the note
+    pub fn zap(source: S, duration: f32) -> Self {
+        let adsr = ADSR { attack: 0.02, decay: duration * 0.5, sustain: 0.2, release: duration * 0.3 };
+        Self::new(source, SvfMode::LowPass, 6.0, CutoffSweep::Envelope { base: 200.0, peak: 8000.0, adsr, duration })
+    }
+
+    /// Create an LFO-driven "wah" band-pass
+    pub fn wah(source: S) -> Self {
+        Self::new(source, SvfMode::BandPass, 4.0, CutoffSweep::Lfo { center: 1000.0, depth: 1.5, rate: 3.0 })
+    }
+}
+
+impl<S> Iterator for FilterSweep<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.filter.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S> Source for FilterSweep<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(FilterSweep);
+
+/// First-order all-pass section used by the phaser
+#[derive(Clone, Default)]
+struct AllPassStage {
+    previous_input: f32,
+    previous_output: f32,
+}
+
+impl AllPassStage {
+    /// Process a sample with coefficient `a` (the phase shift is 90° at the break frequency)
+    fn process_sample(&mut self, input: f32, a: f32) -> f32 {
+        let output = a * input + self.previous_input - a * self.previous_output;
+        self.previous_input = input;
+        self.previous_output = output;
+        output
+    }
+}
+
+/// Cascaded all-pass stages with an LFO-swept break frequency
+///
+/// Sweeps notches through the spectrum by mixing the signal with a copy passed
+/// through the stages.
+#[derive(Clone)]
+pub struct PhaseShifter {
+    stages: Vec<AllPassStage>,
+    min_frequency: f32,
+    max_frequency: f32,
+    feedback: f32,
+    mix: f32,
+    lfo: Lfo,
+    last_output: f32,
+    sample_rate: f32,
+}
+
+impl PhaseShifter {
+    /// Create a new phase shifter
+    /// - stages: number of all-pass stages (each pair adds a notch)
+    /// - rate: LFO rate in Hz
+    /// - feedback: amount of phased signal fed back (0.0 - 0.9)
+    /// - mix: wet/dry mix (0.0 = dry only, 1.0 = wet only)
+    pub fn new(stages: usize, rate: f32, feedback: f32, mix: f32, sample_rate: f32) -> Self {
+        Self {
+            stages: vec![AllPassStage::default(); stages.max(1)],
+            min_frequency: 200.0,
+            max_frequency: 2000.0,
+            feedback: feedback.clamp(0.0, 0.9),
+            mix: mix.clamp(0.0, 1.0),
+            lfo: Lfo::new(LfoShape::Sine, rate, sample_rate),
+            last_output: 0.0,
+            sample_rate,
+        }
+    }
+
+    /// Set the range swept by the all-pass break frequency (Hz)
+    pub fn with_range(mut self, min_frequency: f32, max_frequency: f32) -> Self {
+        self.min_frequency = min_frequency.max(1.0);
+        self.max_frequency = max_frequency.max(self.min_frequency);
+        self
+    }
+
+    /// Set the wet/dry mix (0.0 = dry only, 1.0 = wet only)
+    pub fn set_mix(&mut self, mix: f32) {
+        self.mix = mix.clamp(0.0, 1.0);
+    }
+}
+
+impl SampleProcessor for PhaseShifter {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        // Sweep the break frequency exponentially between the range limits
+        let lfo = 0.5 + 0.5 * self.lfo.next_value();
+        let frequency = self.min_frequency * (self.max_frequency / self.min_frequency).powf(lfo);
+
+        let tan = (std::f32::consts::PI * frequency / self.sample_rate).tan();
+        let a = (tan - 1.0) / (tan + 1.0);
+
+        let mut phased = input + self.last_output * self.feedback;
+        for stage in &mut self.stages {
+            phased = stage.process_sample(phased, a);
+        }
+        self.last_output = phased;
+
+        input * (1.0 - self.mix) + phased * self.mix
+    }
+}
+
+/// Phaser effect wrapper for any Source
+pub struct Phaser<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    phaser: PhaseShifter,
+    controls: MixControl,
+}
+
+impl<S> Phaser<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new phaser effect (see `PhaseShifter::new`)
+    pub fn new(source: S, stages: usize, rate: f32, feedback: f32, mix: f32) -> Self {
+        let sample_rate = source.sample_rate() as f32;
+        Self {
+            source,
+            phaser: PhaseShifter::new(stages, rate, feedback, 1.0, sample_rate),
+            controls: MixControl::new(mix),
+        }
+    }
+
+    /// Set the range swept by the all-pass break frequency (Hz)
+    pub fn with_range(mut self, min_frequency: f32, max_frequency: f32) -> Self {
+        self.phaser = self.phaser.with_range(min_frequency, max_frequency);
+        self
+    }
+
+    /// Create a gentle phaser for sustained chords
+    pub fn gentle(source: S) -> Self {
+        Self::new(source, 4, 0.5, 0.3, 0.5)
+    }
+
+    /// Create a deep, resonant phaser with more notches
+    pub fn deep(source: S) -> Self {
+        Self::new(source, 8, 0.3, 0.7, 0.5)
+    }
+}
+
+impl<S> Iterator for Phaser<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.phaser.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S> Source for Phaser<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(Phaser);
+
+/// Transfer curves available to the distortion effect
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum Shaper {
+    /// Smooth saturation that rounds off peaks
+    Tanh,
+    /// Flat clipping at full scale
+    HardClip,
+    /// Peaks beyond full scale fold back towards zero
+    Foldback,
+}
+
+impl Shaper {
+    /// Apply the transfer curve to a sample
+    pub fn apply(&self, input: f32) -> f32 {
+        match self {
+            Shaper::Tanh => input.tanh(),
+            Shaper::HardClip => input.clamp(-1.0, 1.0),
+            Shaper::Foldback => {
+                // Triangle wave in the input keeps the output within ±1 for any drive
+                let folded = (input + 1.0).rem_euclid(4.0);
+                if folded < 2.0 { folded - 1.0 } else { 3.0 - folded }
+            }
+        }
+    }
+}
+
+/// Oversampled waveshaper behind the distortion effect
+///
+/// The shaper runs at twice the sample rate with a low-pass before decimation,
+/// which keeps the added harmonics from aliasing back into the audible range.
+#[derive(Clone, Debug)]
+pub struct Waveshaper {
+    shaper: Shaper,
+    drive: f32,
+    output: f32,
+    previous_input: f32,
+    anti_alias: [Biquad; 2],
+}
+
+impl Waveshaper {
+    /// Create a new waveshaper
+    /// - shaper: transfer curve
+    /// - drive: gain applied before shaping (1.0 = unity, higher is dirtier)
+    /// - output: gain applied after shaping
+    pub fn new(shaper: Shaper, drive: f32, output: f32, sample_rate: f32) -> Self {
+        // Cascaded Butterworth sections at the original Nyquist, running at the doubled rate
+        let anti_alias = Biquad::low_pass(sample_rate * 0.45, BUTTERWORTH_Q, sample_rate * 2.0);
+
+        Self {
+            shaper,
+            drive: drive.max(0.0),
+            output,
+            previous_input: 0.0,
+            anti_alias: [anti_alias.clone(), anti_alias],
+        }
+    }
+
+    /// Set the gain applied before shaping
+    pub fn set_drive(&mut self, drive: f32) {
+        self.drive = drive.max(0.0);
+    }
+
+    /// Shape and band-limit one oversampled sample
+    fn shape_oversampled(&mut self, input: f32) -> f32 {
+        let shaped = self.shaper.apply(input * self.drive);
+        let filtered = self.anti_alias[0].process_sample(shaped);
+        self.anti_alias[1].process_sample(filtered)
+    }
+}
+
+impl SampleProcessor for Waveshaper {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        // Linear interpolation for the in-between sample, keep every second output
+        let midpoint = (self.previous_input + input) * 0.5;
+        self.previous_input = input;
+        self.shape_oversampled(midpoint);
+        self.shape_oversampled(input) * self.output
+    }
+}
+
+/// Distortion effect wrapper for any Source
+pub struct Distortion<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    shaper: Waveshaper,
+    controls: MixControl,
+}
+
+impl<S> Distortion<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new distortion effect (see `Waveshaper::new`)
+    pub fn new(source: S, shaper: Shaper, drive: f32, output: f32) -> Self {
+        let sample_rate = source.sample_rate() as f32;
+        Self {
+            source,
+            shaper: Waveshaper::new(shaper, drive, output, sample_rate),
+            controls: MixControl::new(1.0),
+        }
+    }
+
+    /// Create a warm tanh saturation
+    pub fn warm(source: S) -> Self {
+        Self::new(source, Shaper::Tanh, 2.0, 0.8)
+    }
+
+    /// Create a gritty hard-clipped crunch for alerts
+    pub fn crunch(source: S) -> Self {
+        Self::new(source, Shaper::HardClip, 6.0, 0.5)
+    }
+
+    /// Create a metallic foldback distortion for errors
+    pub fn fold(source: S) -> Self {
+        Self::new(source, Shaper::Foldback, 4.0, 0.7)
+    }
+}
+
+impl<S> Iterator for Distortion<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.shaper.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S> Source for Distortion<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(Distortion);
+
+/// Shapes available to the LFO
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum LfoShape {
+    Sine,
+    Triangle,
+    Sawtooth,
+    Square,
+    /// A new random level held for each cycle
+    SampleAndHold,
+}
+
+impl From<WaveForm> for LfoShape {
+    fn from(waveform: WaveForm) -> Self {
+        match waveform {
+            WaveForm::Sine => LfoShape::Sine,
+            WaveForm::Triangle => LfoShape::Triangle,
+            WaveForm::Sawtooth => LfoShape::Sawtooth,
+            WaveForm::Square => LfoShape::Square,
+        }
+    }
+}
+
+/// Low-frequency oscillator for modulating effect parameters
+///
+/// Values range from `-depth` to `depth`. The LFO can be stepped one sample at
+/// a time with `next_value` or evaluated at any time with `value_at`.
+#[derive(Clone, Debug)]
+pub struct Lfo {
+    shape: LfoShape,
+    rate: f32,
+    depth: f32,
+    start_phase: f32,
+    seed: u64,
+    phase: f32,
+    cycle: u64,
+    sample_rate: f32,
+}
+
+impl Lfo {
+    /// Create a new LFO with full depth, starting at phase 0
+    /// - rate: cycles per second (Hz)
+    pub fn new(shape: impl Into<LfoShape>, rate: f32, sample_rate: f32) -> Self {
+        Self {
+            shape: shape.into(),
+            rate,
+            depth: 1.0,
+            start_phase: 0.0,
+            seed: 0,
+            phase: 0.0,
+            cycle: 0,
+            sample_rate,
+        }
+    }
+
+    /// Set the output amplitude (values range from -depth to depth)
+    pub fn with_depth(mut self, depth: f32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Set the starting phase as a fraction of a cycle (0.0 - 1.0)
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.start_phase = phase.rem_euclid(1.0);
+        self.reset();
+        self
+    }
+
+    /// Set the seed for the sample-and-hold levels
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Change the rate (Hz) without resetting the phase
+    pub fn set_rate(&mut self, rate: f32) {
+        self.rate = rate;
+    }
+
+    /// Restart the LFO from its starting phase
+    pub fn reset(&mut self) {
+        self.phase = self.start_phase;
+        self.cycle = 0;
+    }
+
+    /// Get the value at a time in seconds since the start, without changing state
+    pub fn value_at(&self, time: f32) -> f32 {
+        let position = self.start_phase + self.rate * time;
+        self.shape_value(position.rem_euclid(1.0), position.floor() as i64 as u64)
+    }
+
+    /// Get the next value and advance by one sample
+    pub fn next_value(&mut self) -> f32 {
+        let value = self.shape_value(self.phase, self.cycle);
+        let next = self.phase + self.rate / self.sample_rate;
+        self.cycle = self.cycle.wrapping_add(next.floor() as i64 as u64);
+        self.phase = next.rem_euclid(1.0);
+        value
+    }
+
+    /// Value of the shape at a phase within a given cycle
+    fn shape_value(&self, phase: f32, cycle: u64) -> f32 {
+        let value = match self.shape {
+            LfoShape::Sine => (2.0 * std::f32::consts::PI * phase).sin(),
+            LfoShape::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            LfoShape::Sawtooth => 2.0 * phase - 1.0,
+            LfoShape::Square => if phase < 0.5 { 1.0 } else { -1.0 },
+            LfoShape::SampleAndHold => {
+                // SplitMix64 of the cycle index, so levels are reproducible from any start point
+                let mut z = self.seed.wrapping_add(cycle.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+                z ^= z >> 31;
+                (z >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+            }
+        };
+        value * self.depth
+    }
+}
+
+/// LFO-driven gain modulation behind the tremolo effect
+#[derive(Clone, Debug)]
+pub struct AmplitudeModulator {
+    lfo: Lfo,
+    depth: f32,
+}
+
+impl AmplitudeModulator {
+    /// Create a new amplitude modulator
+    /// - rate: pulses per second (Hz)
+    /// - depth: how far the gain dips (0.0 = none, 1.0 = down to silence)
+    /// - waveform: shape of the gain modulation
+    pub fn new(rate: f32, depth: f32, waveform: WaveForm, sample_rate: f32) -> Self {
+        Self {
+            lfo: Lfo::new(waveform, rate, sample_rate),
+            depth: depth.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Set how far the gain dips (0.0 = none, 1.0 = down to silence)
+    pub fn set_depth(&mut self, depth: f32) {
+        self.depth = depth.clamp(0.0, 1.0);
+    }
+}
+
+impl SampleProcessor for AmplitudeModulator {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        input * tremolo_gain(self.lfo.next_value(), self.depth)
+    }
+}
+
+/// Tremolo effect wrapper for any Source
+pub struct Tremolo<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    modulator: AmplitudeModulator,
+    controls: MixControl,
+}
+
+impl<S> Tremolo<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new tremolo effect (see `AmplitudeModulator::new`)
+    pub fn new(source: S, rate: f32, depth: f32, waveform: WaveForm) -> Self {
+        let sample_rate = source.sample_rate() as f32;
+        Self {
+            source,
+            modulator: AmplitudeModulator::new(rate, depth, waveform, sample_rate),
+            controls: MixControl::new(1.0),
+        }
+    }
+
+    /// Create a smooth, gentle pulse
+    pub fn gentle(source: S) -> Self {
+        Self::new(source, 5.0, 0.3, WaveForm::Sine)
+    }
+
+    /// Create a hard on/off pulse for alerts
+    pub fn pulse(source: S) -> Self {
+        Self::new(source, 8.0, 1.0, WaveForm::Square)
+    }
+}
+
+/// Gain for a tremolo LFO value, ranging from 1.0 - depth up to 1.0
+fn tremolo_gain(lfo_value: f32, depth: f32) -> f32 {
+    1.0 - depth * (0.5 - 0.5 * lfo_value)
+}
+
+impl<S> Iterator for Tremolo<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.modulator.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S> Source for Tremolo<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(Tremolo);
+
+/// Apply tremolo to samples at `sample_rate` in place
+pub fn tremolo(samples: &mut [f32], rate: f32, depth: f32, waveform: WaveForm, sample_rate: f32) {
+    AmplitudeModulator::new(rate, depth, waveform, sample_rate).process_buffer(samples);
+}
+
+/// Multiplies the input by a sine carrier, giving the sum and difference
+/// frequencies instead of the originals for metallic, robotic timbres
+#[derive(Clone, Debug)]
+pub struct RingModulator {
+    carrier: Lfo,
+}
+
+impl RingModulator {
+    /// Create a new ring modulator
+    /// - frequency: carrier frequency (Hz)
+    pub fn new(frequency: f32, sample_rate: f32) -> Self {
+        Self { carrier: Lfo::new(LfoShape::Sine, frequency, sample_rate) }
+    }
+
+    /// Change the carrier frequency (Hz) without resetting its phase
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.carrier.set_rate(frequency);
+    }
+}
+
+impl SampleProcessor for RingModulator {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        input * self.carrier.next_value()
+    }
+}
+
+/// Ring modulation effect wrapper for any Source
+pub struct RingMod<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    modulator: RingModulator,
+    controls: MixControl,
+}
+
+impl<S> RingMod<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new ring modulation effect
+    /// - frequency: carrier frequency (Hz)
+    /// - mix: wet/dry mix (0.0 - 1.0)
+    pub fn new(source: S, frequency: f32, mix: f32) -> Self {
+        let sample_rate = source.sample_rate() as f32;
+        Self {
+            source,
+            modulator: RingModulator::new(frequency, sample_rate),
+            controls: MixControl::new(mix),
+        }
+    }
+}
+
+impl<S> Iterator for RingMod<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.modulator.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S> Source for RingMod<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(RingMod);
+
+/// Length of the pitch shifter's delay window (seconds)
+const PITCH_SHIFT_WINDOW: f32 = 0.05;
+
+/// Delay-line pitch shifter: two read heads sweep through a short window at a
+/// different speed than it is written, crossfaded so the wraparound is inaudible
+#[derive(Clone, Debug)]
+pub struct PitchShifter {
+    buffer: Vec<f32>,
+    write: usize,
+    delay: f32,
+    ratio: f32,
+}
+
+impl PitchShifter {
+    /// Create a new pitch shifter
+    /// - semitones: shift applied to the input (12.0 = one octave up)
+    pub fn new(semitones: f32, sample_rate: f32) -> Self {
+        Self {
+            buffer: vec![0.0; ((PITCH_SHIFT_WINDOW * sample_rate) as usize).max(4)],
+            write: 0,
+            delay: 0.0,
+            ratio: (2.0_f32).powf(semitones / 12.0),
+        }
+    }
+
+    /// Linearly interpolated sample from `delay` samples ago
+    fn read(&self, delay: f32) -> f32 {
+        let size = self.buffer.len();
+        let position = (self.write as f32 - delay).rem_euclid(size as f32);
+        let index = position as usize % size;
+        let fraction = position.fract();
+        self.buffer[index] * (1.0 - fraction) + self.buffer[(index + 1) % size] * fraction
+    }
+}
+
+impl SampleProcessor for PitchShifter {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let size = self.buffer.len() as f32;
+        self.buffer[self.write] = input;
+        // Heads half a window apart with sin² windows, which always sum to one
+        let output = [0.0, size / 2.0]
+            .into_iter()
+            .map(|offset| {
+                let delay = (self.delay + offset) % size;
+                (std::f32::consts::PI * delay / size).sin().powi(2) * self.read(delay)
+            })
+            .sum();
+        self.delay = (self.delay + 1.0 - self.ratio).rem_euclid(size);
+        self.write = (self.write + 1) % self.buffer.len();
+        output
+    }
+}
+
+/// Reverb whose tail is shifted up an octave and fed back in, so it blooms
+/// into a rising, glassy shimmer
+#[derive(Clone)]
+pub struct ShimmerReverb {
+    reverb: Freeverb,
+    shifter: PitchShifter,
+    shimmer: f32,
+    feedback: f32,
+}
+
+impl ShimmerReverb {
+    /// Create a new shimmer reverb
+    /// - room_size: decay length (0.0 - 1.0)
+    /// - shimmer: level of the octave-up tail fed back into the reverb (0.0 - 0.9)
+    pub fn new(room_size: f32, shimmer: f32, sample_rate: f32) -> Self {
+        Self {
+            reverb: Freeverb::new(room_size, 0.2, 1.0, sample_rate),
+            shifter: PitchShifter::new(12.0, sample_rate),
+            shimmer: shimmer.clamp(0.0, 0.9),
+            feedback: 0.0,
+        }
+    }
+}
+
+impl SampleProcessor for ShimmerReverb {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let (left, right) = self.reverb.process_sample(input + self.feedback * self.shimmer);
+        let wet = (left + right) * 0.5;
+        // Soft clip the loop so a long tail can't run away
+        self.feedback = self.shifter.process_sample(wet).tanh();
+        wet
+    }
+}
+
+/// Shimmer reverb effect wrapper for any Source
+pub struct Shimmer<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    reverb: ShimmerReverb,
+    controls: MixControl,
+}
+
+impl<S> Shimmer<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new shimmer reverb effect (see `ShimmerReverb::new`)
+    /// - mix: wet/dry mix (0.0 - 1.0)
+    pub fn new(source: S, room_size: f32, shimmer: f32, mix: f32) -> Self {
+        let sample_rate = source.sample_rate() as f32;
+        Self {
+            source,
+            reverb: ShimmerReverb::new(room_size, shimmer, sample_rate),
+            controls: MixControl::new(mix),
+        }
+    }
+}
+
+impl<S> Iterator for Shimmer<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.reverb.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S> Source for Shimmer<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(Shimmer);
+
+/// How the AGC measures the input level
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum LevelDetection {
+    /// Instantaneous peak level, fast but reacts to every transient
+    Peak,
+    /// Level averaged over a short (10ms) window, closer to perceived loudness
+    Rms,
+}
+
+/// Window of the RMS level detector (seconds)
+const AGC_RMS_WINDOW: f32 = 0.01;
+
+/// How much slower the program-dependent follower charges and releases
+const AGC_PROGRAM_ATTACK_SCALE: f32 = 10.0;
+const AGC_PROGRAM_RELEASE_SCALE: f32 = 5.0;
+
+/// Automatic Gain Control (AGC) for dynamic range compression
+#[derive(Clone)]
+pub struct AutomaticGainControl {
+    target_level: f32,
+    attack_time: f32,
+    release_time: f32,
+    current_gain: f32,
+    envelope_follower: f32,
+    detection: LevelDetection,
+    mean_square: f32,
+    program_dependent: bool,
+    program_follower: f32,
+    sample_rate: f32,
+}
+
+impl AutomaticGainControl {
+    /// Create a new AGC
+    /// - target_level: desired output level (0.0 - 1.0)
+    /// - attack_time: time to reduce gain when signal is loud (seconds)
+    /// - release_time: time to increase gain when signal is quiet (seconds)
+    pub fn new(target_level: f32, attack_time: f32, release_time: f32, sample_rate: f32) -> Self {
+        Self {
+            target_level: target_level.clamp(0.0, 1.0),
+            attack_time,
+            release_time,
             current_gain: 1.0,
             envelope_follower: 0.0,
+            detection: LevelDetection::Peak,
+            mean_square: 0.0,
+            program_dependent: false,
+            program_follower: 0.0,
+            sample_rate,
+        }
+    }
+
+    /// Set how the input level is measured
+    pub fn with_detection(mut self, detection: LevelDetection) -> Self {
+        self.detection = detection;
+        self
+    }
+
+    /// Enable program-dependent release
+    ///
+    /// A second, slower follower tracks sustained material. Gain recovers
+    /// quickly after isolated transients but slowly between the notes of
+    /// a phrase, which stops the AGC pumping on arpeggios.
+    pub fn with_program_dependent_release(mut self, enabled: bool) -> Self {
+        self.program_dependent = enabled;
+        self
+    }
+
+    /// Current gain applied to the signal, useful for metering
+    pub fn current_gain(&self) -> f32 {
+        self.current_gain
+    }
+
+    /// Process a single sample, without needing `SampleProcessor` in scope
+    pub fn process_sample(&mut self, input: f32) -> f32 {
+        SampleProcessor::process_sample(self, input)
+    }
+}
+
+/// Move an envelope follower towards a level with separate attack and release coefficients
+fn follow(envelope: f32, level: f32, attack_coeff: f32, release_coeff: f32) -> f32 {
+    let coeff = if level > envelope { attack_coeff } else { release_coeff };
+    level + (envelope - level) * coeff
+}
+
+impl SampleProcessor for AutomaticGainControl {
+    /// Process a single sample through the AGC
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let input_level = match self.detection {
+            LevelDetection::Peak => input.abs(),
+            LevelDetection::Rms => {
+                let coeff = time_coefficient(AGC_RMS_WINDOW, self.sample_rate);
+                self.mean_square = input * input + (self.mean_square - input * input) * coeff;
+                self.mean_square.sqrt()
+            }
+        };
+        
+        // Envelope follower: fast response to loud signals, slow response to quiet signals
+        let attack_coeff = (-1.0 / (self.attack_time * self.sample_rate)).exp();
+        let mut release_coeff = (-1.0 / (self.release_time * self.sample_rate)).exp();
+        self.envelope_follower = follow(self.envelope_follower, input_level, attack_coeff, release_coeff);
+        let mut level = self.envelope_follower;
+
+        if self.program_dependent {
+            // Only sustained material charges the slow follower; while it dominates, release slowly too
+            let program_attack = time_coefficient(self.attack_time * AGC_PROGRAM_ATTACK_SCALE, self.sample_rate);
+            let program_release = time_coefficient(self.release_time * AGC_PROGRAM_RELEASE_SCALE, self.sample_rate);
+            self.program_follower = follow(self.program_follower, input_level, program_attack, program_release);
+            if self.program_follower > level {
+                level = self.program_follower;
+                release_coeff = program_release;
+            }
+        }
+
+        // Calculate required gain
+        let required_gain = if level > 0.0001 {
+            self.target_level / level
+        } else {
+            1.0
+        };
+
+        // Smooth gain changes
+        let gain_diff = required_gain - self.current_gain;
+        let gain_coeff = if gain_diff > 0.0 { release_coeff } else { attack_coeff };
+        self.current_gain += gain_diff * (1.0 - gain_coeff);
+
+        // Limit gain to prevent excessive amplification
+        self.current_gain = self.current_gain.clamp(0.1, 10.0);
+
+        input * self.current_gain
+    }
+}
+
+/// AGC wrapper for any Source
+pub struct AGC<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    agc: AutomaticGainControl,
+    controls: MixControl,
+}
+
+impl<S> AGC<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new AGC effect
+    pub fn new(source: S, target_level: f32, attack_time: f32, release_time: f32) -> Self {
+        Self {
+            agc: AutomaticGainControl::new(target_level, attack_time, release_time, source.sample_rate() as f32),
+            source,
+            controls: MixControl::new(1.0),
+        }
+    }
+
+    /// Create an AGC effect from a configured gain control
+    pub fn from_processor(source: S, agc: AutomaticGainControl) -> Self {
+        Self {
+            source,
+            agc,
+            controls: MixControl::new(1.0),
+        }
+    }
+
+    /// Create a gentle AGC for musical content
+    ///
+    /// Uses RMS detection and program-dependent release so melodic
+    /// material is levelled without pumping between notes.
+    pub fn gentle(source: S) -> Self {
+        let agc = AutomaticGainControl::new(0.7, 0.003, 0.1, source.sample_rate() as f32) // Fast attack, slow release
+            .with_detection(LevelDetection::Rms)
+            .with_program_dependent_release(true);
+        Self::from_processor(source, agc)
+    }
+
+    /// Create a limiting AGC for preventing clipping
+    pub fn limiter(source: S) -> Self {
+        Self::new(source, 0.95, 0.001, 0.05) // Very fast attack, quick release
+    }
+}
+
+impl<S> Iterator for AGC<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.agc.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S> Source for AGC<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(AGC);
+
+/// Dynamic range compressor with threshold, ratio, soft knee and makeup gain
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Compressor {
+    threshold_db: f32,
+    ratio: f32,
+    knee_db: f32,
+    makeup_db: f32,
+    attack_coeff: f32,
+    release_coeff: f32,
+    sample_rate: f32,
+    /// Current gain reduction, which is running state rather than configuration
+    #[cfg_attr(feature = "serde", serde(skip))]
+    gain_reduction_db: f32,
+}
+
+impl Compressor {
+    /// Create a new compressor with a hard knee, 5ms attack and 100ms release
+    /// - threshold_db: level above which gain is reduced (dBFS)
+    /// - ratio: input/output slope above the threshold (4.0 = 4:1)
+    pub fn new(threshold_db: f32, ratio: f32, sample_rate: f32) -> Self {
+        Self {
+            threshold_db,
+            ratio: ratio.max(1.0),
+            knee_db: 0.0,
+            makeup_db: 0.0,
+            attack_coeff: time_coefficient(0.005, sample_rate),
+            release_coeff: time_coefficient(0.1, sample_rate),
             sample_rate,
+            gain_reduction_db: 0.0,
         }
     }
 
-    /// Process a single sample through the AGC
-    pub fn process_sample(&mut self, input: f32) -> f32 {
-        let input_level = input.abs();
-        
-        // Envelope follower (peak detector with decay)
-        let attack_coeff = (-1.0 / (self.attack_time * self.sample_rate)).exp();
-        let release_coeff = (-1.0 / (self.release_time * self.sample_rate)).exp();
-        
-        if input_level > self.envelope_follower {
-            // Attack: fast response to loud signals
-            self.envelope_follower = input_level + (self.envelope_follower - input_level) * attack_coeff;
+    /// Set the time to react to signals over the threshold (seconds)
+    pub fn with_attack(mut self, attack_time: f32) -> Self {
+        self.attack_coeff = time_coefficient(attack_time, self.sample_rate);
+        self
+    }
+
+    /// Set the time to recover once the signal falls (seconds)
+    pub fn with_release(mut self, release_time: f32) -> Self {
+        self.release_coeff = time_coefficient(release_time, self.sample_rate);
+        self
+    }
+
+    /// Set the width of the soft knee around the threshold (dB, 0 = hard knee)
+    pub fn with_knee(mut self, knee_db: f32) -> Self {
+        self.knee_db = knee_db.max(0.0);
+        self
+    }
+
+    /// Set the gain applied after compression (dB)
+    pub fn with_makeup(mut self, makeup_db: f32) -> Self {
+        self.makeup_db = makeup_db;
+        self
+    }
+
+    /// Check the threshold is at most 0 dBFS and the makeup gain is a usable level
+    pub fn validate(&self) -> Result<()> {
+        check_range("compressor threshold (dBFS)", self.threshold_db, -120.0..=0.0)?;
+        check_range("compressor makeup gain (dB)", self.makeup_db, -60.0..=60.0)
+    }
+
+    /// Current gain reduction in dB (zero or negative), useful for metering
+    pub fn gain_reduction_db(&self) -> f32 {
+        self.gain_reduction_db
+    }
+
+    /// Static output level (dB) for an input level (dB)
+    fn output_level_db(&self, input_db: f32) -> f32 {
+        let overshoot = input_db - self.threshold_db;
+        let slope = 1.0 / self.ratio - 1.0;
+
+        if 2.0 * overshoot < -self.knee_db {
+            input_db
+        } else if 2.0 * overshoot.abs() <= self.knee_db {
+            // Quadratic interpolation across the knee
+            input_db + slope * (overshoot + self.knee_db / 2.0).powi(2) / (2.0 * self.knee_db)
         } else {
-            // Release: slow response to quiet signals
-            self.envelope_follower = input_level + (self.envelope_follower - input_level) * release_coeff;
+            self.threshold_db + overshoot / self.ratio
         }
+    }
 
-        // Calculate required gain
-        let required_gain = if self.envelope_follower > 0.0001 {
-            self.target_level / self.envelope_follower
-        } else {
-            1.0
-        };
+    /// Process a single sample, without needing `SampleProcessor` in scope
+    pub fn process_sample(&mut self, input: f32) -> f32 {
+        SampleProcessor::process_sample(self, input)
+    }
+}
 
-        // Smooth gain changes
-        let gain_diff = required_gain - self.current_gain;
-        let gain_coeff = if gain_diff > 0.0 { release_coeff } else { attack_coeff };
-        self.current_gain += gain_diff * (1.0 - gain_coeff);
+impl SampleProcessor for Compressor {
+    /// Process a single sample through the compressor
+    fn process_sample(&mut self, input: f32) -> f32 {
+        let input_db = linear_to_db(input.abs());
+        let target = self.output_level_db(input_db) - input_db;
 
-        // Limit gain to prevent excessive amplification
-        self.current_gain = self.current_gain.clamp(0.1, 10.0);
+        // Smooth the gain reduction: attack when it deepens, release when it recovers
+        let coeff = if target < self.gain_reduction_db { self.attack_coeff } else { self.release_coeff };
+        self.gain_reduction_db = target + (self.gain_reduction_db - target) * coeff;
 
-        input * self.current_gain
+        input * db_to_linear(self.gain_reduction_db + self.makeup_db)
     }
 }
 
-/// AGC wrapper for any Source
-pub struct AGC<S>
+/// One-pole smoothing coefficient for a time constant in seconds
+fn time_coefficient(time: f32, sample_rate: f32) -> f32 {
+    if time <= 0.0 {
+        0.0
+    } else {
+        (-1.0 / (time * sample_rate)).exp()
+    }
+}
+
+/// Compressor wrapper for any Source
+pub struct Compression<S>
 where
     S: Source<Item = f32>,
 {
     source: S,
-    agc: AutomaticGainControl,
+    compressor: Compressor,
+    controls: MixControl,
 }
 
-impl<S> AGC<S>
+impl<S> Compression<S>
 where
     S: Source<Item = f32>,
 {
-    /// Create a new AGC effect
-    pub fn new(source: S, target_level: f32, attack_time: f32, release_time: f32) -> Self {
+    /// Create a new compression effect from a configured compressor
+    pub fn new(source: S, compressor: Compressor) -> Self {
+        Self { source, compressor, controls: MixControl::new(1.0) }
+    }
+
+    /// Create gentle glue compression for musical content
+    pub fn gentle(source: S) -> Self {
+        let compressor = Compressor::new(-18.0, 2.0, source.sample_rate() as f32)
+            .with_attack(0.01)
+            .with_release(0.15)
+            .with_knee(6.0)
+            .with_makeup(3.0);
+        Self::new(source, compressor)
+    }
+
+    /// Create punchy compression that tames transients
+    pub fn punchy(source: S) -> Self {
+        let compressor = Compressor::new(-12.0, 6.0, source.sample_rate() as f32)
+            .with_attack(0.002)
+            .with_release(0.08)
+            .with_knee(3.0)
+            .with_makeup(4.0);
+        Self::new(source, compressor)
+    }
+}
+
+impl<S> Iterator for Compression<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.source.next().map(|sample| {
+            let wet = self.compressor.process_sample(sample);
+            self.controls.output(sample, wet)
+        })
+    }
+}
+
+impl<S> Source for Compression<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_span_len(&self) -> Option<usize> {
+        self.source.current_span_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl_mix_effect!(Compression);
+
+/// Change the level of samples in place by a number of decibels
+pub fn apply_gain_db(samples: &mut [f32], gain_db: f32) {
+    GainStage::new(gain_db).process_buffer(samples);
+}
+
+/// Fixed gain expressed in decibels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GainStage {
+    gain_db: f32,
+    gain: f32,
+}
+
+impl GainStage {
+    /// Create a gain stage (0 dB = unity, -6 dB = about half amplitude)
+    pub fn new(gain_db: f32) -> Self {
         Self {
-            agc: AutomaticGainControl::new(target_level, attack_time, release_time, SAMPLE_RATE as f32),
-            source,
+            gain_db,
+            gain: db_to_linear(gain_db),
         }
     }
 
-    /// Create a gentle AGC for musical content
-    pub fn gentle(source: S) -> Self {
-        Self::new(source, 0.7, 0.003, 0.1) // Fast attack, slow release
+    /// Get the gain in dB
+    pub fn gain_db(&self) -> f32 {
+        self.gain_db
     }
 
-    /// Create a limiting AGC for preventing clipping
-    pub fn limiter(source: S) -> Self {
-        Self::new(source, 0.95, 0.001, 0.05) // Very fast attack, quick release
+    /// Set the gain in dB
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        *self = Self::new(gain_db);
     }
 }
 
-impl<S> Iterator for AGC<S>
+impl SampleProcessor for GainStage {
+    fn process_sample(&mut self, input: f32) -> f32 {
+        input * self.gain
+    }
+}
+
+/// Gain effect wrapper for any Source
+pub struct Gain<S>
+where
+    S: Source<Item = f32>,
+{
+    source: S,
+    gain: GainStage,
+    controls: MixControl,
+}
+
+impl<S> Gain<S>
+where
+    S: Source<Item = f32>,
+{
+    /// Create a new gain effect
+    /// - gain_db: level change in decibels
+    pub fn new(source: S, gain_db: f32) -> Self {
+        Self {
+            source,
+            gain: GainStage::new(gain_db),
+            controls: MixControl::new(1.0),
+        }
+    }
+}
+
+impl<S> Iterator for Gain<S>
 where
     S: Source<Item = f32>,
 {
@@ -416,12 +2234,13 @@ where
 
     fn next(&mut self) -> Option<Self::Item> {
         self.source.next().map(|sample| {
-            self.agc.process_sample(sample)
+            let wet = self.gain.process_sample(sample);
+            self.controls.output(sample, wet)
         })
     }
 }
 
-impl<S> Source for AGC<S>
+impl<S> Source for Gain<S>
 where
     S: Source<Item = f32>,
 {
@@ -442,6 +2261,8 @@ where
     }
 }
 
+impl_mix_effect!(Gain);
+
 /// Normalize audio samples to a target peak level
 pub fn normalize_samples(samples: &mut [f32], target_peak: f32) {
     if samples.is_empty() {
@@ -471,14 +2292,86 @@ pub fn peak_normalize(samples: Vec<f32>, target_peak: f32) -> Vec<f32> {
     normalized
 }
 
+/// Fade the start of samples at `sample_rate` in from silence over a duration (seconds)
+///
+/// Uses a raised-cosine curve, which avoids the audible corner of a linear ramp.
+pub fn fade_in(samples: &mut [f32], duration: f32, sample_rate: f32) {
+    let length = fade_length(samples.len(), duration, sample_rate);
+    for (i, sample) in samples.iter_mut().take(length).enumerate() {
+        *sample *= raised_cosine(i as f32 / length as f32);
+    }
+}
+
+/// Fade the end of samples at `sample_rate` out to silence over a duration (seconds)
+pub fn fade_out(samples: &mut [f32], duration: f32, sample_rate: f32) {
+    let length = fade_length(samples.len(), duration, sample_rate);
+    let start = samples.len() - length;
+    for (i, sample) in samples[start..].iter_mut().enumerate() {
+        *sample *= raised_cosine(1.0 - (i + 1) as f32 / length as f32);
+    }
+}
+
+/// Join two sample arrays, overlapping the end of `a` with the start of `b` for `overlap` seconds
+///
+/// Uses equal-power gains so the level doesn't dip in the middle of the transition.
+pub fn crossfade(a: &[f32], b: &[f32], overlap: f32, sample_rate: f32) -> Vec<f32> {
+    let length = fade_length(a.len().min(b.len()), overlap, sample_rate);
+    let split = a.len() - length;
+    let mut output = Vec::with_capacity(a.len() + b.len() - length);
+    output.extend_from_slice(&a[..split]);
+
+    for i in 0..length {
+        let position = (i as f32 + 0.5) / length as f32;
+        let angle = position * std::f32::consts::FRAC_PI_2;
+        output.push(a[split + i] * angle.cos() + b[i] * angle.sin());
+    }
+
+    output.extend_from_slice(&b[length..]);
+    output
+}
+
+/// Length of the micro-fades applied by `declick` (seconds)
+pub const DECLICK_DURATION: f32 = 0.002;
+
+/// Remove clicks at known discontinuities with micro-fades
+///
+/// Fades out just before and in just after each boundary (a sample index at `sample_rate`),
+/// and at the start and end of the buffer. Each fade is limited to half of its
+/// segment so very short notes aren't silenced.
+pub fn declick(samples: &mut [f32], boundaries: &[usize], sample_rate: f32) {
+    let mut points: Vec<usize> = boundaries.iter().copied().filter(|&point| point < samples.len()).collect();
+    points.push(0);
+    points.push(samples.len());
+    points.sort_unstable();
+    points.dedup();
+
+    for segment in points.windows(2) {
+        let segment = &mut samples[segment[0]..segment[1]];
+        let half = segment.len() / 2;
+        let length = segment.len();
+        fade_in(&mut segment[..half], DECLICK_DURATION, sample_rate);
+        fade_out(&mut segment[length - half..], DECLICK_DURATION, sample_rate);
+    }
+}
+
+/// Number of samples covered by a fade, limited to the available samples
+fn fade_length(available: usize, duration: f32, sample_rate: f32) -> usize {
+    ((duration.max(0.0) * sample_rate) as usize).min(available)
+}
+
+/// Raised-cosine gain rising from 0.0 to 1.0 as position goes from 0.0 to 1.0
+fn raised_cosine(position: f32) -> f32 {
+    0.5 - 0.5 * (std::f32::consts::PI * position.clamp(0.0, 1.0)).cos()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::audio::{Oscillator, WaveForm};
+    use crate::{SAMPLE_RATE, audio::{Oscillator, WaveForm}};
 
     #[test]
     fn test_delay_buffer() {
-        let mut delay = DelayBuffer::new(10.0, 0.5, 0.3); // 10ms delay
+        let mut delay = DelayBuffer::new(10.0, 0.5, 0.3, SAMPLE_RATE as f32); // 10ms delay
         let output1 = delay.process_sample(1.0);
         let output2 = delay.process_sample(0.0);
         
@@ -488,6 +2381,17 @@ mod tests {
         assert!(output2.abs() < 1.0);
     }
 
+    #[test]
+    fn test_delay_buffer_repeats_after_delay() {
+        let mut delay = DelayBuffer::new(1000.0 / SAMPLE_RATE as f32 * 10.0, 0.5, 1.0, SAMPLE_RATE as f32); // 10 samples
+        let mut impulse = vec![0.0; 25];
+        impulse[0] = 1.0;
+        let output = delay.process(impulse);
+        assert_eq!(output[10], 1.0);
+        assert_eq!(output[20], 0.5);
+        assert_eq!(output.iter().filter(|&&s| s != 0.0).count(), 2);
+    }
+
     #[test]
     fn test_echo_effect() {
         let osc = Oscillator::new(440.0, WaveForm::Sine, 0.1);
@@ -498,6 +2402,69 @@ mod tests {
         assert!(first_sample.unwrap().abs() <= 1.0);
     }
 
+    #[test]
+    fn test_processors_match_sources() {
+        let tone = || Oscillator::new(440.0, WaveForm::Sawtooth, 0.05);
+        let samples: Vec<f32> = tone().collect();
+
+        assert_eq!(DelayBuffer::new(20.0, 0.4, 0.3, SAMPLE_RATE as f32).process(samples.clone()), Echo::new(tone(), 20.0, 0.4, 0.3).collect::<Vec<_>>());
+        assert_eq!(DelayReverb::new(1.0, 0.5, 0.3, SAMPLE_RATE as f32).process(samples.clone()), Reverb::new(tone(), 1.0, 0.5, 0.3).collect::<Vec<_>>());
+        assert_eq!(LowPassFilter::new(2000.0, SAMPLE_RATE as f32).process(samples.clone()), LowPass::new(tone(), 2000.0).collect::<Vec<_>>());
+        assert_eq!(AutomaticGainControl::new(0.95, 0.001, 0.05, SAMPLE_RATE as f32).process(samples.clone()), AGC::limiter(tone()).collect::<Vec<_>>());
+        assert_eq!(Waveshaper::new(Shaper::Tanh, 2.0, 0.8, SAMPLE_RATE as f32).process(samples.clone()), Distortion::warm(tone()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_process_buffer_in_place() {
+        let samples: Vec<f32> = Oscillator::new(440.0, WaveForm::Sine, 0.05).collect();
+        let mut buffer = samples.clone();
+        PhaseShifter::new(4, 0.5, 0.3, 0.5, SAMPLE_RATE as f32).process_buffer(&mut buffer);
+        assert_eq!(buffer, PhaseShifter::new(4, 0.5, 0.3, 0.5, SAMPLE_RATE as f32).process(samples.clone()));
+        assert_ne!(buffer, samples);
+    }
+
+    #[test]
+    fn test_effect_mix_and_bypass() {
+        let tone: Vec<f32> = Oscillator::new(440.0, WaveForm::Square, 0.05).collect();
+        let mut lowpass = LowPass::muffled(Oscillator::new(440.0, WaveForm::Square, 0.05));
+        assert_eq!(lowpass.get_mix(), 1.0);
+        lowpass.set_bypass(true);
+        assert!(lowpass.is_bypassed());
+        assert_eq!(lowpass.collect::<Vec<_>>(), tone);
+
+        let mut reverb = StereoReverb::room(Oscillator::new(440.0, WaveForm::Square, 0.05));
+        reverb.set_mix(0.0);
+        let stereo: Vec<f32> = reverb.collect();
+        assert!(stereo.chunks(2).zip(&tone).all(|(frame, &dry)| frame == [dry, dry]));
+    }
+
+    #[test]
+    fn test_reverb_pre_delay() {
+        let mut impulse = vec![0.0; SAMPLE_RATE as usize / 10];
+        impulse[0] = 1.0;
+        let output = DelayReverb::new(1.0, 0.5, 1.0, SAMPLE_RATE as f32).with_pre_delay(10.0).process(impulse);
+
+        // Nothing before the pre-delay plus the shortest (29ms) line
+        let first = output.iter().position(|&s| s != 0.0).unwrap();
+        assert_eq!(first, (0.010 * SAMPLE_RATE as f32) as usize + (0.029 * SAMPLE_RATE as f32) as usize);
+    }
+
+    #[test]
+    fn test_reverb_damping_darkens_tail() {
+        let mut impulse = vec![0.0; SAMPLE_RATE as usize];
+        impulse[0] = 1.0;
+
+        // Ratio of sample-to-sample change to level: higher means brighter
+        let brightness = |samples: &[f32]| {
+            let tail = &samples[SAMPLE_RATE as usize / 4..];
+            let difference: f32 = tail.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum();
+            difference / tail.iter().map(|s| s * s).sum::<f32>()
+        };
+        let bright = DelayReverb::new(1.0, 0.0, 1.0, SAMPLE_RATE as f32).process(impulse.clone());
+        let dark = DelayReverb::new(1.0, 0.9, 1.0, SAMPLE_RATE as f32).process(impulse);
+        assert!(brightness(&dark) < brightness(&bright) * 0.5);
+    }
+
     #[test]
     fn test_reverb_effect() {
         let osc = Oscillator::new(440.0, WaveForm::Sine, 0.1);
@@ -508,6 +2475,67 @@ mod tests {
         assert!(first_sample.unwrap().abs() <= 1.0);
     }
 
+    #[test]
+    fn test_freeverb_impulse_response() {
+        let mut reverb = Freeverb::new(0.8, 0.5, 1.0, SAMPLE_RATE as f32).with_pre_delay(10.0);
+        let mut impulse = vec![0.0; SAMPLE_RATE as usize];
+        impulse[0] = 1.0;
+        let output = reverb.process(&impulse);
+        
+        assert_eq!(output.len(), impulse.len() * 2);
+        // Nothing arrives before the pre-delay and the two channels differ
+        assert!(output[..2 * 441].iter().all(|&s| s == 0.0));
+        let (left, right): (Vec<f32>, Vec<f32>) = output.chunks(2).map(|frame| (frame[0], frame[1])).unzip();
+        assert_ne!(left, right);
+        assert!(output.iter().all(|s| s.is_finite() && s.abs() < 1.0));
+    }
+
+    #[test]
+    fn test_freeverb_dry_only() {
+        let mut reverb = Freeverb::new(0.5, 0.5, 0.0, SAMPLE_RATE as f32);
+        assert_eq!(reverb.process(&[0.5, -0.25]), vec![0.5, 0.5, -0.25, -0.25]);
+    }
+
+    #[test]
+    fn test_freeverb_freeze_holds_tail() {
+        let rate = SAMPLE_RATE as usize;
+        let mut input = vec![0.0; rate * 2];
+        input[..rate / 20].iter_mut().enumerate().for_each(|(i, s)| *s = (i as f32 * 0.05).sin() * 0.5);
+        // Input after the trigger point is ignored
+        input[rate..rate + 100].iter_mut().for_each(|s| *s = 1.0);
+
+        let mut reverb = Freeverb::new(0.5, 0.5, 1.0, SAMPLE_RATE as f32).with_freeze_at(0.1);
+        let frozen = reverb.process(&input);
+        assert!(reverb.is_frozen());
+        let decaying = Freeverb::new(0.5, 0.5, 1.0, SAMPLE_RATE as f32).process(&input[..rate]);
+
+        let energy = |samples: &[f32], seconds: std::ops::Range<f32>| {
+            samples[(seconds.start * rate as f32) as usize * 2..(seconds.end * rate as f32) as usize * 2].iter().map(|s| s * s).sum::<f32>()
+        };
+        let early = energy(&frozen, 0.2..0.7);
+        assert!(energy(&frozen, 1.5..2.0) > early * 0.5);
+        assert!(energy(&decaying, 0.5..1.0) < early * 0.1);
+        assert!(frozen.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn test_freeverb_freeze_fade() {
+        let mut reverb = Freeverb::new(0.5, 0.5, 1.0, SAMPLE_RATE as f32).with_freeze_at(0.05).with_freeze_fade(0.5);
+        let mut input = vec![0.0; SAMPLE_RATE as usize];
+        input[0] = 1.0;
+        let output = reverb.process(&input);
+        assert!(output[SAMPLE_RATE as usize * 6 / 5..].iter().all(|&s| s == 0.0));
+        assert!(output[..SAMPLE_RATE as usize / 2].iter().any(|&s| s != 0.0));
+    }
+
+    #[test]
+    fn test_stereo_reverb_effect() {
+        let osc = Oscillator::new(440.0, WaveForm::Sine, 0.1);
+        let reverb = StereoReverb::hall(osc);
+        assert_eq!(reverb.channels(), 2);
+        assert_eq!(reverb.count(), 2 * (SAMPLE_RATE as f32 * 0.1) as usize);
+    }
+
     #[test]
     fn test_lowpass_filter() {
         let mut filter = LowPassFilter::new(1000.0, SAMPLE_RATE as f32);
@@ -529,6 +2557,149 @@ mod tests {
         assert!(first_sample.unwrap().abs() <= 1.0);
     }
 
+    #[test]
+    fn test_filter_sweep_effect() {
+        let osc = Oscillator::new(220.0, WaveForm::Sawtooth, 0.2);
+        let samples: Vec<f32> = FilterSweep::zap(osc, 0.2).collect();
+        assert_eq!(samples.len(), (SAMPLE_RATE as f32 * 0.2) as usize);
+        assert!(samples.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn test_phaser_dry_mix_is_transparent() {
+        let dry: Vec<f32> = Oscillator::new(440.0, WaveForm::Sine, 0.05).collect();
+        let phased: Vec<f32> = Phaser::new(Oscillator::new(440.0, WaveForm::Sine, 0.05), 4, 1.0, 0.5, 0.0).collect();
+        assert_eq!(dry, phased);
+    }
+
+    #[test]
+    fn test_phaser_effect() {
+        let dry: Vec<f32> = Oscillator::new(440.0, WaveForm::Sawtooth, 0.1).collect();
+        let phased: Vec<f32> = Phaser::deep(Oscillator::new(440.0, WaveForm::Sawtooth, 0.1)).collect();
+        assert_eq!(dry.len(), phased.len());
+        assert!(phased.iter().all(|s| s.is_finite() && s.abs() <= 2.0));
+        assert_ne!(dry, phased);
+    }
+
+    #[test]
+    fn test_shapers() {
+        assert_eq!(Shaper::HardClip.apply(3.0), 1.0);
+        assert_eq!(Shaper::HardClip.apply(-0.5), -0.5);
+        assert!((Shaper::Tanh.apply(0.01) - 0.01).abs() < 1e-5);
+        assert!((Shaper::Foldback.apply(1.5) - 0.5).abs() < 1e-6);
+        assert!((Shaper::Foldback.apply(-1.25) + 0.75).abs() < 1e-6);
+        assert!((-100..100).all(|i| Shaper::Foldback.apply(i as f32 * 0.37).abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_distortion_effect() {
+        let osc = Oscillator::new(440.0, WaveForm::Sine, 0.1);
+        let samples: Vec<f32> = Distortion::crunch(osc).collect();
+        assert_eq!(samples.len(), (SAMPLE_RATE as f32 * 0.1) as usize);
+        // Hard clipping at full scale times the 0.5 output gain, plus a little filter overshoot
+        assert!(samples.iter().all(|s| s.abs() <= 0.6));
+    }
+
+    #[test]
+    fn test_lfo_waveforms() {
+        let rate = SAMPLE_RATE as f32;
+        let mut square = Lfo::new(WaveForm::Square, rate / 4.0, rate);
+        let values: Vec<f32> = (0..4).map(|_| square.next_value()).collect();
+        assert_eq!(values, vec![1.0, 1.0, -1.0, -1.0]);
+        
+        let mut triangle = Lfo::new(WaveForm::Triangle, rate / 4.0, rate);
+        let values: Vec<f32> = (0..4).map(|_| triangle.next_value()).collect();
+        assert_eq!(values, vec![-1.0, 0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_lfo_depth_and_phase() {
+        let rate = SAMPLE_RATE as f32;
+        let mut sine = Lfo::new(LfoShape::Sine, rate / 4.0, rate).with_depth(0.5).with_phase(0.25);
+        assert!((sine.next_value() - 0.5).abs() < 1e-6);
+        assert!(sine.next_value().abs() < 1e-6);
+        assert!((sine.value_at(2.0 / rate) + 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lfo_sample_and_hold() {
+        let rate = SAMPLE_RATE as f32;
+        let mut lfo = Lfo::new(LfoShape::SampleAndHold, 10.0, rate).with_seed(7);
+        let values: Vec<f32> = (0..SAMPLE_RATE).map(|_| lfo.next_value()).collect();
+
+        // Held within each cycle, changing between cycles, and repeatable from a time
+        let cycle = SAMPLE_RATE as usize / 10;
+        assert!(values[..cycle - 10].iter().all(|&v| v == values[0]));
+        assert_ne!(values[0], values[cycle + 10]);
+        assert!(values.iter().all(|v| (-1.0..1.0).contains(v)));
+        assert_eq!(lfo.value_at(0.32), values[cycle * 3 + cycle / 5]);
+    }
+
+    #[test]
+    fn test_tremolo_slice() {
+        let mut samples = vec![1.0; SAMPLE_RATE as usize];
+        tremolo(&mut samples, 4.0, 0.5, WaveForm::Sine, SAMPLE_RATE as f32);
+        let min = samples.iter().copied().fold(f32::MAX, f32::min);
+        let max = samples.iter().copied().fold(f32::MIN, f32::max);
+        assert!((min - 0.5).abs() < 0.001);
+        assert!((max - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_tremolo_effect() {
+        let osc = Oscillator::new(440.0, WaveForm::Sine, 0.1);
+        let samples: Vec<f32> = Tremolo::pulse(osc).collect();
+        assert_eq!(samples.len(), (SAMPLE_RATE as f32 * 0.1) as usize);
+        // Full depth square modulation silences half of each cycle
+        assert!(samples.iter().filter(|s| **s == 0.0).count() > samples.len() / 3);
+    }
+
+    /// Signal power at one frequency (Goertzel algorithm)
+    fn power_at(samples: &[f32], frequency: f32) -> f32 {
+        let coefficient = 2.0 * (2.0 * std::f32::consts::PI * frequency / SAMPLE_RATE as f32).cos();
+        let (mut previous, mut before) = (0.0f32, 0.0f32);
+        for &sample in samples {
+            let current = sample + coefficient * previous - before;
+            before = previous;
+            previous = current;
+        }
+        (previous * previous + before * before - coefficient * previous * before) / samples.len() as f32
+    }
+
+    #[test]
+    fn test_ring_mod_sidebands() {
+        let tone = || Oscillator::new(440.0, WaveForm::Sine, 0.2);
+        let samples: Vec<f32> = RingMod::new(tone(), 100.0, 1.0).collect();
+        assert_eq!(samples, RingModulator::new(100.0, SAMPLE_RATE as f32).process(tone().collect()));
+        // Sum and difference frequencies replace the original
+        let sideband = power_at(&samples, 340.0).min(power_at(&samples, 540.0));
+        assert!(power_at(&samples, 440.0) < sideband * 0.05);
+    }
+
+    #[test]
+    fn test_pitch_shifter_octave_up() {
+        let tone: Vec<f32> = Oscillator::new(440.0, WaveForm::Sine, 0.5).collect();
+        let shifted = PitchShifter::new(12.0, SAMPLE_RATE as f32).process(tone);
+        let settled = &shifted[SAMPLE_RATE as usize / 10..];
+        assert!(power_at(settled, 880.0) > power_at(settled, 440.0) * 10.0);
+    }
+
+    #[test]
+    fn test_shimmer_effect() {
+        let mut samples: Vec<f32> = Oscillator::new(440.0, WaveForm::Sine, 0.1).collect();
+        samples.resize(SAMPLE_RATE as usize * 2, 0.0);
+        let output = ShimmerReverb::new(0.9, 0.6, SAMPLE_RATE as f32).process(samples);
+        assert!(output.iter().all(|s| s.is_finite() && s.abs() < 2.0));
+        // The tail keeps ringing long after the input stops
+        let tail = &output[SAMPLE_RATE as usize..];
+        assert!(tail.iter().any(|s| s.abs() > 1e-4));
+
+        let osc = Oscillator::new(440.0, WaveForm::Sine, 0.1);
+        let mut shimmer = Shimmer::new(osc, 0.5, 0.5, 0.3);
+        shimmer.set_bypass(true);
+        assert_eq!(shimmer.collect::<Vec<_>>(), Oscillator::new(440.0, WaveForm::Sine, 0.1).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_agc() {
         let mut agc = AutomaticGainControl::new(0.5, 0.01, 0.1, SAMPLE_RATE as f32);
@@ -544,6 +2715,42 @@ mod tests {
         assert!(output2.abs() <= 10.0);
     }
 
+    #[test]
+    fn test_agc_rms_detection() {
+        // A sine at 0.5 peak has an RMS level of about 0.354
+        let sine: Vec<f32> = (0..SAMPLE_RATE / 2)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect();
+        let mut peak = AutomaticGainControl::new(0.5, 0.01, 0.1, SAMPLE_RATE as f32);
+        let mut rms = AutomaticGainControl::new(0.5, 0.01, 0.1, SAMPLE_RATE as f32).with_detection(LevelDetection::Rms);
+        peak.process(sine.clone());
+        rms.process(sine);
+        assert!(peak.current_gain() < 1.2);
+        assert!((rms.current_gain() - 0.5 / 0.354).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_agc_program_dependent_release_reduces_pumping() {
+        // Arpeggio: short plucked notes with quick decays and gaps
+        let note = |frequency: f32| (0..SAMPLE_RATE as usize / 8).map(move |i| {
+            let time = i as f32 / SAMPLE_RATE as f32;
+            0.8 * (-time * 30.0).exp() * (2.0 * std::f32::consts::PI * frequency * time).sin()
+        });
+        let arpeggio: Vec<f32> = [262.0, 330.0, 392.0, 523.0].iter().cycle().take(16).flat_map(|&f| note(f)).collect();
+
+        let gain_spread = |mut agc: AutomaticGainControl| {
+            let gains: Vec<f32> = arpeggio.iter().skip(SAMPLE_RATE as usize / 2).map(|&s| {
+                agc.process_sample(s);
+                agc.current_gain()
+            }).collect();
+            let mean = gains.iter().sum::<f32>() / gains.len() as f32;
+            (gains.iter().map(|g| (g - mean).powi(2)).sum::<f32>() / gains.len() as f32).sqrt()
+        };
+        let plain = gain_spread(AutomaticGainControl::new(0.7, 0.003, 0.1, SAMPLE_RATE as f32));
+        let adaptive = gain_spread(AutomaticGainControl::new(0.7, 0.003, 0.1, SAMPLE_RATE as f32).with_program_dependent_release(true));
+        assert!(adaptive < plain * 0.7, "adaptive {} plain {}", adaptive, plain);
+    }
+
     #[test]
     fn test_agc_effect() {
         let osc = Oscillator::new(440.0, WaveForm::Sine, 0.1);
@@ -554,6 +2761,133 @@ mod tests {
         assert!(first_sample.unwrap().abs() <= 1.0);
     }
 
+    #[test]
+    fn test_compressor_static_curve() {
+        let compressor = Compressor::new(-20.0, 4.0, SAMPLE_RATE as f32);
+        assert_eq!(compressor.output_level_db(-30.0), -30.0);
+        assert_eq!(compressor.output_level_db(0.0), -15.0);
+        
+        // A soft knee bends the curve around the threshold
+        let soft = compressor.with_knee(6.0);
+        assert!((soft.output_level_db(-20.0) - (-20.0 - 0.75 * 6.0 / 8.0)).abs() < 1e-4);
+        assert_eq!(soft.output_level_db(-30.0), -30.0);
+        assert_eq!(soft.output_level_db(0.0), -15.0);
+    }
+
+    #[test]
+    fn test_compressor_settles_with_makeup() {
+        let mut compressor = Compressor::new(-20.0, 4.0, SAMPLE_RATE as f32).with_makeup(6.0);
+        let mut samples = vec![1.0; SAMPLE_RATE as usize / 10];
+        compressor.process_buffer(&mut samples);
+        
+        // 0 dBFS in, -15 dB after 4:1 above -20 dB, +6 dB makeup
+        let expected = 10.0_f32.powf(-9.0 / 20.0);
+        assert!((samples.last().unwrap() - expected).abs() < 0.001);
+        assert!((compressor.gain_reduction_db() + 15.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_compressor_processes_in_place() {
+        let samples: Vec<f32> = Oscillator::new(440.0, WaveForm::Sine, 0.05).collect();
+        let mut in_place = samples.clone();
+        Compressor::new(-20.0, 4.0, SAMPLE_RATE as f32).process_buffer(&mut in_place);
+        let processed = Compressor::new(-20.0, 4.0, SAMPLE_RATE as f32).process(samples);
+        assert_eq!(in_place, processed);
+    }
+
+    #[test]
+    fn test_compression_effect() {
+        let osc = Oscillator::new(440.0, WaveForm::Square, 0.1);
+        let samples: Vec<f32> = Compression::punchy(osc).collect();
+        assert_eq!(samples.len(), (SAMPLE_RATE as f32 * 0.1) as usize);
+        assert!(samples.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn test_fades() {
+        let mut samples = vec![1.0; 1000];
+        let duration = 100.0 / SAMPLE_RATE as f32;
+        fade_in(&mut samples, duration, SAMPLE_RATE as f32);
+        fade_out(&mut samples, duration, SAMPLE_RATE as f32);
+        
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(*samples.last().unwrap(), 0.0);
+        assert_eq!(samples[500], 1.0);
+        assert!(samples[..100].windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(samples[900..].windows(2).all(|pair| pair[0] >= pair[1]));
+        
+        // The fade length follows the given rate
+        let mut samples = vec![1.0; 1000];
+        fade_in(&mut samples, 0.01, 8000.0);
+        assert!(samples[79] < 1.0);
+        assert_eq!(samples[80], 1.0);
+    }
+
+    #[test]
+    fn test_fade_longer_than_samples() {
+        let mut samples = vec![1.0; 10];
+        fade_out(&mut samples, 1.0, SAMPLE_RATE as f32);
+        assert_eq!(samples[9], 0.0);
+        assert!(samples[0] > 0.9);
+    }
+
+    #[test]
+    fn test_declick_boundaries() {
+        let fade = (DECLICK_DURATION * SAMPLE_RATE as f32) as usize;
+        let mut samples = vec![1.0; 1000];
+        declick(&mut samples, &[500, 2000], SAMPLE_RATE as f32);
+
+        // Silent at the edges and at the boundary, untouched away from them
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[500], 0.0);
+        assert!(samples[499] < 0.01);
+        assert_eq!(samples[500 + fade], 1.0);
+        assert_eq!(samples[250], 1.0);
+        assert!(samples[999] < 0.01);
+    }
+
+    #[test]
+    fn test_declick_short_segment() {
+        let mut samples = vec![1.0; 40];
+        declick(&mut samples, &[10], SAMPLE_RATE as f32);
+        // Fades share the 10 sample segment instead of overlapping
+        assert!(samples[..10].iter().all(|s| (0.0..1.0).contains(s)));
+        assert!(samples[4] > 0.5);
+    }
+
+    #[test]
+    fn test_crossfade() {
+        let a = vec![1.0; 300];
+        let b = vec![-1.0; 200];
+        let joined = crossfade(&a, &b, 100.0 / SAMPLE_RATE as f32, SAMPLE_RATE as f32);
+        
+        assert_eq!(joined.len(), 400);
+        assert_eq!(joined[199], 1.0);
+        assert_eq!(joined[300], -1.0);
+        // Equal-power gains meet at about 0.707 each in the middle
+        assert!(joined[250].abs() < 0.05);
+    }
+
+    #[test]
+    fn test_db_conversions() {
+        assert_eq!(db_to_linear(0.0), 1.0);
+        assert!((db_to_linear(-6.0) - 0.501).abs() < 0.001);
+        assert!((linear_to_db(2.0) - 6.0206).abs() < 0.001);
+        assert!((linear_to_db(db_to_linear(-23.5)) + 23.5).abs() < 1e-4);
+        assert_eq!(linear_to_db(0.0), -120.0);
+    }
+
+    #[test]
+    fn test_gain_effect() {
+        let mut samples = vec![0.5, -0.25];
+        apply_gain_db(&mut samples, 20.0);
+        assert!((samples[0] - 5.0).abs() < 1e-5 && (samples[1] + 2.5).abs() < 1e-5);
+
+        let tone: Vec<f32> = Oscillator::new(440.0, WaveForm::Sine, 0.05).collect();
+        let quieter: Vec<f32> = Gain::new(Oscillator::new(440.0, WaveForm::Sine, 0.05), -6.0).collect();
+        assert!(quieter.iter().zip(&tone).all(|(q, t)| (q - t * db_to_linear(-6.0)).abs() < 1e-6));
+    }
+
     #[test]
     fn test_normalize_samples() {
         let mut samples = vec![0.1, -0.5, 0.3, -0.8];
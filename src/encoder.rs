@@ -0,0 +1,523 @@
+//! Audio encoders used by the export layer
+
+use std::io::{Seek, Write};
+use hound::{WavSpec, WavWriter, SampleFormat};
+use rand::{Rng, SeedableRng};
+use crate::{channels::{ChannelLayout, deinterleave}, export::{Dither, DefaultRng}, markers::{LoopRegion, CueMarker, smpl_chunk, cue_chunk, label_chunk, insert_chunks}, error::Result};
+#[cfg(feature = "mp3")]
+use crate::export::{Mp3Options, Mp3BitrateMode};
+
+/// Format of the samples handed to an encoder
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EncodeSpec {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// Encoder turning f32 samples into a file format
+///
+/// Samples are interleaved when `spec.channels` is greater than one.
+/// Implement this trait and register it with `JingleGenerator::register_encoder`
+/// to export formats the crate doesn't support natively.
+pub trait AudioEncoder: Send + Sync {
+    /// Encode samples (-1.0 to 1.0) and write the encoded bytes to the output
+    fn encode(&self, samples: &[f32], spec: EncodeSpec, output: &mut dyn Write) -> Result<()>;
+}
+
+/// Largest 24-bit sample value
+const MAX_24: f32 = 8_388_607.0;
+/// Offset of `dwChannelMask` in the WAVE_FORMAT_EXTENSIBLE header hound writes
+const CHANNEL_MASK_OFFSET: usize = 40;
+
+/// Sample format of WAV and raw PCM exports
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum BitDepth {
+    /// 16-bit integer PCM, dithered with the encoder's `Dither`
+    #[default]
+    Int16,
+    /// 24-bit integer PCM, rounded without dither
+    Int24,
+    /// 32-bit float, written unquantized
+    Float32,
+}
+
+impl BitDepth {
+    /// Bit depth for a number of bits per sample (16, 24 or 32)
+    pub fn from_bits(bits: u16) -> Option<Self> {
+        match bits {
+            16 => Some(BitDepth::Int16),
+            24 => Some(BitDepth::Int24),
+            32 => Some(BitDepth::Float32),
+            _ => None,
+        }
+    }
+
+    /// Bits per sample
+    pub fn bits(self) -> u16 {
+        match self {
+            BitDepth::Int16 => 16,
+            BitDepth::Int24 => 24,
+            BitDepth::Float32 => 32,
+        }
+    }
+}
+
+/// PCM WAV encoder, 16-bit unless `bit_depth` says otherwise
+#[derive(Clone, Debug, Default)]
+pub struct WavEncoder {
+    pub bit_depth: BitDepth,
+    pub dither: Dither,
+    /// Seed for the dither noise so exports are reproducible
+    pub dither_seed: u64,
+    /// Loop written to the `smpl` chunk, in frames at the encoded sample rate
+    pub loop_region: Option<LoopRegion>,
+    /// Markers written to the `cue ` and `LIST` chunks, in frames at the encoded sample rate
+    pub cue_markers: Vec<CueMarker>,
+    /// Speaker mask of extensible headers, or `None` for the layout matching the channel count
+    pub channel_mask: Option<u32>,
+}
+
+impl AudioEncoder for WavEncoder {
+    fn encode(&self, samples: &[f32], spec: EncodeSpec, output: &mut dyn Write) -> Result<()> {
+        if spec.channels == 0 {
+            return Err(crate::error::JingleError::InvalidParameter("WAV needs at least one channel".to_string()));
+        }
+
+        let channels = deinterleave(samples, spec.channels as usize);
+        let frames = channels.iter().map(|channel| channel.len()).max().unwrap_or(0);
+
+        let wav_spec = WavSpec {
+            channels: spec.channels,
+            sample_rate: spec.sample_rate,
+            bits_per_sample: self.bit_depth.bits(),
+            sample_format: if self.bit_depth == BitDepth::Float32 { SampleFormat::Float } else { SampleFormat::Int },
+        };
+
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        let mut writer = WavWriter::new(&mut buffer, wav_spec)?;
+
+        match self.bit_depth {
+            BitDepth::Int16 => {
+                // Quantize each channel separately so noise shaping doesn't leak between channels
+                let pcm_channels: Vec<Vec<i16>> = channels.iter()
+                    .map(|channel| quantize(channel, 1.0, self.dither, self.dither_seed))
+                    .collect();
+                write_frames(&mut writer, &pcm_channels, frames)?;
+            },
+            BitDepth::Int24 => {
+                let pcm_channels: Vec<Vec<i32>> = channels.iter()
+                    .map(|channel| channel.iter().map(|&sample| (sample * MAX_24).round().clamp(-MAX_24 - 1.0, MAX_24) as i32).collect())
+                    .collect();
+                write_frames(&mut writer, &pcm_channels, frames)?;
+            },
+            BitDepth::Float32 => write_frames(&mut writer, &channels, frames)?,
+        }
+
+        writer.finalize()?;
+
+        let mut chunks = Vec::new();
+        if let Some(region) = self.loop_region {
+            region.validate(frames)?;
+            chunks.push((*b"smpl", smpl_chunk(&[region], spec.sample_rate)));
+        }
+        if !self.cue_markers.is_empty() {
+            if let Some(marker) = self.cue_markers.iter().find(|marker| marker.position as usize > frames) {
+                return Err(crate::error::JingleError::InvalidParameter(format!(
+                    "Cue marker '{}' at frame {} is past the end of {} frames",
+                    marker.label, marker.position, frames
+                )));
+            }
+            chunks.push((*b"cue ", cue_chunk(&self.cue_markers)));
+            chunks.push((*b"LIST", label_chunk(&self.cue_markers)));
+        }
+
+        let mut wav = buffer.into_inner();
+        let mask = self.channel_mask.unwrap_or_else(|| ChannelLayout::from_channel_count(spec.channels).channel_mask());
+        set_channel_mask(&mut wav, mask);
+        if !chunks.is_empty() {
+            wav = insert_chunks(&wav, &chunks)?;
+        }
+        output.write_all(&wav)?;
+        Ok(())
+    }
+}
+
+/// Headerless little-endian PCM: interleaved s16le, s24le or f32le depending on `bit_depth`
+///
+/// Players need the sample rate, channel count and sample format given separately,
+/// e.g. `aplay -f S16_LE -r 44100 -c 1`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RawPcmEncoder {
+    pub bit_depth: BitDepth,
+    pub dither: Dither,
+    /// Seed for the dither noise so exports are reproducible
+    pub dither_seed: u64,
+}
+
+impl AudioEncoder for RawPcmEncoder {
+    fn encode(&self, samples: &[f32], _spec: EncodeSpec, output: &mut dyn Write) -> Result<()> {
+        let bytes: Vec<u8> = match self.bit_depth {
+            // Quantized as one stream, so noise shaping runs across interleaved channels
+            BitDepth::Int16 => quantize(samples, 1.0, self.dither, self.dither_seed)
+                .into_iter()
+                .flat_map(i16::to_le_bytes)
+                .collect(),
+            BitDepth::Int24 => samples.iter()
+                .flat_map(|&sample| {
+                    let value = (sample * MAX_24).round().clamp(-MAX_24 - 1.0, MAX_24) as i32;
+                    value.to_le_bytes().into_iter().take(3)
+                })
+                .collect(),
+            BitDepth::Float32 => samples.iter().flat_map(|sample| sample.to_le_bytes()).collect(),
+        };
+        output.write_all(&bytes)?;
+        Ok(())
+    }
+}
+
+/// MP3 encoder backed by LAME (mono or stereo)
+#[cfg(feature = "mp3")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mp3Encoder {
+    pub options: Mp3Options,
+}
+
+#[cfg(feature = "mp3")]
+impl AudioEncoder for Mp3Encoder {
+    fn encode(&self, samples: &[f32], spec: EncodeSpec, output: &mut dyn Write) -> Result<()> {
+        use mp3lame_encoder::{Builder, FlushNoGap, Mode, MonoPcm, InterleavedPcm, VbrMode};
+
+        let mode = match spec.channels {
+            1 => Mode::Mono,
+            2 if self.options.joint_stereo => Mode::JointStereo,
+            2 => Mode::Stereo,
+            n => return Err(crate::error::JingleError::Mp3Error(format!("MP3 supports 1 or 2 channels, got {}", n))),
+        };
+
+        // Initialize LAME encoder
+        let mut builder = Builder::new()
+            .ok_or_else(|| crate::error::JingleError::Mp3Error("Failed to create LAME encoder".to_string()))?;
+        builder.set_num_channels(spec.channels as u8)?;
+        builder.set_sample_rate(spec.sample_rate)?;
+        builder.set_mode(mode)?;
+        builder.set_quality(lame_quality(self.options.quality))?;
+
+        match self.options.mode {
+            Mp3BitrateMode::Cbr(kbps) => {
+                builder.set_vbr_mode(VbrMode::Off)?;
+                builder.set_brate(lame_bitrate(kbps))?;
+            },
+            Mp3BitrateMode::Vbr(quality) => {
+                builder.set_vbr_mode(VbrMode::Mtrh)?;
+                builder.set_vbr_quality(lame_quality(quality))?;
+            },
+            Mp3BitrateMode::Abr(kbps) => {
+                builder.set_vbr_mode(VbrMode::Abr)?;
+                builder.set_brate(lame_bitrate(kbps))?;
+                // SAFETY: the pointer is owned by the builder and only used for this setter call
+                let result = unsafe {
+                    mp3lame_encoder::ffi::lame_set_VBR_mean_bitrate_kbps(builder.as_ptr(), kbps as i32)
+                };
+                if result < 0 {
+                    return Err(crate::error::JingleError::Mp3Error(format!("Invalid ABR bitrate: {} kbps", kbps)));
+                }
+            },
+        }
+
+        let mut encoder = builder.build()?;
+
+        // LAME takes float input directly, but expects it within full scale
+        let pcm: Vec<f32> = samples.iter().map(|sample| sample.clamp(-1.0, 1.0)).collect();
+
+        // Reserve room for the encoded data plus the final flushed frames
+        let mut mp3_buffer = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(pcm.len()));
+        if spec.channels == 1 {
+            encoder.encode_to_vec(MonoPcm(pcm.as_slice()), &mut mp3_buffer)?;
+        } else {
+            encoder.encode_to_vec(InterleavedPcm(pcm.as_slice()), &mut mp3_buffer)?;
+        }
+
+        // Flush buffered frames so the tail of the jingle isn't cut off
+        mp3_buffer.reserve(7200);
+        encoder.flush_to_vec::<FlushNoGap>(&mut mp3_buffer)?;
+
+        output.write_all(&mp3_buffer)?;
+        Ok(())
+    }
+}
+
+/// Ogg Opus encoder (mono or stereo)
+#[cfg(feature = "opus")]
+#[derive(Clone, Copy, Debug)]
+pub struct OpusEncoder {
+    /// Target bitrate in bits per second
+    pub bitrate: u32,
+    /// Ogg stream serial number
+    pub serial: u32,
+}
+
+#[cfg(feature = "opus")]
+impl Default for OpusEncoder {
+    fn default() -> Self {
+        Self { bitrate: 64000, serial: 0 }
+    }
+}
+
+#[cfg(feature = "opus")]
+impl AudioEncoder for OpusEncoder {
+    fn encode(&self, samples: &[f32], spec: EncodeSpec, output: &mut dyn Write) -> Result<()> {
+        use ogg::writing::{PacketWriter, PacketWriteEndInfo};
+        use crate::{channels::interleave, resample::resample};
+
+        // Opus always runs at 48 kHz internally; 20ms frames
+        const OPUS_SAMPLE_RATE: u32 = 48000;
+        const FRAME_SIZE: usize = 960;
+
+        let channel_mode = match spec.channels {
+            1 => opus::Channels::Mono,
+            2 => opus::Channels::Stereo,
+            n => return Err(crate::error::JingleError::OpusError(format!("Opus supports 1 or 2 channels, got {}", n))),
+        };
+        let channel_count = spec.channels as usize;
+
+        let resampled: Vec<Vec<f32>> = deinterleave(samples, channel_count)
+            .iter()
+            .map(|channel| resample(channel, spec.sample_rate, OPUS_SAMPLE_RATE))
+            .collect();
+        let channel_refs: Vec<&[f32]> = resampled.iter().map(|channel| channel.as_slice()).collect();
+        let pcm = interleave(&channel_refs);
+        let frames = pcm.len() / channel_count;
+
+        let mut encoder = opus::Encoder::new(OPUS_SAMPLE_RATE, channel_mode, opus::Application::Audio)?;
+        encoder.set_bitrate(opus::Bitrate::Bits(self.bitrate as i32))?;
+        let pre_skip = encoder.get_lookahead()? as u16;
+
+        let mut data = Vec::new();
+        let mut writer = PacketWriter::new(&mut data);
+
+        // Identification header (RFC 7845 section 5.1)
+        let mut head = b"OpusHead".to_vec();
+        head.push(1); // Version
+        head.push(spec.channels as u8);
+        head.extend(pre_skip.to_le_bytes());
+        head.extend(spec.sample_rate.to_le_bytes());
+        head.extend(0i16.to_le_bytes()); // Output gain
+        head.push(0); // Channel mapping family
+        writer.write_packet(head, self.serial, PacketWriteEndInfo::EndPage, 0)?;
+
+        // Comment header (RFC 7845 section 5.2)
+        let vendor = concat!("jinglebells ", env!("CARGO_PKG_VERSION"));
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend((vendor.len() as u32).to_le_bytes());
+        tags.extend(vendor.as_bytes());
+        tags.extend(0u32.to_le_bytes()); // No user comments
+        writer.write_packet(tags, self.serial, PacketWriteEndInfo::EndPage, 0)?;
+
+        // Encode enough frames to flush the encoder lookahead past the end of the audio
+        let total_frames = frames + pre_skip as usize;
+        let packet_count = total_frames.div_ceil(FRAME_SIZE).max(1);
+        let mut packet = vec![0u8; 4000];
+
+        for i in 0..packet_count {
+            let start = (i * FRAME_SIZE * channel_count).min(pcm.len());
+            let end = (start + FRAME_SIZE * channel_count).min(pcm.len());
+            let mut frame = vec![0.0; FRAME_SIZE * channel_count];
+            frame[..end - start].copy_from_slice(&pcm[start..end]);
+
+            let length = encoder.encode_float(&frame, &mut packet)?;
+            let is_last = i + 1 == packet_count;
+            let (end_info, granule) = if is_last {
+                (PacketWriteEndInfo::EndStream, total_frames as u64)
+            } else {
+                (PacketWriteEndInfo::NormalPacket, ((i + 1) * FRAME_SIZE) as u64)
+            };
+            writer.write_packet(packet[..length].to_vec(), self.serial, end_info, granule)?;
+        }
+
+        drop(writer);
+        output.write_all(&data)?;
+        Ok(())
+    }
+}
+
+/// Replace hound's default speaker mask, `(1 << channels) - 1`, in a WAVE_FORMAT_EXTENSIBLE header
+///
+/// Headers in the plain PCM format (mono or stereo up to 16 bits) have no mask and are left alone.
+fn set_channel_mask(wav: &mut [u8], mask: u32) {
+    if wav.get(12..16) == Some(b"fmt ") && wav.get(20..22) == Some(&0xFFFE_u16.to_le_bytes()) {
+        wav[CHANNEL_MASK_OFFSET..CHANNEL_MASK_OFFSET + 4].copy_from_slice(&mask.to_le_bytes());
+    }
+}
+
+/// Interleave planar channels into a WAV writer, padding shorter channels with silence
+fn write_frames<T, W>(writer: &mut WavWriter<W>, channels: &[Vec<T>], frames: usize) -> Result<()>
+where
+    T: hound::Sample + Copy + Default,
+    W: Write + Seek,
+{
+    for frame in 0..frames {
+        for channel in channels {
+            writer.write_sample(channel.get(frame).copied().unwrap_or_default())?;
+        }
+    }
+    Ok(())
+}
+
+/// Scale, dither and round f32 samples (-1.0 to 1.0) to i16
+pub(crate) fn quantize(samples: &[f32], gain: f32, dither: Dither, dither_seed: u64) -> Vec<i16> {
+    let scale = i16::MAX as f32 * gain;
+    // Separate RNG so dithering never disturbs the generation sequence
+    let mut dither_rng = DefaultRng::seed_from_u64(dither_seed);
+    let mut error = 0.0f32;
+
+    samples.iter().map(|&sample| {
+        let scaled = sample * scale;
+        let value = match dither {
+            Dither::None => scaled,
+            Dither::Tpdf => scaled + tpdf_noise(&mut dither_rng),
+            Dither::NoiseShaped => {
+                // Subtract the previous quantization error before dithering
+                let shaped = scaled - error;
+                let quantized = (shaped + tpdf_noise(&mut dither_rng)).round().clamp(i16::MIN as f32, i16::MAX as f32);
+                error = quantized - shaped;
+                quantized
+            }
+        };
+        value.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    }).collect()
+}
+
+/// Triangular probability density noise spanning ±1 LSB
+fn tpdf_noise(rng: &mut DefaultRng) -> f32 {
+    rng.gen_range(-0.5..0.5) + rng.gen_range(-0.5..0.5)
+}
+
+/// Map a 0-9 quality level onto LAME's quality setting
+#[cfg(feature = "mp3")]
+fn lame_quality(quality: u8) -> mp3lame_encoder::Quality {
+    use mp3lame_encoder::Quality;
+    match quality {
+        0 => Quality::Best,
+        1 => Quality::SecondBest,
+        2 => Quality::NearBest,
+        3 => Quality::VeryNice,
+        4 => Quality::Nice,
+        5 => Quality::Good,
+        6 => Quality::Decent,
+        7 => Quality::Ok,
+        8 => Quality::SecondWorst,
+        _ => Quality::Worst,
+    }
+}
+
+/// Map a bitrate in kbps onto the closest bitrate supported by MP3
+#[cfg(feature = "mp3")]
+fn lame_bitrate(kbps: u32) -> mp3lame_encoder::Bitrate {
+    use mp3lame_encoder::Bitrate;
+    let bitrates = [
+        (8, Bitrate::Kbps8), (16, Bitrate::Kbps16), (24, Bitrate::Kbps24), (32, Bitrate::Kbps32),
+        (40, Bitrate::Kbps40), (48, Bitrate::Kbps48), (64, Bitrate::Kbps64), (80, Bitrate::Kbps80),
+        (96, Bitrate::Kbps96), (112, Bitrate::Kbps112), (128, Bitrate::Kbps128), (160, Bitrate::Kbps160),
+        (192, Bitrate::Kbps192), (224, Bitrate::Kbps224), (256, Bitrate::Kbps256), (320, Bitrate::Kbps320),
+    ];
+    bitrates.iter()
+        .min_by_key(|(rate, _)| (*rate as i64 - kbps as i64).abs())
+        .map(|(_, bitrate)| *bitrate)
+        .unwrap_or(Bitrate::Kbps192)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wav_encoder_stereo() {
+        let encoder = WavEncoder::default();
+        let mut output = Vec::new();
+        encoder.encode(&[0.5, -0.5, 0.25, -0.25], EncodeSpec { sample_rate: 8000, channels: 2 }, &mut output).unwrap();
+
+        let mut reader = hound::WavReader::new(std::io::Cursor::new(output)).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.spec().sample_rate, 8000);
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(samples, vec![16384, -16384, 8192, -8192]);
+    }
+
+    #[test]
+    fn test_wav_encoder_bit_depths() {
+        let samples = [0.5, -0.25, 1.5];
+        let encode = |bit_depth| {
+            let mut output = Vec::new();
+            WavEncoder { bit_depth, ..WavEncoder::default() }
+                .encode(&samples, EncodeSpec { sample_rate: 8000, channels: 1 }, &mut output)
+                .unwrap();
+            hound::WavReader::new(std::io::Cursor::new(output)).unwrap()
+        };
+
+        let mut reader = encode(BitDepth::Int24);
+        assert_eq!((reader.spec().bits_per_sample, reader.spec().sample_format), (24, SampleFormat::Int));
+        let pcm: Vec<i32> = reader.samples::<i32>().map(|s| s.unwrap()).collect();
+        assert_eq!(pcm, vec![4_194_304, -2_097_152, 8_388_607]);
+
+        let mut reader = encode(BitDepth::Float32);
+        assert_eq!((reader.spec().bits_per_sample, reader.spec().sample_format), (32, SampleFormat::Float));
+        let float: Vec<f32> = reader.samples::<f32>().map(|s| s.unwrap()).collect();
+        assert_eq!(float, samples);
+
+        assert_eq!(BitDepth::from_bits(24), Some(BitDepth::Int24));
+        assert_eq!(BitDepth::from_bits(8), None);
+        assert_eq!(BitDepth::Float32.bits(), 32);
+    }
+
+    #[test]
+    fn test_raw_pcm_encoder() {
+        let samples = [0.5, -0.5, 1.5];
+        let encode = |bit_depth| {
+            let mut output = Vec::new();
+            RawPcmEncoder { bit_depth, ..RawPcmEncoder::default() }
+                .encode(&samples, EncodeSpec { sample_rate: 8000, channels: 1 }, &mut output)
+                .unwrap();
+            output
+        };
+        assert_eq!(encode(BitDepth::Int16), [16384i16, -16384, 32767].into_iter().flat_map(i16::to_le_bytes).collect::<Vec<u8>>());
+        assert_eq!(encode(BitDepth::Int24), [0x00, 0x00, 0x40, 0x00, 0x00, 0xc0, 0xff, 0xff, 0x7f]);
+        assert_eq!(encode(BitDepth::Float32), samples.into_iter().flat_map(f32::to_le_bytes).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_wav_encoder_loop_chunk() {
+        let encoder = WavEncoder { loop_region: Some(LoopRegion::new(2, 8)), ..WavEncoder::default() };
+        let mut output = Vec::new();
+        encoder.encode(&[0.1; 10], EncodeSpec { sample_rate: 8000, channels: 1 }, &mut output).unwrap();
+
+        // The smpl chunk sits ahead of the samples and readers skip it
+        let smpl = output.windows(4).position(|w| w == b"smpl").unwrap();
+        let data = output.windows(4).position(|w| w == b"data").unwrap();
+        assert!(smpl < data);
+        assert_eq!(u32::from_le_bytes(output[4..8].try_into().unwrap()) as usize, output.len() - 8);
+        let reader = hound::WavReader::new(std::io::Cursor::new(output)).unwrap();
+        assert_eq!(reader.len(), 10);
+
+        let invalid = WavEncoder { loop_region: Some(LoopRegion::new(2, 20)), ..WavEncoder::default() };
+        assert!(invalid.encode(&[0.1; 10], EncodeSpec { sample_rate: 8000, channels: 1 }, &mut Vec::new()).is_err());
+    }
+
+    #[cfg(feature = "mp3")]
+    #[test]
+    fn test_mp3_bitrate_mapping() {
+        assert_eq!(lame_bitrate(190) as u16, 192);
+        assert_eq!(lame_bitrate(1000) as u16, 320);
+        assert_eq!(lame_bitrate(0) as u16, 8);
+    }
+
+    #[cfg(feature = "mp3")]
+    #[test]
+    fn test_mp3_encoder_stereo() {
+        let encoder = Mp3Encoder { options: Mp3Options::vbr(4) };
+        let samples: Vec<f32> = (0..8820).map(|i| (i as f32 * 0.05).sin() * 0.5).collect();
+        let mut output = Vec::new();
+        encoder.encode(&samples, EncodeSpec { sample_rate: 44100, channels: 2 }, &mut output).unwrap();
+        assert!(!output.is_empty());
+    }
+}
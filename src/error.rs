@@ -1,6 +1,7 @@
 //! Error types for the jingle maker library
 
 use std::fmt;
+use crate::clipping::ClippingReport;
 
 /// Main error type for jingle generation operations
 #[derive(Debug)]
@@ -13,12 +14,20 @@ pub enum JingleError {
     WavError(hound::Error),
     /// MP3 encoding error
     Mp3Error(String),
+    /// Opus encoding error
+    OpusError(String),
     /// Invalid parameter error
     InvalidParameter(String),
     /// Audio playback error
     PlaybackError(String),
     /// Random number generation error
     RandomError(String),
+    /// SoundFont file could not be parsed
+    SoundFontError(String),
+    /// A render was stopped through its `CancelToken`
+    Cancelled,
+    /// A final render went beyond full scale under `OutputPolicy::ErrorOnClip`
+    Clipping(ClippingReport),
 }
 
 impl fmt::Display for JingleError {
@@ -28,9 +37,17 @@ impl fmt::Display for JingleError {
             JingleError::IoError(err) => write!(f, "I/O error: {}", err),
             JingleError::WavError(err) => write!(f, "WAV encoding error: {}", err),
             JingleError::Mp3Error(msg) => write!(f, "MP3 encoding error: {}", msg),
+            JingleError::OpusError(msg) => write!(f, "Opus encoding error: {}", msg),
             JingleError::InvalidParameter(msg) => write!(f, "Invalid parameter: {}", msg),
             JingleError::PlaybackError(msg) => write!(f, "Audio playback error: {}", msg),
             JingleError::RandomError(msg) => write!(f, "Random generation error: {}", msg),
+            JingleError::SoundFontError(msg) => write!(f, "SoundFont error: {}", msg),
+            JingleError::Cancelled => write!(f, "Render cancelled"),
+            JingleError::Clipping(report) => write!(
+                f,
+                "Output clips: {} samples beyond full scale (peak {:.2}), first at sample {}",
+                report.count, report.peak, report.positions.first().copied().unwrap_or(0)
+            ),
         }
     }
 }
@@ -58,18 +75,42 @@ impl From<hound::Error> for JingleError {
 }
 
 #[cfg(feature = "mp3")]
-impl From<lame::EncodeError> for JingleError {
-    fn from(err: lame::EncodeError) -> Self {
-        JingleError::Mp3Error(format!("MP3 encode error: {:?}", err))
+impl From<mp3lame_encoder::EncodeError> for JingleError {
+    fn from(err: mp3lame_encoder::EncodeError) -> Self {
+        JingleError::Mp3Error(format!("MP3 encode error: {}", err))
     }
 }
 
 #[cfg(feature = "mp3")]
-impl From<lame::Error> for JingleError {
-    fn from(err: lame::Error) -> Self {
-        JingleError::Mp3Error(format!("MP3 error: {:?}", err))
+impl From<mp3lame_encoder::BuildError> for JingleError {
+    fn from(err: mp3lame_encoder::BuildError) -> Self {
+        JingleError::Mp3Error(format!("MP3 error: {}", err))
+    }
+}
+
+#[cfg(feature = "opus")]
+impl From<opus::Error> for JingleError {
+    fn from(err: opus::Error) -> Self {
+        JingleError::OpusError(err.to_string())
     }
 }
 
 /// Result type alias for jingle operations
-pub type Result<T> = std::result::Result<T, JingleError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, JingleError>;
+/// Fail with `InvalidParameter` unless `value` is a finite number inside `range`
+pub(crate) fn check_range(name: &str, value: f32, range: std::ops::RangeInclusive<f32>) -> Result<()> {
+    if value.is_finite() && range.contains(&value) {
+        Ok(())
+    } else {
+        Err(JingleError::InvalidParameter(format!("{} must be between {} and {}, got {}", name, range.start(), range.end(), value)))
+    }
+}
+
+/// Fail with `InvalidParameter` unless `value` is a finite number above zero and at most `max`
+pub(crate) fn check_positive(name: &str, value: f32, max: f32) -> Result<()> {
+    if value.is_finite() && value > 0.0 && value <= max {
+        Ok(())
+    } else {
+        Err(JingleError::InvalidParameter(format!("{} must be above 0 and at most {}, got {}", name, max, value)))
+    }
+}
@@ -1,18 +1,312 @@
-//! Audio file export functionality (WAV and MP3)
+//! Audio file export functionality (WAV, MP3 and Opus)
 
-use std::path::Path;
-use hound::{WavSpec, WavWriter, SampleFormat};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use hound::{WavWriter, SampleFormat};
+use rand::{Rng, RngCore, SeedableRng};
+use crate::{SAMPLE_RATE, A4_FREQUENCY, audio::{Oscillator, MelodySource, WaveForm, ADSR, MAX_DURATION}, music::Melody, presets::JinglePreset, manifest::{ExportManifest, RenderChoices}, channels::{ChannelLayout, mixdown, interleave}, encoder::{AudioEncoder, BitDepth, EncodeSpec, RawPcmEncoder, WavEncoder, quantize}, markers::{LoopRegion, CueMarker}, loudness::loudness_gain, chain::EffectChain, effects::{declick, apply_gain_db}, resample::resample, registry::PresetRegistry, constraints::Constraints, version::GenerationVersion, clipping::OutputPolicy, cancel::CancelToken, progress::{ProgressPhase, ProgressEventCallback}, chiptune, error::{Result, check_range}};
 #[cfg(feature = "mp3")]
-use lame::Lame;
-use rand::{Rng, SeedableRng};
-use rand::rngs::StdRng;
-use crate::{SAMPLE_RATE, audio::{Oscillator, WaveForm}, music::Melody, error::Result};
+use crate::encoder::Mp3Encoder;
+#[cfg(feature = "opus")]
+use crate::encoder::OpusEncoder;
+
+/// Dither applied when converting f32 samples to 16-bit integers on export
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Dither {
+    /// Plain rounding, no added noise
+    #[default]
+    None,
+    /// Triangular (TPDF) dither of ±1 LSB
+    Tpdf,
+    /// TPDF dither with first-order error feedback pushing noise to high frequencies
+    NoiseShaped,
+}
+
+/// Bitrate control for MP3 encoding
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mp3BitrateMode {
+    /// Constant bitrate in kbps
+    Cbr(u32),
+    /// Variable bitrate with a quality level from 0 (best) to 9 (smallest)
+    Vbr(u8),
+    /// Average bitrate targeting the given kbps
+    Abr(u32),
+}
+
+/// Options for MP3 export
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mp3Options {
+    pub mode: Mp3BitrateMode,
+    /// Encoder algorithm quality from 0 (best, slowest) to 9 (worst, fastest)
+    pub quality: u8,
+    /// Use joint stereo rather than plain stereo for two-channel exports
+    pub joint_stereo: bool,
+}
+
+impl Default for Mp3Options {
+    fn default() -> Self {
+        Self {
+            mode: Mp3BitrateMode::Cbr(192),
+            quality: 0,
+            joint_stereo: true,
+        }
+    }
+}
+
+impl Mp3Options {
+    /// Constant bitrate encoding at the given kbps
+    pub fn cbr(kbps: u32) -> Self {
+        Self { mode: Mp3BitrateMode::Cbr(kbps), ..Self::default() }
+    }
+
+    /// Variable bitrate encoding at a quality level from 0 (best) to 9 (smallest)
+    pub fn vbr(quality: u8) -> Self {
+        Self { mode: Mp3BitrateMode::Vbr(quality.min(9)), ..Self::default() }
+    }
+
+    /// Average bitrate encoding targeting the given kbps
+    pub fn abr(kbps: u32) -> Self {
+        Self { mode: Mp3BitrateMode::Abr(kbps), ..Self::default() }
+    }
+
+    /// Set the encoder algorithm quality from 0 (best) to 9 (fastest)
+    pub fn with_quality(mut self, quality: u8) -> Self {
+        self.quality = quality.min(9);
+        self
+    }
+}
+
+/// Options for splitting long renders into several files
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChunkOptions {
+    /// Maximum duration of each file in seconds
+    pub max_duration: f32,
+    /// Seconds of audio repeated at the start of each following file
+    pub overlap: f32,
+    /// Fade the overlapping regions out and in so consecutive files crossfade
+    pub crossfade: bool,
+}
+
+impl ChunkOptions {
+    /// Split into files of at most `max_duration` seconds without overlap
+    pub fn new(max_duration: f32) -> Self {
+        Self {
+            max_duration,
+            overlap: 0.0,
+            crossfade: false,
+        }
+    }
+
+    /// Repeat `overlap` seconds of audio between consecutive files
+    pub fn with_overlap(mut self, overlap: f32) -> Self {
+        self.overlap = overlap.max(0.0);
+        self
+    }
+
+    /// Enable or disable crossfading the overlapping regions
+    pub fn with_crossfade(mut self, crossfade: bool) -> Self {
+        self.crossfade = crossfade;
+        self
+    }
+}
+
+/// Phrasing for `generate_melody_samples_with_options`
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MelodyRenderOptions {
+    /// Envelope for every note, or `None` for the generator's envelope
+    pub adsr: Option<ADSR>,
+    /// Fraction of each note's duration that sounds (1.0 legato, around 0.5 staccato); the rest is silence
+    pub articulation: f32,
+    /// Gain in dB for each note, repeating when shorter than the melody; empty for no change
+    pub note_gains_db: Vec<f32>,
+    /// Seconds each note keeps sounding into the next, summed with it (0.0 for strictly sequential notes)
+    pub overlap: f32,
+}
+
+impl Default for MelodyRenderOptions {
+    fn default() -> Self {
+        Self {
+            adsr: None,
+            articulation: 1.0,
+            note_gains_db: Vec::new(),
+            overlap: 0.0,
+        }
+    }
+}
+
+impl MelodyRenderOptions {
+    /// Notes sound for their full duration
+    pub fn legato() -> Self {
+        Self::default()
+    }
+
+    /// Notes sound for half their duration
+    pub fn staccato() -> Self {
+        Self::default().with_articulation(0.5)
+    }
+
+    /// Set the envelope for every note
+    pub fn with_adsr(mut self, adsr: ADSR) -> Self {
+        self.adsr = Some(adsr);
+        self
+    }
+
+    /// Set the fraction of each note's duration that sounds (0.05 - 1.0)
+    pub fn with_articulation(mut self, articulation: f32) -> Self {
+        self.articulation = articulation.clamp(0.05, 1.0);
+        self
+    }
+
+    /// Set the gain in dB of each note, e.g. `[0.0, -6.0]` to accent every other note
+    pub fn with_note_gains_db(mut self, gains_db: Vec<f32>) -> Self {
+        self.note_gains_db = gains_db;
+        self
+    }
+
+    /// Let the release of each note overlap the attack of the next by this many seconds
+    ///
+    /// Note start times are unchanged; the last note is not extended.
+    pub fn with_overlap(mut self, overlap: f32) -> Self {
+        self.overlap = overlap.max(0.0);
+        self
+    }
+
+    /// Check the envelope, articulation, note gains and overlap are finite and in range
+    pub fn validate(&self) -> Result<()> {
+        if let Some(adsr) = self.adsr {
+            adsr.validate()?;
+        }
+        check_range("articulation", self.articulation, 0.0..=1.0)?;
+        for &gain_db in &self.note_gains_db {
+            check_range("note gain (dB)", gain_db, -120.0..=60.0)?;
+        }
+        check_range("overlap (s)", self.overlap, 0.0..=MAX_DURATION)
+    }
+}
+
+/// Engine settings chosen when a generator is constructed, see `JingleGenerator::with_config`
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeneratorConfig {
+    /// Rate (Hz) oscillators, filters and effects run at and renders are produced at
+    pub sample_rate: u32,
+    /// Concert pitch: every tone is transposed by `a4_hz / A4_FREQUENCY`, so notes
+    /// and preset frequencies written in standard tuning sound at this reference
+    pub a4_hz: f32,
+    /// Envelope used for oscillator notes and tones
+    pub default_adsr: ADSR,
+    /// Linear gain applied to everything the generator synthesizes
+    pub master_gain: f32,
+    /// What exports do with samples beyond full scale
+    pub output_policy: OutputPolicy,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            sample_rate: SAMPLE_RATE,
+            a4_hz: A4_FREQUENCY,
+            default_adsr: ADSR::default(),
+            master_gain: 1.0,
+            output_policy: OutputPolicy::default(),
+        }
+    }
+}
+
+impl GeneratorConfig {
+    /// Set the rate (Hz) renders are produced at
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Set the concert pitch in Hz (e.g. 432.0 or 442.0)
+    pub fn with_a4(mut self, a4_hz: f32) -> Self {
+        self.a4_hz = a4_hz;
+        self
+    }
+
+    /// Set the envelope used for oscillator notes and tones
+    pub fn with_adsr(mut self, adsr: ADSR) -> Self {
+        self.default_adsr = adsr;
+        self
+    }
+
+    /// Set the linear gain applied to everything the generator synthesizes
+    pub fn with_master_gain(mut self, master_gain: f32) -> Self {
+        self.master_gain = master_gain;
+        self
+    }
+
+    /// Set what exports do with samples beyond full scale: clamp them, normalize, or fail
+    pub fn with_output_policy(mut self, output_policy: OutputPolicy) -> Self {
+        self.output_policy = output_policy;
+        self
+    }
+}
+
+/// Callback receiving `(done, total)` progress updates
+pub type ProgressCallback = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
+/// Random draws shared by every member of a variation family or sound theme
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FamilyTraits {
+    pub(crate) variation: f32,
+    pub(crate) pitch_offset: f32,
+    pub(crate) rhythm: f32,
+    pub(crate) note_count_offset: i32,
+    /// Waveform returned by `random_waveform`, when the timbre is shared too
+    pub(crate) waveform: Option<WaveForm>,
+}
+
+/// Random number generator used unless another one is injected with `JingleGenerator::with_rng`
+///
+/// This is ChaCha12 seeded through `SeedableRng::seed_from_u64`, pinned to
+/// `rand_chacha` rather than `rand::rngs::StdRng`, whose algorithm may change
+/// between `rand` releases. A stored seed therefore keeps rendering the same
+/// jingle across versions of this crate; changing the stream is a breaking change.
+pub type DefaultRng = rand_chacha::ChaCha12Rng;
+
+/// A generator's random number source, boxed so any `RngCore` can be injected
+type BoxedRng = Box<dyn RngCore + Send + Sync>;
+
+/// Seed a boxed RNG of type `R`
+fn seeded<R: RngCore + SeedableRng + Send + Sync + 'static>(seed: u64) -> BoxedRng {
+    Box::new(R::seed_from_u64(seed))
+}
 
 /// Main generator for creating and exporting jingle audio
 pub struct JingleGenerator {
     sample_rate: u32,
-    rng: StdRng,
+    rng: BoxedRng,
+    /// Reseeds `rng` with its own type in `set_seed`, `reset_seed` and `with_call_seed`
+    seed_rng: fn(u64) -> BoxedRng,
     current_seed: Option<u64>,
+    dither: Dither,
+    bit_depth: BitDepth,
+    bitrate: Option<u32>,
+    loudness_target: Option<f32>,
+    output_sample_rate: Option<u32>,
+    write_manifests: bool,
+    pub(crate) generation_version: GenerationVersion,
+    declick: bool,
+    intensity: Option<f32>,
+    chiptune: bool,
+    adsr: ADSR,
+    a4_hz: f32,
+    master_gain: f32,
+    output_policy: OutputPolicy,
+    constraints: Option<Constraints>,
+    encoders: HashMap<String, Arc<dyn AudioEncoder>>,
+    presets: PresetRegistry,
+    progress: Option<ProgressCallback>,
+    pub(crate) progress_events: Option<ProgressEventCallback>,
+    pub(crate) cancel: Option<CancelToken>,
+    family: Option<FamilyTraits>,
+    pub(crate) last_choices: RenderChoices,
 }
 
 impl JingleGenerator {
@@ -20,8 +314,31 @@ impl JingleGenerator {
     pub fn new() -> Self {
         Self {
             sample_rate: SAMPLE_RATE,
-            rng: StdRng::from_entropy(),
+            rng: Box::new(DefaultRng::from_entropy()),
+            seed_rng: seeded::<DefaultRng>,
             current_seed: None,
+            dither: Dither::default(),
+            bit_depth: BitDepth::default(),
+            bitrate: None,
+            loudness_target: None,
+            output_sample_rate: None,
+            write_manifests: false,
+            generation_version: GenerationVersion::LATEST,
+            declick: false,
+            intensity: None,
+            chiptune: false,
+            adsr: ADSR::default(),
+            a4_hz: A4_FREQUENCY,
+            master_gain: 1.0,
+            output_policy: OutputPolicy::default(),
+            constraints: None,
+            encoders: HashMap::new(),
+            presets: PresetRegistry::new(),
+            progress: None,
+            progress_events: None,
+            cancel: None,
+            family: None,
+            last_choices: RenderChoices::default(),
         }
     }
     
@@ -29,8 +346,119 @@ impl JingleGenerator {
     pub fn with_seed(seed: u64) -> Self {
         Self {
             sample_rate: SAMPLE_RATE,
-            rng: StdRng::seed_from_u64(seed),
+            rng: seeded::<DefaultRng>(seed),
+            seed_rng: seeded::<DefaultRng>,
             current_seed: Some(seed),
+            dither: Dither::default(),
+            bit_depth: BitDepth::default(),
+            bitrate: None,
+            loudness_target: None,
+            output_sample_rate: None,
+            write_manifests: false,
+            generation_version: GenerationVersion::LATEST,
+            declick: false,
+            intensity: None,
+            chiptune: false,
+            adsr: ADSR::default(),
+            a4_hz: A4_FREQUENCY,
+            master_gain: 1.0,
+            output_policy: OutputPolicy::default(),
+            constraints: None,
+            encoders: HashMap::new(),
+            presets: PresetRegistry::new(),
+            progress: None,
+            progress_events: None,
+            cancel: None,
+            family: None,
+            last_choices: RenderChoices::default(),
+        }
+    }
+
+    /// Create a generator drawing from an RNG of type `R` seeded with `seed`
+    ///
+    /// Use this to pin a specific algorithm (e.g. `rand_chacha::ChaCha8Rng` or
+    /// a PCG) instead of `DefaultRng`; reseeding keeps the same type.
+    ///
+    /// ```rust
+    /// use jinglebells::{JingleGenerator, DefaultRng};
+    ///
+    /// let mut injected = JingleGenerator::with_rng::<DefaultRng>(7);
+    /// assert_eq!(injected.random_seed(), JingleGenerator::with_seed(7).random_seed());
+    /// ```
+    pub fn with_rng<R: RngCore + SeedableRng + Send + Sync + 'static>(seed: u64) -> Self {
+        Self {
+            rng: seeded::<R>(seed),
+            seed_rng: seeded::<R>,
+            ..Self::with_seed(seed)
+        }
+    }
+
+    /// Apply engine settings: sample rate, tuning, default envelope and master gain
+    ///
+    /// Fails with `InvalidParameter` for a sample rate outside 8 - 192 kHz, a
+    /// concert pitch that isn't positive or a negative gain.
+    ///
+    /// ```rust
+    /// use jinglebells::{JingleGenerator, GeneratorConfig, WaveForm};
+    ///
+    /// let config = GeneratorConfig::default().with_sample_rate(48000).with_a4(432.0);
+    /// let generator = JingleGenerator::with_seed(1).with_config(config).unwrap();
+    /// assert_eq!(generator.generate_tone(440.0, 0.5, WaveForm::Sine).len(), 24000);
+    /// ```
+    pub fn with_config(mut self, config: GeneratorConfig) -> Result<Self> {
+        if !(8000..=192000).contains(&config.sample_rate) {
+            return Err(crate::error::JingleError::InvalidParameter(format!("Sample rate {} Hz is outside 8000 - 192000 Hz", config.sample_rate)));
+        }
+        if !config.a4_hz.is_finite() || config.a4_hz <= 0.0 {
+            return Err(crate::error::JingleError::InvalidParameter(format!("Concert pitch {} Hz must be positive", config.a4_hz)));
+        }
+        if !config.master_gain.is_finite() || config.master_gain < 0.0 {
+            return Err(crate::error::JingleError::InvalidParameter(format!("Master gain {} must not be negative", config.master_gain)));
+        }
+        self.sample_rate = config.sample_rate;
+        self.a4_hz = config.a4_hz;
+        self.adsr = config.default_adsr;
+        self.master_gain = config.master_gain;
+        self.output_policy = config.output_policy;
+        Ok(self)
+    }
+
+    /// Get the engine settings this generator renders with
+    pub fn config(&self) -> GeneratorConfig {
+        GeneratorConfig {
+            sample_rate: self.sample_rate,
+            a4_hz: self.a4_hz,
+            default_adsr: self.adsr,
+            master_gain: self.master_gain,
+            output_policy: self.output_policy,
+        }
+    }
+
+    /// Get the rate (Hz) renders are produced at
+    pub fn get_sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Get what exports do with samples beyond full scale
+    pub fn get_output_policy(&self) -> OutputPolicy {
+        self.output_policy
+    }
+
+    /// Transpose a frequency in standard tuning to the configured concert pitch
+    pub(crate) fn tuned(&self, frequency: f32) -> f32 {
+        frequency * self.a4_hz / A4_FREQUENCY
+    }
+
+    /// Scale synthesized samples by the master gain
+    pub(crate) fn master(&self, mut samples: Vec<f32>) -> Vec<f32> {
+        self.apply_master(&mut samples);
+        samples
+    }
+    
+    /// Scale synthesized samples in place by the master gain
+    pub(crate) fn apply_master(&self, samples: &mut [f32]) {
+        if self.master_gain != 1.0 {
+            samples.iter_mut().for_each(|sample| *sample *= self.master_gain);
         }
     }
 
@@ -49,7 +477,7 @@ impl JingleGenerator {
     /// Reset the RNG to the original seed (if available)
     pub fn reset_seed(&mut self) -> Result<()> {
         if let Some(seed) = self.current_seed {
-            self.rng = StdRng::seed_from_u64(seed);
+            self.rng = (self.seed_rng)(seed);
             Ok(())
         } else {
             Err(crate::error::JingleError::RandomError("No seed available to reset".to_string()))
@@ -58,19 +486,223 @@ impl JingleGenerator {
 
     /// Set a new seed for the generator
     pub fn set_seed(&mut self, seed: u64) {
-        self.rng = StdRng::seed_from_u64(seed);
+        self.rng = (self.seed_rng)(seed);
         self.current_seed = Some(seed);
     }
 
+    /// Share random draws with other members of a family or theme
+    pub(crate) fn set_family(&mut self, traits: Option<FamilyTraits>) {
+        self.family = traits;
+    }
+
+    /// Run a render with its own RNG seeded from `seed`, leaving this generator's random sequence untouched
+    ///
+    /// The result depends only on the seed and the render, not on how many jingles
+    /// the generator has produced before, so a single call can be reproduced exactly.
+    ///
+    /// ```rust
+    /// use jinglebells::{JingleGenerator, WaveForm};
+    ///
+    /// let mut generator = JingleGenerator::new();
+    /// let first = generator.with_call_seed(7, |g| g.create_success_jingle(WaveForm::Sine, None, None));
+    /// generator.create_alert_jingle(WaveForm::Square, None, None);
+    /// let again = generator.with_call_seed(7, |g| g.create_success_jingle(WaveForm::Sine, None, None));
+    /// assert_eq!(first, again);
+    /// ```
+    pub fn with_call_seed<T, F: FnOnce(&mut Self) -> T>(&mut self, seed: u64, render: F) -> T {
+        let previous_rng = std::mem::replace(&mut self.rng, (self.seed_rng)(seed));
+        let previous_seed = self.current_seed.replace(seed);
+        let result = render(self);
+        self.rng = previous_rng;
+        self.current_seed = previous_seed;
+        result
+    }
+
     /// Get the current seed (if available)
     pub fn get_current_seed(&self) -> Option<u64> {
         self.current_seed
     }
 
+    /// Set the dither used for 16-bit conversion on export
+    pub fn set_dither(&mut self, dither: Dither) {
+        self.dither = dither;
+    }
+
+    /// Get the dither used for 16-bit conversion on export
+    pub fn get_dither(&self) -> Dither {
+        self.dither
+    }
+
+    /// Set the sample format of WAV and raw PCM exports
+    pub fn set_bit_depth(&mut self, bit_depth: BitDepth) {
+        self.bit_depth = bit_depth;
+    }
+
+    /// Get the sample format of WAV and raw PCM exports
+    pub fn get_bit_depth(&self) -> BitDepth {
+        self.bit_depth
+    }
+
+    /// Set the bitrate (kbps) of MP3 and Opus files exported by extension, or `None` for 192 kbps MP3 and 64 kbps Opus
+    pub fn set_bitrate(&mut self, kbps: Option<u32>) {
+        self.bitrate = kbps;
+    }
+
+    /// Get the bitrate (kbps) of MP3 and Opus files exported by extension
+    pub fn get_bitrate(&self) -> Option<u32> {
+        self.bitrate
+    }
+
+    /// Set a target integrated loudness (LUFS) applied before export, or `None` to disable
+    pub fn set_loudness_target(&mut self, target_lufs: Option<f32>) {
+        self.loudness_target = target_lufs;
+    }
+
+    /// Get the target integrated loudness applied before export
+    pub fn get_loudness_target(&self) -> Option<f32> {
+        self.loudness_target
+    }
+
+    /// Set the sample rate files are exported at, or `None` to export at the internal rate
+    pub fn set_output_sample_rate(&mut self, sample_rate: Option<u32>) {
+        self.output_sample_rate = sample_rate;
+    }
+
+    /// Get the sample rate files are exported at
+    pub fn get_output_sample_rate(&self) -> u32 {
+        self.output_sample_rate.unwrap_or(self.sample_rate)
+    }
+
+    /// Enable or disable micro-fades at note and segment joins
+    ///
+    /// When enabled, `generate_melody_samples` and `combine_samples` run `declick`
+    /// over the boundaries between the pieces they join.
+    pub fn set_declick(&mut self, enabled: bool) {
+        self.declick = enabled;
+    }
+
+    /// Check whether micro-fades are applied at note and segment joins
+    pub fn get_declick(&self) -> bool {
+        self.declick
+    }
+
+    /// Set how urgent preset jingles sound (0.0 - 1.0), or `None` to render them unchanged
+    ///
+    /// Higher intensities raise the octave, pick harsher waveforms, play notes faster
+    /// and render louder; lower intensities do the opposite. 0.5 is neutral.
+    pub fn set_intensity(&mut self, intensity: Option<f32>) {
+        self.intensity = intensity.map(|intensity| intensity.clamp(0.0, 1.0));
+    }
+
+    /// Get the intensity applied to preset jingles
+    pub fn get_intensity(&self) -> Option<f32> {
+        self.intensity
+    }
+
+    /// Enable or disable chiptune mode for every render
+    ///
+    /// Tones play on triangle or pulse channels through a 4-bit DAC, pitch slides
+    /// step at the chip's frame rate, noise comes from a shift register and
+    /// additive or FM timbres are replaced by single chip voices (see `chiptune`).
+    pub fn set_chiptune(&mut self, enabled: bool) {
+        self.chiptune = enabled;
+    }
+
+    /// Check whether chiptune mode is enabled
+    pub fn get_chiptune(&self) -> bool {
+        self.chiptune
+    }
+
+    /// Set the envelope used for every oscillator note and tone
+    pub fn set_adsr(&mut self, adsr: ADSR) {
+        self.adsr = adsr;
+    }
+
+    /// Get the envelope used for oscillator notes and tones
+    pub fn get_adsr(&self) -> ADSR {
+        self.adsr
+    }
+
+    /// Set limits that every preset render is fitted to, or `None` to remove them
+    pub fn set_constraints(&mut self, constraints: Option<Constraints>) {
+        self.constraints = constraints;
+    }
+
+    /// Get the limits applied to preset renders
+    pub fn get_constraints(&self) -> Option<Constraints> {
+        self.constraints
+    }
+
+    /// Enable or disable writing a JSON manifest next to each file exported by `export_variations`
+    pub fn set_write_manifests(&mut self, enabled: bool) {
+        self.write_manifests = enabled;
+    }
+
+    /// Register an encoder used by `export_to_file` for files with the given extension
+    ///
+    /// Registered encoders take precedence over the built-in WAV, MP3 and Opus paths.
+    /// The extension is matched case-insensitively and without the leading dot.
+    pub fn register_encoder<E: AudioEncoder + 'static>(&mut self, extension: &str, encoder: E) {
+        let extension = extension.trim_start_matches('.').to_lowercase();
+        self.encoders.insert(extension, Arc::new(encoder));
+    }
+
+    /// Register a custom preset rendered by `render_named` under the given name
+    ///
+    /// Registered presets take precedence over built-in presets with the same name.
+    pub fn register_preset<F>(&mut self, name: &str, render: F)
+    where
+        F: Fn(&mut JingleGenerator, &crate::spec::JingleSpec) -> Vec<f32> + Send + Sync + 'static,
+    {
+        self.presets.register(name, render);
+    }
+
+    /// Get the registry of custom presets
+    pub fn presets(&self) -> &PresetRegistry {
+        &self.presets
+    }
+
+    /// Report progress while rendering melodies and exporting files
+    ///
+    /// The callback receives `(done, total)`: notes rendered for `generate_melody_samples`,
+    /// samples written for single-file exports, files written for batch exports, and
+    /// sounds rendered for families and themes.
+    pub fn with_progress<F: Fn(usize, usize) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Remove the progress callback and the structured progress callback
+    pub fn clear_progress(&mut self) {
+        self.progress = None;
+        self.progress_events = None;
+    }
+
+    /// Send a progress update to both callbacks, if any
+    pub(crate) fn report_progress(&self, phase: ProgressPhase, done: usize, total: usize) {
+        if let Some(progress) = &self.progress {
+            progress(done, total);
+        }
+        self.report_phase(phase, done, total);
+    }
+
+    /// Get the random choices made while rendering the most recent preset jingle
+    pub fn last_choices(&self) -> &RenderChoices {
+        &self.last_choices
+    }
+
+    /// Build a manifest for samples exported to a path, including the seed and last render choices
+    pub fn manifest<P: AsRef<Path>>(&self, samples: &[f32], path: P) -> ExportManifest {
+        ExportManifest::new(samples, path, self.get_output_sample_rate())
+            .with_seed(self.current_seed)
+            .with_generation_version(self.generation_version)
+            .with_choices(self.last_choices.clone())
+    }
+
     /// Create a new generator with the same seed but independent state
     pub fn fork(&self) -> Result<Self> {
         if let Some(seed) = self.current_seed {
-            Ok(Self::with_seed(seed))
+            Ok(Self::with_seed(seed).with_settings_from(self))
         } else {
             Err(crate::error::JingleError::RandomError("Cannot fork generator without seed".to_string()))
         }
@@ -79,92 +711,598 @@ impl JingleGenerator {
     /// Create a new generator with a derived seed for consistent but different randomness
     pub fn derive_seed(&self, variation: u64) -> Result<Self> {
         if let Some(seed) = self.current_seed {
-            Ok(Self::with_seed(seed.wrapping_add(variation)))
+            Ok(Self::with_seed(seed.wrapping_add(variation)).with_settings_from(self))
         } else {
             Err(crate::error::JingleError::RandomError("Cannot derive seed without original seed".to_string()))
         }
     }
     
-    /// Generate audio samples from a melody
-    pub fn generate_melody_samples(&self, melody: &Melody, octave: i32, waveform: WaveForm) -> Vec<f32> {
-        let mut all_samples = Vec::new();
-        
-        for (note, duration) in &melody.notes {
-            let frequency = note.frequency(octave);
-            let oscillator = Oscillator::new(frequency, waveform, *duration);
-            let samples: Vec<f32> = oscillator.collect();
-            all_samples.extend(samples);
-        }
-        
-        all_samples
+    /// Copy engine and export settings, the RNG type, registered encoders and registered presets from another generator
+    pub(crate) fn with_settings_from(mut self, other: &Self) -> Self {
+        self.seed_rng = other.seed_rng;
+        if let Some(seed) = self.current_seed {
+            self.rng = (self.seed_rng)(seed);
+        }
+        self.sample_rate = other.sample_rate;
+        self.a4_hz = other.a4_hz;
+        self.master_gain = other.master_gain;
+        self.output_policy = other.output_policy;
+        self.dither = other.dither;
+        self.bit_depth = other.bit_depth;
+        self.bitrate = other.bitrate;
+        self.loudness_target = other.loudness_target;
+        self.output_sample_rate = other.output_sample_rate;
+        self.write_manifests = other.write_manifests;
+        self.generation_version = other.generation_version;
+        self.declick = other.declick;
+        self.intensity = other.intensity;
+        self.chiptune = other.chiptune;
+        self.adsr = other.adsr;
+        self.constraints = other.constraints;
+        self.encoders = other.encoders.clone();
+        self.presets = other.presets.clone();
+        self.progress = other.progress.clone();
+        self.progress_events = other.progress_events.clone();
+        self.cancel = other.cancel.clone();
+        self
+    }
+    
+    /// Generate audio samples from a melody
+    pub fn generate_melody_samples(&self, melody: &Melody, octave: i32, waveform: WaveForm) -> Vec<f32> {
+        self.generate_melody_samples_with_cues(melody, octave, waveform).0
+    }
+    
+    /// Generate audio samples from a melody along with a cue marker at each note onset
+    ///
+    /// Markers are labelled with the note name and octave (e.g. "C#5") and can be
+    /// written with `export_to_wav_with_cues`.
+    pub fn generate_melody_samples_with_cues(&self, melody: &Melody, octave: i32, waveform: WaveForm) -> (Vec<f32>, Vec<CueMarker>) {
+        self.melody_samples(melody, octave, waveform, &MelodyRenderOptions::default())
+    }
+    
+    /// Stream a melody as a rodio `Source`, rendering each note only when playback reaches it
+    ///
+    /// Plays the same samples as `generate_melody_samples`, apart from the micro-fades
+    /// added when declicking is enabled.
+    pub fn melody_source(&self, melody: &Melody, octave: i32, waveform: WaveForm) -> MelodySource {
+        let notes = melody.notes.iter().map(|(note, duration)| (self.tuned(note.frequency(octave)), *duration)).collect();
+        let waveform = if self.chiptune { chiptune::chip_waveform(waveform) } else { waveform };
+        MelodySource::from_frequencies(notes, waveform)
+            .with_adsr(self.adsr)
+            .with_sample_rate(self.sample_rate)
+            .with_output(self.chiptune, self.master_gain)
+            .with_time_phase(self.generation_version == GenerationVersion::V1)
+    }
+    
+    /// Generate audio samples from a melody phrased with an envelope, articulation and per-note gains
+    ///
+    /// ```rust
+    /// use jinglebells::{JingleGenerator, MelodyRenderOptions, Melody, Note, WaveForm};
+    ///
+    /// let melody = Melody { notes: vec![(Note::C, 0.2), (Note::E, 0.2), (Note::G, 0.4)] };
+    /// let options = MelodyRenderOptions::staccato().with_note_gains_db(vec![0.0, -6.0]);
+    /// let samples = JingleGenerator::new().generate_melody_samples_with_options(&melody, 5, WaveForm::Sine, &options);
+    /// assert!(!samples.is_empty());
+    /// ```
+    pub fn generate_melody_samples_with_options(&self, melody: &Melody, octave: i32, waveform: WaveForm, options: &MelodyRenderOptions) -> Vec<f32> {
+        self.melody_samples(melody, octave, waveform, options).0
+    }
+    
+    /// Render each note's sounding part followed by silence for the rest of its duration
+    fn melody_samples(&self, melody: &Melody, octave: i32, waveform: WaveForm, options: &MelodyRenderOptions) -> (Vec<f32>, Vec<CueMarker>) {
+        let mut samples = Vec::new();
+        let markers = self.melody_into(melody, octave, waveform, options, &mut samples);
+        (samples, markers)
+    }
+    
+    /// Append a melody to a buffer, reusing its capacity instead of allocating per note
+    ///
+    /// Clearing and refilling the same buffer avoids reallocating when rendering many melodies.
+    pub fn generate_melody_samples_into(&self, melody: &Melody, octave: i32, waveform: WaveForm, out: &mut Vec<f32>) {
+        self.melody_into(melody, octave, waveform, &MelodyRenderOptions::default(), out);
+    }
+    
+    /// Append each note's sounding part followed by silence for the rest of its duration
+    ///
+    /// Returns cue markers relative to the start of the appended melody.
+    fn melody_into(&self, melody: &Melody, octave: i32, waveform: WaveForm, options: &MelodyRenderOptions, out: &mut Vec<f32>) -> Vec<CueMarker> {
+        let offset = out.len();
+        let mut markers = Vec::with_capacity(melody.notes.len());
+        let total = melody.notes.len();
+        let adsr = options.adsr.unwrap_or(self.adsr);
+        let articulation = options.articulation.clamp(0.05, 1.0);
+        let overlap = options.overlap.max(0.0);
+        let duration: f32 = melody.notes.iter().map(|(_, duration)| duration.max(0.0)).sum();
+        out.reserve(((duration + overlap) * self.sample_rate as f32) as usize + total);
+        let mut voice = Vec::new();
+        let mut start = out.len();
+        
+        for (i, (note, duration)) in melody.notes.iter().enumerate() {
+            markers.push(CueMarker::new((start - offset) as u32, format!("{}{}", note.name(), octave)));
+            let gain_db = options.note_gains_db.get(i % options.note_gains_db.len().max(1)).copied();
+            if overlap > 0.0 {
+                // Render each voice apart and sum it over the tail of the previous one
+                let sounding = duration * articulation + if i + 1 < total { overlap } else { 0.0 };
+                voice.clear();
+                self.tone_into(note.frequency(octave), sounding, waveform, adsr, &mut voice);
+                if let Some(gain_db) = gain_db {
+                    apply_gain_db(&mut voice, gain_db);
+                }
+                if out.len() < start + voice.len() {
+                    out.resize(start + voice.len(), 0.0);
+                }
+                for (mixed, sample) in out[start..].iter_mut().zip(&voice) {
+                    *mixed += sample;
+                }
+            } else {
+                self.tone_into(note.frequency(octave), duration * articulation, waveform, adsr, out);
+                if let Some(gain_db) = gain_db {
+                    apply_gain_db(&mut out[start..], gain_db);
+                }
+            }
+            start += (duration * self.sample_rate as f32) as usize;
+            if out.len() < start {
+                out.resize(start, 0.0);
+            }
+            self.report_progress(ProgressPhase::Rendering, i + 1, total);
+        }
+        
+        if self.declick {
+            // Overlapping notes fade in and out through their envelopes, so only the ends need it
+            let boundaries: Vec<usize> = if overlap > 0.0 {
+                Vec::new()
+            } else {
+                markers.iter().map(|marker| marker.position as usize).collect()
+            };
+            declick(&mut out[offset..], &boundaries, self.sample_rate as f32);
+        }
+        
+        markers
+    }
+    
+    /// Run samples through an effect chain at the generator's sample rate
+    pub fn apply_effects(&self, samples: &[f32], chain: &EffectChain) -> Vec<f32> {
+        chain.process_at(samples.to_vec(), self.sample_rate)
+    }
+    
+    /// Export audio samples to a file, detecting format from extension
+    pub fn export_to_file<P: AsRef<Path>>(&self, samples: &[f32], path: P) -> Result<()> {
+        let path_ref = path.as_ref();
+        let encoder = self.encoder_for_path(path_ref)?;
+        self.export_with_encoder(encoder.as_ref(), samples, path_ref)
+    }
+    
+    /// Encode audio samples in the format of a file extension and write them to any writer, such as stdout
+    /// - extension: picks the encoder like the extension of an `export_to_file` path, e.g. `wav` or `raw`
+    pub fn export_to_writer<W: Write>(&self, samples: &[f32], extension: &str, output: &mut W) -> Result<()> {
+        let encoder = self.encoder_for_extension(extension)?;
+        self.encode_channels(encoder.as_ref(), &[samples], output)?;
+        output.flush()?;
+        Ok(())
+    }
+    
+    /// Split audio into sequentially numbered files of at most `options.max_duration` seconds
+    /// - path: base path; chunks are written as `<stem>_001.<ext>`, `<stem>_002.<ext>`, ...
+    /// - the format of each chunk is detected from the extension like `export_to_file`
+    ///
+    /// The loudness target is measured over the whole render so every chunk gets the same gain.
+    /// Returns the written paths in order
+    pub fn export_chunked<P: AsRef<Path>>(&self, samples: &[f32], path: P, options: &ChunkOptions) -> Result<Vec<PathBuf>> {
+        let path = path.as_ref();
+        let max_samples = (options.max_duration * self.sample_rate as f32) as usize;
+        let overlap_samples = (options.overlap * self.sample_rate as f32) as usize;
+        if max_samples == 0 || overlap_samples >= max_samples {
+            return Err(crate::error::JingleError::InvalidParameter(format!(
+                "Chunk duration {}s must be positive and longer than the overlap {}s",
+                options.max_duration, options.overlap
+            )));
+        }
+        
+        let encoder = self.encoder_for_path(path)?;
+        let gain = self.export_gain(samples);
+        let step = max_samples - overlap_samples;
+        let total = samples.len().saturating_sub(overlap_samples).div_ceil(step).max(1);
+        
+        let mut written = Vec::with_capacity(total);
+        let mut start = 0;
+        loop {
+            let end = (start + max_samples).min(samples.len());
+            let is_first = start == 0;
+            let is_last = end == samples.len();
+            
+            let mut chunk = samples[start..end].to_vec();
+            if options.crossfade && overlap_samples > 0 {
+                // Linear fades over the shared region sum back to the original signal
+                let fade = overlap_samples.min(chunk.len());
+                let chunk_len = chunk.len();
+                for i in 0..fade {
+                    if !is_first {
+                        chunk[i] *= i as f32 / fade as f32;
+                    }
+                    if !is_last {
+                        chunk[chunk_len - 1 - i] *= (i + 1) as f32 / fade as f32;
+                    }
+                }
+            }
+            
+            self.check_cancelled()?;
+            let chunk_path = numbered_path(path, written.len() + 1);
+            let mut output = std::io::BufWriter::new(std::fs::File::create(&chunk_path)?);
+            self.encode_channels_with_gain(encoder.as_ref(), &[&chunk], gain, &mut output)?;
+            output.flush()?;
+            written.push(chunk_path);
+            self.report_progress(ProgressPhase::Exporting, written.len(), total);
+            
+            if is_last {
+                break;
+            }
+            start += step;
+        }
+        
+        Ok(written)
+    }
+    
+    /// Export audio samples to a file with a specific encoder
+    pub fn export_with_encoder<E: AudioEncoder + ?Sized, P: AsRef<Path>>(&self, encoder: &E, samples: &[f32], path: P) -> Result<()> {
+        self.report_progress(ProgressPhase::Exporting, 0, samples.len());
+        let mut output = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.encode_channels(encoder, &[samples], &mut output)?;
+        output.flush()?;
+        self.report_progress(ProgressPhase::Exporting, samples.len(), samples.len());
+        Ok(())
+    }
+    
+    /// Encode audio samples in memory with a specific encoder
+    pub fn encode_with<E: AudioEncoder + ?Sized>(&self, encoder: &E, samples: &[f32]) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        self.encode_channels(encoder, &[samples], &mut output)?;
+        Ok(output)
+    }
+    
+    /// Apply the loudness target and output sample rate, then hand interleaved frames to an encoder
+    fn encode_channels<E: AudioEncoder + ?Sized>(&self, encoder: &E, channels: &[&[f32]], output: &mut dyn Write) -> Result<()> {
+        if channels.is_empty() || channels.len() > u16::MAX as usize {
+            return Err(crate::error::JingleError::InvalidParameter(format!("Unsupported channel count: {}", channels.len())));
+        }
+        
+        // Measure loudness on the mixdown so every channel gets the same gain
+        let gain = if channels.len() == 1 {
+            self.export_gain(channels[0])
+        } else {
+            self.export_gain(&mixdown(channels))
+        };
+        self.encode_channels_with_gain(encoder, channels, gain, output)
+    }
+    
+    /// Resample planar channels to the output rate, apply a gain and encode them as interleaved frames
+    fn encode_channels_with_gain<E: AudioEncoder + ?Sized>(&self, encoder: &E, channels: &[&[f32]], gain: f32, output: &mut dyn Write) -> Result<()> {
+        let sample_rate = self.get_output_sample_rate();
+        let prepared: Vec<Vec<f32>> = channels.iter()
+            .map(|channel| resample(channel, self.sample_rate, sample_rate).into_iter().map(|sample| sample * gain).collect())
+            .collect();
+        let mut samples = if prepared.len() == 1 {
+            prepared.into_iter().next().unwrap_or_default()
+        } else {
+            let prepared_refs: Vec<&[f32]> = prepared.iter().map(|channel| channel.as_slice()).collect();
+            interleave(&prepared_refs)
+        };
+        self.apply_output_policy(&mut samples)?;
+        
+        let spec = EncodeSpec { sample_rate, channels: channels.len() as u16 };
+        encoder.encode(&samples, spec, output)
+    }
+    
+    /// Pick the encoder for a path: a registered encoder, or the built-in one for its extension
+    fn encoder_for_path(&self, path: &Path) -> Result<Arc<dyn AudioEncoder>> {
+        self.encoder_for_extension(path.extension().and_then(|s| s.to_str()).unwrap_or_default())
+    }
+    
+    /// Pick the encoder for a file extension: a registered encoder, or the built-in one
+    fn encoder_for_extension(&self, extension: &str) -> Result<Arc<dyn AudioEncoder>> {
+        if let Some(encoder) = self.encoders.get(&extension.to_lowercase()) {
+            return Ok(encoder.clone());
+        }
+        match extension {
+            "raw" => Ok(Arc::new(RawPcmEncoder {
+                bit_depth: self.bit_depth,
+                dither: self.dither,
+                dither_seed: self.current_seed.unwrap_or(0),
+            })),
+            #[cfg(feature = "mp3")]
+            "mp3" => Ok(Arc::new(Mp3Encoder { options: Mp3Options::cbr(self.bitrate.unwrap_or(192)) })), // Default to 192 kbps
+            #[cfg(not(feature = "mp3"))]
+            "mp3" => Err(crate::error::JingleError::Mp3Error("MP3 support not enabled. Compile with --features mp3".to_string())),
+            #[cfg(feature = "opus")]
+            "opus" | "ogg" => Ok(Arc::new(OpusEncoder {
+                bitrate: self.bitrate.map_or(64000, |kbps| kbps * 1000), // Default to 64 kbps
+                serial: self.current_seed.unwrap_or(0) as u32,
+            })),
+            #[cfg(not(feature = "opus"))]
+            "opus" | "ogg" => Err(crate::error::JingleError::OpusError("Opus support not enabled. Compile with --features opus".to_string())),
+            _ => Ok(Arc::new(self.wav_encoder())), // Default to WAV
+        }
+    }
+    
+    /// File extensions this generator can export, built-in and registered, sorted
+    ///
+    /// Extensions without an encoder fall back to WAV.
+    pub fn supported_formats(&self) -> Vec<String> {
+        let mut formats = vec!["raw".to_string(), "wav".to_string()];
+        if cfg!(feature = "mp3") {
+            formats.push("mp3".to_string());
+        }
+        if cfg!(feature = "opus") {
+            formats.push("ogg".to_string());
+            formats.push("opus".to_string());
+        }
+        formats.extend(self.encoders.keys().cloned());
+        formats.sort();
+        formats.dedup();
+        formats
+    }
+    
+    /// Built-in WAV encoder using this generator's dither settings
+    fn wav_encoder(&self) -> WavEncoder {
+        WavEncoder {
+            bit_depth: self.bit_depth,
+            dither: self.dither,
+            dither_seed: self.current_seed.unwrap_or(0),
+            loop_region: None,
+            cue_markers: Vec::new(),
+            channel_mask: None,
+        }
+    }
+    
+    /// Render and export `count` variations of a preset into a directory
+    /// - each variation uses a seed derived from this generator's seed via `derive_seed`,
+    ///   starting at 1 so no variation repeats the generator's own render
+    /// - naming: builds the file name from the variation index and its seed
+    /// - a JSON manifest is written next to each file when enabled with `set_write_manifests`
+    ///
+    /// Returns the written paths together with the seed used for each one
+    pub fn export_variations<P, F>(
+        &self,
+        preset: JinglePreset,
+        waveform: WaveForm,
+        count: usize,
+        dir: P,
+        naming: F,
+    ) -> Result<Vec<(PathBuf, u64)>>
+    where
+        P: AsRef<Path>,
+        F: Fn(usize, u64) -> String,
+    {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        
+        let mut written = Vec::with_capacity(count);
+        for i in 0..count {
+            self.check_cancelled()?;
+            let mut variation = self.derive_seed(i as u64 + 1)?;
+            // Progress is reported per file rather than per note or sample
+            variation.clear_progress();
+            let seed = variation.current_seed.unwrap_or_default();
+            let samples = preset.generate(&mut variation, waveform);
+            
+            let path = dir.join(naming(i, seed));
+            variation.export_to_file(&samples, &path)?;
+            if self.write_manifests {
+                // Record the defaults the preset resolved, so the manifest can reproduce the render
+                variation.manifest(&samples, &path)
+                    .with_preset(preset, waveform, Some(preset.default_duration()), Some(preset.default_frequency()))
+                    .write_sidecar()?;
+            }
+            written.push((path, seed));
+            self.report_progress(ProgressPhase::Exporting, i + 1, count);
+        }
+        
+        Ok(written)
+    }
+    
+    /// Render `count` closely related variants of a preset
+    /// - every member uses a seed derived from this generator's seed via `derive_seed`
+    /// - members share key, scale and tempo, drawn once from the first seed
+    /// - melody pattern and waveform still vary between members
+    pub fn generate_family(&self, preset: JinglePreset, count: usize) -> Result<Vec<Vec<f32>>> {
+        let mut parent = self.derive_seed(0)?;
+        let traits = FamilyTraits {
+            variation: parent.random_variation(),
+            pitch_offset: parent.random_pitch_offset(),
+            rhythm: parent.random_rhythm_variation(),
+            note_count_offset: parent.rng.gen_range(-2..=2),
+            waveform: None,
+        };
+        
+        let mut spec = preset.spec(WaveForm::Sine, None, None);
+        let mut family = Vec::with_capacity(count);
+        for i in 0..count {
+            self.check_cancelled()?;
+            let mut member = self.derive_seed(i as u64)?;
+            member.clear_progress();
+            member.set_family(Some(traits));
+            family.push(member.render(&spec));
+            // The first member picks the scale the rest of the family keeps
+            if spec.scale.is_none() {
+                spec.scale = member.last_choices.scale;
+            }
+            self.report_progress(ProgressPhase::Rendering, i + 1, count);
+        }
+        
+        Ok(family)
+    }
+    
+    /// Export audio samples to a WAV file
+    pub fn export_to_wav<P: AsRef<Path>>(&self, samples: &[f32], path: P) -> Result<()> {
+        std::fs::write(path, self.encode_wav(samples)?)?;
+        Ok(())
+    }
+    
+    /// Append audio samples to an existing WAV file, creating it if missing
+    ///
+    /// The file must be a mono 16-bit WAV at the generator's output sample rate.
+    /// Only the new samples are held in memory.
+    pub fn append_to_wav<P: AsRef<Path>>(&self, samples: &[f32], path: P) -> Result<()> {
+        let path = path.as_ref();
+        if !path.exists() {
+            // Always 16-bit so later appends match the file
+            let encoder = WavEncoder { bit_depth: BitDepth::Int16, ..self.wav_encoder() };
+            std::fs::write(path, self.encode_with(&encoder, samples)?)?;
+            return Ok(());
+        }
+        
+        let mut writer = WavWriter::append(path)?;
+        let spec = writer.spec();
+        let sample_rate = self.get_output_sample_rate();
+        if spec.channels != 1 || spec.bits_per_sample != 16 || spec.sample_format != SampleFormat::Int || spec.sample_rate != sample_rate {
+            return Err(crate::error::JingleError::InvalidParameter(format!(
+                "Cannot append mono 16-bit {} Hz audio to a {}-channel {}-bit {} Hz file",
+                sample_rate, spec.channels, spec.bits_per_sample, spec.sample_rate
+            )));
+        }
+        
+        let gain = self.export_gain(samples);
+        let mut samples: Vec<f32> = resample(samples, self.sample_rate, sample_rate).into_iter().map(|sample| sample * gain).collect();
+        self.apply_output_policy(&mut samples)?;
+        for sample_i16 in quantize(&samples, 1.0, self.dither, self.current_seed.unwrap_or(0)) {
+            writer.write_sample(sample_i16)?;
+        }
+        
+        writer.finalize()?;
+        Ok(())
+    }
+    
+    /// Encode audio samples as an in-memory WAV file
+    pub fn encode_wav(&self, samples: &[f32]) -> Result<Vec<u8>> {
+        self.encode_with(&self.wav_encoder(), samples)
+    }
+    
+    /// Export audio samples to a WAV file with a loop region in its `smpl` chunk
+    /// - region: loop in frames of `samples` (the internal sample rate)
+    pub fn export_to_wav_with_loop<P: AsRef<Path>>(&self, samples: &[f32], path: P, region: LoopRegion) -> Result<()> {
+        std::fs::write(path, self.encode_wav_with_loop(samples, region)?)?;
+        Ok(())
+    }
+    
+    /// Encode audio samples as an in-memory WAV file with a loop region
+    pub fn encode_wav_with_loop(&self, samples: &[f32], region: LoopRegion) -> Result<Vec<u8>> {
+        region.validate(samples.len())?;
+        let encoder = WavEncoder {
+            loop_region: Some(region.rescale(self.sample_rate, self.get_output_sample_rate())),
+            ..self.wav_encoder()
+        };
+        self.encode_with(&encoder, samples)
+    }
+    
+    /// Export audio samples to a WAV file with cue markers
+    /// - markers: positions in frames of `samples` (the internal sample rate)
+    pub fn export_to_wav_with_cues<P: AsRef<Path>>(&self, samples: &[f32], path: P, markers: &[CueMarker]) -> Result<()> {
+        std::fs::write(path, self.encode_wav_with_cues(samples, markers)?)?;
+        Ok(())
+    }
+    
+    /// Encode audio samples as an in-memory WAV file with cue markers
+    pub fn encode_wav_with_cues(&self, samples: &[f32], markers: &[CueMarker]) -> Result<Vec<u8>> {
+        let output_rate = self.get_output_sample_rate();
+        let encoder = WavEncoder {
+            cue_markers: markers.iter().map(|marker| marker.rescale(self.sample_rate, output_rate)).collect(),
+            ..self.wav_encoder()
+        };
+        self.encode_with(&encoder, samples)
+    }
+    
+    /// Export planar channels to a multi-channel WAV file
+    /// - channels: one sample buffer per channel, in the layout's channel order
+    pub fn export_multichannel_wav<P: AsRef<Path>>(&self, channels: &[Vec<f32>], layout: ChannelLayout, path: P) -> Result<()> {
+        std::fs::write(path, self.encode_multichannel_wav(channels, layout)?)?;
+        Ok(())
+    }
+    
+    /// Encode planar channels as an in-memory multi-channel WAV file
+    pub fn encode_multichannel_wav(&self, channels: &[Vec<f32>], layout: ChannelLayout) -> Result<Vec<u8>> {
+        if channels.len() != layout.channel_count() as usize {
+            return Err(crate::error::JingleError::InvalidParameter(format!(
+                "{:?} layout expects {} channels, got {}",
+                layout,
+                layout.channel_count(),
+                channels.len()
+            )));
+        }
+        
+        let channel_refs: Vec<&[f32]> = channels.iter().map(|channel| channel.as_slice()).collect();
+        let encoder = WavEncoder { channel_mask: Some(layout.channel_mask()), ..self.wav_encoder() };
+        let mut output = Vec::new();
+        self.encode_channels(&encoder, &channel_refs, &mut output)?;
+        Ok(output)
+    }
+    
+    /// Export audio samples to an MP3 file
+    #[cfg(feature = "mp3")]
+    pub fn export_to_mp3<P: AsRef<Path>>(&self, samples: &[f32], path: P, bitrate: u32) -> Result<()> {
+        std::fs::write(path, self.encode_mp3(samples, bitrate)?)?;
+        Ok(())
+    }
+    
+    /// Encode audio samples as in-memory CBR MP3 data
+    #[cfg(feature = "mp3")]
+    pub fn encode_mp3(&self, samples: &[f32], bitrate: u32) -> Result<Vec<u8>> {
+        self.encode_mp3_with_options(samples, &Mp3Options::cbr(bitrate))
+    }
+    
+    /// Export audio samples to an MP3 file with explicit encoder options
+    #[cfg(feature = "mp3")]
+    pub fn export_to_mp3_with_options<P: AsRef<Path>>(&self, samples: &[f32], path: P, options: &Mp3Options) -> Result<()> {
+        std::fs::write(path, self.encode_mp3_with_options(samples, options)?)?;
+        Ok(())
     }
     
-    /// Export audio samples to a file, detecting format from extension
-    pub fn export_to_file<P: AsRef<Path>>(&self, samples: &[f32], path: P) -> Result<()> {
-        let path_ref = path.as_ref();
-        match path_ref.extension().and_then(|s| s.to_str()) {
-            Some("wav") => self.export_to_wav(samples, path),
-            #[cfg(feature = "mp3")]
-            Some("mp3") => self.export_to_mp3(samples, path, 192), // Default to 192 kbps
-            #[cfg(not(feature = "mp3"))]
-            Some("mp3") => Err(crate::error::JingleError::Mp3Error("MP3 support not enabled. Compile with --features mp3".to_string())),
-            _ => self.export_to_wav(samples, path), // Default to WAV
-        }
+    /// Encode audio samples as in-memory MP3 data with explicit encoder options
+    #[cfg(feature = "mp3")]
+    pub fn encode_mp3_with_options(&self, samples: &[f32], options: &Mp3Options) -> Result<Vec<u8>> {
+        self.encode_with(&Mp3Encoder { options: *options }, samples)
     }
     
-    /// Export audio samples to a WAV file
-    pub fn export_to_wav<P: AsRef<Path>>(&self, samples: &[f32], path: P) -> Result<()> {
-        let spec = WavSpec {
-            channels: 1,
-            sample_rate: self.sample_rate,
-            bits_per_sample: 16,
-            sample_format: SampleFormat::Int,
+    /// Export audio samples to an Ogg Opus file
+    #[cfg(feature = "opus")]
+    pub fn export_to_opus<P: AsRef<Path>>(&self, samples: &[f32], path: P, bitrate: u32) -> Result<()> {
+        std::fs::write(path, self.encode_opus(samples, bitrate)?)?;
+        Ok(())
+    }
+    
+    /// Encode audio samples as in-memory Ogg Opus data
+    /// - bitrate: target bitrate in bits per second (e.g. 64000)
+    #[cfg(feature = "opus")]
+    pub fn encode_opus(&self, samples: &[f32], bitrate: u32) -> Result<Vec<u8>> {
+        let encoder = OpusEncoder {
+            bitrate,
+            serial: self.current_seed.unwrap_or(0) as u32,
         };
-        
-        let mut writer = WavWriter::create(path, spec)?;
-        
-        for &sample in samples {
-            // Convert f32 sample (-1.0 to 1.0) to i16
-            let sample_i16 = (sample * i16::MAX as f32) as i16;
-            writer.write_sample(sample_i16)?;
-        }
-        
-        writer.finalize()?;
+        self.encode_with(&encoder, samples)
+    }
+    
+    /// Export audio samples to a file without blocking the async runtime, detecting format from extension
+    ///
+    /// Encoding happens in memory on the calling task; only the file write is asynchronous.
+    #[cfg(feature = "async")]
+    pub async fn export_to_file_async<P: AsRef<Path>>(&self, samples: &[f32], path: P) -> Result<()> {
+        let encoder = self.encoder_for_path(path.as_ref())?;
+        let bytes = self.encode_with(encoder.as_ref(), samples)?;
+        tokio::fs::write(path, bytes).await?;
         Ok(())
     }
     
-    /// Export audio samples to an MP3 file
-    #[cfg(feature = "mp3")]
-    pub fn export_to_mp3<P: AsRef<Path>>(&self, samples: &[f32], path: P, bitrate: u32) -> Result<()> {
-        use std::fs::File;
-        use std::io::Write;
-        
-        // Initialize LAME encoder
-        let mut lame = Lame::new()
-            .ok_or_else(|| crate::error::JingleError::Mp3Error("Failed to create LAME encoder".to_string()))?;
-        
-        lame.set_channels(1)?;
-        lame.set_sample_rate(self.sample_rate)?;
-        lame.set_kilobitrate(bitrate as i32)?;
-        lame.set_quality(0)?; // 0 = highest quality
-        lame.init_params()?;
-        
-        // Convert f32 samples to i16 for LAME encoder
-        let i16_samples: Vec<i16> = samples.iter()
-            .map(|&sample| (sample * i16::MAX as f32) as i16)
-            .collect();
-        
-        // Prepare output buffer (estimate size)
-        let mut mp3_buffer = vec![0u8; i16_samples.len() * 5 / 4 + 7200];
-        
-        // Encode to MP3 (mono, so use same channel for left and right)
-        let bytes_written = lame.encode(&i16_samples, &i16_samples, &mut mp3_buffer)?;
-        mp3_buffer.truncate(bytes_written);
-        
-        // Write to file
-        let mut file = File::create(path)?;
-        file.write_all(&mp3_buffer)?;
-        
+    /// Export audio samples to a WAV file without blocking the async runtime
+    #[cfg(feature = "async")]
+    pub async fn export_to_wav_async<P: AsRef<Path>>(&self, samples: &[f32], path: P) -> Result<()> {
+        let bytes = self.encode_wav(samples)?;
+        tokio::fs::write(path, bytes).await?;
+        Ok(())
+    }
+    
+    /// Export audio samples to an MP3 file without blocking the async runtime
+    #[cfg(all(feature = "async", feature = "mp3"))]
+    pub async fn export_to_mp3_async<P: AsRef<Path>>(&self, samples: &[f32], path: P, bitrate: u32) -> Result<()> {
+        let bytes = self.encode_mp3(samples, bitrate)?;
+        tokio::fs::write(path, bytes).await?;
         Ok(())
     }
     
@@ -174,48 +1312,110 @@ impl JingleGenerator {
         self.export_to_mp3(samples, path, bitrate)
     }
     
+    /// Convert f32 samples (-1.0 to 1.0) to i16, applying the configured loudness target and dither
+    pub fn samples_to_i16(&self, samples: &[f32]) -> Vec<i16> {
+        quantize(samples, self.export_gain(samples), self.dither, self.current_seed.unwrap_or(0))
+    }
+    
+    /// Gain applied on export to reach the configured loudness target
+    fn export_gain(&self, samples: &[f32]) -> f32 {
+        self.loudness_target
+            .map(|target| loudness_gain(samples, self.sample_rate, target))
+            .unwrap_or(1.0)
+    }
+    
     /// Generate a single tone with specified parameters
+    ///
+    /// The frequency is in standard tuning and is transposed to the configured concert pitch.
     pub fn generate_tone(&self, frequency: f32, duration: f32, waveform: WaveForm) -> Vec<f32> {
-        let oscillator = Oscillator::new(frequency, waveform, duration);
-        oscillator.collect()
+        let mut samples = Vec::new();
+        self.generate_tone_into(frequency, duration, waveform, &mut samples);
+        samples
+    }
+    
+    /// Append a single tone to a buffer, reusing its capacity
+    pub fn generate_tone_into(&self, frequency: f32, duration: f32, waveform: WaveForm, out: &mut Vec<f32>) {
+        self.tone_into(frequency, duration, waveform, self.adsr, out);
+    }
+    
+    /// Append a single tone shaped by a given envelope
+    fn tone_into(&self, frequency: f32, duration: f32, waveform: WaveForm, adsr: ADSR, out: &mut Vec<f32>) {
+        let waveform = if self.chiptune { chiptune::chip_waveform(waveform) } else { waveform };
+        let start = out.len();
+        Oscillator::new(self.tuned(frequency), waveform, duration)
+            .with_adsr(adsr)
+            .with_sample_rate(self.sample_rate)
+            .with_time_phase(self.generation_version == GenerationVersion::V1)
+            .render_into(out);
+        if self.chiptune {
+            chiptune::quantize(&mut out[start..]);
+        }
+        self.apply_master(&mut out[start..]);
     }
     
     /// Combine multiple sample arrays with optional gaps
     pub fn combine_samples(&self, sample_arrays: &[Vec<f32>], gap_duration: f32) -> Vec<f32> {
         let mut combined = Vec::new();
-        let gap_samples = (self.sample_rate as f32 * gap_duration) as usize;
+        self.combine_samples_into(sample_arrays, gap_duration, &mut combined);
+        combined
+    }
+    
+    /// Append multiple sample arrays with optional gaps to a buffer, reusing its capacity
+    pub fn combine_samples_into(&self, sample_arrays: &[Vec<f32>], gap_duration: f32, out: &mut Vec<f32>) {
+        let offset = out.len();
+        let mut boundaries = Vec::with_capacity(sample_arrays.len() * 2);
+        let gap_samples = if gap_duration > 0.0 { (self.sample_rate as f32 * gap_duration) as usize } else { 0 };
+        out.reserve(sample_arrays.iter().map(Vec::len).sum::<usize>() + gap_samples * sample_arrays.len().saturating_sub(1));
         
         for (i, samples) in sample_arrays.iter().enumerate() {
-            combined.extend(samples);
+            boundaries.push(out.len() - offset);
+            out.extend_from_slice(samples);
             
-            // Add gap between samples (except after the last one)
-            if i < sample_arrays.len() - 1 && gap_duration > 0.0 {
-                combined.extend(vec![0.0; gap_samples]);
+            // Add gap between samples (except after the last one), fading each array out before its gap
+            if i < sample_arrays.len() - 1 {
+                if gap_samples > 0 && self.generation_version >= GenerationVersion::V2 {
+                    boundaries.push(out.len() - offset);
+                }
+                out.resize(out.len() + gap_samples, 0.0);
             }
         }
         
-        combined
+        if self.declick {
+            declick(&mut out[offset..], &boundaries, self.sample_rate as f32);
+        }
     }
     
     /// Get a random variation factor for parameters (0.6 to 1.4 range for more dramatic variation)
     pub fn random_variation(&mut self) -> f32 {
+        if let Some(family) = self.family {
+            return family.variation;
+        }
         self.rng.gen_range(0.6..=1.4)
     }
     
     /// Get a random pitch offset in semitones (-4 to +4 for wider pitch range)
     pub fn random_pitch_offset(&mut self) -> f32 {
+        if let Some(family) = self.family {
+            return family.pitch_offset;
+        }
         self.rng.gen_range(-4.0..=4.0)
     }
     
     /// Get a random rhythm variation factor (0.5 to 2.0 range)
     pub fn random_rhythm_variation(&mut self) -> f32 {
+        if let Some(family) = self.family {
+            return family.rhythm;
+        }
         self.rng.gen_range(0.5..=2.0)
     }
     
     /// Get a random note count variation for melodies (±1-2 notes)
     pub fn random_note_count_variation(&mut self, base_count: usize) -> usize {
-        let variation = self.rng.gen_range(-2..=2);
-        ((base_count as i32 + variation).max(2).min(10)) as usize
+        let variation = match self.family {
+            Some(family) => family.note_count_offset,
+            None => self.rng.gen_range(-2..=2),
+        };
+        (base_count as i32 + variation).clamp(2, 10) as usize
     }
     
     /// Choose a random scale from available options
@@ -256,8 +1456,16 @@ impl JingleGenerator {
         self.rng.gen_range(range)
     }
     
+    /// Draw a seed for a nested render from the generator's random sequence
+    pub fn random_seed(&mut self) -> u64 {
+        self.rng.r#gen()
+    }
+    
     /// Choose a random waveform for variety
     pub fn random_waveform(&mut self) -> WaveForm {
+        if let Some(waveform) = self.family.and_then(|family| family.waveform) {
+            return waveform;
+        }
         let waveforms = [
             WaveForm::Sine,
             WaveForm::Triangle,
@@ -268,6 +1476,16 @@ impl JingleGenerator {
     }
 }
 
+/// Insert a 1-based, zero-padded index before a path's extension (`loop.wav` -> `loop_001.wav`)
+fn numbered_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("chunk");
+    let file_name = match path.extension().and_then(|s| s.to_str()) {
+        Some(extension) => format!("{}_{:03}.{}", stem, index, extension),
+        None => format!("{}_{:03}", stem, index),
+    };
+    path.with_file_name(file_name)
+}
+
 impl Default for JingleGenerator {
     fn default() -> Self {
         Self::new()
@@ -293,6 +1511,99 @@ mod tests {
         assert_eq!(samples.len(), (SAMPLE_RATE as f32 * 0.1) as usize);
     }
 
+    #[test]
+    fn test_generator_config() {
+        let crossings = |samples: &[f32]| samples.windows(2).filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0).count();
+        let config = GeneratorConfig::default().with_sample_rate(48000).with_a4(432.0).with_master_gain(0.5);
+        let generator = JingleGenerator::with_seed(3).with_config(config).unwrap();
+        let tone = generator.generate_tone(440.0, 1.0, WaveForm::Sine);
+        assert_eq!(tone.len(), 48000);
+        assert!((crossings(&tone) as i32 - 432).abs() <= 1, "{}", crossings(&tone));
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let reference = JingleGenerator::new().generate_tone(440.0, 1.0, WaveForm::Sine);
+        assert!((peak(&tone) - peak(&reference) * 0.5).abs() < 0.01);
+
+        // Presets and their effects render at the configured rate
+        let spec = crate::spec::JingleSpec::new(JinglePreset::Success).with_seed(5).with_effects(EffectChain::new().reverb(0.5, 0.5, 0.3));
+        let fast = generator.fork().unwrap().render(&spec);
+        let standard = JingleGenerator::new().render(&spec);
+        let ratio = fast.len() as f32 / standard.len() as f32;
+        assert!((ratio - 48000.0 / SAMPLE_RATE as f32).abs() < 0.01, "{}", ratio);
+        assert_eq!(generator.fork().unwrap().config().sample_rate, 48000);
+
+        assert!(JingleGenerator::new().with_config(GeneratorConfig::default().with_sample_rate(0)).is_err());
+        assert!(JingleGenerator::new().with_config(GeneratorConfig::default().with_a4(-440.0)).is_err());
+        assert!(JingleGenerator::new().with_config(GeneratorConfig::default().with_master_gain(f32::NAN)).is_err());
+    }
+
+    #[test]
+    fn test_melody_render_options() {
+        let generator = JingleGenerator::new();
+        let melody = Melody { notes: vec![(Note::C, 0.2), (Note::E, 0.2), (Note::G, 0.2), (Note::C, 0.2)] };
+        let note = (0.2 * SAMPLE_RATE as f32) as usize;
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+        let legato = generator.generate_melody_samples_with_options(&melody, 5, WaveForm::Sine, &MelodyRenderOptions::legato());
+        assert_eq!(legato, generator.generate_melody_samples(&melody, 5, WaveForm::Sine));
+
+        // Staccato keeps the timing but silences the second half of every note
+        let staccato = generator.generate_melody_samples_with_options(&melody, 5, WaveForm::Sine, &MelodyRenderOptions::staccato());
+        assert_eq!(staccato.len() / note, legato.len() / note);
+        for i in 0..melody.notes.len() {
+            assert!(peak(&staccato[i * note..i * note + note / 2]) > 0.05);
+            assert!(staccato[i * note + note / 2 + 1..(i + 1) * note].iter().all(|&s| s == 0.0));
+        }
+
+        let accented = MelodyRenderOptions::default().with_note_gains_db(vec![0.0, -12.0]);
+        let accented = generator.generate_melody_samples_with_options(&melody, 5, WaveForm::Sine, &accented);
+        assert!((peak(&accented[note..note * 2]) / peak(&legato[note..note * 2]) - 0.25).abs() < 0.01);
+        assert_eq!(accented[..note], legato[..note]);
+
+        let percussive = MelodyRenderOptions::default().with_adsr(ADSR { attack: 0.0, decay: 0.15, sustain: 0.0, release: 0.05 });
+        let percussive = generator.generate_melody_samples_with_options(&melody, 5, WaveForm::Sine, &percussive);
+        assert!(peak(&percussive[..note / 10]) > peak(&legato[..note / 10]) * 2.0);
+
+        // The second note's attack sounds over the first note's release
+        let overlapped = generator.generate_melody_samples_with_options(&melody, 5, WaveForm::Sine, &MelodyRenderOptions::default().with_overlap(0.1));
+        assert_eq!(overlapped.len(), legato.len());
+        assert!(peak(&overlapped[note..note + note / 20]) > peak(&legato[note..note + note / 20]) * 2.0);
+        assert_eq!(overlapped[note * 3 + note / 2..], legato[note * 3 + note / 2..]);
+    }
+
+    #[test]
+    fn test_melody_source_matches_render() {
+        let melody = Melody::from_scale(Scale::Major, Note::C, 4, MelodyPattern::Ascending, 0.05);
+        let config = GeneratorConfig::default().with_sample_rate(22050).with_a4(442.0).with_master_gain(0.8);
+        let mut generator = JingleGenerator::new().with_config(config).unwrap();
+        for chiptune in [false, true] {
+            generator.set_chiptune(chiptune);
+            let streamed: Vec<f32> = generator.melody_source(&melody, 5, WaveForm::Sine).collect();
+            assert_eq!(streamed, generator.generate_melody_samples(&melody, 5, WaveForm::Sine));
+        }
+    }
+
+    #[test]
+    fn test_render_into_reuses_buffer() {
+        let mut generator = JingleGenerator::new();
+        generator.set_declick(true);
+        let melody = Melody::from_scale(Scale::Major, Note::C, 4, MelodyPattern::Ascending, 0.05);
+        let expected = generator.generate_melody_samples(&melody, 5, WaveForm::Square);
+
+        let mut buffer = Vec::new();
+        generator.generate_melody_samples_into(&melody, 5, WaveForm::Square, &mut buffer);
+        assert_eq!(buffer, expected);
+        let allocation = (buffer.as_ptr(), buffer.capacity());
+        buffer.clear();
+        generator.generate_melody_samples_into(&melody, 5, WaveForm::Square, &mut buffer);
+        assert_eq!(buffer, expected);
+        assert_eq!((buffer.as_ptr(), buffer.capacity()), allocation);
+
+        // Appending leaves what is already in the buffer untouched
+        generator.generate_tone_into(440.0, 0.05, WaveForm::Sine, &mut buffer);
+        assert_eq!(buffer[..expected.len()], expected[..]);
+        assert_eq!(buffer[expected.len()..], generator.generate_tone(440.0, 0.05, WaveForm::Sine)[..]);
+    }
+
     #[test]
     fn test_melody_sample_generation() {
         let generator = JingleGenerator::new();
@@ -312,6 +1623,33 @@ mod tests {
         assert_eq!(combined[3], 0.4);
     }
 
+    #[test]
+    fn test_declicked_combination() {
+        let mut generator = JingleGenerator::with_seed(3);
+        generator.set_declick(true);
+        let combined = generator.combine_samples(&[vec![0.5; 1000], vec![-0.5; 1000]], 0.0);
+        assert_eq!(combined.len(), 2000);
+        assert_eq!(combined[1000], 0.0);
+        assert!(combined[999].abs() < 0.01);
+        assert_eq!(combined[500], 0.5);
+        assert!(generator.fork().unwrap().get_declick());
+    }
+
+    #[test]
+    fn test_declicked_combination_with_gap() {
+        let mut generator = JingleGenerator::with_seed(3);
+        generator.set_declick(true);
+        let combined = generator.combine_samples(&[vec![0.5; 1000], vec![-0.5; 1000]], 0.01);
+        let gap = (SAMPLE_RATE as f32 * 0.01) as usize;
+        assert_eq!(combined.len(), 2000 + gap);
+        // Each array fades out before the gap and back in after it
+        assert!(combined[999].abs() < 0.01);
+        assert!(combined[1000..1000 + gap].iter().all(|&s| s == 0.0));
+        assert_eq!(combined[1000 + gap], 0.0);
+        assert_eq!(combined[500], 0.5);
+        assert_eq!(combined[1500 + gap], -0.5);
+    }
+
     #[test]
     fn test_seeded_generation() {
         let mut gen1 = JingleGenerator::with_seed(12345);
@@ -342,6 +1680,36 @@ mod tests {
         assert_eq!(generator.random_variation(), first_value);
     }
 
+    #[test]
+    fn test_default_rng_stream_is_stable() {
+        // Stored seeds rely on these exact values; update only with a breaking release
+        let mut generator = JingleGenerator::with_seed(42);
+        assert_eq!(generator.random_seed(), 9713269763989775522);
+        assert_eq!(generator.random_seed(), 10011513049433592189);
+        assert_eq!(generator.random_range(0..1000), 868);
+    }
+
+    #[test]
+    fn test_injected_rng() {
+        use rand_chacha::ChaCha8Rng;
+
+        let mut default = JingleGenerator::with_rng::<DefaultRng>(42);
+        assert_eq!(default.random_seed(), JingleGenerator::with_seed(42).random_seed());
+
+        let mut injected = JingleGenerator::with_rng::<ChaCha8Rng>(42);
+        let first = injected.random_seed();
+        assert_eq!(first, ChaCha8Rng::seed_from_u64(42).r#gen::<u64>());
+        assert_ne!(first, JingleGenerator::with_seed(42).random_seed());
+
+        // Reseeding, forking and per-call seeds keep the injected type
+        injected.reset_seed().unwrap();
+        assert_eq!(injected.random_seed(), first);
+        assert_eq!(injected.fork().unwrap().random_seed(), first);
+        assert_eq!(injected.with_call_seed(42, |g| g.random_seed()), first);
+        injected.set_seed(42);
+        assert_eq!(injected.random_seed(), first);
+    }
+
     #[test]
     fn test_fork_generator() {
         let mut original = JingleGenerator::with_seed(12345);
@@ -361,6 +1729,450 @@ mod tests {
         assert_eq!(derived.get_current_seed(), Some(12345u64.wrapping_add(100)));
     }
 
+    #[test]
+    fn test_generate_family() {
+        let generator = JingleGenerator::with_seed(42);
+        let family = generator.generate_family(JinglePreset::Success, 6).unwrap();
+        assert_eq!(family.len(), 6);
+        assert_eq!(generator.generate_family(JinglePreset::Success, 6).unwrap(), family);
+        
+        // Members are related but not identical renders
+        assert!(family.iter().skip(1).any(|member| member != &family[0]));
+        
+        // A shared tempo leaves one length per melody pattern
+        let mut lengths: Vec<usize> = family.iter().map(Vec::len).collect();
+        lengths.sort_unstable();
+        lengths.dedup();
+        assert!(lengths.len() <= 2, "{:?}", lengths);
+        
+        assert!(JingleGenerator::new().generate_family(JinglePreset::Success, 2).is_err());
+    }
+
+    #[test]
+    fn test_apply_effects() {
+        let generator = JingleGenerator::with_seed(1);
+        let samples = generator.generate_tone(440.0, 0.1, WaveForm::Sine);
+        let chain = EffectChain::new().echo(30.0, 0.3, 0.4).limiter();
+        assert_eq!(generator.apply_effects(&samples, &chain), chain.process(samples.clone()));
+        assert_eq!(generator.apply_effects(&samples, &EffectChain::new()), samples);
+    }
+
+    #[test]
+    fn test_no_dither_rounds() {
+        let generator = JingleGenerator::new();
+        let converted = generator.samples_to_i16(&[0.0, 1.0, -1.0, 2.0, 0.5]);
+        assert_eq!(converted, vec![0, i16::MAX, -i16::MAX, i16::MAX, 16384]);
+    }
+
+    #[test]
+    fn test_tpdf_dither_stays_within_one_lsb() {
+        let mut generator = JingleGenerator::with_seed(7);
+        generator.set_dither(Dither::Tpdf);
+        let samples: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.01).sin() * 0.001).collect();
+        let plain = JingleGenerator::new().samples_to_i16(&samples);
+        let dithered = generator.samples_to_i16(&samples);
+        
+        assert!(plain.iter().zip(&dithered).all(|(a, b)| (*a as i32 - *b as i32).abs() <= 1));
+        assert_ne!(plain, dithered);
+    }
+
+    #[test]
+    fn test_noise_shaped_dither_is_deterministic() {
+        let mut generator = JingleGenerator::with_seed(7);
+        generator.set_dither(Dither::NoiseShaped);
+        let samples = vec![0.0001; 100];
+        assert_eq!(generator.samples_to_i16(&samples), generator.samples_to_i16(&samples));
+    }
+
+    #[test]
+    fn test_loudness_target_scales_export() {
+        let mut generator = JingleGenerator::new();
+        let samples = generator.generate_tone(440.0, 0.5, WaveForm::Sine);
+        let plain_peak = generator.samples_to_i16(&samples).iter().map(|s| s.unsigned_abs()).max().unwrap();
+        
+        generator.set_loudness_target(Some(-30.0));
+        let quiet_peak = generator.samples_to_i16(&samples).iter().map(|s| s.unsigned_abs()).max().unwrap();
+        assert!(quiet_peak < plain_peak);
+    }
+
+    #[test]
+    fn test_export_at_output_sample_rate() {
+        let mut generator = JingleGenerator::new();
+        generator.set_output_sample_rate(Some(48000));
+        let samples = generator.generate_tone(440.0, 0.1, WaveForm::Sine);
+        let path = std::env::temp_dir().join("jinglebells_test_output_rate.wav");
+        generator.export_to_wav(&samples, &path).unwrap();
+        
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.spec().sample_rate, 48000);
+        assert_eq!(reader.len(), 4800);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_encode_wav_matches_file_export() {
+        let generator = JingleGenerator::with_seed(1);
+        let samples = generator.generate_tone(440.0, 0.05, WaveForm::Sine);
+        let path = std::env::temp_dir().join("jinglebells_test_encode_wav.wav");
+        generator.export_to_wav(&samples, &path).unwrap();
+        
+        assert_eq!(std::fs::read(&path).unwrap(), generator.encode_wav(&samples).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_wav_export() {
+        let generator = JingleGenerator::with_seed(1);
+        let samples = generator.generate_tone(440.0, 0.05, WaveForm::Sine);
+        let path = std::env::temp_dir().join("jinglebells_test_async_export.wav");
+        generator.export_to_file_async(&samples, &path).await.unwrap();
+        
+        assert_eq!(std::fs::read(&path).unwrap(), generator.encode_wav(&samples).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(not(feature = "opus"))]
+    #[test]
+    fn test_opus_export_requires_feature() {
+        let generator = JingleGenerator::new();
+        let path = std::env::temp_dir().join("jinglebells_test_disabled.opus");
+        assert!(matches!(
+            generator.export_to_file(&[0.0; 10], &path),
+            Err(crate::error::JingleError::OpusError(_))
+        ));
+    }
+
+    #[test]
+    fn test_multichannel_wav_export() {
+        let generator = JingleGenerator::new();
+        let tone = generator.generate_tone(440.0, 0.05, WaveForm::Sine);
+        let channels = crate::channels::pan(&tone, &[1.0, 0.8, 0.0, 0.0, 0.5, 0.5]);
+        let bytes = generator.encode_multichannel_wav(&channels, ChannelLayout::Surround51).unwrap();
+        
+        let reader = hound::WavReader::new(std::io::Cursor::new(&bytes)).unwrap();
+        assert_eq!(reader.spec().channels, 6);
+        assert_eq!(reader.len() as usize, tone.len() * 6);
+    }
+
+    #[test]
+    fn test_multichannel_wav_channel_mask() {
+        let generator = JingleGenerator::new();
+        let channel_mask = |bytes: &[u8]| u32::from_le_bytes(bytes[40..44].try_into().unwrap());
+        for (layout, mask) in [
+            (ChannelLayout::Quad, 0x33),
+            (ChannelLayout::Surround51, 0x3F),
+            (ChannelLayout::Surround71, 0x63F),
+            (ChannelLayout::Discrete(4), 0),
+        ] {
+            let channels = vec![vec![0.0; 10]; layout.channel_count() as usize];
+            let bytes = generator.encode_multichannel_wav(&channels, layout).unwrap();
+            // WAVE_FORMAT_EXTENSIBLE, with the mask after the 22-byte extension size and valid bits
+            assert_eq!(&bytes[12..16], b"fmt ");
+            assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 0xFFFE);
+            assert_eq!(channel_mask(&bytes), mask, "{:?}", layout);
+            assert!(hound::WavReader::new(std::io::Cursor::new(bytes)).is_ok());
+        }
+
+        // Stereo at 16 bits keeps the plain PCM header, which has no mask
+        let bytes = generator.encode_multichannel_wav(&[vec![0.0; 10], vec![0.0; 10]], ChannelLayout::Stereo).unwrap();
+        assert_eq!(u16::from_le_bytes([bytes[20], bytes[21]]), 1);
+    }
+
+    #[test]
+    fn test_multichannel_layout_mismatch() {
+        let generator = JingleGenerator::new();
+        let channels = vec![vec![0.0; 10]; 3];
+        assert!(generator.encode_multichannel_wav(&channels, ChannelLayout::Quad).is_err());
+    }
+
+    #[test]
+    fn test_export_variations_with_manifests() {
+        let mut generator = JingleGenerator::with_seed(99);
+        generator.set_write_manifests(true);
+        let dir = std::env::temp_dir().join("jinglebells_test_export_manifests");
+        let written = generator
+            .export_variations(JinglePreset::Success, WaveForm::Triangle, 2, &dir, |i, _| format!("success_{}.wav", i))
+            .unwrap();
+        
+        for (path, seed) in &written {
+            let json = std::fs::read_to_string(path.with_extension("json")).unwrap();
+            assert!(json.contains("\"preset\": \"success\""));
+            assert!(json.contains(&format!("\"seed\": {}", seed)));
+            assert!(json.contains("\"scale\": \""));
+            assert!(json.contains(&format!("\"duration\": {}", JinglePreset::Success.default_duration())));
+            assert!(json.contains(&format!("\"frequency\": {}", JinglePreset::Success.default_frequency())));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_append_to_wav() {
+        let generator = JingleGenerator::new();
+        let path = std::env::temp_dir().join("jinglebells_test_append.wav");
+        let _ = std::fs::remove_file(&path);
+        
+        let first = generator.generate_tone(440.0, 0.05, WaveForm::Sine);
+        let second = generator.generate_tone(660.0, 0.1, WaveForm::Sine);
+        generator.append_to_wav(&first, &path).unwrap();
+        generator.append_to_wav(&second, &path).unwrap();
+        
+        let reader = hound::WavReader::open(&path).unwrap();
+        assert_eq!(reader.len() as usize, first.len() + second.len());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_export_bit_depth() {
+        let mut generator = JingleGenerator::with_seed(4);
+        generator.set_bit_depth(BitDepth::Float32);
+        assert_eq!(generator.derive_seed(1).unwrap().get_bit_depth(), BitDepth::Float32);
+        let samples = generator.generate_tone(440.0, 0.05, WaveForm::Sine);
+        let path = std::env::temp_dir().join("jinglebells_test_bit_depth.wav");
+        generator.export_to_file(&samples, &path).unwrap();
+        
+        let spec = hound::WavReader::open(&path).unwrap().spec();
+        assert_eq!((spec.bits_per_sample, spec.sample_format), (32, SampleFormat::Float));
+        std::fs::remove_file(&path).unwrap();
+        
+        // Appends stay 16-bit whatever the export depth
+        generator.append_to_wav(&samples, &path).unwrap();
+        generator.append_to_wav(&samples, &path).unwrap();
+        assert_eq!(hound::WavReader::open(&path).unwrap().spec().bits_per_sample, 16);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_export_to_writer() {
+        let generator = JingleGenerator::with_seed(4);
+        let samples = generator.generate_tone(440.0, 0.05, WaveForm::Sine);
+        
+        let mut wav = Vec::new();
+        generator.export_to_writer(&samples, "wav", &mut wav).unwrap();
+        assert_eq!(wav, generator.encode_wav(&samples).unwrap());
+        
+        let mut raw = Vec::new();
+        generator.export_to_writer(&samples, "raw", &mut raw).unwrap();
+        let pcm: Vec<u8> = generator.samples_to_i16(&samples).into_iter().flat_map(i16::to_le_bytes).collect();
+        assert_eq!(raw, pcm);
+    }
+
+    #[test]
+    fn test_append_to_mismatched_wav() {
+        let generator = JingleGenerator::new();
+        let path = std::env::temp_dir().join("jinglebells_test_append_stereo.wav");
+        generator.export_multichannel_wav(&[vec![0.0; 10], vec![0.0; 10]], ChannelLayout::Stereo, &path).unwrap();
+        
+        assert!(generator.append_to_wav(&[0.0; 10], &path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "mp3")]
+    #[test]
+    fn test_mp3_encoding_modes() {
+        let generator = JingleGenerator::with_seed(5);
+        let samples = generator.generate_tone(440.0, 0.3, WaveForm::Sine);
+        
+        for options in [Mp3Options::cbr(128), Mp3Options::vbr(2), Mp3Options::abr(96).with_quality(5)] {
+            let mp3 = generator.encode_mp3_with_options(&samples, &options).unwrap();
+            assert!(!mp3.is_empty(), "{:?} produced no data", options);
+        }
+    }
+
+    /// Encoder writing raw little-endian f32 samples
+    struct RawEncoder;
+
+    impl AudioEncoder for RawEncoder {
+        fn encode(&self, samples: &[f32], _spec: EncodeSpec, output: &mut dyn std::io::Write) -> Result<()> {
+            for sample in samples {
+                output.write_all(&sample.to_le_bytes())?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_registered_encoder() {
+        let mut generator = JingleGenerator::new();
+        generator.register_encoder(".RAW", RawEncoder);
+        let path = std::env::temp_dir().join("jinglebells_test_custom.raw");
+        generator.export_to_file(&[0.5, -0.25], &path).unwrap();
+        
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes, [0.5f32.to_le_bytes(), (-0.25f32).to_le_bytes()].concat());
+        std::fs::remove_file(&path).unwrap();
+        
+        let formats = generator.supported_formats();
+        assert!(formats.contains(&"raw".to_string()) && formats.contains(&"wav".to_string()));
+        assert_eq!(JingleGenerator::new().supported_formats().contains(&"mp3".to_string()), cfg!(feature = "mp3"));
+    }
+
+    #[test]
+    fn test_registered_encoder_overrides_wav() {
+        let mut generator = JingleGenerator::with_seed(3);
+        generator.register_encoder("wav", RawEncoder);
+        generator.set_output_sample_rate(Some(22050));
+        
+        // Settings and encoders carry over to derived generators
+        let derived = generator.derive_seed(1).unwrap();
+        assert_eq!(derived.get_output_sample_rate(), 22050);
+        let path = std::env::temp_dir().join("jinglebells_test_override.wav");
+        derived.export_to_file(&vec![0.0; 100], &path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap().len(), 50 * 4);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_export_chunked() {
+        let generator = JingleGenerator::new();
+        let samples = generator.generate_tone(440.0, 1.0, WaveForm::Sine);
+        let dir = std::env::temp_dir().join("jinglebells_test_chunked");
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        let written = generator.export_chunked(&samples, dir.join("loop.wav"), &ChunkOptions::new(0.4)).unwrap();
+        let names: Vec<_> = written.iter().map(|p| p.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, vec!["loop_001.wav", "loop_002.wav", "loop_003.wav"]);
+        
+        let total: u32 = written.iter().map(|p| hound::WavReader::open(p).unwrap().len()).sum();
+        assert_eq!(total as usize, samples.len());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_chunked_crossfade() {
+        let generator = JingleGenerator::new();
+        let samples = vec![0.5; 1000];
+        let dir = std::env::temp_dir().join("jinglebells_test_chunked_crossfade");
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        let chunk = 600.0 / SAMPLE_RATE as f32;
+        let overlap = 200.0 / SAMPLE_RATE as f32;
+        let options = ChunkOptions::new(chunk).with_overlap(overlap).with_crossfade(true);
+        let written = generator.export_chunked(&samples, dir.join("pad.wav"), &options).unwrap();
+        assert_eq!(written.len(), 2);
+        
+        let read = |path: &PathBuf| -> Vec<i16> {
+            hound::WavReader::open(path).unwrap().samples::<i16>().map(|s| s.unwrap()).collect()
+        };
+        let (first, second) = (read(&written[0]), read(&written[1]));
+        assert_eq!(first.len(), 600);
+        assert_eq!(second.len(), 600);
+        // The faded tail of one chunk and the faded head of the next sum to the original level
+        for i in 0..200 {
+            let sum = first[400 + i] as i32 + second[i] as i32;
+            assert!((sum - 16384).abs() <= 1, "sample {} sums to {}", i, sum);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_chunked_rejects_overlap_longer_than_chunk() {
+        let generator = JingleGenerator::new();
+        let path = std::env::temp_dir().join("jinglebells_test_chunked_invalid.wav");
+        assert!(generator.export_chunked(&[0.0; 100], &path, &ChunkOptions::new(0.1).with_overlap(0.2)).is_err());
+    }
+
+    #[test]
+    fn test_progress_callback() {
+        use std::sync::Mutex;
+        
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let recorded = updates.clone();
+        let generator = JingleGenerator::with_seed(8).with_progress(move |done, total| recorded.lock().unwrap().push((done, total)));
+        
+        let melody = Melody::from_scale(Scale::Major, Note::C, 4, MelodyPattern::Ascending, 0.01);
+        generator.generate_melody_samples(&melody, 4, WaveForm::Sine);
+        let total = melody.notes.len();
+        assert_eq!(*updates.lock().unwrap(), (1..=total).map(|done| (done, total)).collect::<Vec<_>>());
+        
+        updates.lock().unwrap().clear();
+        let dir = std::env::temp_dir().join("jinglebells_test_progress");
+        generator.export_variations(JinglePreset::Alert, WaveForm::Sine, 2, &dir, |i, _| format!("{}.wav", i)).unwrap();
+        assert_eq!(*updates.lock().unwrap(), vec![(1, 2), (2, 2)]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_chunked_progress_counts_files() {
+        use std::sync::Mutex;
+        
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let recorded = updates.clone();
+        let generator = JingleGenerator::new().with_progress(move |done, total| recorded.lock().unwrap().push((done, total)));
+        let dir = std::env::temp_dir().join("jinglebells_test_chunked_progress");
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        let options = ChunkOptions::new(0.01).with_overlap(0.002);
+        let written = generator.export_chunked(&vec![0.0; 2000], dir.join("a.wav"), &options).unwrap();
+        let updates = updates.lock().unwrap();
+        assert_eq!(updates.len(), written.len());
+        assert!(updates.iter().all(|&(_, total)| total == written.len()));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_wav_loop_survives_append() {
+        let mut generator = JingleGenerator::new();
+        generator.set_output_sample_rate(Some(22050));
+        let samples = generator.generate_tone(220.0, 0.2, WaveForm::Sine);
+        let path = std::env::temp_dir().join("jinglebells_test_loop.wav");
+        generator.export_to_wav_with_loop(&samples, &path, LoopRegion::new(0, samples.len() as u32)).unwrap();
+        generator.append_to_wav(&samples, &path).unwrap();
+        
+        let bytes = std::fs::read(&path).unwrap();
+        let smpl = bytes.windows(4).position(|w| w == b"smpl").unwrap();
+        // Loop end is stored inclusive at the output rate
+        assert_eq!(u32::from_le_bytes(bytes[smpl + 56..smpl + 60].try_into().unwrap()), 4409);
+        assert_eq!(hound::WavReader::open(&path).unwrap().len(), 2 * 4410);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_melody_cue_markers() {
+        let generator = JingleGenerator::new();
+        let melody = Melody::from_scale(Scale::Major, Note::C, 4, MelodyPattern::Ascending, 0.05);
+        let (samples, markers) = generator.generate_melody_samples_with_cues(&melody, 5, WaveForm::Sine);
+        
+        assert_eq!(markers.len(), melody.notes.len());
+        assert_eq!(markers[0], CueMarker::new(0, "C5"));
+        assert!(markers.windows(2).all(|pair| pair[0].position < pair[1].position));
+        
+        let bytes = generator.encode_wav_with_cues(&samples, &markers).unwrap();
+        assert!(bytes.windows(4).any(|w| w == b"cue "));
+        assert!(bytes.windows(8).any(|w| w == b"labl\x07\x00\x00\x00"));
+        assert_eq!(hound::WavReader::new(std::io::Cursor::new(bytes)).unwrap().len() as usize, samples.len());
+    }
+
+    #[test]
+    fn test_cue_marker_past_end() {
+        let generator = JingleGenerator::new();
+        assert!(generator.encode_wav_with_cues(&[0.0; 10], &[CueMarker::new(20, "late")]).is_err());
+    }
+
+    #[test]
+    fn test_export_variations() {
+        let generator = JingleGenerator::with_seed(12345);
+        let dir = std::env::temp_dir().join("jinglebells_test_export_variations");
+        let written = generator
+            .export_variations(JinglePreset::Notification, WaveForm::Sine, 3, &dir, |i, _| format!("notification_{}.wav", i))
+            .unwrap();
+        
+        assert_eq!(written.len(), 3);
+        for (i, (path, seed)) in written.iter().enumerate() {
+            assert!(path.exists());
+            assert_eq!(*seed, 12345u64.wrapping_add(i as u64 + 1));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_variations_requires_seed() {
+        let generator = JingleGenerator::new();
+        let dir = std::env::temp_dir().join("jinglebells_test_export_variations_unseeded");
+        assert!(generator.export_variations(JinglePreset::Alert, WaveForm::Sine, 2, &dir, |i, _| format!("{}.wav", i)).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_no_seed_operations() {
         let generator = JingleGenerator::new();
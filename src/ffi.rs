@@ -0,0 +1,146 @@
+//! C interface for embedding the engine in other languages
+//!
+//! Build the shared library with
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`; the `ffi`
+//! feature also regenerates `include/jinglebells.h` from this module.
+//! Generators are opaque handles created with `jingle_generator_new` and
+//! released with `jingle_generator_free`; functions returning `int32_t` report
+//! `JINGLE_OK` or one of the negative `JINGLE_ERROR_*` codes.
+
+use std::ffi::{CStr, c_char};
+use std::ptr;
+use crate::{export::JingleGenerator, presets::JinglePreset};
+
+/// The call succeeded
+pub const JINGLE_OK: i32 = 0;
+/// A pointer was null or a string was not valid UTF-8
+pub const JINGLE_ERROR_INVALID_ARGUMENT: i32 = -1;
+/// No built-in preset has the given name
+pub const JINGLE_ERROR_UNKNOWN_PRESET: i32 = -2;
+/// The file could not be encoded or written
+pub const JINGLE_ERROR_EXPORT: i32 = -3;
+
+/// Borrow a C string as UTF-8, or `None` if it is null or invalid
+///
+/// # Safety
+/// `string` must be null or point to a NUL-terminated string.
+unsafe fn to_str<'a>(string: *const c_char) -> Option<&'a str> {
+    if string.is_null() {
+        return None;
+    }
+    unsafe { CStr::from_ptr(string) }.to_str().ok()
+}
+
+/// Create a generator whose random choices follow `seed`
+///
+/// Free it with `jingle_generator_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn jingle_generator_new(seed: u64) -> *mut JingleGenerator {
+    Box::into_raw(Box::new(JingleGenerator::with_seed(seed)))
+}
+
+/// Free a generator created with `jingle_generator_new`; null is ignored
+///
+/// # Safety
+/// `generator` must be null or a handle from `jingle_generator_new` that has not been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jingle_generator_free(generator: *mut JingleGenerator) {
+    if !generator.is_null() {
+        drop(unsafe { Box::from_raw(generator) });
+    }
+}
+
+/// Rate (Hz) of the samples a generator renders, or 0 for a null handle
+///
+/// # Safety
+/// `generator` must be null or a live handle from `jingle_generator_new`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jingle_sample_rate(generator: *const JingleGenerator) -> u32 {
+    unsafe { generator.as_ref() }.map_or(0, JingleGenerator::get_sample_rate)
+}
+
+/// Render a built-in preset with its own waveform into a caller-provided buffer
+///
+/// Writes at most `capacity` samples and returns the full length of the
+/// jingle, so a return value above `capacity` means the buffer was too short.
+/// Returns a negative `JINGLE_ERROR_*` code on failure.
+///
+/// # Safety
+/// `generator` must be a live handle, `name` a NUL-terminated string and
+/// `buffer` valid for `capacity` writes (it may be null when `capacity` is 0).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jingle_render_preset(generator: *mut JingleGenerator, name: *const c_char, buffer: *mut f32, capacity: usize) -> i64 {
+    let (Some(generator), Some(name)) = (unsafe { generator.as_mut() }, unsafe { to_str(name) }) else {
+        return JINGLE_ERROR_INVALID_ARGUMENT as i64;
+    };
+    if buffer.is_null() && capacity > 0 {
+        return JINGLE_ERROR_INVALID_ARGUMENT as i64;
+    }
+    let Some(preset) = JinglePreset::from_name(name) else {
+        return JINGLE_ERROR_UNKNOWN_PRESET as i64;
+    };
+
+    let samples = preset.generate(generator, preset.style().waveform);
+    let written = samples.len().min(capacity);
+    if written > 0 {
+        unsafe { ptr::copy_nonoverlapping(samples.as_ptr(), buffer, written) };
+    }
+    samples.len() as i64
+}
+
+/// Export `length` samples to a 16-bit WAV file at the generator's sample rate
+///
+/// # Safety
+/// `generator` must be a live handle, `samples` valid for `length` reads
+/// and `path` a NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn jingle_export_wav(generator: *const JingleGenerator, samples: *const f32, length: usize, path: *const c_char) -> i32 {
+    let (Some(generator), Some(path)) = (unsafe { generator.as_ref() }, unsafe { to_str(path) }) else {
+        return JINGLE_ERROR_INVALID_ARGUMENT;
+    };
+    if samples.is_null() {
+        return JINGLE_ERROR_INVALID_ARGUMENT;
+    }
+    let samples = unsafe { std::slice::from_raw_parts(samples, length) };
+    match generator.export_to_wav(samples, path) {
+        Ok(()) => JINGLE_OK,
+        Err(_) => JINGLE_ERROR_EXPORT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_render_and_export() {
+        let generator = jingle_generator_new(3);
+        let name = CString::new("notification").unwrap();
+        unsafe {
+            assert_eq!(jingle_sample_rate(generator), crate::SAMPLE_RATE);
+            let mut buffer = vec![0.0f32; 16];
+            let length = jingle_render_preset(generator, name.as_ptr(), buffer.as_mut_ptr(), buffer.len());
+            assert!(length > 16);
+
+            buffer.resize(length as usize, 0.0);
+            let reseeded = jingle_generator_new(3);
+            assert_eq!(jingle_render_preset(reseeded, name.as_ptr(), buffer.as_mut_ptr(), buffer.len()), length);
+            assert!(buffer.iter().any(|&s| s != 0.0));
+
+            let unknown = CString::new("no-such-preset").unwrap();
+            assert_eq!(jingle_render_preset(generator, unknown.as_ptr(), ptr::null_mut(), 0), JINGLE_ERROR_UNKNOWN_PRESET as i64);
+            assert_eq!(jingle_render_preset(generator, ptr::null(), ptr::null_mut(), 0), JINGLE_ERROR_INVALID_ARGUMENT as i64);
+
+            let path = std::env::temp_dir().join("jinglebells_ffi_test.wav");
+            let c_path = CString::new(path.to_str().unwrap()).unwrap();
+            assert_eq!(jingle_export_wav(reseeded, buffer.as_ptr(), buffer.len(), c_path.as_ptr()), JINGLE_OK);
+            assert!(std::fs::metadata(&path).unwrap().len() > 44);
+            std::fs::remove_file(path).unwrap();
+
+            jingle_generator_free(generator);
+            jingle_generator_free(reseeded);
+            jingle_generator_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Biquad filters using the RBJ audio EQ cookbook recipes, and a sweepable state-variable filter
+
+use crate::{SAMPLE_RATE, Real, to_sample, audio::ADSR, effects::{Lfo, LfoShape}, error::{Result, check_range, check_positive}};
+
+/// Pi at the precision of the filter state
+const REAL_PI: Real = std::f64::consts::PI as Real;
+
+/// Butterworth Q giving a maximally flat passband
+pub const BUTTERWORTH_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Second-order IIR filter section (transposed direct form II)
+#[derive(Clone, Debug)]
+pub struct Biquad {
+    b0: Real,
+    b1: Real,
+    b2: Real,
+    a1: Real,
+    a2: Real,
+    z1: Real,
+    z2: Real,
+}
+
+impl Biquad {
+    /// Create a filter from raw coefficients, normalized so that a0 is 1
+    pub fn from_coefficients(b: [f32; 3], a: [f32; 2]) -> Self {
+        Self {
+            b0: Real::from(b[0]),
+            b1: Real::from(b[1]),
+            b2: Real::from(b[2]),
+            a1: Real::from(a[0]),
+            a2: Real::from(a[1]),
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    /// Normalize cookbook coefficients by a0
+    fn normalized(b0: Real, b1: Real, b2: Real, a0: Real, a1: Real, a2: Real) -> Self {
+        Self { b0: b0 / a0, b1: b1 / a0, b2: b2 / a0, a1: a1 / a0, a2: a2 / a0, z1: 0.0, z2: 0.0 }
+    }
+
+    /// Cosine of the angular frequency and the alpha term shared by every recipe
+    fn omega(frequency: f32, q: f32, sample_rate: f32) -> (Real, Real) {
+        let frequency = Real::from(frequency.clamp(1.0, sample_rate * 0.49));
+        let w0 = 2.0 * REAL_PI * frequency / Real::from(sample_rate);
+        (w0.cos(), w0.sin() / (2.0 * Real::from(q.max(0.01))))
+    }
+
+    /// Low-pass filter; higher Q adds a resonant peak at the cutoff
+    pub fn low_pass(cutoff: f32, q: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::omega(cutoff, q, sample_rate);
+        Self::normalized(
+            (1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0,
+            1.0 + alpha, -2.0 * cos, 1.0 - alpha,
+        )
+    }
+
+    /// High-pass filter; higher Q adds a resonant peak at the cutoff
+    pub fn high_pass(cutoff: f32, q: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::omega(cutoff, q, sample_rate);
+        Self::normalized(
+            (1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0,
+            1.0 + alpha, -2.0 * cos, 1.0 - alpha,
+        )
+    }
+
+    /// Band-pass filter with 0 dB peak gain at the center frequency
+    pub fn band_pass(center: f32, q: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::omega(center, q, sample_rate);
+        Self::normalized(
+            alpha, 0.0, -alpha,
+            1.0 + alpha, -2.0 * cos, 1.0 - alpha,
+        )
+    }
+
+    /// Notch filter removing the center frequency
+    pub fn notch(center: f32, q: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::omega(center, q, sample_rate);
+        Self::normalized(
+            1.0, -2.0 * cos, 1.0,
+            1.0 + alpha, -2.0 * cos, 1.0 - alpha,
+        )
+    }
+
+    /// Peaking EQ boosting or cutting around the center frequency
+    pub fn peak(center: f32, q: f32, gain_db: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::omega(center, q, sample_rate);
+        let a = Real::powf(10.0, Real::from(gain_db) / 40.0);
+        Self::normalized(
+            1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a,
+            1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a,
+        )
+    }
+
+    /// Low shelf boosting or cutting below the corner frequency
+    pub fn low_shelf(corner: f32, q: f32, gain_db: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::omega(corner, q, sample_rate);
+        let a = Real::powf(10.0, Real::from(gain_db) / 40.0);
+        let beta = 2.0 * a.sqrt() * alpha;
+        Self::normalized(
+            a * ((a + 1.0) - (a - 1.0) * cos + beta),
+            2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+            a * ((a + 1.0) - (a - 1.0) * cos - beta),
+            (a + 1.0) + (a - 1.0) * cos + beta,
+            -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+            (a + 1.0) + (a - 1.0) * cos - beta,
+        )
+    }
+
+    /// High shelf boosting or cutting above the corner frequency
+    pub fn high_shelf(corner: f32, q: f32, gain_db: f32, sample_rate: f32) -> Self {
+        let (cos, alpha) = Self::omega(corner, q, sample_rate);
+        let a = Real::powf(10.0, Real::from(gain_db) / 40.0);
+        let beta = 2.0 * a.sqrt() * alpha;
+        Self::normalized(
+            a * ((a + 1.0) + (a - 1.0) * cos + beta),
+            -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+            a * ((a + 1.0) + (a - 1.0) * cos - beta),
+            (a + 1.0) - (a - 1.0) * cos + beta,
+            2.0 * ((a - 1.0) - (a + 1.0) * cos),
+            (a + 1.0) - (a - 1.0) * cos - beta,
+        )
+    }
+
+    /// Process a single sample through the filter
+    pub fn process_sample(&mut self, input: f32) -> f32 {
+        let input = Real::from(input);
+        let output = self.b0 * input + self.z1;
+        self.z1 = self.b1 * input - self.a1 * output + self.z2;
+        self.z2 = self.b2 * input - self.a2 * output;
+        to_sample(output)
+    }
+
+    /// Filter a block of samples in place
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples.iter_mut() {
+            *sample = self.process_sample(*sample);
+        }
+    }
+
+    /// Replace the coefficients with those of another filter, keeping the state
+    ///
+    /// Useful for sweeping a filter without clicks from resetting its memory.
+    pub fn set_coefficients(&mut self, other: &Biquad) {
+        self.b0 = other.b0;
+        self.b1 = other.b1;
+        self.b2 = other.b2;
+        self.a1 = other.a1;
+        self.a2 = other.a2;
+    }
+
+    /// Reset the filter state
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
+
+/// Output taken from a state-variable filter
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum SvfMode {
+    LowPass,
+    HighPass,
+    BandPass,
+    Notch,
+}
+
+/// Resonant state-variable filter whose cutoff can change every sample
+///
+/// Uses the trapezoidal-integrated topology, which stays stable while the
+/// cutoff is swept quickly.
+#[derive(Clone, Debug)]
+pub struct StateVariableFilter {
+    mode: SvfMode,
+    damping: Real,
+    sample_rate: f32,
+    ic1eq: Real,
+    ic2eq: Real,
+}
+
+impl StateVariableFilter {
+    /// Create a new state-variable filter
+    /// - q: resonance (0.707 is flat, higher values ring at the cutoff)
+    pub fn new(mode: SvfMode, q: f32, sample_rate: f32) -> Self {
+        Self {
+            mode,
+            damping: 1.0 / Real::from(q.max(0.01)),
+            sample_rate,
+            ic1eq: 0.0,
+            ic2eq: 0.0,
+        }
+    }
+
+    /// Process a single sample with the cutoff frequency to use for it (Hz)
+    pub fn process_sample(&mut self, input: f32, cutoff: f32) -> f32 {
+        let cutoff = cutoff.clamp(10.0, self.sample_rate * 0.49);
+        let g = (REAL_PI * Real::from(cutoff) / Real::from(self.sample_rate)).tan();
+        let a1 = 1.0 / (1.0 + g * (g + self.damping));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let input = Real::from(input);
+        let v3 = input - self.ic2eq;
+        let band = a1 * self.ic1eq + a2 * v3;
+        let low = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * band - self.ic1eq;
+        self.ic2eq = 2.0 * low - self.ic2eq;
+
+        let high = input - self.damping * band - low;
+        to_sample(match self.mode {
+            SvfMode::LowPass => low,
+            SvfMode::HighPass => high,
+            SvfMode::BandPass => band,
+            SvfMode::Notch => low + high,
+        })
+    }
+
+    /// Filter a block of samples in place, following a cutoff sweep from time zero
+    pub fn process_sweep(&mut self, samples: &mut [f32], sweep: &CutoffSweep) {
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let cutoff = sweep.cutoff_at(i as f32 / self.sample_rate);
+            *sample = self.process_sample(*sample, cutoff);
+        }
+    }
+
+    /// Reset the filter state
+    pub fn reset(&mut self) {
+        self.ic1eq = 0.0;
+        self.ic2eq = 0.0;
+    }
+}
+
+/// How a filter cutoff moves over the duration of a note
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum CutoffSweep {
+    /// Constant cutoff (Hz)
+    Fixed(f32),
+    /// Cutoff follows an ADSR envelope from `base` (level 0) to `peak` (level 1)
+    Envelope {
+        base: f32,
+        peak: f32,
+        adsr: ADSR,
+        /// Note duration in seconds, used to place the release phase
+        duration: f32,
+    },
+    /// Cutoff swings around `center` by `depth` octaves at `rate` Hz
+    Lfo {
+        center: f32,
+        depth: f32,
+        rate: f32,
+    },
+}
+
+impl CutoffSweep {
+    /// Check the cutoffs are below the Nyquist limit and the envelope or LFO settings are usable
+    pub fn validate(&self) -> Result<()> {
+        let nyquist = SAMPLE_RATE as f32 / 2.0;
+        match *self {
+            CutoffSweep::Fixed(cutoff) => check_positive("sweep cutoff (Hz)", cutoff, nyquist),
+            CutoffSweep::Envelope { base, peak, adsr, duration } => {
+                check_positive("sweep base cutoff (Hz)", base, nyquist)?;
+                check_positive("sweep peak cutoff (Hz)", peak, nyquist)?;
+                check_positive("sweep duration (s)", duration, crate::audio::MAX_DURATION)?;
+                adsr.validate()
+            }
+            CutoffSweep::Lfo { center, depth, rate } => {
+                check_positive("sweep center cutoff (Hz)", center, nyquist)?;
+                check_range("sweep depth (octaves)", depth, 0.0..=8.0)?;
+                check_range("sweep rate (Hz)", rate, 0.0..=100.0)
+            }
+        }
+    }
+
+    /// Cutoff frequency (Hz) at a time in seconds
+    pub fn cutoff_at(&self, time: f32) -> f32 {
+        match *self {
+            CutoffSweep::Fixed(cutoff) => cutoff,
+            CutoffSweep::Envelope { base, peak, adsr, duration } => {
+                // Interpolate in octaves so the sweep sounds even
+                let level = adsr.level(time, duration).clamp(0.0, 1.0);
+                base.max(1.0) * (peak.max(1.0) / base.max(1.0)).powf(level)
+            },
+            CutoffSweep::Lfo { center, depth, rate } => {
+                center * 2.0_f32.powf(Lfo::new(LfoShape::Sine, rate, 1.0).with_depth(depth).value_at(time))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+    use crate::SAMPLE_RATE;
+
+    /// Peak output level of a filtered sine after the filter has settled
+    fn response(mut filter: Biquad, frequency: f32) -> f32 {
+        let mut samples: Vec<f32> = (0..SAMPLE_RATE as usize / 2)
+            .map(|i| (2.0 * PI * frequency * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect();
+        filter.process(&mut samples);
+        samples[samples.len() / 2..].iter().map(|x| x.abs()).fold(0.0f32, f32::max)
+    }
+
+    #[test]
+    fn test_low_and_high_pass() {
+        let rate = SAMPLE_RATE as f32;
+        assert!(response(Biquad::low_pass(1000.0, BUTTERWORTH_Q, rate), 100.0) > 0.98);
+        assert!(response(Biquad::low_pass(1000.0, BUTTERWORTH_Q, rate), 10000.0) < 0.02);
+        assert!(response(Biquad::high_pass(1000.0, BUTTERWORTH_Q, rate), 100.0) < 0.02);
+        // Butterworth response is -3 dB at the cutoff
+        assert!((response(Biquad::low_pass(1000.0, BUTTERWORTH_Q, rate), 1000.0) - 0.707).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_resonance_boosts_cutoff() {
+        let rate = SAMPLE_RATE as f32;
+        assert!(response(Biquad::low_pass(1000.0, 4.0, rate), 1000.0) > 3.9);
+    }
+
+    #[test]
+    fn test_band_pass_and_notch() {
+        let rate = SAMPLE_RATE as f32;
+        assert!((response(Biquad::band_pass(2000.0, 1.0, rate), 2000.0) - 1.0).abs() < 0.01);
+        assert!(response(Biquad::band_pass(2000.0, 1.0, rate), 100.0) < 0.1);
+        assert!(response(Biquad::notch(2000.0, 1.0, rate), 2000.0) < 0.01);
+    }
+
+    #[test]
+    fn test_state_variable_filter_modes() {
+        let rate = SAMPLE_RATE as f32;
+        let settled_peak = |mode: SvfMode, frequency: f32| {
+            let mut filter = StateVariableFilter::new(mode, BUTTERWORTH_Q, rate);
+            let mut samples: Vec<f32> = (0..SAMPLE_RATE as usize / 2)
+                .map(|i| (2.0 * PI * frequency * i as f32 / rate).sin())
+                .collect();
+            filter.process_sweep(&mut samples, &CutoffSweep::Fixed(1000.0));
+            samples[samples.len() / 2..].iter().map(|x| x.abs()).fold(0.0f32, f32::max)
+        };
+
+        assert!(settled_peak(SvfMode::LowPass, 100.0) > 0.98);
+        assert!(settled_peak(SvfMode::LowPass, 10000.0) < 0.02);
+        assert!(settled_peak(SvfMode::HighPass, 100.0) < 0.02);
+        assert!(settled_peak(SvfMode::Notch, 1000.0) < 0.01);
+    }
+
+    #[test]
+    fn test_cutoff_sweeps() {
+        let adsr = ADSR { attack: 0.1, decay: 0.1, sustain: 0.5, release: 0.1 };
+        let envelope = CutoffSweep::Envelope { base: 200.0, peak: 5000.0, adsr, duration: 1.0 };
+        assert!((envelope.cutoff_at(0.0) - 200.0).abs() < 0.01);
+        assert!((envelope.cutoff_at(0.1) - 5000.0).abs() < 1.0);
+        // Half the envelope is halfway between the cutoffs in octaves
+        assert!((envelope.cutoff_at(0.5) - 1000.0).abs() < 1.0);
+
+        let lfo = CutoffSweep::Lfo { center: 1000.0, depth: 1.0, rate: 1.0 };
+        assert!((lfo.cutoff_at(0.25) - 2000.0).abs() < 1.0);
+        assert!((lfo.cutoff_at(0.75) - 500.0).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_peak_and_shelves() {
+        let rate = SAMPLE_RATE as f32;
+        let boost = 10.0_f32.powf(6.0 / 20.0);
+        assert!((response(Biquad::peak(1000.0, 1.0, 6.0, rate), 1000.0) - boost).abs() < 0.02);
+        assert!((response(Biquad::low_shelf(500.0, BUTTERWORTH_Q, 6.0, rate), 50.0) - boost).abs() < 0.05);
+        assert!((response(Biquad::high_shelf(2000.0, BUTTERWORTH_Q, -6.0, rate), 15000.0) - 1.0 / boost).abs() < 0.02);
+    }
+}
@@ -0,0 +1,214 @@
+//! One-expression pipeline from a melody to a finished, normalized jingle
+//!
+//! ```rust
+//! use jinglebells::{Jingle, EffectKind, Melody, Note, WaveForm};
+//!
+//! let melody = Melody { notes: vec![(Note::C, 0.15), (Note::E, 0.15), (Note::G, 0.3)] };
+//! let samples = Jingle::builder()
+//!     .seed(42)
+//!     .melody(melody)
+//!     .waveform(WaveForm::Triangle)
+//!     .effect(EffectKind::Reverb { room_size: 0.8, damping: 0.5, mix: 0.3 })
+//!     .normalize(0.9)
+//!     .build()
+//!     .unwrap();
+//! assert!(!samples.is_empty());
+//! ```
+
+use std::path::Path;
+use crate::{
+    audio::{WaveForm, ADSR},
+    music::Melody,
+    chain::{EffectChain, EffectKind},
+    effects::normalize_samples,
+    export::{JingleGenerator, GeneratorConfig, MelodyRenderOptions},
+    error::{JingleError, Result},
+};
+
+/// Entry point for building a jingle, see `Jingle::builder`
+pub struct Jingle;
+
+impl Jingle {
+    /// Start describing a jingle: melody, timbre, effects and output level
+    pub fn builder() -> JingleBuilder {
+        JingleBuilder::default()
+    }
+}
+
+/// Settings for rendering a melody through the generator, an effect chain and normalization in order
+#[derive(Clone, Debug)]
+pub struct JingleBuilder {
+    seed: Option<u64>,
+    config: Option<GeneratorConfig>,
+    melody: Option<Melody>,
+    octave: i32,
+    waveform: WaveForm,
+    options: MelodyRenderOptions,
+    effects: EffectChain,
+    normalize: Option<f32>,
+}
+
+impl Default for JingleBuilder {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            config: None,
+            melody: None,
+            octave: 5,
+            waveform: WaveForm::Sine,
+            options: MelodyRenderOptions::default(),
+            effects: EffectChain::new(),
+            normalize: None,
+        }
+    }
+}
+
+impl JingleBuilder {
+    /// Seed the generator so the same settings always render the same samples
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set the sample rate, tuning, default envelope and master gain
+    pub fn config(mut self, config: GeneratorConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Set the melody to play
+    pub fn melody(mut self, melody: Melody) -> Self {
+        self.melody = Some(melody);
+        self
+    }
+
+    /// Set the octave the melody is played in (defaults to 5)
+    pub fn octave(mut self, octave: i32) -> Self {
+        self.octave = octave;
+        self
+    }
+
+    /// Set the waveform (defaults to sine)
+    pub fn waveform(mut self, waveform: WaveForm) -> Self {
+        self.waveform = waveform;
+        self
+    }
+
+    /// Set the envelope of every note
+    pub fn adsr(mut self, adsr: ADSR) -> Self {
+        self.options = self.options.with_adsr(adsr);
+        self
+    }
+
+    /// Set the articulation, per-note gains and overlap of the melody
+    ///
+    /// Replaces any envelope set with `adsr` by the one in `options`.
+    pub fn options(mut self, options: MelodyRenderOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Add an effect after those already added
+    pub fn effect(mut self, effect: EffectKind) -> Self {
+        self.effects = self.effects.push(effect);
+        self
+    }
+
+    /// Replace the effects with a configured chain
+    pub fn effects(mut self, chain: EffectChain) -> Self {
+        self.effects = chain;
+        self
+    }
+
+    /// Scale the finished jingle so its peak is at this level (0.0 - 1.0)
+    pub fn normalize(mut self, peak: f32) -> Self {
+        self.normalize = Some(peak);
+        self
+    }
+
+    /// Create the generator the jingle is rendered with
+    fn generator(&self) -> Result<JingleGenerator> {
+        let generator = match self.seed {
+            Some(seed) => JingleGenerator::with_seed(seed),
+            None => JingleGenerator::new(),
+        };
+        match self.config {
+            Some(config) => generator.with_config(config),
+            None => Ok(generator),
+        }
+    }
+
+    /// Render the samples with a generator that has been configured from these settings
+    fn render(&self, generator: &JingleGenerator) -> Result<Vec<f32>> {
+        let melody = self.melody.as_ref()
+            .filter(|melody| !melody.notes.is_empty())
+            .ok_or_else(|| JingleError::InvalidParameter("A jingle needs a melody with at least one note".to_string()))?;
+        if let Some(peak) = self.normalize
+            && (!peak.is_finite() || peak <= 0.0 || peak > 1.0)
+        {
+            return Err(JingleError::InvalidParameter(format!("Normalization peak {} must be above 0.0 and at most 1.0", peak)));
+        }
+        self.options.validate()?;
+        self.effects.validate()?;
+
+        let samples = generator.generate_melody_samples_with_options(melody, self.octave, self.waveform, &self.options);
+        let mut samples = generator.apply_effects(&samples, &self.effects);
+        if let Some(peak) = self.normalize {
+            normalize_samples(&mut samples, peak);
+        }
+        Ok(samples)
+    }
+
+    /// Render the jingle: melody, then effects, then normalization
+    pub fn build(&self) -> Result<Vec<f32>> {
+        self.render(&self.generator()?)
+    }
+
+    /// Render the jingle and export it, detecting the format from the extension like `JingleGenerator::export_to_file`
+    pub fn export<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let generator = self.generator()?;
+        generator.export_to_file(&self.render(&generator)?, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::Note;
+
+    fn melody() -> Melody {
+        Melody { notes: vec![(Note::C, 0.1), (Note::E, 0.1), (Note::G, 0.2)] }
+    }
+
+    #[test]
+    fn test_builder_matches_manual_pipeline() {
+        let adsr = ADSR { attack: 0.01, decay: 0.05, sustain: 0.6, release: 0.05 };
+        let samples = Jingle::builder()
+            .seed(42)
+            .melody(melody())
+            .waveform(WaveForm::Triangle)
+            .adsr(adsr)
+            .effect(EffectKind::Echo { delay_ms: 30.0, feedback: 0.3, mix: 0.3 })
+            .normalize(0.9)
+            .build()
+            .unwrap();
+
+        let generator = JingleGenerator::with_seed(42);
+        let options = MelodyRenderOptions::default().with_adsr(adsr);
+        let manual = generator.generate_melody_samples_with_options(&melody(), 5, WaveForm::Triangle, &options);
+        let mut manual = generator.apply_effects(&manual, &EffectChain::new().echo(30.0, 0.3, 0.3));
+        normalize_samples(&mut manual, 0.9);
+        assert_eq!(samples, manual);
+
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!((peak - 0.9).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        assert!(Jingle::builder().build().is_err());
+        assert!(Jingle::builder().melody(Melody::new()).build().is_err());
+        assert!(Jingle::builder().melody(melody()).normalize(1.5).build().is_err());
+        assert!(Jingle::builder().melody(melody()).config(GeneratorConfig::default().with_sample_rate(10)).build().is_err());
+    }
+}
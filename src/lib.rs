@@ -7,9 +7,46 @@
 //! 
 //! - Custom oscillator synthesis with multiple waveforms (sine, triangle, sawtooth, square)
 //! - ADSR envelope control for natural-sounding audio
+//! - Melodies streamed note by note as rodio sources, without rendering them up front
+//! - Pull-based block rendering for real-time audio callbacks
+//! - Preset and spec renders returned as rodio sources for sinks and effect wrappers
+//! - Biquad filters (low/high/band-pass, notch, peak and shelving EQ) and resonant filter sweeps
+//! - Composable effect chains configurable at runtime or parsed from text like `reverb:small,echo:120ms:0.3`, with parameter automation
+//! - Multi-track mixer with per-track gain, pan, mute/solo and effects, summed under a headroom ceiling
+//! - Polyphonic voice allocation giving overlapping notes and block chords their own envelopes
+//! - Sequencer placing notes, chords and samples at second or beat times on overlapping lanes, with lane automation
+//! - Compact melody notation (`"C4:e E4:e G4:q C5:h"`) with a per-note octave, rests and dotted values
+//! - Imported WAV one-shots resampled to the library rate and layered with synthesized sounds
+//! - SoundFont (SF2) instruments for rendering melodies with sampled pianos, marimbas and bells (`sf2` feature)
 //! - Musical theory support with scales, chords, and progressions
-//! - WAV file export functionality
-//! - Preset jingle generators for common notification types
+//! - WAV file export functionality, including multi-channel layouts, loop points and cue markers
+//! - Loudness measurement and normalization to a LUFS target
+//! - Sample rate conversion for exporting at rates other than 44.1 kHz
+//! - Per-generator sample rate, concert pitch, default envelope and master gain
+//! - Structured progress events tagged with the composing, rendering, effects or exporting phase
+//! - Drain-to-disk WAV rendering that streams long outputs block by block instead of buffering them
+//! - Cancel tokens for stopping sequencer renders, convolution and batch exports mid-way
+//! - Clipping reports, and an output policy that clamps, normalizes or rejects renders beyond full scale
+//! - Optional `f64` feature for double-precision V1 oscillator phase, filter and reverb state in long renders
+//! - Oscillators, envelopes and music theory usable without std behind the `no_std` feature
+//! - WebAssembly build with JavaScript bindings for previewing presets in the browser (`wasm` feature)
+//! - C interface and generated header for embedding in other languages (`ffi` feature)
+//! - Serde support for music, envelope, waveform, effect and generator settings and jingle specs (`serde` feature)
+//! - Pluggable encoders registered by file extension
+//! - Preset jingle generators for common notification types, retro game sound effects and sci-fi interface textures
+//! - Chiptune mode rendering every preset with pulse, triangle and noise channels
+//! - Constraints on duration, frequency range, avoided bands, peak and loudness for platform guidelines
+//! - Sound themes: matched preset sets sharing key, tempo, timbre and effects
+//! - Directional stereo rendering from interaural time and level differences
+//! - Telephony tones: DTMF digits and regional dial, busy, ringback and reorder tones
+//! - Earcons composing object, action and status motifs into one jingle
+//! - Complementary pairs (send/receive, open/close, connect/disconnect) where the second sound answers the first
+//! - Custom presets registered by name alongside the built-in ones
+//! - Seeds shared as `brave-teal-otter` slugs, and a catalog of seeds that render each preset well
+//! - Versioned generation algorithm recorded in manifests, so stored seeds keep their sound across releases
+//! - Best-of-N rendering, scored by consonance, spectral balance, clipping and duration fit or a custom closure
+//! - `Jingle::builder()` pipeline from a melody through effects to normalized samples or a file
+//! - Declarative `JingleSpec` descriptions rendered with `JingleGenerator::render`, loadable from TOML or JSON files with the `serde` feature
 //!
 //! ## Example
 //! 
@@ -18,25 +55,219 @@
 //! 
 //! let mut generator = JingleGenerator::new();
 //! let samples = generator.create_notification_jingle(WaveForm::Sine, None, None);
-//! generator.export_to_wav(&samples, "notification.wav").unwrap();
+//! let path = std::env::temp_dir().join("jinglebells_doc_notification.wav");
+//! generator.export_to_wav(&samples, &path).unwrap();
+//! # std::fs::remove_file(&path).unwrap();
 //! ```
+//!
+//! ## Without std
+//!
+//! The synthesis and music theory core (`audio`, `music` and `chiptune`) builds
+//! without std for embedded targets that play the samples through their own
+//! DAC drivers. Disable the default `std` feature and enable `no_std`, which
+//! supplies the float math from libm:
+//!
+//! ```toml
+//! jinglebells = { version = "0.1", default-features = false, features = ["no_std"] }
+//! ```
+//!
+//! Playback through rodio, effects, presets and file export need `std`.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+// The test harness links std, whose float methods shadow the libm ones in `math::Float`
+#![cfg_attr(all(test, not(feature = "std")), allow(unused_imports, dead_code))]
+
+#[cfg(not(any(feature = "std", feature = "no_std")))]
+compile_error!("enable either the `std` feature or, for builds without std, the `no_std` feature");
+
+extern crate alloc;
+
+mod math;
 pub mod audio;
 pub mod music;
+#[cfg(feature = "std")]
 pub mod export;
+#[cfg(feature = "std")]
 pub mod presets;
+#[cfg(feature = "std")]
 pub mod error;
+#[cfg(feature = "std")]
 pub mod effects;
+#[cfg(feature = "std")]
+pub mod filter;
+#[cfg(feature = "std")]
+pub mod convolution;
+#[cfg(feature = "std")]
+pub mod loudness;
+#[cfg(feature = "std")]
+pub mod resample;
+#[cfg(feature = "std")]
+pub mod channels;
+#[cfg(feature = "std")]
+pub mod manifest;
+#[cfg(feature = "std")]
+pub mod encoder;
+#[cfg(feature = "std")]
+pub mod markers;
+#[cfg(feature = "std")]
+pub mod chain;
+#[cfg(feature = "std")]
+pub mod automation;
+#[cfg(feature = "std")]
+pub mod spec;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod earcon;
+#[cfg(feature = "std")]
+pub mod theme;
+#[cfg(feature = "std")]
+pub mod constraints;
+#[cfg(feature = "std")]
+pub mod telephony;
+#[cfg(feature = "std")]
+pub mod scoring;
+pub mod chiptune;
+#[cfg(feature = "std")]
+pub mod pairs;
+#[cfg(feature = "std")]
+pub mod spatial;
+#[cfg(feature = "std")]
+pub mod renderer;
+#[cfg(feature = "std")]
+pub mod jingle;
+#[cfg(feature = "std")]
+pub mod seeds;
+#[cfg(feature = "std")]
+pub mod version;
+#[cfg(feature = "std")]
+pub mod clipping;
+#[cfg(feature = "std")]
+pub mod cancel;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "std")]
+pub mod streaming;
+#[cfg(feature = "std")]
+pub mod mixer;
+#[cfg(feature = "std")]
+pub mod sample;
+#[cfg(feature = "std")]
+pub mod voices;
+#[cfg(feature = "std")]
+pub mod sequence;
+#[cfg(feature = "std")]
+pub mod notation;
+#[cfg(feature = "sf2")]
+pub mod soundfont;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 
-pub use audio::{WaveForm, ADSR, Oscillator, LayeredOscillator, OscillatorLayer};
+pub use math::{db_to_linear, linear_to_db};
+pub use audio::{WaveForm, ADSR, Oscillator, LayeredOscillator, OscillatorLayer, MelodySource, OSCILLATOR_HEADROOM};
 pub use music::{Note, Scale, Chord, ChordProgression, Melody, MelodyPattern, RhythmPattern};
-pub use export::JingleGenerator;
+#[cfg(feature = "std")]
+pub use export::{JingleGenerator, DefaultRng, GeneratorConfig, MelodyRenderOptions, Dither, Mp3Options, Mp3BitrateMode, ChunkOptions, ProgressCallback};
+#[cfg(feature = "std")]
 pub use presets::*;
+#[cfg(feature = "std")]
 pub use error::JingleError;
-pub use effects::{DelayBuffer, Echo, Reverb, LowPassFilter, LowPass, AutomaticGainControl, AGC, normalize_samples, peak_normalize};
+#[cfg(feature = "std")]
+pub use effects::{SampleProcessor, Effect, DelayBuffer, Echo, DelayReverb, Reverb, Freeverb, StereoReverb, LowPassFilter, LowPass, SweptFilter, FilterSweep, PhaseShifter, Phaser, Waveshaper, Distortion, Shaper, Compressor, Compression, Lfo, LfoShape, AmplitudeModulator, Tremolo, tremolo, RingModulator, RingMod, PitchShifter, ShimmerReverb, Shimmer, AutomaticGainControl, LevelDetection, AGC, GainStage, Gain, apply_gain_db, normalize_samples, peak_normalize, fade_in, fade_out, crossfade, declick, DECLICK_DURATION};
+#[cfg(feature = "std")]
+pub use convolution::{ConvolutionReverb, ImpulseResponse};
+#[cfg(feature = "std")]
+pub use filter::{Biquad, StateVariableFilter, SvfMode, CutoffSweep};
+#[cfg(feature = "std")]
+pub use loudness::{integrated_loudness, normalize_loudness};
+#[cfg(feature = "std")]
+pub use resample::{Resampler, resample};
+#[cfg(feature = "std")]
+pub use channels::ChannelLayout;
+#[cfg(feature = "std")]
+pub use manifest::{ExportManifest, RenderChoices};
+#[cfg(feature = "std")]
+pub use encoder::{AudioEncoder, BitDepth, EncodeSpec, RawPcmEncoder, WavEncoder};
+#[cfg(feature = "std")]
+pub use markers::{LoopRegion, CueMarker};
+#[cfg(feature = "std")]
+pub use chain::{EffectChain, EffectKind, EffectSource};
+#[cfg(feature = "std")]
+pub use automation::{Automation, Automated, Processed};
+#[cfg(feature = "std")]
+pub use spec::{JingleSpec, MelodySpec, SpecDocument, SpecFormat};
+#[cfg(feature = "std")]
+pub use registry::{PresetRegistry, PresetFn};
+#[cfg(feature = "std")]
+pub use earcon::{Earcon, EarconRole, EarconVocabulary, Motif};
+#[cfg(feature = "std")]
+pub use theme::{SoundTheme, ThemeSound, THEME_MANIFEST};
+#[cfg(feature = "std")]
+pub use constraints::Constraints;
+#[cfg(feature = "std")]
+pub use telephony::{CallProgressTone, ToneRegion, ToneSpec, dtmf_frequencies, dtmf_tone, dtmf_sequence, call_progress_tone};
+#[cfg(feature = "std")]
+pub use scoring::{Scorer, ScoreBreakdown, HeuristicScorer, Candidate};
+pub use chiptune::{NoiseChannel, CHIPTUNE_VOICES, CHIPTUNE_FRAME_RATE};
+#[cfg(feature = "std")]
+pub use pairs::{JinglePair, PairKind};
+#[cfg(feature = "std")]
+pub use spatial::{Direction, spatialize};
+#[cfg(feature = "std")]
+pub use renderer::Renderer;
+#[cfg(feature = "std")]
+pub use jingle::{Jingle, JingleBuilder};
+#[cfg(feature = "std")]
+pub use seeds::{seed_to_slug, slug_to_seed, CatalogSeed, SEED_CATALOG, catalog_seeds};
+#[cfg(feature = "std")]
+pub use version::GenerationVersion;
+#[cfg(feature = "std")]
+pub use clipping::{OutputPolicy, ClippingReport};
+#[cfg(feature = "std")]
+pub use cancel::CancelToken;
+#[cfg(feature = "std")]
+pub use progress::{ProgressEvent, ProgressPhase, ProgressEventCallback};
+#[cfg(feature = "std")]
+pub use streaming::StreamingWavWriter;
+#[cfg(feature = "std")]
+pub use mixer::{Mixer, Track, MIXER_HEADROOM_DB};
+#[cfg(feature = "std")]
+pub use sample::{Sample, SamplePlayer};
+#[cfg(feature = "std")]
+pub use voices::{VoiceAllocator, VoiceId, VoiceNote, DEFAULT_MAX_VOICES};
+#[cfg(feature = "std")]
+pub use sequence::{Sequence, SequenceLane, SequenceEvent, SequenceTime, DEFAULT_TEMPO};
+#[cfg(feature = "std")]
+pub use notation::{Phrase, PhraseNote};
+#[cfg(feature = "sf2")]
+pub use soundfont::{SoundFont, SoundFontInstrument, SoundFontPreset};
+#[cfg(feature = "mp3")]
+pub use encoder::Mp3Encoder;
+#[cfg(feature = "opus")]
+pub use encoder::OpusEncoder;
 
-/// Standard sample rate used throughout the library
+/// Default sample rate, used unless a generator is configured with `GeneratorConfig`
 pub const SAMPLE_RATE: u32 = 44100;
 
 /// A4 frequency reference for musical note calculations
-pub const A4_FREQUENCY: f32 = 440.0;
\ No newline at end of file
+pub const A4_FREQUENCY: f32 = 440.0;
+
+/// Precision of V1 oscillator phase and of filter and reverb state: `f64` with the `f64` feature, otherwise `f32`
+///
+/// Samples pass between stages as `f32`, whose 24-bit mantissa already exceeds
+/// 16-bit export; the error that builds up over long renders comes from
+/// recursive state and phase, which is what this type covers. Phase accumulators
+/// (`GenerationVersion::V2` on) are always `f64`.
+#[cfg(feature = "f64")]
+pub type Real = f64;
+/// Precision of V1 oscillator phase and of filter and reverb state: `f64` with the `f64` feature, otherwise `f32`
+#[cfg(not(feature = "f64"))]
+pub type Real = f32;
+
+/// Convert internal state back to a sample
+#[allow(clippy::unnecessary_cast)]
+pub(crate) fn to_sample(value: Real) -> f32 {
+    value as f32
+}
\ No newline at end of file
@@ -0,0 +1,167 @@
+//! Loudness measurement and normalization (ITU-R BS.1770)
+
+use crate::{effects::db_to_linear, filter::Biquad};
+
+/// Absolute gate below which blocks are ignored (LUFS)
+const ABSOLUTE_GATE: f32 = -70.0;
+
+/// Relative gate below the ungated loudness (LU)
+const RELATIVE_GATE: f32 = -10.0;
+
+/// Gating block length in seconds
+const BLOCK_DURATION: f32 = 0.4;
+
+/// Step between gating blocks in seconds (75% overlap)
+const BLOCK_STEP: f32 = 0.1;
+
+/// High shelf of the K-weighting pre-filter, modelling the acoustic effect of the head
+fn k_weighting_shelf(sample_rate: f32) -> Biquad {
+    let f0 = 1681.9745;
+    let gain_db = 3.9998438;
+    let q = 0.70717525;
+
+    let k = (std::f32::consts::PI * f0 / sample_rate).tan();
+    let vh = db_to_linear(gain_db);
+    let vb = vh.powf(0.49966678);
+    let a0 = 1.0 + k / q + k * k;
+
+    Biquad::from_coefficients(
+        [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    )
+}
+
+/// RLB high-pass of the K-weighting pre-filter, removing sub-bass content
+fn k_weighting_high_pass(sample_rate: f32) -> Biquad {
+    let f0 = 38.13547;
+    let q = 0.500327;
+
+    let k = (std::f32::consts::PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+
+    Biquad::from_coefficients(
+        [1.0, -2.0, 1.0],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    )
+}
+
+/// Convert a mean square power to loudness in LUFS
+fn power_to_lufs(power: f32) -> f32 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// Measure the integrated loudness of mono samples in LUFS
+///
+/// Uses K-weighting and the two-stage gating of BS.1770. Signals shorter
+/// than a single 400ms gating block are measured as one block.
+/// Returns negative infinity for silence.
+pub fn integrated_loudness(samples: &[f32], sample_rate: u32) -> f32 {
+    if samples.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+
+    // Apply the K-weighting pre-filter and square the result
+    let mut shelf = k_weighting_shelf(sample_rate as f32);
+    let mut high_pass = k_weighting_high_pass(sample_rate as f32);
+    let squared: Vec<f32> = samples
+        .iter()
+        .map(|&sample| {
+            let weighted = high_pass.process_sample(shelf.process_sample(sample));
+            weighted * weighted
+        })
+        .collect();
+
+    // Mean square power of each overlapping gating block
+    let block_len = ((BLOCK_DURATION * sample_rate as f32) as usize).min(squared.len());
+    let step = ((BLOCK_STEP * sample_rate as f32) as usize).max(1);
+    let block_powers: Vec<f32> = (0..=squared.len() - block_len)
+        .step_by(step)
+        .map(|start| squared[start..start + block_len].iter().sum::<f32>() / block_len as f32)
+        .collect();
+
+    let gated_mean = |threshold: f32| -> Option<f32> {
+        let gated: Vec<f32> = block_powers
+            .iter()
+            .copied()
+            .filter(|&power| power_to_lufs(power) > threshold)
+            .collect();
+        if gated.is_empty() {
+            None
+        } else {
+            Some(gated.iter().sum::<f32>() / gated.len() as f32)
+        }
+    };
+
+    // Absolute gate first, then relative gate against the absolutely gated loudness
+    let Some(absolute_power) = gated_mean(ABSOLUTE_GATE) else {
+        return f32::NEG_INFINITY;
+    };
+    let relative_threshold = power_to_lufs(absolute_power) + RELATIVE_GATE;
+
+    match gated_mean(relative_threshold) {
+        Some(power) => power_to_lufs(power),
+        None => power_to_lufs(absolute_power),
+    }
+}
+
+/// Gain (linear) required to bring samples to a target loudness
+///
+/// Returns 1.0 when the input is silent and cannot be measured.
+pub fn loudness_gain(samples: &[f32], sample_rate: u32, target_lufs: f32) -> f32 {
+    let measured = integrated_loudness(samples, sample_rate);
+    if measured.is_finite() {
+        db_to_linear(target_lufs - measured)
+    } else {
+        1.0
+    }
+}
+
+/// Scale samples in place to a target integrated loudness in LUFS
+pub fn normalize_loudness(samples: &mut [f32], sample_rate: u32, target_lufs: f32) {
+    let gain = loudness_gain(samples, sample_rate, target_lufs);
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SAMPLE_RATE;
+
+    fn sine(frequency: f32, amplitude: f32, duration: f32) -> Vec<f32> {
+        let count = (SAMPLE_RATE as f32 * duration) as usize;
+        (0..count)
+            .map(|i| {
+                let time = i as f32 / SAMPLE_RATE as f32;
+                amplitude * (2.0 * std::f32::consts::PI * frequency * time).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_full_scale_sine_loudness() {
+        // A 0 dBFS 997 Hz sine measures -3.01 LUFS on a single channel
+        let samples = sine(997.0, 1.0, 2.0);
+        let loudness = integrated_loudness(&samples, SAMPLE_RATE);
+        assert!((loudness + 3.01).abs() < 0.1, "measured {}", loudness);
+    }
+
+    #[test]
+    fn test_silence_loudness() {
+        assert_eq!(integrated_loudness(&[0.0; 1000], SAMPLE_RATE), f32::NEG_INFINITY);
+        assert_eq!(integrated_loudness(&[], SAMPLE_RATE), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_normalize_loudness() {
+        let mut samples = sine(440.0, 0.3, 0.25); // Shorter than one gating block
+        normalize_loudness(&mut samples, SAMPLE_RATE, -16.0);
+        let loudness = integrated_loudness(&samples, SAMPLE_RATE);
+        assert!((loudness + 16.0).abs() < 0.1, "measured {}", loudness);
+    }
+}
@@ -1,14 +1,141 @@
-use clap::{Parser, Subcommand, ValueEnum};
-use jinglebells::{JingleGenerator, WaveForm};
-use std::path::PathBuf;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap::parser::ValueSource;
+use jinglebells::{BitDepth, EffectChain, GeneratorConfig, JingleError, JingleGenerator, JinglePreset, JingleSpec, Phrase, SpecDocument, SpecFormat, WaveForm};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use cli::config::ConfigFile;
+
+mod cli {
+    pub mod config;
+}
 
 #[derive(Parser)]
 #[command(name = "jinglebells")]
 #[command(about = "A CLI jingle generator using Rust and Rodio")]
 #[command(version = "0.1.0")]
 struct Cli {
+    /// Config file with shared defaults (default: ~/.config/jinglebells/config.toml)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    
+    /// Effects applied before export and playback, e.g. "reverb:small,echo:120ms:0.3,lowpass:3000"
+    #[arg(long, global = true)]
+    effects: Option<String>,
+    
+    /// Output format, replacing the output file's extension
+    #[arg(long, global = true, value_enum)]
+    format: Option<FormatArg>,
+    
+    /// Bitrate in kbps for mp3 and ogg output (8-512)
+    #[arg(long, global = true, value_parser = clap::value_parser!(u32).range(8..=512))]
+    bitrate: Option<u32>,
+    
+    /// Bits per sample for wav output; 32 writes floating point
+    #[arg(long, global = true, value_parser = ["16", "24", "32"])]
+    bit_depth: Option<String>,
+    
+    /// Write the audio to stdout instead of a file, like `--output -`
+    #[arg(long, global = true)]
+    stdout: bool,
+    
+    /// Write headerless little-endian PCM at the bit depth instead of WAV
+    #[arg(long, global = true, conflicts_with = "format")]
+    raw: bool,
+    
     #[command(subcommand)]
-    preset: Preset,
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(flatten)]
+    Preset(Preset),
+    /// List presets with their defaults and the supported output formats
+    List {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Render a melody written as notes, e.g. "C4:e E4:e G4:q C5:h"
+    Melody {
+        /// Notes: a pitch with optional octave, then `:` and w/h/q/e/s (`.` dotted) or beats; R is a rest
+        notes: String,
+        
+        /// Waveform type
+        #[arg(short, long, value_enum, default_value = "sine")]
+        waveform: WaveFormArg,
+        
+        /// Tempo in beats per minute (20-400)
+        #[arg(short, long, default_value = "120.0")]
+        tempo: f32,
+        
+        /// Output file path, or `-` for stdout
+        #[arg(short, long, default_value = "output.wav")]
+        output: PathBuf,
+        
+        /// Generate file only without playing
+        #[arg(short, long)]
+        generate_only: bool,
+    },
+    /// Render a matched set of notification sounds sharing key, tempo and timbre
+    Theme {
+        /// Seed for reproducible generation
+        #[arg(short, long)]
+        seed: Option<u64>,
+        
+        /// Directory the sounds and theme.json manifest are written to
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+    },
+    /// Render a named preset defined in the config file
+    Custom {
+        /// Name of a [presets.<name>] table in the config file
+        name: String,
+        
+        /// Output file path, or `-` for stdout
+        #[arg(short, long, default_value = "output.wav")]
+        output: PathBuf,
+        
+        /// Seed for reproducible generation, overriding the preset's own
+        #[arg(short, long)]
+        seed: Option<u64>,
+        
+        /// Generate file only without playing
+        #[arg(short, long)]
+        generate_only: bool,
+    },
+    /// Render a spec file, or a JSON spec read from stdin with `--stdin`
+    Render {
+        /// Spec file (.toml or .json) with a preset or a melody's notes
+        #[arg(required_unless_present = "stdin", conflicts_with = "stdin")]
+        spec: Option<PathBuf>,
+        
+        /// Read a serde JSON `JingleSpec` or melody spec from stdin
+        #[arg(long)]
+        stdin: bool,
+        
+        /// Output file path, or `-` for stdout
+        #[arg(short, long, default_value = "output.wav")]
+        output: PathBuf,
+        
+        /// Generate file only without playing
+        #[arg(short, long)]
+        generate_only: bool,
+    },
+    /// Preview variations from the keyboard: reroll, tweak duration and pitch, switch presets and save favorites
+    Interactive {
+        /// Preset to start with
+        #[arg(short, long, default_value = "notification")]
+        preset: String,
+        
+        /// Seed of the first variation
+        #[arg(short, long)]
+        seed: Option<u64>,
+        
+        /// Directory accepted sounds are saved to
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -27,7 +154,7 @@ enum Preset {
         #[arg(short, long, value_enum, default_value = "sine")]
         waveform: WaveFormArg,
         
-        /// Output file path
+        /// Output file path, or `-` for stdout
         #[arg(short, long, default_value = "output.wav")]
         output: PathBuf,
         
@@ -57,7 +184,7 @@ enum Preset {
         #[arg(short, long, value_enum, default_value = "square")]
         waveform: WaveFormArg,
         
-        /// Output file path
+        /// Output file path, or `-` for stdout
         #[arg(short, long, default_value = "output.wav")]
         output: PathBuf,
         
@@ -87,7 +214,7 @@ enum Preset {
         #[arg(short, long, value_enum, default_value = "triangle")]
         waveform: WaveFormArg,
         
-        /// Output file path
+        /// Output file path, or `-` for stdout
         #[arg(short, long, default_value = "output.wav")]
         output: PathBuf,
         
@@ -117,7 +244,7 @@ enum Preset {
         #[arg(short, long, value_enum, default_value = "sawtooth")]
         waveform: WaveFormArg,
         
-        /// Output file path
+        /// Output file path, or `-` for stdout
         #[arg(short, long, default_value = "output.wav")]
         output: PathBuf,
         
@@ -147,7 +274,7 @@ enum Preset {
         #[arg(short, long, value_enum, default_value = "sine")]
         waveform: WaveFormArg,
         
-        /// Output file path
+        /// Output file path, or `-` for stdout
         #[arg(short, long, default_value = "output.wav")]
         output: PathBuf,
         
@@ -177,7 +304,7 @@ enum Preset {
         #[arg(short, long, value_enum, default_value = "sine")]
         waveform: WaveFormArg,
         
-        /// Output file path
+        /// Output file path, or `-` for stdout
         #[arg(short, long, default_value = "output.wav")]
         output: PathBuf,
         
@@ -207,7 +334,7 @@ enum Preset {
         #[arg(short, long, value_enum, default_value = "sine")]
         waveform: WaveFormArg,
         
-        /// Output file path
+        /// Output file path, or `-` for stdout
         #[arg(short, long, default_value = "output.wav")]
         output: PathBuf,
         
@@ -237,7 +364,7 @@ enum Preset {
         #[arg(short, long, value_enum, default_value = "sine")]
         waveform: WaveFormArg,
         
-        /// Output file path
+        /// Output file path, or `-` for stdout
         #[arg(short, long, default_value = "output.wav")]
         output: PathBuf,
         
@@ -255,6 +382,23 @@ enum Preset {
     },
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Debug)]
+enum FormatArg {
+    Wav,
+    Mp3,
+    Ogg,
+}
+
+impl FormatArg {
+    fn extension(self) -> &'static str {
+        match self {
+            FormatArg::Wav => "wav",
+            FormatArg::Mp3 => "mp3",
+            FormatArg::Ogg => "ogg",
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Debug)]
 enum WaveFormArg {
     Sine,
@@ -275,21 +419,53 @@ impl From<WaveFormArg> for WaveForm {
 }
 
 impl Preset {
-    fn generate_samples(&self, generator: &mut JingleGenerator) -> Vec<f32> {
+    fn generate_samples(&self, generator: &mut JingleGenerator, waveform: WaveForm) -> Vec<f32> {
         let (_, _, _, duration, frequency, _) = self.get_params();
         
         let duration_opt = if duration != 1.0 { Some(duration) } else { None };
         let frequency_opt = if frequency != 440.0 { Some(frequency) } else { None };
         
         match self {
-            Preset::Notification { waveform, .. } => generator.create_notification_jingle(WaveForm::from(*waveform), duration_opt, frequency_opt),
-            Preset::Alert { waveform, .. } => generator.create_alert_jingle(WaveForm::from(*waveform), duration_opt, frequency_opt),
-            Preset::Success { waveform, .. } => generator.create_success_jingle(WaveForm::from(*waveform), duration_opt, frequency_opt),
-            Preset::Error { waveform, .. } => generator.create_error_jingle(WaveForm::from(*waveform), duration_opt, frequency_opt),
-            Preset::Startup { waveform, .. } => generator.create_startup_jingle(WaveForm::from(*waveform), duration_opt, frequency_opt),
-            Preset::Shutdown { waveform, .. } => generator.create_shutdown_jingle(WaveForm::from(*waveform), duration_opt, frequency_opt),
-            Preset::Message { waveform, .. } => generator.create_message_jingle(WaveForm::from(*waveform), duration_opt, frequency_opt),
-            Preset::Completion { waveform, .. } => generator.create_completion_jingle(WaveForm::from(*waveform), duration_opt, frequency_opt),
+            Preset::Notification { .. } => generator.create_notification_jingle(waveform, duration_opt, frequency_opt),
+            Preset::Alert { .. } => generator.create_alert_jingle(waveform, duration_opt, frequency_opt),
+            Preset::Success { .. } => generator.create_success_jingle(waveform, duration_opt, frequency_opt),
+            Preset::Error { .. } => generator.create_error_jingle(waveform, duration_opt, frequency_opt),
+            Preset::Startup { .. } => generator.create_startup_jingle(waveform, duration_opt, frequency_opt),
+            Preset::Shutdown { .. } => generator.create_shutdown_jingle(waveform, duration_opt, frequency_opt),
+            Preset::Message { .. } => generator.create_message_jingle(waveform, duration_opt, frequency_opt),
+            Preset::Completion { .. } => generator.create_completion_jingle(waveform, duration_opt, frequency_opt),
+        }
+    }
+    
+    fn jingle_preset(&self) -> JinglePreset {
+        match self {
+            Preset::Notification { .. } => JinglePreset::Notification,
+            Preset::Alert { .. } => JinglePreset::Alert,
+            Preset::Success { .. } => JinglePreset::Success,
+            Preset::Error { .. } => JinglePreset::Error,
+            Preset::Startup { .. } => JinglePreset::Startup,
+            Preset::Shutdown { .. } => JinglePreset::Shutdown,
+            Preset::Message { .. } => JinglePreset::Message,
+            Preset::Completion { .. } => JinglePreset::Completion,
+        }
+    }
+    
+    /// Waveform given with `--waveform`, or the argument's default
+    fn waveform(&self) -> WaveForm {
+        match self {
+            Preset::Notification { waveform, .. } | Preset::Alert { waveform, .. } | Preset::Success { waveform, .. }
+            | Preset::Error { waveform, .. } | Preset::Startup { waveform, .. } | Preset::Shutdown { waveform, .. }
+            | Preset::Message { waveform, .. } | Preset::Completion { waveform, .. } => WaveForm::from(*waveform),
+        }
+    }
+    
+    /// Default of the `--waveform` argument
+    fn default_waveform(&self) -> WaveForm {
+        match self {
+            Preset::Alert { .. } => WaveForm::Square,
+            Preset::Success { .. } => WaveForm::Triangle,
+            Preset::Error { .. } => WaveForm::Sawtooth,
+            _ => WaveForm::Sine,
         }
     }
     
@@ -308,10 +484,10 @@ impl Preset {
     
 }
 
-fn play_samples(samples: &[f32]) -> Result<(), jinglebells::JingleError> {
+fn play_samples(generator: &JingleGenerator, samples: &[f32]) -> Result<(), JingleError> {
     // Get output stream handle
     let mut stream_handle = rodio::OutputStreamBuilder::open_default_stream()
-        .map_err(|e| jinglebells::JingleError::PlaybackError(e.to_string()))?;
+        .map_err(|e| JingleError::PlaybackError(e.to_string()))?;
     
     // Disable drop logging to avoid interfering with CLI output
     stream_handle.log_on_drop(false);
@@ -319,11 +495,8 @@ fn play_samples(samples: &[f32]) -> Result<(), jinglebells::JingleError> {
     // Create sink connected to the stream
     let sink = rodio::Sink::connect_new(&stream_handle.mixer());
     
-    // Convert samples to the format rodio expects
-    let source = rodio::buffer::SamplesBuffer::new(1, jinglebells::SAMPLE_RATE, samples.to_vec());
-    
-    // Add the source to the sink
-    sink.append(source);
+    // Play at the rate the samples were rendered at
+    sink.append(generator.samples_source(samples.to_vec()));
     
     // Block until playback is complete
     sink.sleep_until_end();
@@ -331,16 +504,36 @@ fn play_samples(samples: &[f32]) -> Result<(), jinglebells::JingleError> {
     Ok(())
 }
 
-fn print_replay_command(preset: &Preset, seed: u64, seed_was_explicit: bool) {
-    // Don't print anything if an explicit seed was given
-    if seed_was_explicit {
-        return;
-    }
+/// Path of one variation when several are generated, e.g. `output-02.wav` for the second of ten
+fn numbered_output(output: &Path, number: u32, count: u32) -> PathBuf {
+    let file_stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let file_ext = output.extension().unwrap_or_default().to_string_lossy();
+    let width = count.to_string().len().max(2);
+    let numbered_output = if file_ext.is_empty() {
+        format!("{}-{:0width$}", file_stem, number)
+    } else {
+        format!("{}-{:0width$}.{}", file_stem, number, file_ext)
+    };
+    output.with_file_name(numbered_output)
+}
+
+/// File name of the running binary for printed commands
+fn exe_name() -> String {
     let current_exe = std::env::current_exe()
         .unwrap_or_else(|_| std::path::PathBuf::from("jinglebells"));
-    let exe_name = current_exe.file_name()
+    current_exe.file_name()
         .unwrap_or_else(|| std::ffi::OsStr::new("jinglebells"))
-        .to_string_lossy();
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Command regenerating a sound rendered from a random seed
+fn replay_command(preset: &Preset, waveform: WaveForm, effects: Option<&str>, seed: u64, seed_was_explicit: bool) -> Option<String> {
+    // Nothing to print if an explicit seed was given
+    if seed_was_explicit {
+        return None;
+    }
+    let exe_name = exe_name();
     
     let mut cmd_args = Vec::new();
     
@@ -371,20 +564,15 @@ fn print_replay_command(preset: &Preset, seed: u64, seed_was_explicit: bool) {
         cmd_args.push(frequency.to_string());
     }
     
-    // Add waveform if it's not the default for this preset
-    let waveform_arg = match preset {
-        Preset::Notification { waveform, .. } => if *waveform != WaveFormArg::Sine { Some(format!("{:?}", waveform).to_lowercase()) } else { None },
-        Preset::Alert { waveform, .. } => if *waveform != WaveFormArg::Square { Some(format!("{:?}", waveform).to_lowercase()) } else { None },
-        Preset::Success { waveform, .. } => if *waveform != WaveFormArg::Triangle { Some(format!("{:?}", waveform).to_lowercase()) } else { None },
-        Preset::Error { waveform, .. } => if *waveform != WaveFormArg::Sawtooth { Some(format!("{:?}", waveform).to_lowercase()) } else { None },
-        Preset::Startup { waveform, .. } | Preset::Shutdown { waveform, .. } | Preset::Message { waveform, .. } | Preset::Completion { waveform, .. } => {
-            if *waveform != WaveFormArg::Sine { Some(format!("{:?}", waveform).to_lowercase()) } else { None }
-        },
-    };
-    
-    if let Some(wf) = waveform_arg {
+    // Add waveform if it's not the default for this preset, including one picked by the config file
+    if waveform != preset.default_waveform() {
         cmd_args.push("--waveform".to_string());
-        cmd_args.push(wf);
+        cmd_args.push(format!("{:?}", waveform).to_lowercase());
+    }
+    
+    if let Some(effects) = effects {
+        cmd_args.push("--effects".to_string());
+        cmd_args.push(format!("\"{}\"", effects));
     }
     
     // Always add the seed to ensure reproducibility
@@ -397,61 +585,595 @@ fn print_replay_command(preset: &Preset, seed: u64, seed_was_explicit: bool) {
         cmd_args.push(count.to_string());
     }
     
-    // Print the command
-    println!("To replay this sound: {} {}", exe_name, cmd_args.join(" "));
+    Some(format!("To replay this sound: {} {}", exe_name, cmd_args.join(" ")))
 }
 
-fn main() -> Result<(), jinglebells::JingleError> {
-    let cli = Cli::parse();
+/// Quote a string for JSON output
+fn json_string(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn list_presets(json: bool, config: &ConfigFile) {
+    let presets = JinglePreset::all();
+    let formats = JingleGenerator::new().supported_formats();
+    let waveform_name = |preset: &JinglePreset| {
+        format!("{:?}", config.waveform(*preset).unwrap_or(preset.style().waveform)).to_lowercase()
+    };
+    
+    if json {
+        let entries: Vec<String> = presets.iter().map(|preset| format!(
+            "    {{\"name\": {}, \"description\": {}, \"waveform\": {}, \"duration\": {}}}",
+            json_string(preset.name()),
+            json_string(preset.description()),
+            json_string(&waveform_name(preset)),
+            preset.default_duration(),
+        )).collect();
+        let custom: Vec<String> = config.presets.iter().map(|(name, spec)| format!(
+            "    {{\"name\": {}, \"preset\": {}, \"waveform\": {}}}",
+            json_string(name),
+            json_string(spec.preset.name()),
+            json_string(&format!("{:?}", spec.waveform).to_lowercase()),
+        )).collect();
+        let formats: Vec<String> = formats.iter().map(|format| json_string(format)).collect();
+        let custom = if custom.is_empty() { String::new() } else { format!("\n{}\n  ", custom.join(",\n")) };
+        println!(
+            "{{\n  \"presets\": [\n{}\n  ],\n  \"custom_presets\": [{}],\n  \"formats\": [{}]\n}}",
+            entries.join(",\n"), custom, formats.join(", "),
+        );
+        return;
+    }
     
-    let (output, count, seed, _duration, _frequency, generate_only) = cli.preset.get_params();
+    let name_width = presets.iter().map(|preset| preset.name().len()).max().unwrap_or(0);
+    println!("Presets:");
+    for preset in &presets {
+        println!(
+            "  {:name_width$}  {:8}  {:>5.2}s  {}",
+            preset.name(),
+            waveform_name(preset),
+            preset.default_duration(),
+            preset.description(),
+        );
+    }
+    if !config.presets.is_empty() {
+        let custom_width = config.presets.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        println!();
+        println!("Custom presets:");
+        for (name, spec) in &config.presets {
+            println!("  {:custom_width$}  {:8}  based on {}", name, format!("{:?}", spec.waveform).to_lowercase(), spec.preset.name());
+        }
+    }
+    println!();
+    println!("Output formats: {}", formats.join(", "));
+}
+
+fn main() -> Result<(), JingleError> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    let mut config = match &cli.config {
+        Some(path) => ConfigFile::load(path)?,
+        None => ConfigFile::load_default()?,
+    };
+    // Export flags override the config file
+    if let Some(format) = cli.format {
+        config.format = Some(format.extension().to_string());
+    }
+    if cli.raw {
+        config.format = Some("raw".to_string());
+    }
+    if cli.bitrate.is_some() {
+        config.bitrate = cli.bitrate;
+    }
+    if let Some(bits) = &cli.bit_depth {
+        config.bit_depth = bits.parse().ok().and_then(BitDepth::from_bits);
+    }
+    if let Some(format) = &config.format {
+        let supported = JingleGenerator::new().supported_formats();
+        if !supported.contains(format) {
+            return Err(JingleError::InvalidParameter(format!(
+                "{} output isn't supported by this build (supported: {})", format, supported.join(", ")
+            )));
+        }
+    }
+    // Config values only fill in arguments that weren't given on the command line
+    let args = matches.subcommand().map(|(_, args)| args).unwrap_or(&matches);
+    let effects = cli.effects.as_deref().map(EffectChain::parse).transpose()?.unwrap_or_default();
+    let replay_effects = cli.effects.as_deref().filter(|_| !effects.is_empty());
+    let output_arg = |output: &Path| if cli.stdout { PathBuf::from(STDOUT) } else { output.to_path_buf() };
+    if cli.stdout && matches!(cli.command, Command::Theme { .. } | Command::Interactive { .. } | Command::List { .. }) {
+        return Err(JingleError::InvalidParameter("--stdout only works with commands rendering a single sound".to_string()));
+    }
+    
+    match &cli.command {
+        Command::List { json } => {
+            list_presets(*json, &config);
+            Ok(())
+        },
+        Command::Melody { notes, waveform, tempo, output, generate_only } => {
+            let output = configured_output(&config, &output_arg(output), explicit(args, "output"), cli.format.is_some())?;
+            run_melody(&config, &effects, notes, WaveForm::from(*waveform), *tempo, &output, *generate_only)
+        },
+        Command::Theme { seed, out_dir } => {
+            if !effects.is_empty() {
+                return Err(JingleError::InvalidParameter("--effects can't be used with theme, whose sounds share the theme's own effects".to_string()));
+            }
+            run_theme(&config, *seed, configured_dir(&config, out_dir, explicit(args, "out_dir")))
+        },
+        Command::Interactive { preset, seed, out_dir } => {
+            run_interactive(&config, &effects, preset, *seed, configured_dir(&config, out_dir, explicit(args, "out_dir")))
+        },
+        Command::Custom { name, output, seed, generate_only } => {
+            let output = configured_output(&config, &output_arg(output), explicit(args, "output"), cli.format.is_some())?;
+            run_custom(&config, &effects, replay_effects, name, &output, *seed, *generate_only)
+        },
+        Command::Render { spec, output, generate_only, .. } => {
+            let document = match spec {
+                Some(path) => SpecDocument::parse(&std::fs::read_to_string(path)?, SpecFormat::from_path(path))?,
+                None => serde_json::from_reader(std::io::stdin().lock())
+                    .map_err(|err| JingleError::InvalidParameter(format!("spec on stdin: {}", err)))?,
+            };
+            let output = configured_output(&config, &output_arg(output), explicit(args, "output"), cli.format.is_some())?;
+            run_render(&config, &effects, &document, &output, *generate_only)
+        },
+        Command::Preset(preset) => {
+            let waveform = match config.waveform(preset.jingle_preset()) {
+                Some(waveform) if !explicit(args, "waveform") => waveform,
+                _ => preset.waveform(),
+            };
+            let output = configured_output(&config, &output_arg(&preset.get_params().0), explicit(args, "output"), cli.format.is_some())?;
+            run_preset(&config, &effects, replay_effects, preset, waveform, &output)
+        },
+    }
+}
+
+/// Whether an argument was given on the command line rather than left at its default
+fn explicit(args: &ArgMatches, id: &str) -> bool {
+    args.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+/// Output path in the config's directory and format, unless one was given on the command line
+///
+/// A `--format` flag replaces the extension even of an explicit output path.
+fn configured_output(config: &ConfigFile, output: &Path, explicit: bool, format_flag: bool) -> Result<PathBuf, JingleError> {
+    let mut output = output.to_path_buf();
+    if is_stdout(&output) {
+        return Ok(output);
+    }
+    if let Some(format) = &config.format
+        && (format_flag || !explicit)
+    {
+        output.set_extension(format);
+    }
+    if explicit {
+        return Ok(output);
+    }
+    if let Some(dir) = &config.output_dir {
+        std::fs::create_dir_all(dir)?;
+        output = dir.join(output);
+    }
+    Ok(output)
+}
+
+/// Output path standing for stdout
+const STDOUT: &str = "-";
+
+fn is_stdout(output: &Path) -> bool {
+    output == Path::new(STDOUT)
+}
+
+/// Export to the output file, or to stdout in the config's format (WAV by default)
+fn write_output(config: &ConfigFile, generator: &JingleGenerator, samples: &[f32], output: &Path) -> Result<(), JingleError> {
+    if is_stdout(output) {
+        let extension = config.format.as_deref().unwrap_or("wav");
+        match generator.export_to_writer(samples, extension, &mut std::io::stdout().lock()) {
+            // The reader stopping early, like `| head`, isn't a failure
+            Err(JingleError::IoError(err)) if err.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+            result => result,
+        }
+    } else {
+        generator.export_to_file(samples, output)?;
+        println!("Wrote {}", output.display());
+        Ok(())
+    }
+}
+
+/// Print a status line, on stderr when the audio itself is going to stdout
+fn status(output: &Path, message: &str) {
+    if is_stdout(output) {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// The config's output directory, unless one was given on the command line
+fn configured_dir<'a>(config: &'a ConfigFile, dir: &'a Path, explicit: bool) -> &'a Path {
+    match &config.output_dir {
+        Some(config_dir) if !explicit => config_dir,
+        _ => dir,
+    }
+}
+
+/// Generator rendering at the config's sample rate and exporting with its bitrate and bit depth
+fn configured_generator(config: &ConfigFile, seed: Option<u64>) -> Result<JingleGenerator, JingleError> {
+    let mut generator = match seed {
+        Some(seed) => JingleGenerator::with_seed(seed),
+        None => JingleGenerator::new(),
+    };
+    generator.set_bitrate(config.bitrate);
+    generator.set_bit_depth(config.bit_depth.unwrap_or_default());
+    match config.sample_rate {
+        Some(sample_rate) => generator.with_config(GeneratorConfig::default().with_sample_rate(sample_rate)),
+        None => Ok(generator),
+    }
+}
+
+/// Seed from the clock, for runs without `--seed`
+fn random_seed() -> u64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    
+    let mut hasher = DefaultHasher::new();
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos().hash(&mut hasher);
+    hasher.finish()
+}
+
+fn run_theme(config: &ConfigFile, seed: Option<u64>, out_dir: &Path) -> Result<(), JingleError> {
+    let seed = seed.unwrap_or_else(random_seed);
+    let extension = config.format.as_deref().unwrap_or("wav");
+    let (theme, written) = configured_generator(config, None)?.export_theme(seed, out_dir, extension)?;
+    
+    println!("Theme {} ({:?} {:?}, {:?})", seed, theme.root_note, theme.scale, theme.waveform);
+    for path in &written {
+        println!("Wrote {}", path.display());
+    }
+    println!("Wrote {}", out_dir.join(jinglebells::THEME_MANIFEST).display());
+    Ok(())
+}
+
+fn run_melody(config: &ConfigFile, effects: &EffectChain, notes: &str, waveform: WaveForm, tempo: f32, output: &Path, generate_only: bool) -> Result<(), JingleError> {
+    let phrase = Phrase::parse(notes)?.with_tempo(tempo);
+    let generator = configured_generator(config, None)?;
+    let samples = generator.apply_effects(&generator.render_phrase(&phrase, waveform)?, effects);
+    
+    write_output(config, &generator, &samples, output)?;
+    
+    if !generate_only && !is_stdout(output) {
+        play_samples(&generator, &samples)?;
+    }
+    Ok(())
+}
+
+fn run_custom(config: &ConfigFile, effects: &EffectChain, effects_arg: Option<&str>, name: &str, output: &Path, seed: Option<u64>, generate_only: bool) -> Result<(), JingleError> {
+    let spec = config.preset(name)
+        .ok_or_else(|| JingleError::InvalidParameter(format!("no preset `{}` in the config file", name)))?;
+    let actual_seed = seed.or(spec.seed).unwrap_or_else(random_seed);
+    let mut generator = configured_generator(config, None)?;
+    let samples = generator.try_render(&spec.clone().with_seed(actual_seed))?;
+    let samples = generator.apply_effects(&samples, effects);
+    
+    write_output(config, &generator, &samples, output)?;
+    
+    if !generate_only && !is_stdout(output) {
+        play_samples(&generator, &samples)?;
+    }
+    
+    // Only a random seed needs printing; the config or the command line already records the others
+    if seed.is_none() && spec.seed.is_none() {
+        let effects = effects_arg.map(|text| format!(" --effects \"{}\"", text)).unwrap_or_default();
+        status(output, &format!("To replay this sound: {} custom {} --seed {}{}", exe_name(), name, actual_seed, effects));
+    }
+    Ok(())
+}
+
+fn run_render(config: &ConfigFile, effects: &EffectChain, document: &SpecDocument, output: &Path, generate_only: bool) -> Result<(), JingleError> {
+    let mut generator = configured_generator(config, None)?;
+    let samples = generator.render_document(document)?;
+    let samples = generator.apply_effects(&samples, effects);
+    
+    write_output(config, &generator, &samples, output)?;
+    
+    if !generate_only && !is_stdout(output) {
+        play_samples(&generator, &samples)?;
+    }
+    Ok(())
+}
+
+fn run_preset(config: &ConfigFile, effects: &EffectChain, effects_arg: Option<&str>, preset: &Preset, waveform: WaveForm, output: &Path) -> Result<(), JingleError> {
+    let (_output, count, seed, _duration, _frequency, generate_only) = preset.get_params();
     
     // Validate parameters
     if count == 0 || count > 100 {
         eprintln!("Error: Count must be between 1 and 100");
         std::process::exit(1);
     }
+    if count > 1 && is_stdout(output) {
+        return Err(JingleError::InvalidParameter("--count can't be used when writing to stdout".to_string()));
+    }
     
     // Always use a seed - generate one if not provided
-    let actual_seed = seed.unwrap_or_else(|| {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        use std::time::{SystemTime, UNIX_EPOCH};
-        
-        let mut hasher = DefaultHasher::new();
-        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos().hash(&mut hasher);
-        hasher.finish()
-    });
+    let actual_seed = seed.unwrap_or_else(random_seed);
     
-    let mut generator = JingleGenerator::with_seed(actual_seed);
+    let generator = configured_generator(config, Some(actual_seed))?;
     
     for i in 0..count {
-        let samples = cli.preset.generate_samples(&mut generator);
-        
-        // Save to file if generate_only is specified
-        if generate_only {
-            if count > 1 {
-                let file_stem = output.file_stem().unwrap_or_default().to_string_lossy();
-                let file_ext = output.extension().unwrap_or_default().to_string_lossy();
-                let numbered_output = if file_ext.is_empty() {
-                    format!("{}_{}", file_stem, i)
-                } else {
-                    format!("{}_{}.{}", file_stem, i, file_ext)
-                };
-                let numbered_path = output.with_file_name(&numbered_output);
-                generator.export_to_file(&samples, &numbered_path)?;
-            } else {
-                generator.export_to_file(&samples, &output)?;
-            }
+        // Each variation gets its own derived seed so it can be regenerated alone with `--seed`
+        let mut variation = generator.derive_seed(i as u64)?;
+        let variation_seed = variation.get_current_seed().unwrap_or(actual_seed);
+        let samples = preset.generate_samples(&mut variation, waveform);
+        let samples = variation.apply_effects(&samples, effects);
+        
+        // Always write the file, numbering it when generating several variations
+        if count > 1 {
+            let path = numbered_output(output, i + 1, count);
+            variation.export_to_file(&samples, &path)?;
+            println!("Wrote {} (seed {})", path.display(), variation_seed);
         } else {
-            // Play audio by default
-            play_samples(&samples)?;
-            
-            // Print the replay command if seed wasn't explicit
-            print_replay_command(&cli.preset, actual_seed, seed.is_some());
+            write_output(config, &variation, &samples, output)?;
         }
+        
+        // Play audio unless only generating files or piping them
+        if !generate_only && !is_stdout(output) {
+            play_samples(&variation, &samples)?;
+        }
+    }
+    
+    // Print the replay command if seed wasn't explicit
+    if let Some(command) = replay_command(preset, waveform, effects_arg, actual_seed, seed.is_some()) {
+        status(output, &command);
     }
     
     Ok(())
 }
 
+/// Ratio between neighbouring semitones, the pitch step of the up and down arrows
+const SEMITONE: f32 = 1.059_463_1;
+/// Duration step of the left and right arrows in seconds
+const DURATION_STEP: f32 = 0.1;
+const INTERACTIVE_HELP: &str = "left/right duration, up/down pitch, tab/shift-tab preset, r reroll, space replay, a save, q quit";
+
+/// A key press in interactive mode
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    BackTab,
+    Char(char),
+}
+
+fn read_byte(input: &mut impl Read) -> Option<u8> {
+    let mut byte = [0u8; 1];
+    input.read_exact(&mut byte).ok().map(|_| byte[0])
+}
+
+/// Read one key, decoding arrow escape sequences; `None` at the end of input
+fn read_key(input: &mut impl Read) -> Option<Key> {
+    loop {
+        let key = match read_byte(input)? {
+            0x1b => {
+                if read_byte(input)? != b'[' {
+                    continue;
+                }
+                match read_byte(input)? {
+                    b'A' => Key::Up,
+                    b'B' => Key::Down,
+                    b'C' => Key::Right,
+                    b'D' => Key::Left,
+                    b'Z' => Key::BackTab,
+                    _ => continue,
+                }
+            },
+            // Ctrl-C and Ctrl-D arrive as bytes while the terminal is in raw mode
+            0x03 | 0x04 => Key::Char('q'),
+            byte if byte.is_ascii() => Key::Char(byte as char),
+            _ => continue,
+        };
+        return Some(key);
+    }
+}
+
+/// Terminal switched to unbuffered, unechoed input until dropped
+#[cfg(unix)]
+struct RawMode(libc::termios);
+
+#[cfg(unix)]
+impl RawMode {
+    /// `None` when stdin isn't a terminal, leaving keys to be read line by line
+    fn enable() -> Option<Self> {
+        // SAFETY: tcgetattr fills the zeroed termios before it is read or passed back
+        unsafe {
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                return None;
+            }
+            let original = termios;
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            (libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) == 0).then_some(Self(original))
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: restores the settings read by tcgetattr in `enable`
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.0);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+struct RawMode;
+
+#[cfg(not(unix))]
+impl RawMode {
+    fn enable() -> Option<Self> {
+        None
+    }
+}
+
+/// Audio output kept open across previews so a new variation cuts off the previous one
+struct Player {
+    stream: rodio::OutputStream,
+    sink: rodio::Sink,
+}
+
+impl Player {
+    fn open() -> Result<Self, JingleError> {
+        let mut stream = rodio::OutputStreamBuilder::open_default_stream()
+            .map_err(|e| JingleError::PlaybackError(e.to_string()))?;
+        stream.log_on_drop(false);
+        let sink = rodio::Sink::connect_new(stream.mixer());
+        Ok(Self { stream, sink })
+    }
+    
+    fn play(&mut self, generator: &JingleGenerator, samples: &[f32]) {
+        // Dropping the old sink stops whatever it is still playing
+        self.sink = rodio::Sink::connect_new(self.stream.mixer());
+        self.sink.append(generator.samples_source(samples.to_vec()));
+    }
+}
+
+/// Variation shown by the interactive loop
+struct Preview {
+    presets: Vec<JinglePreset>,
+    index: usize,
+    seed: u64,
+    duration: Option<f32>,
+    semitones: i32,
+}
+
+impl Preview {
+    fn preset(&self) -> JinglePreset {
+        self.presets[self.index]
+    }
+    
+    fn spec(&self, config: &ConfigFile) -> JingleSpec {
+        let preset = self.preset();
+        let mut spec = JingleSpec::new(preset)
+            .with_waveform(config.waveform(preset).unwrap_or(preset.style().waveform))
+            .with_seed(self.seed);
+        if let Some(duration) = self.duration {
+            spec = spec.with_duration(duration);
+        }
+        if self.semitones != 0 {
+            spec = spec.with_frequency(440.0 * SEMITONE.powi(self.semitones));
+        }
+        spec
+    }
+    
+    /// Lengthen or shorten by whole steps from the preset's default duration
+    fn nudge_duration(&mut self, steps: f32) {
+        let duration = self.duration.unwrap_or(self.preset().default_duration()) + steps * DURATION_STEP;
+        self.duration = Some(duration.clamp(DURATION_STEP, 5.0));
+    }
+    
+    /// Move through the preset list, dropping the tweaks made to the previous preset
+    fn switch(&mut self, step: isize) {
+        self.index = (self.index as isize + step).rem_euclid(self.presets.len() as isize) as usize;
+        self.duration = None;
+        self.semitones = 0;
+    }
+    
+    fn status(&self) -> String {
+        let duration = self.duration.unwrap_or(self.preset().default_duration());
+        format!("{:16} seed {:<20} {:>5.2}s {:+3} st", self.preset().name(), self.seed, duration, self.semitones)
+    }
+}
+
+/// First `<preset>-<seed>` file name in the directory that isn't taken yet
+fn unused_output(out_dir: &Path, preview: &Preview, extension: &str) -> PathBuf {
+    let stem = format!("{}-{}", preview.preset().name(), preview.seed);
+    let mut path = out_dir.join(format!("{}.{}", stem, extension));
+    let mut number = 2;
+    while path.exists() {
+        path = out_dir.join(format!("{}-{}.{}", stem, number, extension));
+        number += 1;
+    }
+    path
+}
+
+fn run_interactive(config: &ConfigFile, effects: &EffectChain, preset: &str, seed: Option<u64>, out_dir: &Path) -> Result<(), JingleError> {
+    let presets = JinglePreset::all();
+    let index = presets.iter().position(|candidate| candidate.name() == preset)
+        .ok_or_else(|| JingleError::InvalidParameter(format!("unknown preset `{}`", preset)))?;
+    let mut preview = Preview { presets, index, seed: seed.unwrap_or_else(random_seed), duration: None, semitones: 0 };
+    let mut generator = configured_generator(config, None)?;
+    let extension = config.format.as_deref().unwrap_or("wav");
+    
+    // Keep going without sound so variations can still be saved and listened to elsewhere
+    let mut player = Player::open()
+        .inspect_err(|err| eprintln!("Playback unavailable, previews are only rendered: {}", err))
+        .ok();
+    let _raw_mode = RawMode::enable();
+    let mut input = std::io::stdin().lock();
+    
+    println!("{}", INTERACTIVE_HELP);
+    let mut samples = Vec::new();
+    let mut render = true;
+    loop {
+        if render {
+            match generator.try_render(&preview.spec(config)) {
+                Ok(rendered) => {
+                    samples = generator.apply_effects(&rendered, effects);
+                    println!("{}", preview.status());
+                    if let Some(player) = &mut player {
+                        player.play(&generator, &samples);
+                    }
+                },
+                Err(err) => {
+                    samples.clear();
+                    println!("{}: {}", preview.status(), err);
+                },
+            }
+        }
+        
+        let Some(key) = read_key(&mut input) else {
+            break;
+        };
+        render = true;
+        match key {
+            Key::Up => preview.semitones = (preview.semitones + 1).min(24),
+            Key::Down => preview.semitones = (preview.semitones - 1).max(-24),
+            Key::Right => preview.nudge_duration(1.0),
+            Key::Left => preview.nudge_duration(-1.0),
+            Key::Char('\t') => preview.switch(1),
+            Key::BackTab => preview.switch(-1),
+            Key::Char('r') => preview.seed = random_seed(),
+            Key::Char(' ') => {
+                render = false;
+                if let Some(player) = &mut player {
+                    player.play(&generator, &samples);
+                }
+            },
+            Key::Char('a') => {
+                render = false;
+                if samples.is_empty() {
+                    continue;
+                }
+                let path = unused_output(out_dir, &preview, extension);
+                match std::fs::create_dir_all(out_dir).map_err(JingleError::from).and_then(|_| generator.export_to_file(&samples, &path)) {
+                    Ok(()) => println!("Saved {}", path.display()),
+                    Err(err) => println!("Could not save {}: {}", path.display(), err),
+                }
+            },
+            Key::Char('q') => break,
+            Key::Char(_) => render = false,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Sidecar manifests describing how an exported jingle was generated
+
+use std::path::{Path, PathBuf};
+use crate::{
+    audio::WaveForm,
+    music::{Note, Scale, MelodyPattern, ChordProgression},
+    presets::JinglePreset,
+    version::GenerationVersion,
+    error::Result,
+};
+
+/// Choices made by the random generator while rendering the most recent jingle
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RenderChoices {
+    pub root_note: Option<Note>,
+    pub scale: Option<Scale>,
+    pub pattern: Option<MelodyPattern>,
+    pub progression: Option<ChordProgression>,
+    pub waveform: Option<WaveForm>,
+    pub note_count: Option<usize>,
+}
+
+/// Description of an exported audio file, written as JSON next to it
+#[derive(Clone, Debug)]
+pub struct ExportManifest {
+    pub audio_path: PathBuf,
+    pub preset: Option<JinglePreset>,
+    pub seed: Option<u64>,
+    /// Algorithm the seed was rendered with
+    pub generation_version: Option<GenerationVersion>,
+    pub waveform: Option<WaveForm>,
+    pub duration: Option<f32>,
+    pub frequency: Option<f32>,
+    pub choices: RenderChoices,
+    pub peak: f32,
+    pub sample_rate: u32,
+    pub sample_count: usize,
+}
+
+impl ExportManifest {
+    /// Create a manifest for rendered samples exported to a path
+    pub fn new<P: AsRef<Path>>(samples: &[f32], audio_path: P, sample_rate: u32) -> Self {
+        Self {
+            audio_path: audio_path.as_ref().to_path_buf(),
+            preset: None,
+            seed: None,
+            generation_version: None,
+            waveform: None,
+            duration: None,
+            frequency: None,
+            choices: RenderChoices::default(),
+            peak: samples.iter().map(|&x| x.abs()).fold(0.0f32, |acc, x| acc.max(x)),
+            sample_rate,
+            sample_count: samples.len(),
+        }
+    }
+
+    /// Record the seed used for generation
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Record the algorithm version the seed was rendered with
+    pub fn with_generation_version(mut self, version: GenerationVersion) -> Self {
+        self.generation_version = Some(version);
+        self
+    }
+
+    /// Record the preset and the parameters it was requested with
+    pub fn with_preset(mut self, preset: JinglePreset, waveform: WaveForm, duration: Option<f32>, frequency: Option<f32>) -> Self {
+        self.preset = Some(preset);
+        self.waveform = Some(waveform);
+        self.duration = duration;
+        self.frequency = frequency;
+        self
+    }
+
+    /// Record the random choices made during rendering
+    pub fn with_choices(mut self, choices: RenderChoices) -> Self {
+        self.choices = choices;
+        self
+    }
+
+    /// Path of the sidecar file (the audio path with a `.json` extension)
+    pub fn sidecar_path(&self) -> PathBuf {
+        self.audio_path.with_extension("json")
+    }
+
+    /// Serialize the manifest as a JSON object
+    pub fn to_json(&self) -> String {
+        let fields = [
+            ("file", json_string(&self.audio_path.to_string_lossy())),
+            ("preset", json_option(self.preset.map(|p| json_string(p.name())))),
+            ("seed", json_option(self.seed.map(|s| s.to_string()))),
+            ("generation_version", json_option(self.generation_version.map(|v| json_string(v.name())))),
+            ("waveform", json_option(self.waveform.map(|w| json_debug(&w)))),
+            ("duration", json_option(self.duration.map(json_number))),
+            ("frequency", json_option(self.frequency.map(json_number))),
+            ("root_note", json_option(self.choices.root_note.map(|n| json_debug(&n)))),
+            ("scale", json_option(self.choices.scale.map(|s| json_debug(&s)))),
+            ("pattern", json_option(self.choices.pattern.map(|p| json_debug(&p)))),
+            ("progression", json_option(self.choices.progression.as_ref().map(json_debug))),
+            ("used_waveform", json_option(self.choices.waveform.map(|w| json_debug(&w)))),
+            ("note_count", json_option(self.choices.note_count.map(|c| c.to_string()))),
+            ("peak", json_number(self.peak)),
+            ("sample_rate", self.sample_rate.to_string()),
+            ("sample_count", self.sample_count.to_string()),
+        ];
+
+        let body: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("  \"{}\": {}", key, value))
+            .collect();
+        format!("{{\n{}\n}}\n", body.join(",\n"))
+    }
+
+    /// Write the manifest next to the audio file and return the sidecar path
+    pub fn write_sidecar(&self) -> Result<PathBuf> {
+        let path = self.sidecar_path();
+        std::fs::write(&path, self.to_json())?;
+        Ok(path)
+    }
+}
+
+/// Quote and escape a string for JSON
+pub(crate) fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Lowercase Debug name of an enum value as a JSON string
+pub(crate) fn json_debug<T: std::fmt::Debug>(value: &T) -> String {
+    json_string(&format!("{:?}", value).to_lowercase())
+}
+
+/// Format a float as a JSON number (non-finite values become null)
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Format an optional JSON value, using null when absent
+fn json_option(value: Option<String>) -> String {
+    value.unwrap_or_else(|| "null".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_json() {
+        let manifest = ExportManifest::new(&[0.25, -0.5], "out/alert \"1\".wav", 44100)
+            .with_seed(Some(42))
+            .with_generation_version(GenerationVersion::V1)
+            .with_preset(JinglePreset::Alert, WaveForm::Square, Some(0.5), None)
+            .with_choices(RenderChoices {
+                scale: Some(Scale::Pentatonic),
+                ..RenderChoices::default()
+            });
+        let json = manifest.to_json();
+
+        assert!(json.contains("\"file\": \"out/alert \\\"1\\\".wav\""));
+        assert!(json.contains("\"preset\": \"alert\""));
+        assert!(json.contains("\"seed\": 42"));
+        assert!(json.contains("\"generation_version\": \"v1\""));
+        assert!(json.contains("\"frequency\": null"));
+        assert!(json.contains("\"scale\": \"pentatonic\""));
+        assert!(json.contains("\"peak\": 0.5"));
+    }
+
+    #[test]
+    fn test_sidecar_path() {
+        let manifest = ExportManifest::new(&[], "jingles/success.wav", 44100);
+        assert_eq!(manifest.sidecar_path(), PathBuf::from("jingles/success.json"));
+    }
+}
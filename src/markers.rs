@@ -0,0 +1,212 @@
+//! Loop points and cue markers written into WAV metadata chunks
+
+use crate::error::{JingleError, Result};
+
+/// Region of audio played repeatedly by samplers and game engines
+///
+/// Positions are sample frames; `start` is inclusive and `end` is exclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LoopRegion {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl LoopRegion {
+    /// Create a loop region from frame positions
+    pub fn new(start: u32, end: u32) -> Self {
+        Self { start, end }
+    }
+
+    /// Create a loop region from times in seconds
+    pub fn from_seconds(start: f32, end: f32, sample_rate: u32) -> Self {
+        Self {
+            start: (start.max(0.0) * sample_rate as f32).round() as u32,
+            end: (end.max(0.0) * sample_rate as f32).round() as u32,
+        }
+    }
+
+    /// Convert the region to frame positions at another sample rate
+    pub fn rescale(&self, from_rate: u32, to_rate: u32) -> Self {
+        let scale = |frame: u32| (frame as u64 * to_rate as u64).div_ceil(from_rate as u64) as u32;
+        Self {
+            start: scale(self.start),
+            end: scale(self.end),
+        }
+    }
+
+    /// Check that the region is non-empty and fits within `frames` sample frames
+    pub fn validate(&self, frames: usize) -> Result<()> {
+        if self.start >= self.end || self.end as usize > frames {
+            return Err(JingleError::InvalidParameter(format!(
+                "Loop region {}..{} must be non-empty and within {} frames",
+                self.start, self.end, frames
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Labelled position shown as a marker by audio editors
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CueMarker {
+    /// Position in sample frames
+    pub position: u32,
+    pub label: String,
+}
+
+impl CueMarker {
+    /// Create a cue marker at a frame position
+    pub fn new(position: u32, label: impl Into<String>) -> Self {
+        Self {
+            position,
+            label: label.into(),
+        }
+    }
+
+    /// Move the marker to the matching frame at another sample rate
+    pub fn rescale(&self, from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            position: (self.position as u64 * to_rate as u64 / from_rate as u64) as u32,
+            label: self.label.clone(),
+        }
+    }
+}
+
+/// Build the payload of a `cue ` chunk with one point per marker
+pub(crate) fn cue_chunk(markers: &[CueMarker]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(4 + markers.len() * 24);
+    data.extend((markers.len() as u32).to_le_bytes());
+
+    for (i, marker) in markers.iter().enumerate() {
+        data.extend((i as u32 + 1).to_le_bytes()); // Cue point ID
+        data.extend(marker.position.to_le_bytes()); // Play order position
+        data.extend(b"data");
+        data.extend(0u32.to_le_bytes()); // Chunk start
+        data.extend(0u32.to_le_bytes()); // Block start
+        data.extend(marker.position.to_le_bytes()); // Sample offset
+    }
+
+    data
+}
+
+/// Build the payload of a `LIST` chunk labelling the points of `cue_chunk`
+pub(crate) fn label_chunk(markers: &[CueMarker]) -> Vec<u8> {
+    let mut data = b"adtl".to_vec();
+
+    for (i, marker) in markers.iter().enumerate() {
+        // Cue point ID followed by a null-terminated label
+        let size = 4 + marker.label.len() + 1;
+        data.extend(b"labl");
+        data.extend((size as u32).to_le_bytes());
+        data.extend((i as u32 + 1).to_le_bytes());
+        data.extend(marker.label.as_bytes());
+        data.push(0);
+        if size % 2 == 1 {
+            data.push(0);
+        }
+    }
+
+    data
+}
+
+/// Build the payload of a `smpl` chunk describing forward loops
+pub(crate) fn smpl_chunk(loops: &[LoopRegion], sample_rate: u32) -> Vec<u8> {
+    let mut data = Vec::with_capacity(36 + loops.len() * 24);
+    data.extend(0u32.to_le_bytes()); // Manufacturer
+    data.extend(0u32.to_le_bytes()); // Product
+    data.extend((1_000_000_000 / sample_rate.max(1)).to_le_bytes()); // Sample period (ns)
+    data.extend(60u32.to_le_bytes()); // MIDI unity note (middle C)
+    data.extend(0u32.to_le_bytes()); // MIDI pitch fraction
+    data.extend(0u32.to_le_bytes()); // SMPTE format
+    data.extend(0u32.to_le_bytes()); // SMPTE offset
+    data.extend((loops.len() as u32).to_le_bytes());
+    data.extend(0u32.to_le_bytes()); // Sampler data size
+
+    for (i, region) in loops.iter().enumerate() {
+        data.extend((i as u32).to_le_bytes()); // Cue point ID
+        data.extend(0u32.to_le_bytes()); // Forward loop
+        data.extend(region.start.to_le_bytes());
+        data.extend(region.end.saturating_sub(1).to_le_bytes()); // Last frame is inclusive
+        data.extend(0u32.to_le_bytes()); // Fraction
+        data.extend(0u32.to_le_bytes()); // Play forever
+    }
+
+    data
+}
+
+/// Insert chunks into a RIFF/WAVE file just before its `data` chunk
+///
+/// Keeping metadata ahead of the samples lets `append_to_wav` extend the file
+/// without overwriting it.
+pub(crate) fn insert_chunks(wav: &[u8], chunks: &[([u8; 4], Vec<u8>)]) -> Result<Vec<u8>> {
+    if wav.len() < 12 || &wav[0..4] != b"RIFF" || &wav[8..12] != b"WAVE" {
+        return Err(JingleError::InvalidParameter("Not a RIFF/WAVE file".to_string()));
+    }
+
+    // Walk the chunk list to find where the data chunk starts
+    let mut offset = 12;
+    loop {
+        if offset + 8 > wav.len() {
+            return Err(JingleError::InvalidParameter("WAV file has no data chunk".to_string()));
+        }
+        if &wav[offset..offset + 4] == b"data" {
+            break;
+        }
+        let size = u32::from_le_bytes([wav[offset + 4], wav[offset + 5], wav[offset + 6], wav[offset + 7]]) as usize;
+        offset += 8 + size + size % 2;
+    }
+
+    let mut output = Vec::with_capacity(wav.len() + chunks.iter().map(|(_, data)| data.len() + 9).sum::<usize>());
+    output.extend_from_slice(&wav[..offset]);
+    for (id, data) in chunks {
+        output.extend_from_slice(id);
+        output.extend((data.len() as u32).to_le_bytes());
+        output.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            output.push(0); // Chunks are word aligned
+        }
+    }
+    output.extend_from_slice(&wav[offset..]);
+
+    let riff_size = (output.len() - 8) as u32;
+    output[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loop_region_rescale() {
+        let region = LoopRegion::from_seconds(0.5, 1.0, 44100);
+        assert_eq!(region, LoopRegion::new(22050, 44100));
+        assert_eq!(region.rescale(44100, 48000), LoopRegion::new(24000, 48000));
+        assert!(region.validate(44100).is_ok());
+        assert!(region.validate(44099).is_err());
+        assert!(LoopRegion::new(10, 10).validate(100).is_err());
+    }
+
+    #[test]
+    fn test_cue_and_label_chunks() {
+        let markers = [CueMarker::new(0, "C5"), CueMarker::new(441, "E5")];
+        let cue = cue_chunk(&markers);
+        assert_eq!(cue.len(), 4 + 2 * 24);
+        assert_eq!(&cue[28..32], &2u32.to_le_bytes());
+        assert_eq!(&cue[48..52], &441u32.to_le_bytes());
+
+        let labels = label_chunk(&markers);
+        assert_eq!(&labels[0..8], b"adtllabl");
+        assert_eq!(&labels[12..19], b"\x01\x00\x00\x00C5\x00");
+        assert_eq!(labels.len() % 2, 0);
+    }
+
+    #[test]
+    fn test_smpl_chunk_layout() {
+        let data = smpl_chunk(&[LoopRegion::new(100, 200)], 44100);
+        assert_eq!(data.len(), 60);
+        assert_eq!(&data[28..32], &1u32.to_le_bytes());
+        assert_eq!(&data[44..48], &100u32.to_le_bytes());
+        assert_eq!(&data[48..52], &199u32.to_le_bytes());
+    }
+}
@@ -0,0 +1,47 @@
+//! Decibel conversions, and the float functions the synthesis core needs when built without std
+
+/// Convert a level in decibels to a linear gain
+pub fn db_to_linear(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
+
+/// Convert a linear gain to decibels (floored at -120 dB for silence)
+pub fn linear_to_db(gain: f32) -> f32 {
+    20.0 * gain.abs().max(1e-6).log10()
+}
+
+/// Float methods std provides, implemented with libm for builds without std
+///
+/// Named like the std methods so the same code compiles either way.
+#[cfg(not(feature = "std"))]
+pub(crate) trait Float {
+    fn sin(self) -> Self;
+    fn powf(self, exponent: Self) -> Self;
+    fn log2(self) -> Self;
+    fn log10(self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn fract(self) -> Self;
+}
+
+#[cfg(not(feature = "std"))]
+macro_rules! impl_float {
+    ($type:ty, $sin:ident, $powf:ident, $log2:ident, $log10:ident, $floor:ident, $ceil:ident, $round:ident, $trunc:ident) => {
+        impl Float for $type {
+            fn sin(self) -> Self { libm::$sin(self) }
+            fn powf(self, exponent: Self) -> Self { libm::$powf(self, exponent) }
+            fn log2(self) -> Self { libm::$log2(self) }
+            fn log10(self) -> Self { libm::$log10(self) }
+            fn floor(self) -> Self { libm::$floor(self) }
+            fn ceil(self) -> Self { libm::$ceil(self) }
+            fn round(self) -> Self { libm::$round(self) }
+            fn fract(self) -> Self { self - libm::$trunc(self) }
+        }
+    };
+}
+
+#[cfg(not(feature = "std"))]
+impl_float!(f32, sinf, powf, log2f, log10f, floorf, ceilf, roundf, truncf);
+#[cfg(not(feature = "std"))]
+impl_float!(f64, sin, pow, log2, log10, floor, ceil, round, trunc);
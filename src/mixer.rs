@@ -0,0 +1,333 @@
+//! Layering several tracks (melody, bass, percussion...) into one stereo mix
+//!
+//! Each track has its own gain, pan, mute/solo switches and effect chain, and
+//! can start at an offset. `Mixer::render` sums the audible tracks and, if the
+//! sum peaks above the headroom ceiling, turns the whole mix down to it.
+//!
+//! ```rust
+//! use jinglebells::{JingleGenerator, Mixer, Track, EffectChain, WaveForm};
+//!
+//! let generator = JingleGenerator::with_seed(1);
+//! let mix = generator.mixer()
+//!     .track(Track::new("lead", generator.generate_tone(880.0, 0.5, WaveForm::Triangle)).with_pan(0.3))
+//!     .track(Track::new("bass", generator.generate_tone(110.0, 0.5, WaveForm::Sine)).with_gain_db(-3.0))
+//!     .track(Track::new("echo", generator.generate_tone(660.0, 0.25, WaveForm::Square))
+//!         .with_offset(0.25)
+//!         .with_effects(EffectChain::new().echo(80.0, 0.3, 0.4)))
+//!     .render();
+//! assert_eq!(mix.len(), 2);
+//! ```
+
+use rodio::source::Source;
+use crate::{
+    SAMPLE_RATE,
+    math::db_to_linear,
+    chain::EffectChain,
+    channels::{deinterleave, mixdown},
+    export::JingleGenerator,
+    resample::resample,
+};
+
+/// Default peak ceiling of a mix in dBFS
+pub const MIXER_HEADROOM_DB: f32 = -1.0;
+
+/// One layer of a mix: mono audio with its own level, placement and effects
+#[derive(Clone, Debug)]
+pub struct Track {
+    name: String,
+    samples: Vec<f32>,
+    sample_rate: u32,
+    offset: f32,
+    gain_db: f32,
+    pan: f32,
+    mute: bool,
+    solo: bool,
+    effects: EffectChain,
+}
+
+impl Track {
+    /// Create a track from mono samples at the library sample rate
+    pub fn new(name: &str, samples: Vec<f32>) -> Self {
+        Self {
+            name: name.to_string(),
+            samples,
+            sample_rate: SAMPLE_RATE,
+            offset: 0.0,
+            gain_db: 0.0,
+            pan: 0.0,
+            mute: false,
+            solo: false,
+            effects: EffectChain::new(),
+        }
+    }
+
+    /// Create a track by rendering a source, mixing multi-channel sources down to mono
+    pub fn from_source<S: Source>(name: &str, source: S) -> Self {
+        let sample_rate = source.sample_rate();
+        let channels = source.channels() as usize;
+        let samples: Vec<f32> = source.collect();
+        let samples = if channels > 1 {
+            let planar = deinterleave(&samples, channels);
+            let planar_refs: Vec<&[f32]> = planar.iter().map(|channel| channel.as_slice()).collect();
+            mixdown(&planar_refs)
+        } else {
+            samples
+        };
+        Self::new(name, samples).with_sample_rate(sample_rate)
+    }
+
+    /// Set the rate (Hz) of the track's samples; the mixer resamples to its own rate
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate.max(1);
+        self
+    }
+
+    /// Start the track this many seconds into the mix
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset.max(0.0);
+        self
+    }
+
+    /// Set the track level in dB
+    pub fn with_gain_db(mut self, gain_db: f32) -> Self {
+        self.gain_db = gain_db;
+        self
+    }
+
+    /// Place the track from -1.0 (left) through 0.0 (center) to 1.0 (right)
+    pub fn with_pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+        self
+    }
+
+    /// Silence the track without removing it
+    pub fn with_mute(mut self, mute: bool) -> Self {
+        self.mute = mute;
+        self
+    }
+
+    /// Solo the track: while any track is soloed, only soloed tracks are heard
+    pub fn with_solo(mut self, solo: bool) -> Self {
+        self.solo = solo;
+        self
+    }
+
+    /// Run the track through an effect chain before it is mixed
+    pub fn with_effects(mut self, effects: EffectChain) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// Name used to find the track with `Mixer::track_mut`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Mute or unmute the track
+    pub fn set_mute(&mut self, mute: bool) {
+        self.mute = mute;
+    }
+
+    /// Solo or unsolo the track
+    pub fn set_solo(&mut self, solo: bool) {
+        self.solo = solo;
+    }
+
+    /// Change the track level in dB
+    pub fn set_gain_db(&mut self, gain_db: f32) {
+        self.gain_db = gain_db;
+    }
+
+    /// Move the track in the stereo field (-1.0 left to 1.0 right)
+    pub fn set_pan(&mut self, pan: f32) {
+        self.pan = pan.clamp(-1.0, 1.0);
+    }
+
+    /// Equal-power left and right gains for the pan position, so a sound keeps its loudness as it moves
+    fn pan_gains(&self) -> (f32, f32) {
+        let angle = (self.pan + 1.0) * std::f32::consts::FRAC_PI_4;
+        (angle.cos(), angle.sin())
+    }
+
+    /// The track's samples at a rate, with effects and gain applied
+    fn processed(&self, sample_rate: u32) -> Vec<f32> {
+        let samples = resample(&self.samples, self.sample_rate, sample_rate);
+        let gain = db_to_linear(self.gain_db);
+        self.effects.process_at(samples, sample_rate).into_iter().map(|sample| sample * gain).collect()
+    }
+}
+
+/// Sums tracks into planar stereo, keeping the peak under a headroom ceiling
+#[derive(Clone, Debug)]
+pub struct Mixer {
+    tracks: Vec<Track>,
+    sample_rate: u32,
+    headroom_db: f32,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mixer {
+    /// Create an empty mixer at the library sample rate
+    pub fn new() -> Self {
+        Self {
+            tracks: Vec::new(),
+            sample_rate: SAMPLE_RATE,
+            headroom_db: MIXER_HEADROOM_DB,
+        }
+    }
+
+    /// Set the rate (Hz) the mix is rendered at
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate.max(1);
+        self
+    }
+
+    /// Set the peak ceiling in dBFS (defaults to `MIXER_HEADROOM_DB`)
+    pub fn with_headroom_db(mut self, headroom_db: f32) -> Self {
+        self.headroom_db = headroom_db.min(0.0);
+        self
+    }
+
+    /// Add a track on top of those already added
+    pub fn track(mut self, track: Track) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    /// Get the tracks in the order they were added
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    /// Find a track by name to change its level, pan, mute or solo
+    pub fn track_mut(&mut self, name: &str) -> Option<&mut Track> {
+        self.tracks.iter_mut().find(|track| track.name == name)
+    }
+
+    /// Get the rate (Hz) the mix is rendered at
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Render the audible tracks as planar `[left, right]` channels
+    ///
+    /// Muted tracks are skipped, and only soloed tracks are heard while any is
+    /// soloed. If the summed peak is above the headroom ceiling the whole mix is
+    /// scaled down to it, so layers never clip and their balance is kept.
+    pub fn render(&self) -> Vec<Vec<f32>> {
+        let any_solo = self.tracks.iter().any(|track| track.solo);
+        let mut left: Vec<f32> = Vec::new();
+        let mut right: Vec<f32> = Vec::new();
+
+        for track in self.tracks.iter().filter(|track| !track.mute && (track.solo || !any_solo)) {
+            let samples = track.processed(self.sample_rate);
+            let start = (track.offset * self.sample_rate as f32).round() as usize;
+            let end = start + samples.len();
+            if left.len() < end {
+                left.resize(end, 0.0);
+                right.resize(end, 0.0);
+            }
+            let (left_gain, right_gain) = track.pan_gains();
+            for (i, sample) in samples.into_iter().enumerate() {
+                left[start + i] += sample * left_gain;
+                right[start + i] += sample * right_gain;
+            }
+        }
+
+        let ceiling = db_to_linear(self.headroom_db);
+        let peak = left.iter().chain(&right).fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        if peak > ceiling {
+            let gain = ceiling / peak;
+            left.iter_mut().chain(right.iter_mut()).for_each(|sample| *sample *= gain);
+        }
+        vec![left, right]
+    }
+
+    /// Render the mix folded down to mono
+    pub fn render_mono(&self) -> Vec<f32> {
+        let mix = self.render();
+        mixdown(&[&mix[0], &mix[1]])
+    }
+}
+
+impl JingleGenerator {
+    /// Create an empty mixer at the generator's sample rate
+    pub fn mixer(&self) -> Mixer {
+        Mixer::new().with_sample_rate(self.get_sample_rate())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn test_pan_and_offset() {
+        let mix = Mixer::new()
+            .track(Track::new("left", vec![0.5; 10]).with_pan(-1.0))
+            .track(Track::new("late", vec![0.5; 10]).with_pan(1.0).with_offset(10.0 / SAMPLE_RATE as f32))
+            .render();
+        assert_eq!(mix[0].len(), 20);
+        assert!((mix[0][0] - 0.5).abs() < 1e-6 && mix[1][0].abs() < 1e-6);
+        assert!(mix[0][15].abs() < 1e-6 && (mix[1][15] - 0.5).abs() < 1e-6);
+
+        // Centered tracks are equal-power
+        let center = Mixer::new().track(Track::new("center", vec![0.5; 4])).render();
+        assert!((center[0][0] - 0.5 * std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+        assert_eq!(center[0], center[1]);
+    }
+
+    #[test]
+    fn test_mute_and_solo() {
+        let mut mixer = Mixer::new()
+            .track(Track::new("a", vec![0.1; 4]).with_pan(-1.0))
+            .track(Track::new("b", vec![0.2; 4]).with_pan(-1.0));
+        assert!((mixer.render()[0][0] - 0.3).abs() < 1e-6);
+
+        mixer.track_mut("a").unwrap().set_mute(true);
+        assert!((mixer.render()[0][0] - 0.2).abs() < 1e-6);
+
+        mixer.track_mut("a").unwrap().set_mute(false);
+        mixer.track_mut("a").unwrap().set_solo(true);
+        assert!((mixer.render()[0][0] - 0.1).abs() < 1e-6);
+        assert!(mixer.track_mut("missing").is_none());
+    }
+
+    #[test]
+    fn test_headroom() {
+        let mix = Mixer::new()
+            .track(Track::new("a", vec![0.8, 0.4]).with_pan(-1.0))
+            .track(Track::new("b", vec![0.8, 0.4]).with_pan(-1.0))
+            .render();
+        assert!((peak(&mix[0]) - db_to_linear(MIXER_HEADROOM_DB)).abs() < 1e-6);
+        // The balance between samples is kept
+        assert!((mix[0][0] / mix[0][1] - 2.0).abs() < 1e-5);
+
+        let quiet = Mixer::new().track(Track::new("a", vec![0.1; 4]).with_gain_db(-6.0).with_pan(-1.0)).render();
+        assert!((quiet[0][0] - 0.1 * db_to_linear(-6.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_source_tracks_and_effects() {
+        let stereo = SamplesBuffer::new(2, SAMPLE_RATE / 2, vec![0.2, 0.4, 0.2, 0.4]);
+        let track = Track::from_source("stereo", stereo);
+        let mix = Mixer::new().track(track.with_pan(-1.0)).render_mono();
+        // Two frames at half rate become about four samples
+        assert!((3..=5).contains(&mix.len()));
+        assert!((mix[0] - 0.15).abs() < 0.05);
+
+        let dry = Mixer::new().track(Track::new("tone", vec![0.5; 100])).render();
+        let wet = Mixer::new().track(Track::new("tone", vec![0.5; 100]).with_effects(EffectChain::new().gain(-6.0))).render();
+        assert!(peak(&wet[0]) < peak(&dry[0]));
+    }
+}
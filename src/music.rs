@@ -1,9 +1,13 @@
 //! Musical theory components for jingle generation
 
+use alloc::{vec, vec::Vec};
+#[cfg(not(feature = "std"))]
+use crate::math::Float;
 use crate::{A4_FREQUENCY};
 
 /// Musical notes with semitone calculations
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum Note {
     C, CSharp, D, DSharp, E, F, FSharp, G, GSharp, A, ASharp, B,
 }
@@ -27,6 +31,24 @@ impl Note {
         }
     }
     
+    /// Get the note name using sharps (e.g. "C#")
+    pub fn name(&self) -> &'static str {
+        match self {
+            Note::C => "C",
+            Note::CSharp => "C#",
+            Note::D => "D",
+            Note::DSharp => "D#",
+            Note::E => "E",
+            Note::F => "F",
+            Note::FSharp => "F#",
+            Note::G => "G",
+            Note::GSharp => "G#",
+            Note::A => "A",
+            Note::ASharp => "A#",
+            Note::B => "B",
+        }
+    }
+    
     /// Calculate the frequency for this note in a given octave
     pub fn frequency(&self, octave: i32) -> f32 {
         let semitone_offset = self.semitone_offset() + (octave - 4) * 12;
@@ -63,6 +85,7 @@ impl Note {
 
 /// Musical scales with interval definitions
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum Scale {
     Major,
     Minor,
@@ -110,6 +133,7 @@ impl Scale {
 
 /// Musical chord with root note and interval structure
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Chord {
     pub root: Note,
     pub intervals: Vec<i32>,
@@ -177,6 +201,7 @@ impl Chord {
 
 /// Common chord progressions
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum ChordProgression {
     Pop,         // I-V-vi-IV
     Jazz,        // ii-V-I
@@ -218,6 +243,7 @@ impl ChordProgression {
 
 /// Melody generation patterns
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum MelodyPattern {
     Ascending,
     Descending,
@@ -228,6 +254,7 @@ pub enum MelodyPattern {
 
 /// A melody composed of notes with durations
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Melody {
     pub notes: Vec<(Note, f32)>, // (note, duration in seconds)
 }
@@ -332,6 +359,7 @@ impl Default for Melody {
 
 /// Rhythm patterns for timing control
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
 pub enum RhythmPattern {
     Steady,      // Equal note durations
     Quick,       // Short, punchy notes
@@ -383,4 +411,17 @@ mod tests {
         assert_eq!(notes[1], Note::E);
         assert_eq!(notes[2], Note::G);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let melody = Melody { notes: vec![(Note::C, 0.2), (Note::FSharp, 0.1)] };
+        let restored: Melody = serde_json::from_str(&serde_json::to_string(&melody).unwrap()).unwrap();
+        assert_eq!(restored.notes, melody.notes);
+
+        let chord: Chord = serde_json::from_str(r#"{"root":"g","intervals":[0,4,7]}"#).unwrap();
+        assert_eq!(chord.notes(), Chord::major(Note::G).notes());
+        let progression: ChordProgression = serde_json::from_str(&serde_json::to_string(&ChordProgression::Jazz).unwrap()).unwrap();
+        assert_eq!(progression, ChordProgression::Jazz);
+    }
 }
\ No newline at end of file
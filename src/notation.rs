@@ -0,0 +1,214 @@
+//! Compact text notation for melodies, e.g. `"C4:e E4:e G4:q C5:h"`
+//!
+//! Each whitespace-separated token is a pitch and an optional duration:
+//!
+//! - Pitch: a letter `A`-`G`, an optional `#` or `b` and an optional octave
+//!   (`C#5`, `Bb3`). The octave carries over from the previous note, starting at 4.
+//!   `R` is a rest.
+//! - Duration after `:`: `w`, `h`, `q`, `e` or `s` for whole to sixteenth notes,
+//!   with a trailing `.` for dotted values, or a number of beats (`1.5`).
+//!   It also carries over from the previous note, starting at a quarter note.
+//!
+//! ```rust
+//! use jinglebells::{JingleGenerator, Phrase, WaveForm};
+//!
+//! let phrase = Phrase::parse("C4:e E4:e G4:q R:e C5:h.").unwrap().with_tempo(140.0);
+//! assert_eq!(phrase.notes().len(), 5);
+//! let samples = JingleGenerator::with_seed(1).render_phrase(&phrase, WaveForm::Triangle).unwrap();
+//! assert!(samples.len() as f32 >= phrase.seconds() * 44100.0);
+//! ```
+
+use crate::{
+    audio::WaveForm,
+    music::Note,
+    export::JingleGenerator,
+    sequence::{Sequence, SequenceLane, SequenceTime, DEFAULT_TEMPO},
+    error::{JingleError, Result},
+};
+
+/// Octave of a note written without one, before any note sets it
+const DEFAULT_OCTAVE: i32 = 4;
+const NOTES: [Note; 12] = [
+    Note::C, Note::CSharp, Note::D, Note::DSharp, Note::E, Note::F,
+    Note::FSharp, Note::G, Note::GSharp, Note::A, Note::ASharp, Note::B,
+];
+
+/// A note or rest of a `Phrase`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhraseNote {
+    /// Note and octave, or `None` for a rest
+    pub pitch: Option<(Note, i32)>,
+    /// Length in beats (quarter notes)
+    pub beats: f32,
+}
+
+/// Melody with its own octave per note, parsed from compact notation
+#[derive(Clone, Debug, PartialEq)]
+pub struct Phrase {
+    notes: Vec<PhraseNote>,
+    tempo: f32,
+}
+
+impl Phrase {
+    /// Parse notes like `"C4:e E4:e G4:q C5:h"` at the default tempo
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut octave = DEFAULT_OCTAVE;
+        let mut beats = 1.0;
+        let mut notes = Vec::new();
+        for token in text.split_whitespace() {
+            let (pitch, duration) = match token.split_once(':') {
+                Some((pitch, duration)) => (pitch, Some(duration)),
+                None => (token, None),
+            };
+            if let Some(duration) = duration {
+                beats = parse_beats(token, duration)?;
+            }
+            let pitch = if pitch.eq_ignore_ascii_case("r") {
+                None
+            } else {
+                let (note, note_octave) = parse_pitch(token, pitch, octave)?;
+                octave = note_octave;
+                Some((note, note_octave))
+            };
+            notes.push(PhraseNote { pitch, beats });
+        }
+        if notes.is_empty() {
+            return Err(JingleError::InvalidParameter("melody has no notes".to_string()));
+        }
+        Ok(Self { notes, tempo: DEFAULT_TEMPO })
+    }
+
+    /// Set the tempo in beats per minute (20 - 400)
+    pub fn with_tempo(mut self, bpm: f32) -> Self {
+        self.tempo = bpm.clamp(20.0, 400.0);
+        self
+    }
+
+    /// Get the notes and rests in order
+    pub fn notes(&self) -> &[PhraseNote] {
+        &self.notes
+    }
+
+    /// Get the tempo in beats per minute
+    pub fn tempo(&self) -> f32 {
+        self.tempo
+    }
+
+    /// Length in seconds, up to the end of the last note (without its release)
+    pub fn seconds(&self) -> f32 {
+        SequenceTime::Beats(self.notes.iter().map(|note| note.beats).sum()).to_seconds(self.tempo)
+    }
+
+    /// One-lane sequence playing the phrase with a waveform
+    pub fn to_sequence(&self, waveform: WaveForm) -> Sequence {
+        let mut lane = SequenceLane::new("melody");
+        let mut start = 0.0;
+        for note in &self.notes {
+            if let Some((pitch, octave)) = note.pitch {
+                lane = lane.note(SequenceTime::Beats(start), pitch.frequency(octave), SequenceTime::Beats(note.beats), waveform);
+            }
+            start += note.beats;
+        }
+        Sequence::new().with_tempo(self.tempo).lane(lane)
+    }
+}
+
+/// Parse a letter, accidental and optional octave, wrapping `Cb` and `B#` into the neighbouring octave
+fn parse_pitch(token: &str, pitch: &str, octave: i32) -> Result<(Note, i32)> {
+    let invalid = || JingleError::InvalidParameter(format!("note `{}`: expected a pitch like C4, F#5 or Bb3", token));
+    let mut chars = pitch.chars();
+    let semitone = match chars.next().map(|c| c.to_ascii_uppercase()) {
+        Some('C') => 0,
+        Some('D') => 2,
+        Some('E') => 4,
+        Some('F') => 5,
+        Some('G') => 7,
+        Some('A') => 9,
+        Some('B') => 11,
+        _ => return Err(invalid()),
+    };
+    let rest = chars.as_str();
+    let (semitone, rest) = match rest.strip_prefix('#') {
+        Some(rest) => (semitone + 1, rest),
+        None => match rest.strip_prefix('b') {
+            Some(rest) => (semitone - 1, rest),
+            None => (semitone, rest),
+        },
+    };
+    let octave = if rest.is_empty() { octave } else { rest.parse().map_err(|_| invalid())? };
+    let index = octave * 12 + semitone;
+    Ok((NOTES[index.rem_euclid(12) as usize], index.div_euclid(12)))
+}
+
+/// Parse a note value letter (optionally dotted) or a number of beats
+fn parse_beats(token: &str, duration: &str) -> Result<f32> {
+    let (value, dotted) = match duration.strip_suffix('.') {
+        Some(value) => (value, true),
+        None => (duration, false),
+    };
+    let beats = match value {
+        "w" => 4.0,
+        "h" => 2.0,
+        "q" => 1.0,
+        "e" => 0.5,
+        "s" => 0.25,
+        _ => match duration.parse::<f32>() {
+            Ok(beats) if beats > 0.0 && beats.is_finite() => return Ok(beats),
+            _ => return Err(JingleError::InvalidParameter(format!(
+                "note `{}`: duration must be w, h, q, e or s (optionally dotted) or a positive number of beats", token
+            ))),
+        },
+    };
+    Ok(if dotted { beats * 1.5 } else { beats })
+}
+
+impl JingleGenerator {
+    /// Render a phrase with a waveform, each note with its own envelope
+    ///
+    /// Fails with `JingleError::Cancelled` if the generator's cancel token is cancelled.
+    pub fn render_phrase(&self, phrase: &Phrase, waveform: WaveForm) -> Result<Vec<f32>> {
+        self.render_sequence(&phrase.to_sequence(waveform))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let phrase = Phrase::parse("C4:e E:e G4:q. R:h Bb3 C#5:1.5 Cb4:s").unwrap();
+        let notes: Vec<(Option<(Note, i32)>, f32)> = phrase.notes().iter().map(|note| (note.pitch, note.beats)).collect();
+        assert_eq!(notes, vec![
+            (Some((Note::C, 4)), 0.5),
+            (Some((Note::E, 4)), 0.5),
+            (Some((Note::G, 4)), 1.5),
+            (None, 2.0),
+            (Some((Note::ASharp, 3)), 2.0),
+            (Some((Note::CSharp, 5)), 1.5),
+            (Some((Note::B, 3)), 0.25),
+        ]);
+        assert_eq!(phrase.tempo(), DEFAULT_TEMPO);
+        assert_eq!(phrase.with_tempo(60.0).seconds(), 8.25);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in ["", "H4:q", "C4:x", "C4:-1", "Cx:q", "C4:"] {
+            assert!(matches!(Phrase::parse(text), Err(JingleError::InvalidParameter(_))), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_render_phrase() {
+        let generator = JingleGenerator::with_seed(2);
+        let phrase = Phrase::parse("A4:q R:q A5:q").unwrap();
+        let samples = generator.render_phrase(&phrase, WaveForm::Sine).unwrap();
+        let rate = generator.get_sample_rate() as usize;
+        assert!(samples.len() >= 3 * rate / 2);
+        // The rest is silent once the first note's release has ended
+        let rest = &samples[rate * 3 / 4..rate * 7 / 8];
+        assert!(rest.iter().all(|sample| sample.abs() < 1e-6));
+        assert!(samples[rate * 5 / 4].abs() > 0.0 || samples[rate * 5 / 4 + 10].abs() > 0.0);
+    }
+}
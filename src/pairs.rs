@@ -0,0 +1,145 @@
+//! Complementary jingle pairs where the second sound answers the first
+
+use crate::{
+    audio::WaveForm,
+    music::{Note, Scale, Melody},
+    manifest::RenderChoices,
+    export::JingleGenerator,
+};
+
+/// Actions that come in complementary pairs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PairKind {
+    /// Short-long leap out, answered by a leap back
+    SendReceive,
+    /// Even three-note arpeggio, answered by its mirror image
+    OpenClose,
+    /// Repeated note then a climb, answered by the same figure falling
+    ConnectDisconnect,
+}
+
+impl PairKind {
+    /// Get all pair kinds
+    pub fn all() -> Vec<PairKind> {
+        vec![PairKind::SendReceive, PairKind::OpenClose, PairKind::ConnectDisconnect]
+    }
+
+    /// Get the name of this pair as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            PairKind::SendReceive => "send-receive",
+            PairKind::OpenClose => "open-close",
+            PairKind::ConnectDisconnect => "connect-disconnect",
+        }
+    }
+
+    /// Note lengths of the call as fractions of its duration
+    fn rhythm(&self) -> &'static [f32] {
+        match self {
+            PairKind::SendReceive => &[0.35, 0.65],
+            PairKind::OpenClose => &[0.33, 0.33, 0.34],
+            PairKind::ConnectDisconnect => &[0.15, 0.15, 0.3, 0.4],
+        }
+    }
+}
+
+/// Two related jingles rendered by `JingleGenerator::create_jingle_pair`
+#[derive(Clone, Debug)]
+pub struct JinglePair {
+    pub kind: PairKind,
+    pub root_note: Note,
+    pub scale: Scale,
+    /// Melody of the first sound, rising from the tonic to the dominant
+    pub call: Melody,
+    /// Melody of the second sound, the call inverted so it falls from the dominant back to the tonic
+    pub answer: Melody,
+    pub first: Vec<f32>,
+    pub second: Vec<f32>,
+}
+
+impl JingleGenerator {
+    /// Create two jingles for complementary actions, the second a musical answer to the first
+    ///
+    /// The call climbs from the tonic to the dominant and the answer is its
+    /// inversion: the same steps taken downwards from the dominant, so it
+    /// resolves on the tonic. Both share key, scale, waveform and rhythm.
+    /// - duration: length of each jingle in seconds (default 0.4)
+    pub fn create_jingle_pair(&mut self, kind: PairKind, waveform: WaveForm, duration: Option<f32>) -> JinglePair {
+        let duration = duration.unwrap_or(0.4).max(0.05) * self.random_variation();
+        let pitch_offset = self.random_pitch_offset();
+        let root_frequency = Note::C.frequency(5) * (2.0_f32).powf(pitch_offset / 12.0);
+        let root_note = Note::from_frequency(root_frequency);
+        let scale = if self.random_bool(0.6) { Scale::Major } else { Scale::Pentatonic };
+
+        let intervals = scale.intervals();
+        let dominant = intervals.iter().position(|&interval| interval == 7).unwrap_or(0) as i32;
+        let passing = self.random_range(1..dominant.max(2));
+        let call: Vec<i32> = match kind {
+            PairKind::SendReceive => vec![0, dominant],
+            PairKind::OpenClose => vec![0, passing, dominant],
+            PairKind::ConnectDisconnect => vec![0, 0, passing, dominant],
+        };
+        let answer: Vec<i32> = call.iter().map(|&degree| dominant - degree).collect();
+
+        // Scale degrees below zero or past the top of the scale continue into the next octave
+        let frequency = |degree: i32| {
+            let length = intervals.len() as i32;
+            let semitones = intervals[degree.rem_euclid(length) as usize] + 12 * degree.div_euclid(length);
+            root_frequency * (2.0_f32).powf(semitones as f32 / 12.0)
+        };
+        let render = |degrees: &[i32]| {
+            let mut melody = Melody::new();
+            let mut samples = Vec::new();
+            for (&degree, &share) in degrees.iter().zip(kind.rhythm()) {
+                melody.notes.push((Note::from_frequency(frequency(degree)), duration * share));
+                samples.extend(self.generate_tone(frequency(degree), duration * share, waveform));
+            }
+            (melody, samples)
+        };
+        let (call, first) = render(&call);
+        let (answer, second) = render(&answer);
+
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            waveform: Some(waveform),
+            note_count: Some(call.notes.len() + answer.notes.len()),
+            ..RenderChoices::default()
+        };
+
+        JinglePair { kind, root_note, scale, call, answer, first, second }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer_inverts_call() {
+        let mut generator = JingleGenerator::with_seed(6);
+        for kind in PairKind::all() {
+            let pair = generator.create_jingle_pair(kind, WaveForm::Triangle, None);
+            let dominant = Note::from_frequency(pair.root_note.frequency(5) * 1.5);
+            let first_note = |melody: &Melody| melody.notes[0].0;
+            let last_note = |melody: &Melody| melody.notes[melody.notes.len() - 1].0;
+
+            assert_eq!(first_note(&pair.call), pair.root_note, "{}", kind.name());
+            assert_eq!(last_note(&pair.call), dominant, "{}", kind.name());
+            assert_eq!(first_note(&pair.answer), dominant, "{}", kind.name());
+            assert_eq!(last_note(&pair.answer), pair.root_note, "{}", kind.name());
+            assert_eq!(pair.call.notes.len(), kind.rhythm().len());
+            assert_eq!(pair.first.len(), pair.second.len());
+            assert!(!pair.first.is_empty() && pair.first != pair.second);
+        }
+    }
+
+    #[test]
+    fn test_pair_is_reproducible() {
+        let first = JingleGenerator::with_seed(9).create_jingle_pair(PairKind::OpenClose, WaveForm::Sine, Some(0.3));
+        let second = JingleGenerator::with_seed(9).create_jingle_pair(PairKind::OpenClose, WaveForm::Sine, Some(0.3));
+        assert_eq!(first.first, second.first);
+        assert_eq!(first.second, second.second);
+        assert_eq!(first.scale, second.scale);
+    }
+}
@@ -1,28 +1,386 @@
 //! Pre-built jingle presets for common notification types
 
 use crate::{
-    audio::WaveForm,
+    audio::{WaveForm, ADSR, OSCILLATOR_HEADROOM},
     music::{Note, Scale, Melody, MelodyPattern, Chord, ChordProgression},
-    export::JingleGenerator,
-    SAMPLE_RATE,
+    export::{JingleGenerator, MelodyRenderOptions},
+    manifest::RenderChoices,
+    markers::LoopRegion,
+    spec::JingleSpec,
+    effects::{apply_gain_db, fade_out, normalize_samples},
+    filter::{StateVariableFilter, SvfMode, CutoffSweep},
+    chiptune::{self, NoiseChannel, CHIPTUNE_VOICES},
+    error::{JingleError, Result},
+    constraints::Constraints,
+    progress::ProgressPhase,
+    chain::EffectChain,
+    version::GenerationVersion,
 };
 
+/// Retro game sound effects rendered by `create_game_sfx`
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum GameSfx {
+    /// Two quick rising blips
+    Coin,
+    /// Fast arpeggio of upward sweeps
+    PowerUp,
+    /// Short upward pitch sweep
+    Jump,
+    /// Fast downward pitch sweep
+    Laser,
+    /// Low-passed noise burst with a long decay
+    Explosion,
+}
+
+impl GameSfx {
+    /// Get all available game sound effects
+    pub fn all() -> Vec<GameSfx> {
+        vec![GameSfx::Coin, GameSfx::PowerUp, GameSfx::Jump, GameSfx::Laser, GameSfx::Explosion]
+    }
+
+    /// Get the name of this sound effect as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameSfx::Coin => "coin",
+            GameSfx::PowerUp => "power-up",
+            GameSfx::Jump => "jump",
+            GameSfx::Laser => "laser",
+            GameSfx::Explosion => "explosion",
+        }
+    }
+
+    /// Describe the sound effect in a few words
+    pub fn description(&self) -> &'static str {
+        match self {
+            GameSfx::Coin => "Two quick rising blips",
+            GameSfx::PowerUp => "Fast arpeggio of upward sweeps",
+            GameSfx::Jump => "Short upward pitch sweep",
+            GameSfx::Laser => "Fast downward pitch sweep",
+            GameSfx::Explosion => "Low-passed noise burst with a long decay",
+        }
+    }
+}
+
+/// Short interface sounds rendered by `create_ui_sound`
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum UiSound {
+    /// Tiny high-passed noise tick
+    Click,
+    /// Soft sine blip with a quick downward pitch drop
+    Tap,
+    /// Two rising blips
+    Toggle,
+    /// Band-passed noise whoosh with a rising sweep
+    Swipe,
+}
+
+impl UiSound {
+    /// Get all available interface sounds
+    pub fn all() -> Vec<UiSound> {
+        vec![UiSound::Click, UiSound::Tap, UiSound::Toggle, UiSound::Swipe]
+    }
+
+    /// Get the name of this interface sound as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            UiSound::Click => "click",
+            UiSound::Tap => "tap",
+            UiSound::Toggle => "toggle",
+            UiSound::Swipe => "swipe",
+        }
+    }
+
+    /// Describe the interface sound in a few words
+    pub fn description(&self) -> &'static str {
+        match self {
+            UiSound::Click => "Tiny high-passed noise tick",
+            UiSound::Tap => "Soft sine blip with a quick downward pitch drop",
+            UiSound::Toggle => "Two rising blips",
+            UiSound::Swipe => "Band-passed noise whoosh with a rising sweep",
+        }
+    }
+}
+
+/// Longest duration of an interface sound in seconds
+pub const UI_SOUND_MAX_DURATION: f32 = 0.12;
+
+/// Futuristic interface textures rendered by `create_scifi_sound`
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum SciFiSound {
+    /// Slow rising sweep blooming into a shimmering tail
+    Sweep,
+    /// Fast falling ring-modulated sweep
+    Zap,
+    /// Sweeps bouncing up and down like a console scanning
+    Scan,
+    /// Soft swell of an open fifth that dissolves into shimmer
+    Shimmer,
+}
+
+impl SciFiSound {
+    /// Get all available sci-fi sounds
+    pub fn all() -> Vec<SciFiSound> {
+        vec![SciFiSound::Sweep, SciFiSound::Zap, SciFiSound::Scan, SciFiSound::Shimmer]
+    }
+
+    /// Get the name of this sci-fi sound as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            SciFiSound::Sweep => "scifi-sweep",
+            SciFiSound::Zap => "scifi-zap",
+            SciFiSound::Scan => "scifi-scan",
+            SciFiSound::Shimmer => "scifi-shimmer",
+        }
+    }
+
+    /// Describe the sci-fi sound in a few words
+    pub fn description(&self) -> &'static str {
+        match self {
+            SciFiSound::Sweep => "Slow rising sweep blooming into a shimmering tail",
+            SciFiSound::Zap => "Fast falling ring-modulated sweep",
+            SciFiSound::Scan => "Sweeps bouncing up and down like a console scanning",
+            SciFiSound::Shimmer => "Soft swell of an open fifth that dissolves into shimmer",
+        }
+    }
+}
+
+/// Urgency levels for `create_warning_jingle`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Get all severities from least to most urgent
+    pub fn all() -> Vec<Severity> {
+        vec![Severity::Low, Severity::Medium, Severity::High, Severity::Critical]
+    }
+
+    /// Get the name of this severity as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    /// Semitones stacked on the root, from consonant to dissonant
+    fn interval(&self) -> i32 {
+        match self {
+            Severity::Low => 7,      // Perfect fifth
+            Severity::Medium => 3,   // Minor third
+            Severity::High => 6,     // Tritone
+            Severity::Critical => 1, // Minor second
+        }
+    }
+
+    /// Default waveform, from smooth to harsh
+    fn waveform(&self) -> WaveForm {
+        match self {
+            Severity::Low => WaveForm::Sine,
+            Severity::Medium => WaveForm::Triangle,
+            Severity::High => WaveForm::Square,
+            Severity::Critical => WaveForm::Sawtooth,
+        }
+    }
+
+    /// Number of beeps and the gap between them as a fraction of a beep
+    fn repetition(&self) -> (usize, f32) {
+        match self {
+            Severity::Low => (1, 1.0),
+            Severity::Medium => (2, 0.6),
+            Severity::High => (3, 0.35),
+            Severity::Critical => (5, 0.2),
+        }
+    }
+}
+
+/// Characteristic sound design of a preset, applied to other melodies by `render_as`
+#[derive(Clone, Debug)]
+pub struct PresetStyle {
+    pub waveform: WaveForm,
+    pub adsr: ADSR,
+    /// Octave melodies are played in
+    pub octave: i32,
+    pub effects: EffectChain,
+}
+
+/// Melodic shape of a preset, interpolated by `morph_specs`
+#[derive(Clone, Copy, Debug)]
+struct Contour {
+    /// Notes per second at the default duration
+    density: f32,
+    /// Overall direction from -1.0 (falling) to 1.0 (rising)
+    direction: f32,
+    /// Chance of repeating the previous pitch instead of stepping
+    repetition: f32,
+    /// Fraction of each note that sounds
+    articulation: f32,
+}
+
+/// Partials of a struck bell as (frequency ratio, amplitude, decay time in seconds at the fundamental)
+const BELL_PARTIALS: [(f32, f32, f32); 5] = [
+    (1.0, 1.0, 1.0),
+    (2.0, 0.6, 0.7),
+    (2.76, 0.4, 0.45),
+    (5.4, 0.25, 0.25),
+    (8.93, 0.15, 0.15),
+];
+
+impl JingleGenerator {
+    /// Pulse wave sweeping exponentially between two frequencies, shaped by an ADSR envelope
+    /// - duty: fraction of each cycle spent high (0.5 is a square wave)
+    /// - chip: step the slide at the chiptune frame rate and quantize the output
+    fn pulse_sweep(&self, start_frequency: f32, end_frequency: f32, duty: f32, duration: f32, adsr: ADSR, chip: bool) -> Vec<f32> {
+        let (start_frequency, end_frequency) = (self.tuned(start_frequency), self.tuned(end_frequency));
+        let length = (duration * self.get_sample_rate() as f32) as usize;
+        let gain = OSCILLATOR_HEADROOM;
+        let mut phase = 0.0_f32;
+    
+        let mut samples: Vec<f32> = (0..length).map(|i| {
+            let time = i as f32 / self.get_sample_rate() as f32;
+            let sweep_time = if chip { chiptune::stepped_time(time) } else { time };
+            // Accumulate phase so the sweep stays continuous
+            let frequency = start_frequency * (end_frequency / start_frequency).powf(sweep_time / duration);
+            phase = (phase + frequency / self.get_sample_rate() as f32).fract();
+            let value = if phase < duty { 1.0 } else { -1.0 };
+            value * adsr.level(time, duration) * gain
+        }).collect();
+        if chip {
+            chiptune::quantize(&mut samples);
+        }
+        self.master(samples)
+    }
+    
+    /// Additive bell tone: inharmonic partials that decay exponentially, the higher ones faster
+    fn bell_tone(&self, frequency: f32, duration: f32) -> Vec<f32> {
+        let frequency = self.tuned(frequency);
+        let length = (duration * self.get_sample_rate() as f32) as usize;
+        let total_amplitude: f32 = BELL_PARTIALS.iter().map(|&(_, amplitude, _)| amplitude).sum();
+        let gain = OSCILLATOR_HEADROOM / total_amplitude;
+    
+        let samples: Vec<f32> = (0..length).map(|i| {
+            let time = i as f32 / self.get_sample_rate() as f32;
+            let strike = (time / 0.002).min(1.0);
+            let sum: f32 = BELL_PARTIALS.iter()
+                .map(|&(ratio, amplitude, decay)| {
+                    let envelope = (-time / (decay * duration)).exp();
+                    (2.0 * std::f32::consts::PI * frequency * ratio * time).sin() * amplitude * envelope
+                })
+                .sum();
+            sum * strike * gain
+        }).collect();
+        self.master(samples)
+    }
+    
+    /// Sine with the first `harmonics` harmonics at 1/n amplitude, shaped by an ADSR envelope
+    fn harmonic_tone(&self, frequency: f32, harmonics: usize, duration: f32, adsr: ADSR) -> Vec<f32> {
+        let frequency = self.tuned(frequency);
+        let length = (duration * self.get_sample_rate() as f32) as usize;
+        // Harmonics above Nyquist would alias back down
+        let harmonics = (1..=harmonics.max(1)).take_while(|&n| frequency * n as f32 * 2.0 < self.get_sample_rate() as f32).count().max(1);
+        let total_amplitude: f32 = (1..=harmonics).map(|n| 1.0 / n as f32).sum();
+        let gain = OSCILLATOR_HEADROOM / total_amplitude;
+    
+        let samples: Vec<f32> = (0..length).map(|i| {
+            let time = i as f32 / self.get_sample_rate() as f32;
+            let sum: f32 = (1..=harmonics)
+                .map(|n| (2.0 * std::f32::consts::PI * frequency * n as f32 * time).sin() / n as f32)
+                .sum();
+            sum * adsr.level(time, duration) * gain
+        }).collect();
+        self.master(samples)
+    }
+    
+    /// Celesta-like FM tone: a harmonic modulator at four times the pitch whose
+    /// index falls quickly, so a bright metallic strike settles into a pure decay
+    fn celesta_tone(&self, frequency: f32, duration: f32) -> Vec<f32> {
+        let frequency = self.tuned(frequency);
+        let length = (duration * self.get_sample_rate() as f32) as usize;
+        let gain = OSCILLATOR_HEADROOM;
+    
+        let samples: Vec<f32> = (0..length).map(|i| {
+            let time = i as f32 / self.get_sample_rate() as f32;
+            let index = 2.5 * (-time / 0.05).exp();
+            let modulator = (2.0 * std::f32::consts::PI * frequency * 4.0 * time).sin() * index;
+            let envelope = (time / 0.002).min(1.0) * (-time / (duration * 0.35)).exp();
+            (2.0 * std::f32::consts::PI * frequency * time + modulator).sin() * envelope * gain
+        }).collect();
+        self.master(samples)
+    }
+    
+    /// Sine wave sweeping exponentially between two frequencies, shaped by an ADSR envelope
+    /// - chip: play a triangle instead, stepping the slide at the chiptune frame rate and quantizing the output
+    fn sine_sweep(&self, start_frequency: f32, end_frequency: f32, duration: f32, adsr: ADSR, chip: bool) -> Vec<f32> {
+        let (start_frequency, end_frequency) = (self.tuned(start_frequency), self.tuned(end_frequency));
+        let length = (duration * self.get_sample_rate() as f32) as usize;
+        let gain = OSCILLATOR_HEADROOM;
+        let mut phase = 0.0_f32;
+    
+        let mut samples: Vec<f32> = (0..length).map(|i| {
+            let time = i as f32 / self.get_sample_rate() as f32;
+            let sweep_time = if chip { chiptune::stepped_time(time) } else { time };
+            let frequency = start_frequency * (end_frequency / start_frequency).powf(sweep_time / duration);
+            phase = (phase + frequency / self.get_sample_rate() as f32).fract();
+            let value = if chip {
+                1.0 - 4.0 * (phase - 0.5).abs()
+            } else {
+                (2.0 * std::f32::consts::PI * phase).sin()
+            };
+            value * adsr.level(time, duration) * gain
+        }).collect();
+        if chip {
+            chiptune::quantize(&mut samples);
+        }
+        self.master(samples)
+    }
+}
+
+/// Flat envelope with just enough ramp at each end to avoid clicks
+fn gate_envelope() -> ADSR {
+    ADSR { attack: 0.002, decay: 0.0, sustain: 1.0, release: 0.002 }
+}
+
+/// Percussive envelope for short effects: near-instant attack, then a decay to silence
+fn blip_envelope(duration: f32) -> ADSR {
+    ADSR {
+        attack: 0.002,
+        decay: duration * 0.3,
+        sustain: 0.6,
+        release: duration * 0.6,
+    }
+}
+
 impl JingleGenerator {
     /// Create a pleasant notification sound with varied scales and patterns
     pub fn create_notification_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
-        let base_duration = duration.unwrap_or(0.15) * self.random_variation();
+        JinglePreset::Notification.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the notification preset from a spec
+    fn notification_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let base_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
         let note_count = self.random_note_count_variation(4);
         let note_duration = (base_duration / note_count as f32) * self.random_rhythm_variation();
         
-        let base_freq = base_frequency.unwrap_or(Note::C.frequency(5));
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
         let pitch_offset = self.random_pitch_offset();
         let adjusted_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
         let root_note = Note::from_frequency(adjusted_freq);
         
         // Randomly choose between pleasant scales and patterns
-        let scale = if self.random_bool(0.7) { Scale::Pentatonic } else { Scale::Major };
-        let pattern = if self.random_bool(0.6) { MelodyPattern::Arpeggio } else { self.random_melody_pattern() };
-        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { waveform };
+        let scale = spec.scale.unwrap_or_else(|| if self.random_bool(0.7) { Scale::Pentatonic } else { Scale::Major });
+        let pattern = spec.pattern.unwrap_or_else(|| if self.random_bool(0.6) { MelodyPattern::Arpeggio } else { self.random_melody_pattern() });
+        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { spec.waveform };
         
         let melody = Melody::from_scale(
             scale, 
@@ -32,24 +390,40 @@ impl JingleGenerator {
             note_duration
         );
         
-        self.generate_melody_samples(&melody, 5, used_waveform)
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            pattern: Some(pattern),
+            waveform: Some(used_waveform),
+            note_count: Some(melody.notes.len()),
+            ..RenderChoices::default()
+        };
+        
+        self.intensity_level(self.generate_melody_samples(&melody, 5, used_waveform))
     }
     
     /// Create an uplifting success sound with varied upward patterns
     pub fn create_success_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
-        let base_duration = duration.unwrap_or(0.8) * self.random_variation();
+        JinglePreset::Success.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the success preset from a spec
+    fn success_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let base_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
         let note_count = self.random_note_count_variation(5);
         let note_duration = (base_duration / note_count as f32) * self.random_rhythm_variation();
         
-        let base_freq = base_frequency.unwrap_or(Note::C.frequency(4));
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
         let pitch_offset = self.random_pitch_offset();
         let adjusted_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
         let root_note = Note::from_frequency(adjusted_freq);
         
         // Prefer uplifting scales and ascending patterns
-        let scale = if self.random_bool(0.6) { Scale::Major } else { Scale::Pentatonic };
-        let pattern = if self.random_bool(0.7) { MelodyPattern::Ascending } else { MelodyPattern::Arpeggio };
-        let used_waveform = if self.random_bool(0.4) { self.random_waveform() } else { waveform };
+        let scale = spec.scale.unwrap_or_else(|| if self.random_bool(0.6) { Scale::Major } else { Scale::Pentatonic });
+        let pattern = spec.pattern.unwrap_or_else(|| if self.random_bool(0.7) { MelodyPattern::Ascending } else { MelodyPattern::Arpeggio });
+        let used_waveform = if self.random_bool(0.4) { self.random_waveform() } else { spec.waveform };
         
         let melody = Melody::from_scale(
             scale,
@@ -59,20 +433,36 @@ impl JingleGenerator {
             note_duration
         );
         
-        self.generate_melody_samples(&melody, 4, used_waveform)
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            pattern: Some(pattern),
+            waveform: Some(used_waveform),
+            note_count: Some(melody.notes.len()),
+            ..RenderChoices::default()
+        };
+        
+        self.intensity_level(self.generate_melody_samples(&melody, 4, used_waveform))
     }
     
     /// Create an attention-grabbing alert with varied patterns and intensity
     pub fn create_alert_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Alert.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the alert preset from a spec
+    fn alert_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
         let mut samples = Vec::new();
         
         // Calculate beep duration based on total duration
-        let total_duration = duration.unwrap_or(0.25) * self.random_variation();
+        let total_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
         let beep_count = self.random_note_count_variation(2).max(2).min(4);
         let beep_duration = (total_duration / (beep_count as f32 * 1.5)) * self.random_rhythm_variation();
         let gap_duration = beep_duration * self.random_float_range(0.3..=0.8);
         
-        let base_freq = base_frequency.unwrap_or(Note::G.frequency(6));
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
         let pitch_offset = self.random_pitch_offset();
         let frequency = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
         
@@ -83,7 +473,16 @@ impl JingleGenerator {
                 _ => WaveForm::Sawtooth,
             }
         } else { 
-            waveform 
+            spec.waveform 
+        };
+        
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(Note::from_frequency(frequency)),
+            waveform: Some(used_waveform),
+            note_count: Some(beep_count),
+            ..RenderChoices::default()
         };
         
         // Variable number of beeps with slight frequency variations
@@ -95,37 +494,42 @@ impl JingleGenerator {
             
             // Add gap between beeps (except after the last one)
             if i < beep_count - 1 {
-                let silence_samples = (SAMPLE_RATE as f32 * gap_duration) as usize;
+                let silence_samples = (self.get_sample_rate() as f32 * gap_duration) as usize;
                 samples.extend(vec![0.0; silence_samples]);
             }
         }
         
-        samples
+        self.intensity_level(samples)
     }
     
     /// Create an error/warning sound with varied minor patterns and dissonance
     pub fn create_error_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
-        let base_duration = duration.unwrap_or(1.25) * self.random_variation();
+        JinglePreset::Error.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the error preset from a spec
+    fn error_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let base_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
         let note_count = self.random_note_count_variation(5);
         let note_duration = (base_duration / note_count as f32) * self.random_rhythm_variation();
         
-        let base_freq = base_frequency.unwrap_or(Note::D.frequency(5));
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
         let pitch_offset = self.random_pitch_offset();
         let adjusted_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
         let root_note = Note::from_frequency(adjusted_freq);
         
         // Prefer more dissonant/unsettling combinations
-        let scale = if self.random_bool(0.7) { Scale::Minor } else { Scale::Chromatic };
-        let pattern = if self.random_bool(0.5) { MelodyPattern::Descending } else { MelodyPattern::Random };
+        let scale = spec.scale.unwrap_or_else(|| if self.random_bool(0.7) { Scale::Minor } else { Scale::Chromatic });
+        let pattern = spec.pattern.unwrap_or_else(|| if self.random_bool(0.5) { MelodyPattern::Descending } else { MelodyPattern::Random });
         let used_waveform = if self.random_bool(0.4) { 
             // Prefer harsher waveforms for errors
             match self.random_range(0..3) {
                 0 => WaveForm::Sawtooth,
                 1 => WaveForm::Square,
-                _ => waveform,
+                _ => spec.waveform,
             }
         } else { 
-            waveform 
+            spec.waveform 
         };
         
         let melody = Melody::from_scale(
@@ -136,16 +540,32 @@ impl JingleGenerator {
             note_duration
         );
         
-        self.generate_melody_samples(&melody, 5, used_waveform)
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            pattern: Some(pattern),
+            waveform: Some(used_waveform),
+            note_count: Some(melody.notes.len()),
+            ..RenderChoices::default()
+        };
+        
+        self.intensity_level(self.generate_melody_samples(&melody, 5, used_waveform))
     }
     
     /// Create a startup chime with varied chord progressions and patterns
     pub fn create_startup_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
-        let base_duration = duration.unwrap_or(0.6) * self.random_variation();
+        JinglePreset::Startup.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the startup preset from a spec
+    fn startup_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let base_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
         let chord_count = self.random_note_count_variation(2).max(2).min(4);
         let chord_duration = (base_duration / chord_count as f32) * self.random_rhythm_variation();
         
-        let base_freq = base_frequency.unwrap_or(Note::C.frequency(4));
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
         let pitch_offset = self.random_pitch_offset();
         let adjusted_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
         let root_note = Note::from_frequency(adjusted_freq);
@@ -159,8 +579,19 @@ impl JingleGenerator {
         let chord_progression = progression.get_chords(root_note);
         let mut chord_samples = Vec::new();
         
-        let pattern = if self.random_bool(0.7) { MelodyPattern::Arpeggio } else { MelodyPattern::Ascending };
-        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { waveform };
+        let pattern = spec.pattern.unwrap_or_else(|| if self.random_bool(0.7) { MelodyPattern::Arpeggio } else { MelodyPattern::Ascending });
+        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { spec.waveform };
+        
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            pattern: Some(pattern),
+            progression: Some(progression),
+            waveform: Some(used_waveform),
+            note_count: Some(chord_count),
+            ..RenderChoices::default()
+        };
         
         // Play a variable number of chords
         for chord in chord_progression.iter().take(chord_count) {
@@ -169,23 +600,28 @@ impl JingleGenerator {
             chord_samples.extend(samples);
         }
         
-        chord_samples
+        self.intensity_level(chord_samples)
     }
     
     /// Create a shutdown sound with varied gentle descending patterns
     pub fn create_shutdown_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
-        let base_duration = duration.unwrap_or(1.6) * self.random_variation();
+        JinglePreset::Shutdown.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the shutdown preset from a spec
+    fn shutdown_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let base_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
         let note_count = self.random_note_count_variation(4);
         let note_duration = (base_duration / note_count as f32) * self.random_rhythm_variation();
         
-        let base_freq = base_frequency.unwrap_or(Note::G.frequency(4));
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
         let pitch_offset = self.random_pitch_offset();
         let adjusted_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
         let root_note = Note::from_frequency(adjusted_freq);
         
         // Prefer gentle, calming scales and patterns
-        let scale = if self.random_bool(0.6) { Scale::Pentatonic } else { Scale::Major };
-        let pattern = if self.random_bool(0.8) { MelodyPattern::Descending } else { MelodyPattern::ScaleRun };
+        let scale = spec.scale.unwrap_or_else(|| if self.random_bool(0.6) { Scale::Pentatonic } else { Scale::Major });
+        let pattern = spec.pattern.unwrap_or_else(|| if self.random_bool(0.8) { MelodyPattern::Descending } else { MelodyPattern::ScaleRun });
         let used_waveform = if self.random_bool(0.2) { 
             // Prefer softer waveforms for shutdown
             match self.random_range(0..2) {
@@ -193,7 +629,7 @@ impl JingleGenerator {
                 _ => WaveForm::Triangle,
             }
         } else { 
-            waveform 
+            spec.waveform 
         };
         
         let melody = Melody::from_scale(
@@ -204,22 +640,47 @@ impl JingleGenerator {
             note_duration
         );
         
-        self.generate_melody_samples(&melody, 4, used_waveform)
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            pattern: Some(pattern),
+            waveform: Some(used_waveform),
+            note_count: Some(melody.notes.len()),
+            ..RenderChoices::default()
+        };
+        
+        self.intensity_level(self.generate_melody_samples(&melody, 4, used_waveform))
     }
     
     /// Create a message received sound with varied short pleasant patterns
     pub fn create_message_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Message.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the message preset from a spec
+    fn message_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
         let mut samples = Vec::new();
         
-        let total_duration = duration.unwrap_or(0.25) * self.random_variation();
+        let total_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
         let note_count = self.random_note_count_variation(2).max(2).min(3);
         
-        let base_freq = base_frequency.unwrap_or(Note::C.frequency(5));
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
         let pitch_offset = self.random_pitch_offset();
         let adjusted_base_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
         let root_note = Note::from_frequency(adjusted_base_freq);
         
-        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { waveform };
+        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { spec.waveform };
+        
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            waveform: Some(used_waveform),
+            note_count: Some(note_count),
+            ..RenderChoices::default()
+        };
         
         if note_count == 2 {
             // Traditional two-note pattern with variations
@@ -245,27 +706,43 @@ impl JingleGenerator {
             // Three-note arpeggio pattern
             let note_duration = total_duration / 3.0;
             let chord = Chord::major(root_note);
-            let melody = Melody::from_chord(chord, 5, MelodyPattern::Arpeggio, note_duration);
+            let pattern = spec.pattern.unwrap_or(MelodyPattern::Arpeggio);
+            let melody = Melody::from_chord(chord, 5, pattern, note_duration);
+            self.last_choices.pattern = Some(pattern);
             samples = self.generate_melody_samples(&melody, 5, used_waveform);
         }
         
-        samples
+        self.intensity_level(samples)
     }
     
     /// Create a completion/done sound with varied satisfying resolutions
     pub fn create_completion_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Completion.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the completion preset from a spec
+    fn completion_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
         let mut samples = Vec::new();
         
-        let base_duration = duration.unwrap_or(0.5) * self.random_variation();
+        let base_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
         let chord_count = self.random_note_count_variation(2).max(2).min(3);
         let chord_duration = (base_duration / chord_count as f32) * self.random_rhythm_variation();
         
-        let base_freq = base_frequency.unwrap_or(Note::C.frequency(4));
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
         let pitch_offset = self.random_pitch_offset();
         let adjusted_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
         let root_note = Note::from_frequency(adjusted_freq);
         
-        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { waveform };
+        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { spec.waveform };
+        
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            waveform: Some(used_waveform),
+            note_count: Some(chord_count),
+            ..RenderChoices::default()
+        };
         
         if chord_count == 2 {
             // Traditional V-I cadence with variations
@@ -278,7 +755,8 @@ impl JingleGenerator {
             };
             let final_chord = Chord::major(root_note);
             
-            let pattern = if self.random_bool(0.8) { MelodyPattern::Arpeggio } else { MelodyPattern::Ascending };
+            let pattern = spec.pattern.unwrap_or_else(|| if self.random_bool(0.8) { MelodyPattern::Arpeggio } else { MelodyPattern::Ascending });
+            self.last_choices.pattern = Some(pattern);
             
             let first_melody = Melody::from_chord(first_chord, 4, pattern, chord_duration * 0.8);
             let final_melody = Melody::from_chord(final_chord, 4, pattern, chord_duration * 1.2);
@@ -290,7 +768,9 @@ impl JingleGenerator {
             let progression = if self.random_bool(0.5) { ChordProgression::Jazz } else { ChordProgression::Classical };
             let chords = progression.get_chords(root_note);
             
-            let pattern = if self.random_bool(0.7) { MelodyPattern::Arpeggio } else { MelodyPattern::Ascending };
+            let pattern = spec.pattern.unwrap_or_else(|| if self.random_bool(0.7) { MelodyPattern::Arpeggio } else { MelodyPattern::Ascending });
+            self.last_choices.pattern = Some(pattern);
+            self.last_choices.progression = Some(progression);
             
             for chord in chords.iter().take(3) {
                 let melody = Melody::from_chord(chord.clone(), 4, pattern, chord_duration);
@@ -299,97 +779,1716 @@ impl JingleGenerator {
             }
         }
         
-        samples
+        self.intensity_level(samples)
     }
-}
-
-/// Preset jingle types available in the library
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum JinglePreset {
-    Notification,
-    Success,
-    Alert,
-    Error,
-    Startup,
-    Shutdown,
-    Message,
-    Completion,
-}
-
-impl JinglePreset {
-    /// Generate samples for this preset using the specified waveform
-    pub fn generate(&self, generator: &mut JingleGenerator, waveform: WaveForm) -> Vec<f32> {
-        self.generate_with_params(generator, waveform, None, None)
+    
+    /// Create a ringtone: an intro motif followed by a repeating phrase that loops cleanly
+    pub fn create_ringtone_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Ringtone.generate_with_params(self, waveform, duration, base_frequency)
     }
     
-    /// Generate samples for this preset with optional duration and frequency parameters
-    pub fn generate_with_params(
-        &self, 
-        generator: &mut JingleGenerator, 
-        waveform: WaveForm,
-        duration: Option<f32>,
-        frequency: Option<f32>
-    ) -> Vec<f32> {
-        match self {
-            JinglePreset::Notification => generator.create_notification_jingle(waveform, duration, frequency),
-            JinglePreset::Success => generator.create_success_jingle(waveform, duration, frequency),
-            JinglePreset::Alert => generator.create_alert_jingle(waveform, duration, frequency),
-            JinglePreset::Error => generator.create_error_jingle(waveform, duration, frequency),
-            JinglePreset::Startup => generator.create_startup_jingle(waveform, duration, frequency),
-            JinglePreset::Shutdown => generator.create_shutdown_jingle(waveform, duration, frequency),
-            JinglePreset::Message => generator.create_message_jingle(waveform, duration, frequency),
-            JinglePreset::Completion => generator.create_completion_jingle(waveform, duration, frequency),
+    /// Create a ringtone along with the region to repeat while the phone keeps ringing
+    ///
+    /// The loop starts after the intro and ends after a phrase that resolves to the
+    /// root and a short rest, so jumping back to the loop start sounds natural.
+    /// Export it with `export_to_wav_with_loop`.
+    pub fn create_ringtone_jingle_with_loop(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> (Vec<f32>, LoopRegion) {
+        self.ringtone_jingle(&JinglePreset::Ringtone.spec(waveform, duration, base_frequency))
+    }
+    
+    /// Render the ringtone preset from a spec, returning its loop region
+    fn ringtone_jingle(&mut self, spec: &JingleSpec) -> (Vec<f32>, LoopRegion) {
+        let target_duration = (self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_float_range(0.85..=1.15)).clamp(5.0, 15.0);
+        let note_duration = 0.2 * self.random_float_range(0.8..=1.25);
+        
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
+        let pitch_offset = self.random_pitch_offset();
+        let adjusted_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
+        let root_note = Note::from_frequency(adjusted_freq);
+        
+        let scale = spec.scale.unwrap_or_else(|| if self.random_bool(0.6) { Scale::Pentatonic } else { Scale::Major });
+        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { spec.waveform };
+        let scale_notes = scale.notes(root_note);
+        
+        // Phrase as a random walk over scale degrees that ends away from the root (a question)
+        let phrase_length = 8;
+        let mut degrees = vec![0];
+        while degrees.len() < phrase_length - 1 {
+            let last = degrees[degrees.len() - 1] as i32;
+            let step = self.random_range(-2..3);
+            degrees.push((last + step).clamp(0, scale_notes.len() as i32 - 1) as usize);
+        }
+        degrees.push(scale_notes.len().min(5) - 1);
+        
+        // The variation keeps the opening and answers by resolving to the root
+        let mut answer = degrees.clone();
+        answer[phrase_length - 2] = self.random_range(1..3) as usize;
+        answer[phrase_length - 1] = 0;
+        
+        let phrase = |degrees: &[usize]| Melody {
+            notes: degrees.iter().map(|&degree| (scale_notes[degree], note_duration)).collect(),
+        };
+        let intro = Melody::from_chord(Chord::major(root_note), 5, MelodyPattern::Ascending, note_duration * 0.75);
+        
+        let mut samples = self.generate_melody_samples(&intro, 5, used_waveform);
+        samples.extend(vec![0.0; (note_duration * self.get_sample_rate() as f32) as usize]);
+        let loop_start = samples.len();
+        
+        let mut cycle = self.generate_melody_samples(&phrase(&degrees), 5, used_waveform);
+        cycle.extend(self.generate_melody_samples(&phrase(&answer), 5, used_waveform));
+        cycle.extend(vec![0.0; (note_duration * 2.0 * self.get_sample_rate() as f32) as usize]);
+        
+        // Repeat as many whole cycles as fit in the target duration, so the loop ends on a cycle boundary
+        // (V1 rounded up and could overshoot by a cycle)
+        let target_samples = (target_duration * self.get_sample_rate() as f32) as usize;
+        let repeats = match self.generation_version {
+            GenerationVersion::V1 => target_samples.saturating_sub(loop_start).div_ceil(cycle.len()),
+            _ => target_samples.saturating_sub(loop_start) / cycle.len(),
+        }.max(1);
+        for _ in 0..repeats {
+            samples.extend_from_slice(&cycle);
         }
+        
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            pattern: Some(MelodyPattern::Random),
+            waveform: Some(used_waveform),
+            note_count: Some(intro.notes.len() + repeats * phrase_length * 2),
+            ..RenderChoices::default()
+        };
+        
+        let region = LoopRegion::new(loop_start as u32, samples.len() as u32);
+        (self.intensity_level(samples), region)
     }
     
-    /// Get all available presets
-    pub fn all() -> Vec<JinglePreset> {
-        vec![
-            JinglePreset::Notification,
-            JinglePreset::Success,
-            JinglePreset::Alert,
-            JinglePreset::Error,
-            JinglePreset::Startup,
-            JinglePreset::Shutdown,
-            JinglePreset::Message,
-            JinglePreset::Completion,
-        ]
+    /// Create an alarm that escalates over `stages` stages of about `stage_duration` seconds each
+    ///
+    /// Every stage repeats a pair of beeps. Each stage repeats faster, sits a whole
+    /// tone higher and adds a harmonic. One loop region is returned per stage,
+    /// covering a single cycle that loops seamlessly, so a player can hold a stage
+    /// for as long as needed before moving on to the next.
+    pub fn create_alarm_jingle(&mut self, stages: usize, stage_duration: f32) -> (Vec<f32>, Vec<LoopRegion>) {
+        let stages = stages.max(1);
+        let stage_duration = stage_duration.max(0.1);
+        let chip = self.get_chiptune();
+        
+        let pitch_offset = self.random_pitch_offset();
+        let base_freq = self.intensity_frequency(Note::A.frequency(5)) * (2.0_f32).powf(pitch_offset / 12.0);
+        let base_period = self.intensity_duration(0.6) * self.random_float_range(0.9..=1.1);
+        
+        let mut samples = Vec::new();
+        let mut regions = Vec::with_capacity(stages);
+        for stage in 0..stages {
+            let frequency = base_freq * (2.0_f32).powf(2.0 * stage as f32 / 12.0);
+            let period = base_period * 0.8_f32.powi(stage as i32);
+            let beep_duration = period * 0.2;
+            let envelope = ADSR { attack: 0.003, decay: 0.0, sustain: 1.0, release: beep_duration * 0.2 };
+            let beep = if chip {
+                // Narrower pulses are the chip's way of adding harmonics
+                let duty = [0.5, 0.25, 0.125][stage.min(2)];
+                self.pulse_sweep(frequency, frequency, duty, beep_duration, envelope, true)
+            } else {
+                self.harmonic_tone(frequency, stage + 1, beep_duration, envelope)
+            };
+            
+            // Beep, short gap, beep, then rest until the next cycle; every cycle starts
+            // and ends in silence, so any number of them join seamlessly
+            let mut cycle = vec![0.0; (period * self.get_sample_rate() as f32) as usize];
+            for onset in [0.0, beep_duration * 1.5] {
+                let offset = (onset * self.get_sample_rate() as f32) as usize;
+                for (sample, value) in cycle[offset..].iter_mut().zip(&beep) {
+                    *sample += value;
+                }
+            }
+            
+            let start = samples.len();
+            regions.push(LoopRegion::new(start as u32, (start + cycle.len()) as u32));
+            let repeats = ((stage_duration / period).round() as usize).max(1);
+            for _ in 0..repeats {
+                samples.extend_from_slice(&cycle);
+            }
+        }
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(Note::from_frequency(base_freq)),
+            waveform: Some(if chip { WaveForm::Square } else { WaveForm::Sine }),
+            note_count: Some(stages * 2),
+            ..RenderChoices::default()
+        };
+        
+        (self.intensity_level(samples), regions)
     }
     
-    /// Get the name of this preset as a string
-    pub fn name(&self) -> &'static str {
-        match self {
-            JinglePreset::Notification => "notification",
-            JinglePreset::Success => "success",
-            JinglePreset::Alert => "alert",
-            JinglePreset::Error => "error",
-            JinglePreset::Startup => "startup",
-            JinglePreset::Shutdown => "shutdown",
-            JinglePreset::Message => "message",
-            JinglePreset::Completion => "completion",
+    /// Create a doorbell chime: two or three bell strikes stepping down by major intervals
+    ///
+    /// Strikes are additive sine bells, so the waveform is not used.
+    pub fn create_doorbell_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Doorbell.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the doorbell preset from a spec
+    fn doorbell_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let total_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_float_range(0.85..=1.15);
+        let strike_count = if self.random_bool(0.6) { 2 } else { 3 };
+        
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
+        let pitch_offset = self.random_pitch_offset();
+        let top_frequency = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
+        
+        // Descending major third (ding-dong), or a major triad from the fifth (ding-dong-dong)
+        let intervals: &[i32] = if strike_count == 2 { &[0, -4] } else { &[0, -3, -7] };
+        
+        // Later strikes overlap the ringing of earlier ones
+        let spacing = total_duration * 0.25;
+        let ring_time = total_duration - spacing * (strike_count - 1) as f32;
+        let mut samples = vec![0.0; (total_duration * self.get_sample_rate() as f32) as usize];
+        for (i, &interval) in intervals.iter().enumerate() {
+            let frequency = top_frequency * (2.0_f32).powf(interval as f32 / 12.0);
+            let offset = (spacing * i as f32 * self.get_sample_rate() as f32) as usize;
+            for (sample, strike) in samples[offset..].iter_mut().zip(self.strike(frequency, ring_time)) {
+                *sample += strike;
+            }
         }
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(Note::from_frequency(top_frequency * (2.0_f32).powf(intervals[strike_count - 1] as f32 / 12.0))),
+            pattern: Some(MelodyPattern::Descending),
+            waveform: Some(WaveForm::Sine),
+            note_count: Some(strike_count),
+            ..RenderChoices::default()
+        };
+        
+        self.intensity_level(samples)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_notification_jingle() {
-        let mut generator = JingleGenerator::new();
-        let samples = generator.create_notification_jingle(WaveForm::Sine, None, None);
-        assert!(!samples.is_empty());
+    
+    /// Create a countdown: `ticks` evenly spaced ticks followed by a completion tone
+    /// - interval: seconds between tick onsets (at least 0.05)
+    /// - final_accent: end on a louder, ringing chord an octave up instead of a plain held tone
+    pub fn create_countdown_jingle(&mut self, waveform: WaveForm, ticks: usize, interval: f32, final_accent: bool) -> Vec<f32> {
+        self.countdown_jingle(&JinglePreset::Countdown.spec(waveform, None, None), ticks, interval, final_accent)
     }
-
-    #[test]
-    fn test_all_presets() {
-        let mut generator = JingleGenerator::new();
+    
+    /// Render a countdown using the waveform and tick frequency from a spec
+    fn countdown_jingle(&mut self, spec: &JingleSpec, ticks: usize, interval: f32, final_accent: bool) -> Vec<f32> {
+        let interval = interval.max(0.05);
+        let waveform = spec.waveform;
+        let tick_frequency = spec.frequency.unwrap_or(spec.preset.default_frequency());
+        let tick_duration = (interval * 0.5).min(0.04);
+        let chip = self.get_chiptune();
         
-        for preset in JinglePreset::all() {
-            let samples = preset.generate(&mut generator, WaveForm::Sine);
-            assert!(!samples.is_empty(), "Preset {} should generate samples", preset.name());
+        // Ticks keep the same pitch so the final tone stands out
+        let interval_samples = (interval * self.get_sample_rate() as f32) as usize;
+        let mut samples = vec![0.0; ticks * interval_samples];
+        for i in 0..ticks {
+            let tick = self.pulse_sweep(tick_frequency, tick_frequency * 0.9, 0.5, tick_duration, blip_envelope(tick_duration), chip);
+            let start = i * interval_samples;
+            for (sample, value) in samples[start..].iter_mut().zip(tick) {
+                *sample += value;
+            }
         }
+        
+        let root_note = Note::from_frequency(tick_frequency);
+        if final_accent {
+            let mut chord = vec![0.0; (1.2 * self.get_sample_rate() as f32) as usize];
+            let voices = if chip { CHIPTUNE_VOICES } else { usize::MAX };
+            for semitones in Chord::major(root_note).intervals.into_iter().take(voices) {
+                let frequency = tick_frequency * 2.0 * (2.0_f32).powf(semitones as f32 / 12.0);
+                for (sample, value) in chord.iter_mut().zip(self.strike(frequency, 1.2)) {
+                    *sample += value;
+                }
+            }
+            samples.extend(chord);
+        } else {
+            samples.extend(self.generate_tone(tick_frequency, 0.5, waveform));
+        }
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            waveform: Some(waveform),
+            note_count: Some(ticks + 1),
+            ..RenderChoices::default()
+        };
+        
+        self.intensity_level(samples)
+    }
+    
+    /// Create a gentle calendar reminder: a rising question phrase, a pause and a falling answer
+    ///
+    /// Rendered a little quieter than the other presets so it is noticeable but unobtrusive.
+    pub fn create_reminder_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Reminder.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the reminder preset from a spec
+    fn reminder_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let total_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_float_range(0.85..=1.15);
+        // Three notes per phrase plus a pause of two notes in between
+        let note_duration = total_duration / 8.0;
+        
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
+        let pitch_offset = self.random_pitch_offset();
+        let adjusted_freq = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
+        let root_note = Note::from_frequency(adjusted_freq);
+        
+        let scale = spec.scale.unwrap_or_else(|| if self.random_bool(0.7) { Scale::Pentatonic } else { Scale::Major });
+        // Softer waveforms only
+        let used_waveform = if self.random_bool(0.3) { WaveForm::Sine } else { spec.waveform };
+        let scale_notes = scale.notes(root_note);
+        
+        // The question ends unresolved on the third or fifth degree, the answer comes home
+        let peak = if self.random_bool(0.5) { 2 } else { scale_notes.len().min(5) - 1 };
+        let middle = self.random_range(1..peak as i32) as usize;
+        let question = Melody {
+            notes: vec![(scale_notes[0], note_duration), (scale_notes[middle], note_duration), (scale_notes[peak], note_duration * 1.5)],
+        };
+        let answer = Melody {
+            notes: vec![(scale_notes[peak], note_duration), (scale_notes[middle], note_duration), (scale_notes[0], note_duration * 1.5)],
+        };
+        
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            waveform: Some(used_waveform),
+            note_count: Some(question.notes.len() + answer.notes.len()),
+            ..RenderChoices::default()
+        };
+        
+        let phrases = [
+            self.generate_melody_samples(&question, 5, used_waveform),
+            self.generate_melody_samples(&answer, 5, used_waveform),
+        ];
+        let mut samples = self.combine_samples(&phrases, note_duration * 2.0);
+        apply_gain_db(&mut samples, -4.0);
+        self.intensity_level(samples)
+    }
+    
+    /// Create a sonic logo for a brand name
+    ///
+    /// The name is hashed like `with_string_seed` into a key, scale, motif and rhythm,
+    /// so the same name always produces the same 1–2 second logo, whatever the
+    /// generator's seed or history. The generator's ADSR still shapes the notes.
+    pub fn create_audio_logo(&mut self, brand: &str) -> Vec<f32> {
+        let seed = JingleGenerator::with_string_seed(brand).get_current_seed().unwrap_or_default();
+        self.with_call_seed(seed, |generator| generator.audio_logo())
+    }
+    
+    /// Render an audio logo from the current random sequence
+    fn audio_logo(&mut self) -> Vec<f32> {
+        let root_note = Note::from_frequency(Note::C.frequency(4) * (2.0_f32).powf(self.random_range(0..12) as f32 / 12.0));
+        let scale = match self.random_range(0..3) {
+            0 => Scale::Major,
+            1 => Scale::Pentatonic,
+            _ => Scale::Minor,
+        };
+        let scale_notes = scale.notes(root_note);
+        let fifth = scale.intervals().iter().position(|&interval| interval == 7).unwrap_or(0);
+        
+        // Start on the root, wander freely, then land on the root or the fifth
+        let note_count = self.random_range(3..6) as usize;
+        let mut degrees = vec![0];
+        for _ in 1..note_count - 1 {
+            degrees.push(self.random_range(0..scale_notes.len() as i32) as usize);
+        }
+        degrees.push(if self.random_bool(0.6) { 0 } else { fifth });
+        
+        // Relative note lengths, with the last note held so the logo rings out
+        let mut weights: Vec<f32> = (1..note_count).map(|_| [0.5, 1.0, 1.0, 1.5][self.random_range(0..4) as usize]).collect();
+        weights.push(2.0);
+        let total_duration = self.random_float_range(1.2..=1.8);
+        let total_weight: f32 = weights.iter().sum();
+        
+        let melody = Melody {
+            notes: degrees.iter().zip(&weights)
+                .map(|(&degree, &weight)| (scale_notes[degree], total_duration * weight / total_weight))
+                .collect(),
+        };
+        let waveform = if self.random_bool(0.5) { WaveForm::Sine } else { WaveForm::Triangle };
+        let octave = if self.random_bool(0.5) { 4 } else { 5 };
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            waveform: Some(waveform),
+            note_count: Some(melody.notes.len()),
+            ..RenderChoices::default()
+        };
+        
+        self.generate_melody_samples(&melody, octave, waveform)
+    }
+    
+    /// Create a festive jingle: a pentatonic melody over sleigh bells shaken at eighth-note rate
+    ///
+    /// About half the time the melody is played with a celesta-like FM timbre
+    /// instead of the requested waveform.
+    pub fn create_holiday_jingle(&mut self, waveform: WaveForm, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Holiday.generate_with_params(self, waveform, duration, base_frequency)
+    }
+    
+    /// Render the holiday preset from a spec
+    fn holiday_jingle(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let base_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_variation();
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
+        let pitch_offset = self.random_pitch_offset();
+        let root_note = Note::from_frequency(base_freq * (2.0_f32).powf(pitch_offset / 12.0));
+        let octave = (base_freq / Note::C.frequency(0)).log2().floor() as i32;
+        
+        let scale = spec.scale.unwrap_or(Scale::Pentatonic);
+        let pattern = spec.pattern.unwrap_or_else(|| if self.random_bool(0.5) { MelodyPattern::Arpeggio } else { self.random_melody_pattern() });
+        // A chip has no FM operator, so chiptune mode always uses the melody voice
+        let celesta = self.random_bool(0.5) && !self.get_chiptune();
+        let waveform = self.intensity_waveform(spec.waveform);
+        
+        // Melody notes fall on the eighth notes the bells are shaken on
+        let note_count = self.random_note_count_variation(6);
+        let eighth = (base_duration / note_count as f32) * self.random_rhythm_variation();
+        let melody = Melody::from_scale(scale, root_note, octave, pattern, eighth);
+        
+        let mut samples = if celesta {
+            let ring = eighth * 3.0;
+            let melody_duration: f32 = melody.notes.iter().map(|&(_, duration)| duration).sum();
+            let mut samples = vec![0.0; ((melody_duration + ring) * self.get_sample_rate() as f32) as usize];
+            let mut onset = 0.0;
+            for &(note, duration) in &melody.notes {
+                let offset = (onset * self.get_sample_rate() as f32) as usize;
+                for (sample, strike) in samples[offset..].iter_mut().zip(self.celesta_tone(note.frequency(octave), duration + ring)) {
+                    *sample += strike * 0.7;
+                }
+                onset += duration;
+            }
+            samples
+        } else {
+            self.generate_melody_samples(&melody, octave, waveform)
+        };
+        
+        let bells = self.sleigh_bells(samples.len() as f32 / self.get_sample_rate() as f32, eighth);
+        for (sample, bell) in samples.iter_mut().zip(bells) {
+            *sample += bell * 0.35;
+        }
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(root_note),
+            scale: Some(scale),
+            pattern: Some(pattern),
+            waveform: if celesta { None } else { Some(waveform) },
+            note_count: Some(melody.notes.len()),
+            ..RenderChoices::default()
+        };
+        
+        self.intensity_level(samples)
+    }
+    
+    /// Band-passed noise bursts every `interval` seconds, accented on the beat like a shaken strap of bells
+    fn sleigh_bells(&mut self, duration: f32, interval: f32) -> Vec<f32> {
+        let mut samples = self.white_noise(duration);
+        let interval = interval.max(0.02);
+        let mut filter = StateVariableFilter::new(SvfMode::BandPass, 1.5, self.get_sample_rate() as f32);
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let time = i as f32 / self.get_sample_rate() as f32;
+            let shake = (time / interval).floor();
+            let since = time - shake * interval;
+            let accent = if (shake as usize).is_multiple_of(2) { 1.0 } else { 0.6 };
+            // A quick jangle that rattles down over 40 ms
+            let envelope = (since / 0.002).min(1.0) * (-since / 0.04).exp() * accent;
+            *sample = filter.process_sample(*sample, 7000.0) * envelope;
+        }
+        samples
+    }
+    
+    /// Create a warning whose urgency follows the severity
+    ///
+    /// Higher severities use more dissonant intervals, harsher waveforms, faster
+    /// repetition and a longer overall duration.
+    pub fn create_warning_jingle(&mut self, severity: Severity, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Warning(severity).generate_with_params(self, WaveForm::Sine, duration, base_frequency)
+    }
+    
+    /// Render a warning from a spec
+    fn warning_jingle(&mut self, severity: Severity, spec: &JingleSpec) -> Vec<f32> {
+        let total_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_float_range(0.9..=1.1);
+        let (beep_count, gap_ratio) = severity.repetition();
+        let beep_duration = total_duration / (beep_count as f32 + gap_ratio * (beep_count - 1) as f32);
+        let gap_samples = (beep_duration * gap_ratio * self.get_sample_rate() as f32) as usize;
+        
+        let base_freq = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency()));
+        // Keep pitch variation small so severities stay comparable
+        let pitch_offset = self.random_float_range(-1.0..=1.0);
+        let root_frequency = base_freq * (2.0_f32).powf(pitch_offset / 12.0);
+        let upper_frequency = root_frequency * (2.0_f32).powf(severity.interval() as f32 / 12.0);
+        let waveform = severity.waveform();
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(Note::from_frequency(root_frequency)),
+            waveform: Some(waveform),
+            note_count: Some(beep_count),
+            ..RenderChoices::default()
+        };
+        
+        // Each beep sounds the root and the interval together
+        let beep: Vec<f32> = self.generate_tone(root_frequency, beep_duration, waveform)
+            .into_iter()
+            .zip(self.generate_tone(upper_frequency, beep_duration, waveform))
+            .map(|(root, upper)| (root + upper) * 0.5)
+            .collect();
+        let beeps = vec![beep; beep_count];
+        self.intensity_level(self.combine_samples(&beeps, gap_samples as f32 / self.get_sample_rate() as f32))
+    }
+    
+    /// Create a micro interface sound, never longer than `UI_SOUND_MAX_DURATION`
+    pub fn create_ui_sound(&mut self, sound: UiSound, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Ui(sound).generate_with_params(self, WaveForm::Sine, duration, base_frequency)
+    }
+    
+    /// Render a interface sound from a spec
+    fn ui_sound(&mut self, sound: UiSound, spec: &JingleSpec) -> Vec<f32> {
+        let chip = self.get_chiptune();
+        let total_duration = (self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_float_range(0.9..=1.1)).clamp(0.002, UI_SOUND_MAX_DURATION);
+        // Interface sounds repeat constantly, so only vary the pitch slightly
+        let frequency = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency())) * (2.0_f32).powf(self.random_float_range(-1.0..=1.0) / 12.0);
+        
+        // Millisecond attacks keep the sounds crisp without clicking themselves
+        let snap = ADSR { attack: 0.0005, decay: total_duration * 0.3, sustain: 0.3, release: total_duration * 0.7 };
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(Note::from_frequency(frequency)),
+            waveform: Some(WaveForm::Sine),
+            note_count: Some(1),
+            ..RenderChoices::default()
+        };
+        
+        let samples = match sound {
+            UiSound::Click => {
+                self.last_choices.waveform = None;
+                let mut samples = self.white_noise(total_duration);
+                let mut filter = StateVariableFilter::new(SvfMode::HighPass, 0.7, self.get_sample_rate() as f32);
+                for (i, sample) in samples.iter_mut().enumerate() {
+                    let time = i as f32 / self.get_sample_rate() as f32;
+                    *sample = filter.process_sample(*sample, frequency) * snap.level(time, total_duration);
+                }
+                samples
+            },
+            UiSound::Tap => self.sine_sweep(frequency, frequency * 0.7, total_duration, snap, chip),
+            UiSound::Toggle => {
+                self.last_choices.note_count = Some(2);
+                let half = total_duration / 2.0;
+                let blip = ADSR { attack: 0.0005, decay: half * 0.3, sustain: 0.3, release: half * 0.7 };
+                let mut samples = self.sine_sweep(frequency, frequency, half, blip, chip);
+                let upper = frequency * (2.0_f32).powf(5.0 / 12.0);
+                samples.extend(self.sine_sweep(upper, upper, half, blip, chip));
+                samples
+            },
+            UiSound::Swipe => {
+                self.last_choices.waveform = None;
+                let mut samples = self.white_noise(total_duration);
+                let swell = ADSR { attack: total_duration * 0.4, decay: 0.0, sustain: 1.0, release: total_duration * 0.6 };
+                let mut filter = StateVariableFilter::new(SvfMode::BandPass, 2.0, self.get_sample_rate() as f32);
+                for (i, sample) in samples.iter_mut().enumerate() {
+                    let time = i as f32 / self.get_sample_rate() as f32;
+                    let cutoff = frequency * 0.5 * 4.0_f32.powf(time / total_duration);
+                    *sample = filter.process_sample(*sample, cutoff) * swell.level(time, total_duration);
+                }
+                samples
+            },
+        };
+        self.intensity_level(samples)
+    }
+    
+    /// Create a retro game sound effect built from pulse waves, pitch sweeps and noise
+    pub fn create_game_sfx(&mut self, sfx: GameSfx, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::Game(sfx).generate_with_params(self, WaveForm::Sine, duration, base_frequency)
+    }
+    
+    /// Render a game sound effect from a spec
+    fn game_sfx(&mut self, sfx: GameSfx, spec: &JingleSpec) -> Vec<f32> {
+        let chip = self.get_chiptune();
+        let total_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_float_range(0.85..=1.15);
+        let pitch_offset = self.random_pitch_offset();
+        let frequency = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency())) * (2.0_f32).powf(pitch_offset / 12.0);
+        
+        // Narrow pulses give the thin, nasal tone of early console sound chips
+        let duty = match self.random_range(0..3) {
+            0 => 0.125,
+            1 => 0.25,
+            _ => 0.5,
+        };
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(Note::from_frequency(frequency)),
+            waveform: Some(WaveForm::Square),
+            ..RenderChoices::default()
+        };
+        
+        let samples = match sfx {
+            GameSfx::Coin => {
+                // A short blip then a longer one a fourth higher
+                let first = total_duration * 0.2;
+                let second = total_duration - first;
+                let fourth = frequency * (2.0_f32).powf(5.0 / 12.0);
+                self.last_choices.note_count = Some(2);
+                let mut samples = self.pulse_sweep(frequency, frequency, duty, first, gate_envelope(), chip);
+                samples.extend(self.pulse_sweep(fourth, fourth, duty, second, blip_envelope(second), chip));
+                samples
+            },
+            GameSfx::PowerUp => {
+                // Major arpeggio over two octaves, each step sliding up a whole tone
+                let steps = [0, 4, 7, 12, 16, 19];
+                let step_duration = total_duration / steps.len() as f32;
+                self.last_choices.note_count = Some(steps.len());
+                let mut samples = Vec::new();
+                for (i, &interval) in steps.iter().enumerate() {
+                    let start = frequency * (2.0_f32).powf(interval as f32 / 12.0);
+                    let envelope = if i + 1 == steps.len() {
+                        blip_envelope(step_duration)
+                    } else {
+                        gate_envelope()
+                    };
+                    samples.extend(self.pulse_sweep(start, start * (2.0_f32).powf(2.0 / 12.0), duty, step_duration, envelope, chip));
+                }
+                samples
+            },
+            GameSfx::Jump => {
+                self.last_choices.note_count = Some(1);
+                let end = frequency * self.random_float_range(2.0..=3.0);
+                self.pulse_sweep(frequency, end, duty, total_duration, blip_envelope(total_duration), chip)
+            },
+            GameSfx::Laser => {
+                self.last_choices.note_count = Some(1);
+                let end = frequency / self.random_float_range(6.0..=10.0);
+                self.pulse_sweep(frequency, end, duty, total_duration, blip_envelope(total_duration), chip)
+            },
+            GameSfx::Explosion => {
+                self.last_choices.waveform = None;
+                self.last_choices.root_note = None;
+                self.noise_burst(total_duration, frequency)
+            },
+        };
+        self.intensity_level(samples)
+    }
+    
+    /// Create a futuristic interface sound from frequency sweeps, ring modulation and shimmer reverb
+    pub fn create_scifi_sound(&mut self, sound: SciFiSound, duration: Option<f32>, base_frequency: Option<f32>) -> Vec<f32> {
+        JinglePreset::SciFi(sound).generate_with_params(self, WaveForm::Sine, duration, base_frequency)
+    }
+    
+    /// Render a sci-fi sound from a spec
+    fn scifi_sound(&mut self, sound: SciFiSound, spec: &JingleSpec) -> Vec<f32> {
+        let chip = self.get_chiptune();
+        let total_duration = self.intensity_duration(spec.duration.unwrap_or(spec.preset.default_duration())) * self.random_float_range(0.9..=1.1);
+        let pitch_offset = self.random_pitch_offset();
+        let frequency = self.intensity_frequency(spec.frequency.unwrap_or(spec.preset.default_frequency())) * (2.0_f32).powf(pitch_offset / 12.0);
+        // A carrier at no simple ratio to the base keeps the sidebands inharmonic
+        let carrier = frequency * self.random_float_range(0.3..=0.45);
+        
+        self.last_choices = RenderChoices {
+            root_note: Some(Note::from_frequency(frequency)),
+            waveform: Some(WaveForm::Sine),
+            note_count: Some(1),
+            ..RenderChoices::default()
+        };
+        
+        // Each sound leaves the rest of the duration for its shimmer tail
+        let (mut samples, body, effects) = match sound {
+            SciFiSound::Sweep => {
+                let body = total_duration * 0.6;
+                let swell = ADSR { attack: body * 0.4, decay: 0.0, sustain: 1.0, release: body * 0.3 };
+                let samples = self.sine_sweep(frequency, frequency * 4.0, body, swell, chip);
+                (samples, body, EffectChain::new().ring_mod(carrier, 0.35).shimmer(0.85, 0.5, 0.4))
+            },
+            SciFiSound::Zap => {
+                let body = total_duration * 0.6;
+                let end = frequency / self.random_float_range(6.0..=10.0);
+                let samples = self.sine_sweep(frequency, end, body, blip_envelope(body), chip);
+                (samples, body, EffectChain::new().ring_mod(carrier * 2.0, 0.6).shimmer(0.6, 0.3, 0.25))
+            },
+            SciFiSound::Scan => {
+                let body = total_duration * 0.7;
+                let passes = self.random_range(2..4) as usize;
+                let pass = body / (passes * 2) as f32;
+                let top = frequency * 1.5;
+                let mut samples = Vec::new();
+                for _ in 0..passes {
+                    samples.extend(self.sine_sweep(frequency, top, pass, gate_envelope(), chip));
+                    samples.extend(self.sine_sweep(top, frequency, pass, gate_envelope(), chip));
+                }
+                self.last_choices.note_count = Some(passes * 2);
+                (samples, body, EffectChain::new().ring_mod(carrier, 0.5).shimmer(0.7, 0.4, 0.3))
+            },
+            SciFiSound::Shimmer => {
+                let body = total_duration * 0.3;
+                let swell = ADSR { attack: body * 0.5, decay: 0.0, sustain: 1.0, release: body * 0.5 };
+                let fifth = frequency * 1.5;
+                let samples: Vec<f32> = self.sine_sweep(frequency, frequency, body, swell, chip)
+                    .into_iter()
+                    .zip(self.sine_sweep(fifth, fifth, body, swell, chip))
+                    .map(|(root, fifth)| (root + fifth) * 0.5)
+                    .collect();
+                self.last_choices.note_count = Some(2);
+                (samples, body, EffectChain::new().ring_mod(carrier, 0.15).shimmer(0.95, 0.7, 0.6))
+            },
+        };
+        samples.resize((total_duration * self.get_sample_rate() as f32) as usize, 0.0);
+        
+        let mut samples = self.apply_effects(&samples, &effects);
+        // Whatever is left of the tail at the end fades out instead of being cut off
+        fade_out(&mut samples, (total_duration - body) * 0.5, self.get_sample_rate() as f32);
+        self.intensity_level(samples)
+    }
+    
+    /// Render a jingle from a spec
+    ///
+    /// Renders the preset with the spec's overrides and runs the result through
+    /// its effect chain. A spec with a seed renders with `with_call_seed`, so it
+    /// always produces the same output. The ADSR override applies to oscillator
+    /// notes; bells, pulses and noise bursts keep their own envelopes.
+    pub fn render(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        match spec.seed {
+            Some(seed) => self.with_call_seed(seed, |generator| generator.render_unseeded(spec)),
+            None => self.render_unseeded(spec),
+        }
+    }
+    
+    /// Play a caller-provided melody with a preset's waveform, envelope, register and effects
+    ///
+    /// Like the presets themselves, the waveform occasionally varies, and intensity applies.
+    ///
+    /// ```rust
+    /// use jinglebells::{JingleGenerator, JinglePreset, Melody, Note};
+    ///
+    /// let motif = Melody { notes: vec![(Note::E, 0.12), (Note::G, 0.12), (Note::C, 0.3)] };
+    /// let samples = JingleGenerator::new().render_as(JinglePreset::Success, &motif);
+    /// assert!(!samples.is_empty());
+    /// ```
+    pub fn render_as(&mut self, preset: JinglePreset, melody: &Melody) -> Vec<f32> {
+        let style = preset.style();
+        let used_waveform = if self.random_bool(0.3) { self.random_waveform() } else { style.waveform };
+        let used_waveform = self.intensity_waveform(used_waveform);
+        
+        self.last_choices = RenderChoices {
+            waveform: Some(used_waveform),
+            note_count: Some(melody.notes.len()),
+            ..RenderChoices::default()
+        };
+        
+        let previous_adsr = self.get_adsr();
+        self.set_adsr(style.adsr);
+        let samples = self.generate_melody_samples(melody, style.octave, used_waveform);
+        self.set_adsr(previous_adsr);
+        self.chip_choices();
+        
+        let samples = if style.effects.is_empty() { samples } else { self.apply_effects(&samples, &style.effects) };
+        self.intensity_level(samples)
+    }
+    
+    /// Render a registered custom preset or a built-in preset by name
+    ///
+    /// The spec supplies the overrides; its `preset` field is replaced by the named
+    /// preset. Seeds and effect chains apply to custom presets as they do to built-in ones.
+    /// Fails with `InvalidParameter` for an unknown name or a spec that doesn't validate.
+    pub fn render_named(&mut self, name: &str, spec: &JingleSpec) -> Result<Vec<f32>> {
+        spec.validate()?;
+        if let Some(custom) = self.presets().get(name) {
+            let render = |generator: &mut JingleGenerator| {
+                let samples = custom(generator, spec);
+                if spec.effects.is_empty() {
+                    samples
+                } else {
+                    generator.apply_effects(&samples, &spec.effects)
+                }
+            };
+            return Ok(match spec.seed {
+                Some(seed) => self.with_call_seed(seed, render),
+                None => render(self),
+            });
+        }
+        
+        let preset = JinglePreset::from_name(name)
+            .ok_or_else(|| JingleError::InvalidParameter(format!("unknown preset `{}`", name)))?;
+        Ok(self.render(&JingleSpec { preset, ..spec.clone() }))
+    }
+    
+    /// Render a hybrid between two presets, e.g. a semi-urgent sound between notification and alert
+    ///
+    /// `t` runs from 0.0 (all `a`) to 1.0 (all `b`). Each preset plays with its style's waveform (from V2).
+    pub fn morph(&mut self, a: JinglePreset, b: JinglePreset, t: f32) -> Vec<f32> {
+        if self.generation_version == GenerationVersion::V1 {
+            return self.crossfade_specs(&JingleSpec::new(a), &JingleSpec::new(b), t);
+        }
+        let spec = |preset: JinglePreset| JingleSpec::new(preset).with_waveform(preset.style().waveform);
+        self.morph_specs(&spec(a), &spec(b), t)
+    }
+    
+    /// Render a hybrid between two specs
+    ///
+    /// Duration, pitch, note density, contour, articulation and envelope are
+    /// interpolated by `t`, and a single melody is composed from them with one seed
+    /// (the first spec's, then the second's, or a fresh draw). That melody is played
+    /// with both waveforms layered and run through both effect chains, each weighted
+    /// by its side of `t`. Specs without an envelope or effects use their preset's style.
+    /// Under V1 the two renders are crossfaded instead.
+    pub fn morph_specs(&mut self, a: &JingleSpec, b: &JingleSpec, t: f32) -> Vec<f32> {
+        if self.generation_version == GenerationVersion::V1 {
+            return self.crossfade_specs(a, b, t);
+        }
+        let t = t.clamp(0.0, 1.0);
+        // Exact at both ends, so t = 0.0 and 1.0 match the plain parameters
+        let lerp = |from: f32, to: f32| from * (1.0 - t) + to * t;
+        let duration = lerp(a.duration.unwrap_or(a.preset.default_duration()), b.duration.unwrap_or(b.preset.default_duration()));
+        // Interpolate pitch in octaves so the midpoint sounds halfway between
+        let frequency = a.frequency.unwrap_or(a.preset.default_frequency()).powf(1.0 - t) * b.frequency.unwrap_or(b.preset.default_frequency()).powf(t);
+        
+        let (contour_a, contour_b) = (a.preset.contour(), b.preset.contour());
+        let note_count = (lerp(contour_a.density, contour_b.density) * duration).round().clamp(1.0, 64.0) as usize;
+        let direction = lerp(contour_a.direction, contour_b.direction);
+        let repetition = lerp(contour_a.repetition, contour_b.repetition);
+        let articulation = lerp(contour_a.articulation, contour_b.articulation);
+        
+        let (style_a, style_b) = (a.preset.style(), b.preset.style());
+        let (adsr_a, adsr_b) = (a.adsr.unwrap_or(style_a.adsr), b.adsr.unwrap_or(style_b.adsr));
+        let adsr = ADSR {
+            attack: lerp(adsr_a.attack, adsr_b.attack),
+            decay: lerp(adsr_a.decay, adsr_b.decay),
+            sustain: lerp(adsr_a.sustain, adsr_b.sustain),
+            release: lerp(adsr_a.release, adsr_b.release),
+        };
+        let effects_a = if a.effects.is_empty() { style_a.effects } else { a.effects.clone() };
+        let effects_b = if b.effects.is_empty() { style_b.effects } else { b.effects.clone() };
+        
+        let seed = match a.seed.or(b.seed) {
+            Some(seed) => seed,
+            None => self.random_seed(),
+        };
+        self.with_call_seed(seed, |generator| {
+            let root_note = Note::from_frequency(frequency);
+            let octave = (frequency / Note::C.frequency(0)).log2().floor() as i32;
+            let scale = if t < 0.5 { a.scale.or(b.scale) } else { b.scale.or(a.scale) }.unwrap_or(Scale::Pentatonic);
+            let scale_notes = scale.notes(root_note);
+            let top = scale_notes.len() - 1;
+            
+            // Walk the scale from the end the contour leans away from, stepping with its direction
+            let note_duration = duration / note_count as f32;
+            let mut degree = if direction < 0.0 { top } else { 0 };
+            let mut notes = Vec::with_capacity(note_count);
+            for _ in 0..note_count {
+                notes.push((scale_notes[degree], note_duration));
+                if !generator.random_bool(repetition.clamp(0.0, 1.0) as f64) {
+                    let step = generator.random_range(1..3) as usize;
+                    degree = if generator.random_bool(((1.0 + direction) / 2.0).clamp(0.0, 1.0) as f64) {
+                        (degree + step).min(top)
+                    } else {
+                        degree.saturating_sub(step)
+                    };
+                }
+            }
+            let melody = Melody { notes };
+            
+            // Layer the two waveforms over the same melody
+            let options = MelodyRenderOptions::default().with_adsr(adsr).with_articulation(articulation);
+            let waveform_a = generator.intensity_waveform(a.waveform);
+            let waveform_b = generator.intensity_waveform(b.waveform);
+            let mut samples = generator.generate_melody_samples_with_options(&melody, octave, waveform_a, &options);
+            if waveform_b != waveform_a && t > 0.0 {
+                let layer = generator.generate_melody_samples_with_options(&melody, octave, waveform_b, &options);
+                for (sample, layered) in samples.iter_mut().zip(layer) {
+                    *sample = *sample * (1.0 - t) + layered * t;
+                }
+            }
+            
+            // Each chain's wet signal is weighted by its side of the blend
+            for (effects, amount) in [(&effects_a, 1.0 - t), (&effects_b, t)] {
+                if amount > 0.0 && !effects.is_empty() {
+                    let wet = generator.apply_effects(&samples, effects);
+                    for (sample, wet) in samples.iter_mut().zip(wet) {
+                        *sample += (wet - *sample) * amount;
+                    }
+                }
+            }
+            // Level-raising chains like the alert's limiter can push the blend past full scale
+            if samples.iter().any(|sample| sample.abs() > 1.0) {
+                normalize_samples(&mut samples, 1.0);
+            }
+            
+            generator.last_choices = RenderChoices {
+                root_note: Some(root_note),
+                scale: Some(scale),
+                pattern: Some(MelodyPattern::Random),
+                waveform: Some(if t < 0.5 { waveform_a } else { waveform_b }),
+                note_count: Some(note_count),
+                ..RenderChoices::default()
+            };
+            generator.chip_choices();
+            generator.intensity_level(samples)
+        })
+    }
+    
+    /// V1 morph: both specs render from the same random draws over the interpolated
+    /// duration and base frequency, then the renders are crossfaded by `t`
+    fn crossfade_specs(&mut self, a: &JingleSpec, b: &JingleSpec, t: f32) -> Vec<f32> {
+        let t = t.clamp(0.0, 1.0);
+        let duration_a = a.duration.unwrap_or(a.preset.default_duration());
+        let duration_b = b.duration.unwrap_or(b.preset.default_duration());
+        let duration = duration_a + (duration_b - duration_a) * t;
+        // Interpolate pitch in octaves so the midpoint sounds halfway between
+        let frequency_a = a.frequency.unwrap_or(a.preset.default_frequency());
+        let frequency_b = b.frequency.unwrap_or(b.preset.default_frequency());
+        let frequency = frequency_a * (frequency_b / frequency_a).powf(t);
+        
+        let seed = self.random_seed();
+        let first = a.clone().with_duration(duration).with_frequency(frequency).with_seed(a.seed.unwrap_or(seed));
+        let second = b.clone().with_duration(duration).with_frequency(frequency).with_seed(b.seed.unwrap_or(seed));
+        let first = self.render(&first);
+        let second = self.render(&second);
+        
+        let length = first.len().max(second.len());
+        (0..length)
+            .map(|i| {
+                first.get(i).copied().unwrap_or(0.0) * (1.0 - t) + second.get(i).copied().unwrap_or(0.0) * t
+            })
+            .collect()
+    }
+    
+    /// Render a spec using the generator's current random sequence
+    fn render_unseeded(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        match self.get_constraints() {
+            Some(constraints) => self.render_constrained(spec, &constraints),
+            None => self.render_preset(spec),
+        }
+    }
+    
+    /// Render a spec fitted to constraints
+    fn render_constrained(&mut self, spec: &JingleSpec, constraints: &Constraints) -> Vec<f32> {
+        let mut spec = constraints.fit_spec(spec);
+        let mut samples = self.render_preset(&spec);
+        
+        // Randomized timing can overshoot the requested duration; shorten and try
+        // again a few times before falling back to trimming
+        if let Some(max) = constraints.max_duration {
+            let max_samples = max * self.get_sample_rate() as f32;
+            for _ in 0..3 {
+                if samples.len() as f32 <= max_samples {
+                    break;
+                }
+                let duration = spec.duration.unwrap_or(max) * max_samples / samples.len() as f32 * 0.95;
+                spec.duration = Some(duration);
+                samples = self.render_preset(&spec);
+            }
+        }
+        constraints.enforce(samples, self.get_sample_rate())
+    }
+    
+    /// Render a spec after validating it and checking it against the generator's constraints
+    ///
+    /// Fails with `InvalidParameter` when the spec has a duration, frequency,
+    /// envelope or effect parameter that can't render (see `JingleSpec::validate`),
+    /// the constraints contradict each other, the spec asks for a duration or
+    /// frequency outside them, or the render can't be brought within them.
+    pub fn try_render(&mut self, spec: &JingleSpec) -> Result<Vec<f32>> {
+        spec.validate()?;
+        let Some(constraints) = self.get_constraints() else {
+            return Ok(self.render(spec));
+        };
+        constraints.validate_spec(spec)?;
+        let samples = self.render(spec);
+        constraints.check(&samples, self.get_sample_rate())?;
+        Ok(samples)
+    }
+    
+    /// Render a preset with the spec's ADSR and effects
+    fn render_preset(&mut self, spec: &JingleSpec) -> Vec<f32> {
+        let previous_adsr = self.get_adsr();
+        if let Some(adsr) = spec.adsr {
+            self.set_adsr(adsr);
+        }
+        
+        self.report_phase(ProgressPhase::Composing, 0, 1);
+        let samples = match spec.preset {
+            JinglePreset::Notification => self.notification_jingle(spec),
+            JinglePreset::Success => self.success_jingle(spec),
+            JinglePreset::Alert => self.alert_jingle(spec),
+            JinglePreset::Error => self.error_jingle(spec),
+            JinglePreset::Startup => self.startup_jingle(spec),
+            JinglePreset::Shutdown => self.shutdown_jingle(spec),
+            JinglePreset::Message => self.message_jingle(spec),
+            JinglePreset::Completion => self.completion_jingle(spec),
+            JinglePreset::Ringtone => self.ringtone_jingle(spec).0,
+            JinglePreset::Doorbell => self.doorbell_jingle(spec),
+            JinglePreset::Reminder => self.reminder_jingle(spec),
+            JinglePreset::Holiday => self.holiday_jingle(spec),
+            JinglePreset::Countdown => self.countdown_jingle(spec, 3, spec.duration.unwrap_or(spec.preset.default_duration()), true),
+            JinglePreset::Warning(severity) => self.warning_jingle(severity, spec),
+            JinglePreset::Ui(sound) => self.ui_sound(sound, spec),
+            JinglePreset::Game(sfx) => self.game_sfx(sfx, spec),
+            JinglePreset::SciFi(sound) => self.scifi_sound(sound, spec),
+        };
+        self.set_adsr(previous_adsr);
+        self.chip_choices();
+        self.report_phase(ProgressPhase::Rendering, 1, 1);
+        
+        if spec.effects.is_empty() {
+            samples
+        } else {
+            self.report_phase(ProgressPhase::Effects, 0, 1);
+            let samples = self.apply_effects(&samples, &spec.effects);
+            self.report_phase(ProgressPhase::Effects, 1, 1);
+            samples
+        }
+    }
+    
+    /// Shift a preset's base frequency down an octave at low intensity and up one at high intensity
+    fn intensity_frequency(&self, frequency: f32) -> f32 {
+        match self.get_intensity() {
+            Some(intensity) if intensity < 0.25 => frequency * 0.5,
+            Some(intensity) if intensity > 0.75 => frequency * 2.0,
+            _ => frequency,
+        }
+    }
+    
+    /// Shorten durations at high intensity so notes come faster, lengthen them at low intensity
+    fn intensity_duration(&self, duration: f32) -> f32 {
+        self.get_intensity().map_or(duration, |intensity| duration * (1.3 - 0.6 * intensity))
+    }
+    
+    /// Move a waveform up to two steps along Sine, Triangle, Square, Sawtooth (softest to harshest)
+    fn intensity_waveform(&self, waveform: WaveForm) -> WaveForm {
+        const HARSHNESS: [WaveForm; 4] = [WaveForm::Sine, WaveForm::Triangle, WaveForm::Square, WaveForm::Sawtooth];
+        let Some(intensity) = self.get_intensity() else {
+            return waveform;
+        };
+        let index = HARSHNESS.iter().position(|&candidate| candidate == waveform).unwrap_or(0) as i32;
+        let steps = ((intensity - 0.5) * 4.0).round() as i32;
+        HARSHNESS[(index + steps).clamp(0, HARSHNESS.len() as i32 - 1) as usize]
+    }
+    
+    /// Scale the level from -4 dB at intensity 0.0 to +4 dB at intensity 1.0
+    ///
+    /// From V2, renders the boost would push past full scale are normalized back to a peak of 1.0.
+    fn intensity_level(&self, mut samples: Vec<f32>) -> Vec<f32> {
+        if let Some(intensity) = self.get_intensity() {
+            apply_gain_db(&mut samples, (intensity - 0.5) * 8.0);
+            if self.generation_version >= GenerationVersion::V2 && samples.iter().any(|sample| sample.abs() > 1.0) {
+                normalize_samples(&mut samples, 1.0);
+            }
+        }
+        samples
+    }
+    
+    /// White noise with an exponential decay, low-passed from `cutoff` down to a rumble
+    fn noise_burst(&mut self, duration: f32, cutoff: f32) -> Vec<f32> {
+        let mut samples = self.white_noise(duration);
+        for (i, sample) in samples.iter_mut().enumerate() {
+            // Drop by roughly 60 dB over the duration
+            *sample *= (-6.9 * i as f32 / self.get_sample_rate() as f32 / duration).exp();
+        }
+        
+        let sweep = CutoffSweep::Envelope {
+            base: 80.0,
+            peak: cutoff,
+            adsr: ADSR { attack: 0.001, decay: duration * 0.5, sustain: 0.2, release: duration * 0.5 },
+            duration,
+        };
+        StateVariableFilter::new(SvfMode::LowPass, 0.9, self.get_sample_rate() as f32).process_sweep(&mut samples, &sweep);
+        samples
+    }
+    
+    /// Unshaped white noise at the oscillator headroom level, drawn from the generator's RNG
+    ///
+    /// In chiptune mode the noise comes from the chip's shift register instead.
+    fn white_noise(&mut self, duration: f32) -> Vec<f32> {
+        let length = (duration * self.get_sample_rate() as f32) as usize;
+        let gain = OSCILLATOR_HEADROOM * self.config().master_gain;
+        if self.get_chiptune() {
+            let mut channel = NoiseChannel::new(self.random_range(1..0x8000) as u16, 2);
+            return (0..length).map(|_| channel.next_sample() * gain).collect();
+        }
+        (0..length).map(|_| self.random_float_range(-1.0..=1.0) * gain).collect()
+    }
+    
+    /// A struck bell, or a decaying triangle blip on a single chip voice in chiptune mode
+    fn strike(&self, frequency: f32, duration: f32) -> Vec<f32> {
+        if self.get_chiptune() {
+            self.sine_sweep(frequency, frequency, duration, blip_envelope(duration), true)
+        } else {
+            self.bell_tone(frequency, duration)
+        }
+    }
+    
+    /// Record the chip channel actually played in place of the chosen waveform
+    fn chip_choices(&mut self) {
+        if self.get_chiptune() {
+            self.last_choices.waveform = self.last_choices.waveform.map(chiptune::chip_waveform);
+        }
+    }
+}
+
+/// Preset jingle types available in the library
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum JinglePreset {
+    Notification,
+    Success,
+    Alert,
+    Error,
+    Startup,
+    Shutdown,
+    Message,
+    Completion,
+    Ringtone,
+    Doorbell,
+    Reminder,
+    /// Pentatonic melody over sleigh bells, sometimes with a celesta timbre
+    Holiday,
+    /// Three ticks and an accented completion tone; the duration sets the tick interval
+    Countdown,
+    /// Warning at a severity level; ignores the requested waveform
+    Warning(Severity),
+    /// Micro interface sound; ignores the requested waveform
+    Ui(UiSound),
+    /// Retro game sound effect; ignores the requested waveform
+    Game(GameSfx),
+    /// Futuristic interface texture; ignores the requested waveform
+    SciFi(SciFiSound),
+}
+
+impl JinglePreset {
+    /// Generate samples for this preset using the specified waveform
+    pub fn generate(&self, generator: &mut JingleGenerator, waveform: WaveForm) -> Vec<f32> {
+        self.generate_with_params(generator, waveform, None, None)
+    }
+    
+    /// Generate samples for this preset from a per-call seed, independent of the generator's history
+    pub fn generate_seeded(&self, generator: &mut JingleGenerator, waveform: WaveForm, seed: u64) -> Vec<f32> {
+        generator.render(&self.spec(waveform, None, None).with_seed(seed))
+    }
+    
+    /// Generate samples for this preset with optional duration and frequency parameters
+    pub fn generate_with_params(
+        &self, 
+        generator: &mut JingleGenerator, 
+        waveform: WaveForm,
+        duration: Option<f32>,
+        frequency: Option<f32>
+    ) -> Vec<f32> {
+        generator.render(&self.spec(waveform, duration, frequency))
+    }
+    
+    /// Build a spec for this preset with optional duration and frequency parameters
+    pub fn spec(&self, waveform: WaveForm, duration: Option<f32>, frequency: Option<f32>) -> JingleSpec {
+        JingleSpec {
+            waveform,
+            duration,
+            frequency,
+            ..JingleSpec::new(*self)
+        }
+    }
+    
+    /// Get all available presets
+    pub fn all() -> Vec<JinglePreset> {
+        let mut presets = vec![
+            JinglePreset::Notification,
+            JinglePreset::Success,
+            JinglePreset::Alert,
+            JinglePreset::Error,
+            JinglePreset::Startup,
+            JinglePreset::Shutdown,
+            JinglePreset::Message,
+            JinglePreset::Completion,
+            JinglePreset::Ringtone,
+            JinglePreset::Doorbell,
+            JinglePreset::Reminder,
+            JinglePreset::Holiday,
+            JinglePreset::Countdown,
+        ];
+        presets.extend(Severity::all().into_iter().map(JinglePreset::Warning));
+        presets.extend(UiSound::all().into_iter().map(JinglePreset::Ui));
+        presets.extend(GameSfx::all().into_iter().map(JinglePreset::Game));
+        presets.extend(SciFiSound::all().into_iter().map(JinglePreset::SciFi));
+        presets
+    }
+    
+    /// Waveform, envelope, register and effects that characterize this preset
+    pub fn style(&self) -> PresetStyle {
+        let waveform = match self {
+            JinglePreset::Alert | JinglePreset::Game(_) => WaveForm::Square,
+            JinglePreset::Error => WaveForm::Sawtooth,
+            JinglePreset::Success | JinglePreset::Ringtone => WaveForm::Triangle,
+            JinglePreset::Warning(severity) => severity.waveform(),
+            _ => WaveForm::Sine,
+        };
+        let adsr = match self {
+            JinglePreset::Notification | JinglePreset::Message | JinglePreset::Countdown | JinglePreset::Ui(_) => {
+                ADSR { attack: 0.005, decay: 0.06, sustain: 0.5, release: 0.08 }
+            },
+            JinglePreset::Alert | JinglePreset::Warning(_) | JinglePreset::Game(_) => {
+                ADSR { attack: 0.002, decay: 0.03, sustain: 0.9, release: 0.03 }
+            },
+            JinglePreset::Error | JinglePreset::Shutdown => ADSR { attack: 0.03, decay: 0.15, sustain: 0.6, release: 0.3 },
+            JinglePreset::Doorbell => ADSR { attack: 0.002, decay: 0.4, sustain: 0.2, release: 0.8 },
+            JinglePreset::Holiday => ADSR { attack: 0.002, decay: 0.15, sustain: 0.4, release: 0.2 },
+            JinglePreset::SciFi(_) => ADSR { attack: 0.08, decay: 0.2, sustain: 0.7, release: 0.6 },
+            _ => ADSR::default(),
+        };
+        let effects = match self {
+            JinglePreset::Doorbell | JinglePreset::Ringtone | JinglePreset::Holiday => EffectChain::new().reverb(0.7, 0.5, 0.25),
+            JinglePreset::Success | JinglePreset::Startup | JinglePreset::Completion => EffectChain::new().reverb(0.5, 0.5, 0.15),
+            JinglePreset::Error | JinglePreset::Shutdown => EffectChain::new().lowpass(3000.0),
+            JinglePreset::Notification | JinglePreset::Message | JinglePreset::Reminder => EffectChain::new().echo(90.0, 0.2, 0.15),
+            JinglePreset::Alert | JinglePreset::Warning(_) => EffectChain::new().limiter(),
+            JinglePreset::Countdown | JinglePreset::Ui(_) | JinglePreset::Game(_) => EffectChain::new(),
+            JinglePreset::SciFi(_) => EffectChain::new().ring_mod(150.0, 0.3).shimmer(0.85, 0.5, 0.35),
+        };
+        // The octave holding the preset's default base frequency
+        let octave = (self.default_frequency() / Note::C.frequency(0)).log2().floor() as i32;
+        PresetStyle { waveform, adsr, octave, effects }
+    }
+    
+    /// Note density, direction, repetition and articulation characteristic of the preset
+    fn contour(&self) -> Contour {
+        // (notes at the default duration, direction, repetition, articulation)
+        let (notes, direction, repetition, articulation) = match self {
+            JinglePreset::Notification => (4.0, 0.5, 0.0, 1.0),
+            JinglePreset::Success => (6.0, 1.0, 0.0, 1.0),
+            JinglePreset::Alert => (3.0, 0.0, 1.0, 0.6),
+            JinglePreset::Error => (3.0, -1.0, 0.2, 1.0),
+            JinglePreset::Startup => (5.0, 1.0, 0.0, 1.0),
+            JinglePreset::Shutdown => (4.0, -1.0, 0.0, 1.0),
+            JinglePreset::Message => (2.0, 0.5, 0.0, 0.8),
+            JinglePreset::Completion => (3.0, 1.0, 0.0, 1.0),
+            JinglePreset::Ringtone => (40.0, 0.0, 0.1, 1.0),
+            JinglePreset::Doorbell => (2.0, -1.0, 0.0, 1.0),
+            JinglePreset::Reminder => (3.0, 0.0, 0.5, 0.7),
+            JinglePreset::Holiday => (7.0, 0.2, 0.1, 0.9),
+            JinglePreset::Countdown => (4.0, 0.0, 1.0, 0.3),
+            JinglePreset::Warning(severity) => {
+                let (beeps, gap) = severity.repetition();
+                (beeps as f32, 0.0, 1.0, 1.0 / (1.0 + gap))
+            },
+            JinglePreset::Ui(_) => (1.0, 0.0, 0.0, 1.0),
+            JinglePreset::Game(GameSfx::Coin) => (2.0, 1.0, 0.0, 1.0),
+            JinglePreset::Game(GameSfx::PowerUp) => (8.0, 1.0, 0.0, 1.0),
+            JinglePreset::Game(GameSfx::Jump) => (3.0, 1.0, 0.0, 1.0),
+            JinglePreset::Game(GameSfx::Laser | GameSfx::Explosion) => (3.0, -1.0, 0.0, 1.0),
+            JinglePreset::SciFi(SciFiSound::Sweep) => (4.0, 1.0, 0.0, 1.0),
+            JinglePreset::SciFi(SciFiSound::Zap) => (3.0, -1.0, 0.0, 1.0),
+            JinglePreset::SciFi(SciFiSound::Scan) => (6.0, 0.0, 0.3, 0.6),
+            JinglePreset::SciFi(SciFiSound::Shimmer) => (5.0, 0.2, 0.0, 1.0),
+        };
+        Contour { density: notes / self.default_duration(), direction, repetition, articulation }
+    }
+    
+    /// Look up a preset by the name returned from `name`
+    pub fn from_name(name: &str) -> Option<JinglePreset> {
+        Self::all().into_iter().find(|preset| preset.name() == name)
+    }
+    
+    /// Total duration in seconds used when a spec doesn't set one (the tick interval for countdowns)
+    pub fn default_duration(&self) -> f32 {
+        match self {
+            JinglePreset::Notification => 0.15,
+            JinglePreset::Success => 0.8,
+            JinglePreset::Alert => 0.25,
+            JinglePreset::Error => 1.25,
+            JinglePreset::Startup => 0.6,
+            JinglePreset::Shutdown => 1.6,
+            JinglePreset::Message => 0.25,
+            JinglePreset::Completion => 0.5,
+            JinglePreset::Ringtone => 8.0,
+            JinglePreset::Doorbell => 2.0,
+            JinglePreset::Reminder => 1.4,
+            JinglePreset::Holiday => 1.2,
+            JinglePreset::Countdown => 1.0,
+            JinglePreset::Warning(Severity::Low) => 0.5,
+            JinglePreset::Warning(Severity::Medium) => 0.9,
+            JinglePreset::Warning(Severity::High) => 1.4,
+            JinglePreset::Warning(Severity::Critical) => 2.2,
+            JinglePreset::Ui(UiSound::Click) => 0.008,
+            JinglePreset::Ui(UiSound::Tap) => 0.03,
+            JinglePreset::Ui(UiSound::Toggle) => 0.06,
+            JinglePreset::Ui(UiSound::Swipe) => 0.1,
+            JinglePreset::Game(GameSfx::Coin) => 0.35,
+            JinglePreset::Game(GameSfx::PowerUp) => 0.6,
+            JinglePreset::Game(GameSfx::Jump) => 0.25,
+            JinglePreset::Game(GameSfx::Laser) => 0.3,
+            JinglePreset::Game(GameSfx::Explosion) => 0.9,
+            JinglePreset::SciFi(SciFiSound::Sweep) => 1.6,
+            JinglePreset::SciFi(SciFiSound::Zap) => 0.5,
+            JinglePreset::SciFi(SciFiSound::Scan) => 1.2,
+            JinglePreset::SciFi(SciFiSound::Shimmer) => 2.5,
+        }
+    }
+    
+    /// Base frequency in Hz used when a spec doesn't set one
+    pub fn default_frequency(&self) -> f32 {
+        match self {
+            JinglePreset::Notification | JinglePreset::Message | JinglePreset::Reminder => Note::C.frequency(5),
+            JinglePreset::Success | JinglePreset::Startup | JinglePreset::Completion => Note::C.frequency(4),
+            JinglePreset::Alert => Note::G.frequency(6),
+            JinglePreset::Error => Note::D.frequency(5),
+            JinglePreset::Shutdown => Note::G.frequency(4),
+            JinglePreset::Ringtone | JinglePreset::Doorbell | JinglePreset::Holiday => Note::E.frequency(5),
+            JinglePreset::Countdown => Note::A.frequency(5),
+            JinglePreset::Warning(_) => Note::A.frequency(4),
+            JinglePreset::Ui(UiSound::Click) => 4000.0,
+            JinglePreset::Ui(UiSound::Tap) => Note::A.frequency(5),
+            JinglePreset::Ui(UiSound::Toggle) => Note::E.frequency(6),
+            JinglePreset::Ui(UiSound::Swipe) => 1200.0,
+            JinglePreset::Game(GameSfx::Coin) => Note::B.frequency(5),
+            JinglePreset::Game(GameSfx::PowerUp) | JinglePreset::Game(GameSfx::Jump) => Note::C.frequency(4),
+            JinglePreset::Game(GameSfx::Laser) => Note::A.frequency(6),
+            JinglePreset::Game(GameSfx::Explosion) => 3000.0,
+            JinglePreset::SciFi(SciFiSound::Sweep) => Note::A.frequency(3),
+            JinglePreset::SciFi(SciFiSound::Zap) => 2000.0,
+            JinglePreset::SciFi(SciFiSound::Scan) => Note::E.frequency(5),
+            JinglePreset::SciFi(SciFiSound::Shimmer) => Note::C.frequency(5),
+        }
+    }
+    
+    /// Get the name of this preset as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            JinglePreset::Notification => "notification",
+            JinglePreset::Success => "success",
+            JinglePreset::Alert => "alert",
+            JinglePreset::Error => "error",
+            JinglePreset::Startup => "startup",
+            JinglePreset::Shutdown => "shutdown",
+            JinglePreset::Message => "message",
+            JinglePreset::Completion => "completion",
+            JinglePreset::Ringtone => "ringtone",
+            JinglePreset::Doorbell => "doorbell",
+            JinglePreset::Reminder => "reminder",
+            JinglePreset::Holiday => "holiday",
+            JinglePreset::Countdown => "countdown",
+            JinglePreset::Warning(Severity::Low) => "warning-low",
+            JinglePreset::Warning(Severity::Medium) => "warning-medium",
+            JinglePreset::Warning(Severity::High) => "warning-high",
+            JinglePreset::Warning(Severity::Critical) => "warning-critical",
+            JinglePreset::Ui(sound) => sound.name(),
+            JinglePreset::Game(sfx) => sfx.name(),
+            JinglePreset::SciFi(sound) => sound.name(),
+        }
+    }
+    
+    /// Describe the preset in a few words, for listings and help text
+    pub fn description(&self) -> &'static str {
+        match self {
+            JinglePreset::Notification => "Pleasant notification from varied scales and patterns",
+            JinglePreset::Success => "Uplifting success sound with upward patterns",
+            JinglePreset::Alert => "Attention-grabbing alert",
+            JinglePreset::Error => "Error sound from minor patterns and dissonance",
+            JinglePreset::Startup => "Startup chime over a chord progression",
+            JinglePreset::Shutdown => "Gentle descending shutdown sound",
+            JinglePreset::Message => "Short pleasant message received sound",
+            JinglePreset::Completion => "Completion sound with a satisfying resolution",
+            JinglePreset::Ringtone => "Intro motif followed by a phrase that loops cleanly",
+            JinglePreset::Doorbell => "Two or three bell strikes stepping down by major intervals",
+            JinglePreset::Reminder => "Rising question phrase, a pause and a falling answer",
+            JinglePreset::Holiday => "Pentatonic melody over sleigh bells",
+            JinglePreset::Countdown => "Three ticks and an accented completion tone",
+            JinglePreset::Warning(Severity::Low) => "Low-urgency warning",
+            JinglePreset::Warning(Severity::Medium) => "Medium-urgency warning",
+            JinglePreset::Warning(Severity::High) => "High-urgency warning",
+            JinglePreset::Warning(Severity::Critical) => "Critical warning",
+            JinglePreset::Ui(sound) => sound.description(),
+            JinglePreset::Game(sfx) => sfx.description(),
+            JinglePreset::SciFi(sound) => sound.description(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SAMPLE_RATE;
+
+    #[test]
+    fn test_notification_jingle() {
+        let mut generator = JingleGenerator::new();
+        let samples = generator.create_notification_jingle(WaveForm::Sine, None, None);
+        assert!(!samples.is_empty());
+    }
+
+    #[test]
+    fn test_descriptions() {
+        for preset in JinglePreset::all() {
+            assert!(!preset.description().is_empty(), "{} has no description", preset.name());
+        }
+        assert_eq!(JinglePreset::Game(GameSfx::Laser).description(), "Fast downward pitch sweep");
+    }
+
+    #[test]
+    fn test_all_presets() {
+        let mut generator = JingleGenerator::new();
+        
+        for preset in JinglePreset::all() {
+            let samples = preset.generate(&mut generator, WaveForm::Sine);
+            assert!(!samples.is_empty(), "Preset {} should generate samples", preset.name());
+        }
+    }
+
+    #[test]
+    fn test_presets_record_choices() {
+        let mut generator = JingleGenerator::with_seed(3);
+        
+        generator.create_notification_jingle(WaveForm::Sine, None, None);
+        assert!(generator.last_choices().scale.is_some());
+        assert!(generator.last_choices().pattern.is_some());
+        
+        generator.create_startup_jingle(WaveForm::Sine, None, None);
+        assert!(generator.last_choices().progression.is_some());
+        assert!(generator.last_choices().scale.is_none());
+    }
+
+    #[test]
+    fn test_render_spec_overrides() {
+        let mut generator = JingleGenerator::new();
+        let spec = JingleSpec::new(JinglePreset::Notification)
+            .with_scale(Scale::Minor)
+            .with_pattern(MelodyPattern::Descending)
+            .with_seed(12);
+        let first = generator.render(&spec);
+        assert_eq!(generator.last_choices().scale, Some(Scale::Minor));
+        assert_eq!(generator.last_choices().pattern, Some(MelodyPattern::Descending));
+        
+        // The seed makes every render of the spec identical, whatever was rendered in between
+        generator.create_message_jingle(WaveForm::Sine, None, None);
+        assert_eq!(generator.render(&spec), first);
+        
+        // Wrappers render the same as an equivalent spec
+        let wrapped = JingleGenerator::with_seed(12).create_alert_jingle(WaveForm::Square, Some(0.4), None);
+        let rendered = JingleGenerator::new().render(&JinglePreset::Alert.spec(WaveForm::Square, Some(0.4), None).with_seed(12));
+        assert_eq!(wrapped, rendered);
+    }
+
+    #[test]
+    fn test_seeded_generation_ignores_history() {
+        let mut fresh = JingleGenerator::with_seed(1);
+        let mut used = JingleGenerator::with_seed(1);
+        for preset in JinglePreset::all() {
+            used.create_error_jingle(WaveForm::Sine, None, None);
+            assert_eq!(preset.generate_seeded(&mut used, WaveForm::Triangle, 77), preset.generate_seeded(&mut fresh, WaveForm::Triangle, 77));
+        }
+        
+        // The generator's own sequence continues where it left off
+        let mut reference = JingleGenerator::with_seed(5);
+        let mut interrupted = JingleGenerator::with_seed(5);
+        reference.create_success_jingle(WaveForm::Sine, None, None);
+        interrupted.create_success_jingle(WaveForm::Sine, None, None);
+        JinglePreset::Alert.generate_seeded(&mut interrupted, WaveForm::Sine, 9);
+        assert_eq!(interrupted.get_current_seed(), Some(5));
+        assert_eq!(interrupted.create_success_jingle(WaveForm::Sine, None, None), reference.create_success_jingle(WaveForm::Sine, None, None));
+    }
+
+    #[test]
+    fn test_render_as() {
+        let motif = Melody { notes: vec![(Note::E, 0.1), (Note::G, 0.1), (Note::C, 0.2)] };
+        assert_eq!(JinglePreset::Success.style().octave, 4);
+        assert_eq!(JinglePreset::Alert.style().octave, 6);
+        
+        let mut generator = JingleGenerator::with_seed(8);
+        let success = generator.render_as(JinglePreset::Success, &motif);
+        assert_eq!(success.len(), (0.4 * SAMPLE_RATE as f32) as usize);
+        assert_eq!(generator.last_choices().note_count, Some(3));
+        let alert = generator.render_as(JinglePreset::Alert, &motif);
+        assert_ne!(success, alert);
+        
+        // The generator's own envelope is left alone
+        assert_eq!(generator.get_adsr().attack, ADSR::default().attack);
+        assert_eq!(JingleGenerator::with_seed(8).render_as(JinglePreset::Success, &motif), success);
+    }
+
+    #[test]
+    fn test_audio_logo() {
+        let logo = JingleGenerator::with_seed(1).create_audio_logo("Acme Rockets");
+        let mut busy = JingleGenerator::new();
+        busy.create_success_jingle(WaveForm::Square, None, None);
+        assert_eq!(busy.create_audio_logo("Acme Rockets"), logo);
+        assert_ne!(busy.create_audio_logo("Acme Rocket"), logo);
+        
+        let seconds = logo.len() as f32 / SAMPLE_RATE as f32;
+        assert!((1.0..=2.0).contains(&seconds), "{}", seconds);
+        assert!(logo.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_morph() {
+        // At the ends only one preset is heard, padded to the longer render
+        let audible = |samples: Vec<f32>| {
+            let end = samples.iter().rposition(|&s| s != 0.0).map_or(0, |i| i + 1);
+            samples[..end].to_vec()
+        };
+        let only_a = JingleGenerator::with_seed(4).morph(JinglePreset::Notification, JinglePreset::Alert, 0.0);
+        let same_a = JingleGenerator::with_seed(4).morph(JinglePreset::Notification, JinglePreset::Error, 0.0);
+        assert_eq!(audible(only_a.clone()), audible(same_a));
+        let only_b = JingleGenerator::with_seed(4).morph(JinglePreset::Notification, JinglePreset::Alert, 1.0);
+        assert_eq!(audible(only_b.clone()), audible(JingleGenerator::with_seed(4).morph(JinglePreset::Error, JinglePreset::Alert, 1.0)));
+        
+        let hybrid = JingleGenerator::with_seed(4).morph(JinglePreset::Notification, JinglePreset::Alert, 0.5);
+        assert!(hybrid != only_a && hybrid != only_b);
+        assert!(hybrid.iter().all(|s| s.abs() <= 1.0));
+        assert!(hybrid.iter().any(|&s| s != 0.0));
+        
+        // Hybrids follow the interpolated duration
+        let short = JingleGenerator::with_seed(4).morph(JinglePreset::Notification, JinglePreset::Shutdown, 0.0);
+        let long = JingleGenerator::with_seed(4).morph(JinglePreset::Notification, JinglePreset::Shutdown, 0.8);
+        assert!(long.len() > short.len());
+    }
+
+    #[test]
+    fn test_morph_interpolates_structure() {
+        // One melody whose note count sits between the sparse and the dense preset
+        let note_count = |t: f32| {
+            let mut generator = JingleGenerator::with_seed(6);
+            generator.morph(JinglePreset::Success, JinglePreset::Ringtone, t);
+            generator.last_choices().note_count.unwrap()
+        };
+        assert_eq!(note_count(0.0), 6);
+        assert_eq!(note_count(1.0), 40);
+        assert!((7..40).contains(&note_count(0.5)));
+        
+        // A seeded spec composes the same hybrid regardless of the generator's history
+        let a = JingleSpec::new(JinglePreset::Startup).with_waveform(WaveForm::Triangle).with_seed(11);
+        let b = JingleSpec::new(JinglePreset::Error).with_waveform(WaveForm::Sawtooth);
+        let first = JingleGenerator::with_seed(1).morph_specs(&a, &b, 0.4);
+        let second = JingleGenerator::with_seed(2).morph_specs(&a, &b, 0.4);
+        assert_eq!(first, second);
+        assert!(first.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_render_spec_adsr_and_effects() {
+        let percussive = ADSR { attack: 0.001, decay: 0.01, sustain: 0.0, release: 0.001 };
+        let spec = JingleSpec::new(JinglePreset::Success).with_adsr(percussive).with_seed(3);
+        let mut generator = JingleGenerator::new();
+        let plucked = generator.render(&spec);
+        
+        // Notes fall silent after their decay, and the generator envelope is restored
+        assert!(plucked.iter().filter(|&&s| s == 0.0).count() > plucked.len() / 2);
+        assert_eq!(generator.get_adsr().attack, ADSR::default().attack);
+        
+        let louder = generator.render(&spec.clone().with_effects(crate::chain::EffectChain::new().gain(6.0)));
+        assert_eq!(louder.len(), plucked.len());
+        assert!(louder.iter().zip(&plucked).all(|(&loud, &quiet)| (loud - quiet * 2.0).abs() < 0.01));
+    }
+
+    #[test]
+    fn test_intensity_shapes_presets() {
+        let peak = |samples: &[f32]| samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+        
+        // A neutral intensity renders exactly like no intensity
+        let plain = JingleGenerator::with_seed(21).create_success_jingle(WaveForm::Sine, None, None);
+        let mut neutral = JingleGenerator::with_seed(21);
+        neutral.set_intensity(Some(0.5));
+        assert_eq!(neutral.create_success_jingle(WaveForm::Sine, None, None), plain);
+        
+        let mut calm = JingleGenerator::with_seed(21);
+        calm.set_intensity(Some(0.0));
+        let quiet = calm.create_success_jingle(WaveForm::Sine, None, None);
+        let calm_choices = calm.last_choices().clone();
+        
+        let mut urgent = JingleGenerator::with_seed(21);
+        urgent.set_intensity(Some(1.0));
+        let loud = urgent.create_success_jingle(WaveForm::Sine, None, None);
+        
+        assert!(loud.len() < quiet.len());
+        assert!(peak(&loud) > peak(&quiet));
+        assert_eq!(calm_choices.waveform, Some(WaveForm::Sine));
+        assert_eq!(urgent.last_choices().waveform, Some(WaveForm::Square));
+    }
+
+    #[test]
+    fn test_full_intensity_stays_in_range() {
+        for preset in JinglePreset::all() {
+            let mut generator = JingleGenerator::with_seed(5);
+            generator.set_intensity(Some(1.0));
+            let samples = preset.generate(&mut generator, WaveForm::Square);
+            let peak = samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+            assert!(peak <= 1.0, "{} peaks at {}", preset.name(), peak);
+        }
+    }
+
+    #[test]
+    fn test_ringtone_loops() {
+        for seed in 0..8 {
+            let (samples, _) = JingleGenerator::with_seed(seed).create_ringtone_jingle_with_loop(WaveForm::Sine, Some(15.0), None);
+            assert!(samples.len() as f32 / SAMPLE_RATE as f32 <= 15.0);
+        }
+
+        let mut generator = JingleGenerator::with_seed(8);
+        let (samples, region) = generator.create_ringtone_jingle_with_loop(WaveForm::Sine, None, None);
+        
+        let duration = samples.len() as f32 / SAMPLE_RATE as f32;
+        assert!((5.0..=15.0).contains(&duration));
+        assert!(region.validate(samples.len()).is_ok());
+        assert!(region.start > 0);
+        assert_eq!(region.end as usize, samples.len());
+        
+        // The loop ends in silence, so wrapping back to the start doesn't click
+        assert_eq!(samples[samples.len() - 1], 0.0);
+    }
+
+    #[test]
+    fn test_doorbell_rings_out() {
+        let mut generator = JingleGenerator::with_seed(2);
+        let samples = generator.create_doorbell_jingle(WaveForm::Sine, Some(2.0), None);
+        assert!(samples.iter().all(|s| s.abs() <= 1.0));
+        assert!(matches!(generator.last_choices().note_count, Some(2) | Some(3)));
+        
+        // Long decay: still audible in the middle, quiet by the end
+        let level = |range: std::ops::Range<usize>| samples[range].iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+        let middle = samples.len() / 2;
+        assert!(level(middle..middle + 1000) > 0.01);
+        assert!(level(samples.len() - 1000..samples.len()) < level(middle..middle + 1000));
+    }
+
+    #[test]
+    fn test_reminder_has_pause() {
+        let mut generator = JingleGenerator::with_seed(6);
+        let samples = generator.create_reminder_jingle(WaveForm::Sine, Some(1.6), None);
+        assert_eq!(generator.last_choices().note_count, Some(6));
+        
+        // A run of silence separates the question from the answer
+        let longest_silence = samples.split(|&s| s != 0.0).map(|run| run.len()).max().unwrap_or(0);
+        assert!(longest_silence as f32 / SAMPLE_RATE as f32 > 0.3);
+        assert!(samples.iter().all(|s| s.abs() < 0.25));
+    }
+
+    #[test]
+    fn test_countdown_ticks() {
+        let mut generator = JingleGenerator::with_seed(1);
+        let samples = generator.create_countdown_jingle(WaveForm::Sine, 4, 0.5, false);
+        let interval = (0.5 * SAMPLE_RATE as f32) as usize;
+        assert_eq!(samples.len(), 4 * interval + (0.5 * SAMPLE_RATE as f32) as usize);
+        assert_eq!(generator.last_choices().note_count, Some(5));
+        
+        // Each tick starts on the grid and is followed by silence
+        for i in 0..4 {
+            let start = i * interval;
+            assert!(samples[start..start + 200].iter().any(|&s| s != 0.0));
+            assert!(samples[start + interval / 2..start + interval].iter().all(|&s| s == 0.0));
+        }
+        
+        let accented = generator.create_countdown_jingle(WaveForm::Sine, 4, 0.5, true);
+        assert!(accented.len() > samples.len());
+    }
+
+    #[test]
+    fn test_warning_severity_escalates() {
+        let mut generator = JingleGenerator::with_seed(4);
+        let mut previous_length = 0;
+        let mut previous_count = 0;
+        for severity in Severity::all() {
+            let samples = generator.create_warning_jingle(severity, None, None);
+            let count = generator.last_choices().note_count.unwrap();
+            assert!(samples.len() > previous_length, "{} should be longer", severity.name());
+            assert!(count > previous_count);
+            assert!(samples.iter().all(|s| s.abs() <= 1.0));
+            previous_length = samples.len();
+            previous_count = count;
+        }
+        assert_eq!(JinglePreset::Warning(Severity::High).name(), "warning-high");
+    }
+
+    #[test]
+    fn test_ui_sounds_are_short() {
+        let mut generator = JingleGenerator::with_seed(9);
+        for sound in UiSound::all() {
+            let samples = generator.create_ui_sound(sound, None, None);
+            assert!(!samples.is_empty(), "{} should generate samples", sound.name());
+            assert!(samples.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+            
+            let long = generator.create_ui_sound(sound, Some(1.0), None);
+            assert!(long.len() <= (UI_SOUND_MAX_DURATION * SAMPLE_RATE as f32) as usize);
+        }
+    }
+
+    #[test]
+    fn test_game_sfx() {
+        let mut generator = JingleGenerator::with_seed(11);
+        for sfx in GameSfx::all() {
+            let samples = generator.create_game_sfx(sfx, None, None);
+            assert!(!samples.is_empty(), "{} should generate samples", sfx.name());
+            assert!(samples.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+        }
+        
+        // Noise bursts come from the generator's RNG, so seeds reproduce them
+        let first = JingleGenerator::with_seed(5).create_game_sfx(GameSfx::Explosion, None, None);
+        let second = JingleGenerator::with_seed(5).create_game_sfx(GameSfx::Explosion, None, None);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_alarm_escalates() {
+        let mut generator = JingleGenerator::with_seed(2);
+        let (samples, regions) = generator.create_alarm_jingle(3, 2.0);
+        assert_eq!(regions.len(), 3);
+        for region in &regions {
+            region.validate(samples.len()).unwrap();
+            // Loops start and end in silence
+            assert_eq!(samples[region.start as usize], 0.0);
+            assert_eq!(samples[region.end as usize - 1], 0.0);
+        }
+        
+        let cycle = |region: &LoopRegion| &samples[region.start as usize..region.end as usize];
+        // Each stage repeats faster than the last
+        assert!(cycle(&regions[1]).len() < cycle(&regions[0]).len());
+        assert!(cycle(&regions[2]).len() < cycle(&regions[1]).len());
+        // The cycle after the loop region is identical, so looping it is seamless
+        let first = &regions[0];
+        let length = (first.end - first.start) as usize;
+        assert_eq!(cycle(first), &samples[first.end as usize..first.end as usize + length]);
+        // Stages follow each other without gaps
+        assert!(regions.windows(2).all(|pair| pair[0].end <= pair[1].start));
+        assert!(samples.len() as f32 >= 5.0 * SAMPLE_RATE as f32);
+    }
+
+    #[test]
+    fn test_holiday_jingle() {
+        let mut generator = JingleGenerator::with_seed(21);
+        let mut timbres = Vec::new();
+        for _ in 0..8 {
+            let samples = generator.create_holiday_jingle(WaveForm::Triangle, None, None);
+            assert!(samples.iter().all(|s| s.is_finite() && s.abs() <= 1.0));
+            assert_eq!(generator.last_choices().scale, Some(Scale::Pentatonic));
+            timbres.push(generator.last_choices().waveform);
+        }
+        // Some renders use the celesta, the rest the requested waveform
+        assert!(timbres.contains(&None) && timbres.contains(&Some(WaveForm::Triangle)));
+        
+        // The bells alone are silent between shakes and accented on the beat
+        let eighth = 0.1;
+        let bells = generator.sleigh_bells(0.4, eighth);
+        let peak = |from: f32, to: f32| bells[(from * SAMPLE_RATE as f32) as usize..(to * SAMPLE_RATE as f32) as usize]
+            .iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+        assert!(peak(0.0, 0.03) > peak(0.08, 0.1) * 4.0);
+        assert!(peak(0.0, 0.03) > peak(0.1, 0.13));
+    }
+
+    #[test]
+    fn test_scifi_sounds() {
+        let mut generator = JingleGenerator::with_seed(13);
+        for sound in SciFiSound::all() {
+            let samples = generator.create_scifi_sound(sound, None, None);
+            let preset = JinglePreset::SciFi(sound);
+            assert!(samples.len() as f32 >= preset.default_duration() * 0.85 * SAMPLE_RATE as f32, "{} is too short", sound.name());
+            assert!(samples.iter().all(|s| s.is_finite() && s.abs() <= 1.0), "{} clips", sound.name());
+            // The shimmer tail fades out rather than stopping abruptly
+            assert!(samples.last().unwrap().abs() < 1e-3, "{} is cut off", sound.name());
+            assert_eq!(JinglePreset::from_name(sound.name()), Some(preset));
+        }
+        
+        let first = JingleGenerator::with_seed(5).create_scifi_sound(SciFiSound::Zap, Some(0.4), None);
+        let second = JingleGenerator::with_seed(5).create_scifi_sound(SciFiSound::Zap, Some(0.4), None);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_pulse_sweep_duty() {
+        let samples = JingleGenerator::new().pulse_sweep(441.0, 441.0, 0.25, 0.1, ADSR { attack: 0.0, decay: 0.0, sustain: 1.0, release: 0.0 }, false);
+        let high = samples.iter().filter(|&&s| s > 0.0).count() as f32;
+        assert!((high / samples.len() as f32 - 0.25).abs() < 0.02);
     }
 
     #[test]
@@ -397,5 +2496,6 @@ mod tests {
         assert_eq!(JinglePreset::Notification.name(), "notification");
         assert_eq!(JinglePreset::Success.name(), "success");
         assert_eq!(JinglePreset::Alert.name(), "alert");
+        assert_eq!(JinglePreset::Game(GameSfx::PowerUp).name(), "power-up");
     }
 }
\ No newline at end of file
@@ -0,0 +1,142 @@
+//! Structured progress events naming the pipeline phase a render is in
+//!
+//! `JingleGenerator::with_progress` reports bare `(done, total)` counts. Events
+//! registered with `with_progress_events` also say what is being counted, so an
+//! application can show "Applying effects… 100%" instead of a spinner.
+//!
+//! ```rust
+//! use std::sync::{Arc, Mutex};
+//! use jinglebells::{JingleGenerator, JinglePreset, JingleSpec, EffectChain, ProgressPhase};
+//!
+//! let phases = Arc::new(Mutex::new(Vec::new()));
+//! let recorded = phases.clone();
+//! let mut generator = JingleGenerator::with_seed(1)
+//!     .with_progress_events(move |event| recorded.lock().unwrap().push(event.phase));
+//! let spec = JingleSpec::new(JinglePreset::Success).with_effects(EffectChain::new().reverb(0.5, 0.5, 0.2));
+//! generator.render(&spec);
+//! assert!(phases.lock().unwrap().contains(&ProgressPhase::Effects));
+//! ```
+
+use std::sync::Arc;
+use crate::export::JingleGenerator;
+
+/// Stage of the render pipeline a progress event belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProgressPhase {
+    /// Choosing notes, rhythm and timbre for a preset
+    Composing,
+    /// Synthesizing notes and sounds
+    Rendering,
+    /// Running the effect chain
+    Effects,
+    /// Encoding and writing files
+    Exporting,
+}
+
+impl ProgressPhase {
+    /// Lowercase name for display or logs (e.g. "effects")
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProgressPhase::Composing => "composing",
+            ProgressPhase::Rendering => "rendering",
+            ProgressPhase::Effects => "effects",
+            ProgressPhase::Exporting => "exporting",
+        }
+    }
+}
+
+/// One progress update: `done` of `total` units of work within a phase
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub phase: ProgressPhase,
+    pub done: usize,
+    pub total: usize,
+}
+
+impl ProgressEvent {
+    /// Completed fraction of the phase (0.0 - 1.0); a phase with no work counts as complete
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 { 1.0 } else { (self.done as f32 / self.total as f32).min(1.0) }
+    }
+
+    /// Completed percentage of the phase (0.0 - 100.0)
+    pub fn percent(&self) -> f32 {
+        self.fraction() * 100.0
+    }
+}
+
+/// Callback receiving structured progress events
+pub type ProgressEventCallback = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+impl JingleGenerator {
+    /// Report structured progress events while composing, rendering, applying effects and exporting
+    ///
+    /// Receives every update `with_progress` does, tagged with its phase, plus
+    /// the start and end of composing and effect processing in `render`.
+    pub fn with_progress_events<F: Fn(&ProgressEvent) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.progress_events = Some(Arc::new(callback));
+        self
+    }
+
+    /// Send an event to the structured progress callback only, if any
+    pub(crate) fn report_phase(&self, phase: ProgressPhase, done: usize, total: usize) {
+        if let Some(progress_events) = &self.progress_events {
+            progress_events(&ProgressEvent { phase, done, total });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::{audio::WaveForm, chain::EffectChain, music::{Melody, Note}, presets::JinglePreset, spec::JingleSpec};
+
+    fn recording() -> (JingleGenerator, Arc<Mutex<Vec<ProgressEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let generator = JingleGenerator::with_seed(5).with_progress_events(move |event| recorded.lock().unwrap().push(*event));
+        (generator, events)
+    }
+
+    #[test]
+    fn test_event_fraction() {
+        let event = ProgressEvent { phase: ProgressPhase::Rendering, done: 1, total: 4 };
+        assert_eq!(event.fraction(), 0.25);
+        assert_eq!(event.percent(), 25.0);
+        assert_eq!(ProgressEvent { total: 0, ..event }.fraction(), 1.0);
+        assert_eq!(ProgressPhase::Exporting.name(), "exporting");
+    }
+
+    #[test]
+    fn test_render_phases() {
+        let (mut generator, events) = recording();
+        let spec = JingleSpec::new(JinglePreset::Notification).with_effects(EffectChain::new().echo(50.0, 0.3, 0.3));
+        generator.render(&spec);
+        let events = events.lock().unwrap();
+        let phases: Vec<ProgressPhase> = events.iter().map(|event| event.phase).collect();
+        assert_eq!(phases.first(), Some(&ProgressPhase::Composing));
+        assert_eq!(events.last(), Some(&ProgressEvent { phase: ProgressPhase::Effects, done: 1, total: 1 }));
+        let composed = phases.iter().position(|&phase| phase != ProgressPhase::Composing).unwrap();
+        let effects = phases.iter().position(|&phase| phase == ProgressPhase::Effects).unwrap();
+        assert!(composed <= effects);
+    }
+
+    #[test]
+    fn test_melody_and_export_phases() {
+        let (generator, events) = recording();
+        let melody = Melody { notes: vec![(Note::C, 0.05), (Note::E, 0.05)] };
+        let samples = generator.generate_melody_samples(&melody, 5, WaveForm::Sine);
+        let path = std::env::temp_dir().join("jinglebells_test_progress_events.wav");
+        generator.export_to_file(&samples, &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[..2], [
+            ProgressEvent { phase: ProgressPhase::Rendering, done: 1, total: 2 },
+            ProgressEvent { phase: ProgressPhase::Rendering, done: 2, total: 2 },
+        ]);
+        assert_eq!(events.last().unwrap().phase, ProgressPhase::Exporting);
+        assert_eq!(events.last().unwrap().fraction(), 1.0);
+    }
+}
@@ -0,0 +1,136 @@
+//! Application-defined presets registered by name
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::{export::JingleGenerator, spec::JingleSpec};
+
+/// Render function for a custom preset
+///
+/// The spec carries the caller's waveform, duration, frequency and other overrides;
+/// its `preset` field is whatever the caller built the spec with and can be ignored.
+pub type PresetFn = Arc<dyn Fn(&mut JingleGenerator, &JingleSpec) -> Vec<f32> + Send + Sync>;
+
+/// Custom presets looked up by name
+///
+/// Register presets with `JingleGenerator::register_preset` and render them,
+/// or any built-in preset, with `JingleGenerator::render_named`.
+///
+/// ```rust
+/// use jinglebells::{JingleGenerator, JinglePreset, JingleSpec};
+///
+/// let mut generator = JingleGenerator::with_seed(1);
+/// generator.register_preset("double-beep", |generator, spec| {
+///     let frequency = spec.frequency.unwrap_or(880.0);
+///     let mut samples = generator.generate_tone(frequency, 0.1, spec.waveform);
+///     samples.extend(generator.generate_tone(frequency, 0.1, spec.waveform));
+///     samples
+/// });
+///
+/// let spec = JingleSpec::new(JinglePreset::Notification).with_frequency(660.0);
+/// let samples = generator.render_named("double-beep", &spec).unwrap();
+/// assert!(!samples.is_empty());
+/// ```
+#[derive(Clone, Default)]
+pub struct PresetRegistry {
+    presets: HashMap<String, PresetFn>,
+}
+
+impl PresetRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a preset under a name, replacing any preset already registered with it
+    pub fn register<F>(&mut self, name: &str, render: F)
+    where
+        F: Fn(&mut JingleGenerator, &JingleSpec) -> Vec<f32> + Send + Sync + 'static,
+    {
+        self.presets.insert(name.to_string(), Arc::new(render));
+    }
+
+    /// Remove a registered preset, returning whether it existed
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.presets.remove(name).is_some()
+    }
+
+    /// Get the render function registered under a name
+    pub fn get(&self, name: &str) -> Option<PresetFn> {
+        self.presets.get(name).cloned()
+    }
+
+    /// Check whether a preset is registered under a name
+    pub fn contains(&self, name: &str) -> bool {
+        self.presets.contains_key(name)
+    }
+
+    /// Names of all registered presets in alphabetical order
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Number of registered presets
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    /// Check whether no presets are registered
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+}
+
+impl std::fmt::Debug for PresetRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PresetRegistry").field("names", &self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JinglePreset, EffectChain, WaveForm};
+
+    fn silence(_: &mut JingleGenerator, spec: &JingleSpec) -> Vec<f32> {
+        vec![0.25; (spec.duration.unwrap_or(0.1) * crate::SAMPLE_RATE as f32) as usize]
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = PresetRegistry::new();
+        assert!(registry.is_empty());
+        registry.register("pad", silence);
+        registry.register("beep", silence);
+        assert_eq!(registry.names(), vec!["beep", "pad"]);
+        assert!(registry.contains("pad"));
+        assert!(registry.unregister("pad"));
+        assert!(!registry.unregister("pad"));
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn test_render_named() {
+        let mut generator = JingleGenerator::with_seed(5);
+        generator.register_preset("flat", silence);
+        
+        let spec = JingleSpec::new(JinglePreset::Success).with_duration(0.5);
+        let samples = generator.render_named("flat", &spec).unwrap();
+        assert_eq!(samples.len(), crate::SAMPLE_RATE as usize / 2);
+        
+        // Effects from the spec run after custom presets too
+        let quiet = spec.clone().with_effects(EffectChain::new().gain(-6.0));
+        assert!(generator.render_named("flat", &quiet).unwrap()[100] < 0.25);
+        
+        // Built-in names still resolve, and registered names shadow them
+        let builtin = generator.derive_seed(0).unwrap().render_named("alert", &spec).unwrap();
+        assert_eq!(builtin, generator.derive_seed(0).unwrap().render(&JingleSpec { preset: JinglePreset::Alert, ..spec.clone() }));
+        generator.register_preset("alert", silence);
+        assert_eq!(generator.render_named("alert", &spec).unwrap(), samples);
+        
+        // Registrations carry over to derived generators
+        assert!(generator.derive_seed(1).unwrap().presets().contains("flat"));
+        assert!(generator.render_named("fanfare", &spec.with_waveform(WaveForm::Square)).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Pull-based rendering in fixed-size blocks, for feeding a real-time audio callback
+//!
+//! A `Renderer` synthesizes only as many samples as each call asks for, so a
+//! jingle can start playing before the rest of it exists.
+
+use rodio::{buffer::SamplesBuffer, source::Source};
+use crate::{
+    audio::WaveForm,
+    music::Melody,
+    chain::{EffectChain, EffectSource},
+    export::JingleGenerator,
+    presets::JinglePreset,
+    spec::JingleSpec,
+};
+
+/// Mono audio generated incrementally with `next_block`
+pub struct Renderer {
+    source: EffectSource,
+    sample_rate: u32,
+    position: usize,
+    finished: bool,
+}
+
+impl Renderer {
+    /// Render from any mono source, such as an oscillator or a `MelodySource`
+    pub fn new<S>(source: S) -> Self
+    where
+        S: Source + Send + 'static,
+    {
+        Self {
+            sample_rate: source.sample_rate(),
+            source: Box::new(source),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Play back samples rendered ahead of time, e.g. by a preset
+    pub fn from_samples(samples: Vec<f32>, sample_rate: u32) -> Self {
+        Self::new(SamplesBuffer::new(1, sample_rate, samples))
+    }
+
+    /// Run the audio through an effect chain as it is rendered
+    pub fn with_effects(mut self, chain: &EffectChain) -> Self {
+        self.source = chain.apply(self.source);
+        self
+    }
+
+    /// Fill a block with the next samples, returning how many came from the jingle
+    ///
+    /// Once the jingle ends the rest of the block is filled with silence, so the
+    /// whole block can always be handed to the audio device. Does not allocate.
+    pub fn next_block(&mut self, out: &mut [f32]) -> usize {
+        let mut written = 0;
+        if !self.finished {
+            for slot in out.iter_mut() {
+                match self.source.next() {
+                    Some(sample) => {
+                        *slot = sample;
+                        written += 1;
+                    },
+                    None => {
+                        self.finished = true;
+                        break;
+                    },
+                }
+            }
+        }
+        out[written..].fill(0.0);
+        self.position += written;
+        written
+    }
+
+    /// Check whether every sample of the jingle has been rendered
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Get the rate (Hz) of the rendered samples
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Get the number of samples rendered so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+impl JingleGenerator {
+    /// Render a melody block by block with the generator's tuning, envelope and output settings
+    pub fn melody_renderer(&self, melody: &Melody, octave: i32, waveform: WaveForm) -> Renderer {
+        Renderer::new(self.melody_source(melody, octave, waveform))
+    }
+
+    /// Wrap samples rendered by this generator as a mono rodio source at its sample rate
+    pub fn samples_source(&self, samples: Vec<f32>) -> SamplesBuffer {
+        SamplesBuffer::new(1, self.get_sample_rate(), samples)
+    }
+
+    /// Render a preset as a mono rodio source, ready for a sink or the effect wrappers
+    ///
+    /// ```rust
+    /// use jinglebells::{Echo, JingleGenerator, JinglePreset, WaveForm};
+    ///
+    /// let mut generator = JingleGenerator::with_seed(3);
+    /// let source = Echo::new(generator.render_source(JinglePreset::Success, WaveForm::Sine), 120.0, 0.3, 0.25);
+    /// assert!(source.count() > 0);
+    /// ```
+    pub fn render_source(&mut self, preset: JinglePreset, waveform: WaveForm) -> impl Source<Item = f32> + Send + 'static {
+        let samples = preset.generate(self, waveform);
+        self.samples_source(samples)
+    }
+
+    /// Render a spec (see `render`) as a mono rodio source
+    pub fn render_spec_source(&mut self, spec: &JingleSpec) -> impl Source<Item = f32> + Send + 'static {
+        let samples = self.render(spec);
+        self.samples_source(samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SAMPLE_RATE, music::{Note, Scale, MelodyPattern}};
+
+    #[test]
+    fn test_blocks_match_source() {
+        let generator = JingleGenerator::new();
+        let melody = Melody::from_scale(Scale::Major, Note::C, 4, MelodyPattern::Ascending, 0.05);
+        let expected: Vec<f32> = generator.melody_source(&melody, 5, WaveForm::Triangle).collect();
+
+        let mut renderer = generator.melody_renderer(&melody, 5, WaveForm::Triangle);
+        let mut block = [1.0; 256];
+        let mut rendered = Vec::new();
+        while !renderer.is_finished() {
+            let written = renderer.next_block(&mut block);
+            rendered.extend_from_slice(&block[..written]);
+            assert!(block[written..].iter().all(|&s| s == 0.0));
+        }
+        assert_eq!(rendered, expected);
+        assert_eq!(renderer.position(), expected.len());
+        assert_eq!(renderer.next_block(&mut block), 0);
+        assert_eq!(renderer.sample_rate(), SAMPLE_RATE);
+    }
+
+    #[test]
+    fn test_renderer_with_effects() {
+        let samples = JingleGenerator::with_seed(2).generate_tone(660.0, 0.05, WaveForm::Sawtooth);
+        let chain = EffectChain::new().echo(10.0, 0.3, 0.4).lowpass(2000.0);
+
+        let mut renderer = Renderer::from_samples(samples.clone(), SAMPLE_RATE).with_effects(&chain);
+        let mut block = vec![0.0; samples.len() + 10];
+        assert_eq!(renderer.next_block(&mut block), samples.len());
+        assert_eq!(block[..samples.len()], chain.process(samples)[..]);
+        assert!(renderer.is_finished());
+    }
+
+    #[test]
+    fn test_preset_sources() {
+        let expected = JinglePreset::Alert.generate(&mut JingleGenerator::with_seed(4), WaveForm::Square);
+        let source = JingleGenerator::with_seed(4).render_source(JinglePreset::Alert, WaveForm::Square);
+        assert_eq!((source.channels(), source.sample_rate()), (1, SAMPLE_RATE));
+        assert_eq!(source.collect::<Vec<f32>>(), expected);
+
+        let spec = JingleSpec::new(JinglePreset::Notification).with_seed(9);
+        let expected = JingleGenerator::new().render(&spec);
+        assert_eq!(JingleGenerator::new().render_spec_source(&spec).collect::<Vec<f32>>(), expected);
+    }
+}
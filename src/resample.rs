@@ -0,0 +1,133 @@
+//! Sample rate conversion using windowed-sinc interpolation
+
+use std::f32::consts::PI;
+
+/// Windowed-sinc resampler converting between two fixed sample rates
+#[derive(Clone, Debug)]
+pub struct Resampler {
+    from_rate: u32,
+    to_rate: u32,
+    half_width: usize,
+}
+
+impl Resampler {
+    /// Create a new resampler
+    /// - from_rate: sample rate of the input (Hz)
+    /// - to_rate: sample rate of the output (Hz)
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            from_rate,
+            to_rate,
+            half_width: 16,
+        }
+    }
+
+    /// Set the number of zero crossings on each side of the sinc kernel (quality vs. speed)
+    pub fn with_half_width(mut self, half_width: usize) -> Self {
+        self.half_width = half_width.max(1);
+        self
+    }
+
+    /// Number of output samples produced for a given input length
+    pub fn output_len(&self, input_len: usize) -> usize {
+        (input_len as u64 * self.to_rate as u64).div_ceil(self.from_rate as u64) as usize
+    }
+
+    /// Resample a block of samples
+    pub fn process(&self, samples: &[f32]) -> Vec<f32> {
+        if self.from_rate == self.to_rate || samples.is_empty() {
+            return samples.to_vec();
+        }
+
+        let ratio = self.from_rate as f64 / self.to_rate as f64;
+        // Lower the cutoff when downsampling to prevent aliasing
+        let cutoff = (self.to_rate as f32 / self.from_rate as f32).min(1.0) * 0.95;
+        let half_width = self.half_width as f32 / cutoff;
+        let reach = half_width.ceil() as i64;
+
+        (0..self.output_len(samples.len()))
+            .map(|n| {
+                let position = n as f64 * ratio;
+                let center = position.floor() as i64;
+                let mut output = 0.0;
+
+                for k in (center - reach + 1)..=(center + reach) {
+                    if k < 0 || k as usize >= samples.len() {
+                        continue;
+                    }
+                    let distance = (position - k as f64) as f32;
+                    output += samples[k as usize] * kernel(distance, cutoff, half_width);
+                }
+
+                output
+            })
+            .collect()
+    }
+}
+
+/// Blackman-windowed sinc kernel evaluated at a distance (in input samples)
+fn kernel(distance: f32, cutoff: f32, half_width: f32) -> f32 {
+    if distance.abs() >= half_width {
+        return 0.0;
+    }
+
+    let x = distance * cutoff;
+    let sinc = if x.abs() < 1e-6 { 1.0 } else { (PI * x).sin() / (PI * x) };
+    let phase = PI * (distance / half_width + 1.0);
+    let window = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+
+    cutoff * sinc * window
+}
+
+/// Resample samples from one sample rate to another
+pub fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    Resampler::new(from_rate, to_rate).process(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, sample_rate: u32, count: usize) -> Vec<f32> {
+        (0..count)
+            .map(|i| (2.0 * PI * frequency * i as f32 / sample_rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn test_same_rate_is_identity() {
+        let samples = vec![0.1, -0.2, 0.3];
+        assert_eq!(resample(&samples, 44100, 44100), samples);
+    }
+
+    #[test]
+    fn test_output_length() {
+        let samples = vec![0.0; 44100];
+        assert_eq!(resample(&samples, 44100, 48000).len(), 48000);
+        assert_eq!(resample(&samples, 44100, 8000).len(), 8000);
+    }
+
+    #[test]
+    fn test_upsampled_sine_matches_reference() {
+        let input = sine(1000.0, 44100, 4410);
+        let output = resample(&input, 44100, 48000);
+        let reference = sine(1000.0, 48000, output.len());
+
+        // Compare away from the edges where the kernel is truncated
+        let max_error = output[200..4600]
+            .iter()
+            .zip(&reference[200..4600])
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_error < 0.01, "max error {}", max_error);
+    }
+
+    #[test]
+    fn test_downsampling_removes_content_above_nyquist() {
+        // 6 kHz is above the 4 kHz Nyquist limit of 8 kHz output
+        let input = sine(6000.0, 44100, 4410);
+        let output = resample(&input, 44100, 8000);
+        let peak = output[100..700].iter().map(|x| x.abs()).fold(0.0f32, f32::max);
+        assert!(peak < 0.05, "peak {}", peak);
+    }
+}
@@ -0,0 +1,280 @@
+//! Imported one-shots (a recorded bell, a click) layered with synthesized material
+//!
+//! `Sample::from_wav` reads any PCM or float WAV, folds it to mono and resamples
+//! it to the library rate. Play it through `SamplePlayer`, add it to a `Mixer`
+//! as a `Track`, or drop it into a render with `JingleGenerator::layer_sample`,
+//! for instance inside a preset registered with `register_preset`.
+//!
+//! ```rust
+//! use jinglebells::{JingleGenerator, Sample, WaveForm};
+//!
+//! let mut generator = JingleGenerator::with_seed(1);
+//! let click = Sample::from_samples(vec![0.8, -0.6, 0.3, -0.1], 44100);
+//! generator.register_preset("clicky-beep", move |generator, spec| {
+//!     let mut samples = generator.generate_tone(spec.frequency.unwrap_or(880.0), 0.2, WaveForm::Sine);
+//!     generator.layer_sample(&mut samples, &click, 0.0, -6.0);
+//!     samples
+//! });
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use rodio::source::Source;
+use crate::{
+    SAMPLE_RATE,
+    math::db_to_linear,
+    channels::{deinterleave, mixdown},
+    export::JingleGenerator,
+    mixer::Track,
+    resample::resample,
+    error::Result,
+};
+
+/// Read a WAV file as mono samples at its own rate
+pub(crate) fn read_wav_mono<P: AsRef<Path>>(path: P) -> Result<(Vec<f32>, u32)> {
+    let mut reader = hound::WavReader::open(path)?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<std::result::Result<_, _>>()?,
+        hound::SampleFormat::Int => {
+            let scale = 1.0 / (1i64 << (spec.bits_per_sample - 1)) as f32;
+            reader.samples::<i32>()
+                .map(|sample| sample.map(|value| value as f32 * scale))
+                .collect::<std::result::Result<_, _>>()?
+        }
+    };
+
+    let channels = deinterleave(&samples, spec.channels as usize);
+    let channel_refs: Vec<&[f32]> = channels.iter().map(|channel| channel.as_slice()).collect();
+    Ok((mixdown(&channel_refs), spec.sample_rate))
+}
+
+/// Mono recorded audio, cheap to clone and share between players
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    samples: Arc<[f32]>,
+    sample_rate: u32,
+}
+
+impl Sample {
+    /// Load a WAV file, mixed down to mono and resampled to the library sample rate
+    pub fn from_wav<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_wav_at(path, SAMPLE_RATE)
+    }
+
+    /// Load a WAV file, mixed down to mono and resampled to a rate (Hz)
+    pub fn from_wav_at<P: AsRef<Path>>(path: P, sample_rate: u32) -> Result<Self> {
+        let (samples, file_rate) = read_wav_mono(path)?;
+        Ok(Self::from_samples(samples, file_rate).resampled(sample_rate))
+    }
+
+    /// Wrap mono samples recorded at a rate (Hz)
+    pub fn from_samples(samples: Vec<f32>, sample_rate: u32) -> Self {
+        Self {
+            samples: samples.into(),
+            sample_rate: sample_rate.max(1),
+        }
+    }
+
+    /// The same sound at another rate (Hz)
+    pub fn resampled(&self, sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1);
+        if sample_rate == self.sample_rate {
+            return self.clone();
+        }
+        Self::from_samples(resample(&self.samples, self.sample_rate, sample_rate), sample_rate)
+    }
+
+    /// Get the mono samples
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Get the rate (Hz) of the samples
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f32 {
+        self.samples.len() as f32 / self.sample_rate as f32
+    }
+
+    /// Play the sample as a rodio source
+    pub fn player(&self) -> SamplePlayer {
+        SamplePlayer::new(self.clone())
+    }
+
+    /// Make a mixer track from the sample
+    pub fn to_track(&self, name: &str) -> Track {
+        Track::new(name, self.samples.to_vec()).with_sample_rate(self.sample_rate)
+    }
+}
+
+/// Source playing a `Sample` once, with its own gain and pitch
+///
+/// Pitch is changed by playing faster or slower, so a shift up also shortens the sound.
+#[derive(Clone, Debug)]
+pub struct SamplePlayer {
+    sample: Sample,
+    position: f64,
+    step: f64,
+    gain: f32,
+}
+
+impl SamplePlayer {
+    /// Play a sample at its original pitch and level
+    pub fn new(sample: Sample) -> Self {
+        Self {
+            sample,
+            position: 0.0,
+            step: 1.0,
+            gain: 1.0,
+        }
+    }
+
+    /// Set the playback level in dB
+    pub fn with_gain_db(mut self, gain_db: f32) -> Self {
+        self.gain = db_to_linear(gain_db);
+        self
+    }
+
+    /// Shift the pitch in semitones (12.0 = one octave up, twice as fast)
+    pub fn with_pitch(mut self, semitones: f32) -> Self {
+        self.step = 2.0_f64.powf(semitones.clamp(-48.0, 48.0) as f64 / 12.0);
+        self
+    }
+
+    /// Samples left to play
+    fn remaining(&self) -> usize {
+        let len = self.sample.samples.len() as f64;
+        if self.position >= len { 0 } else { ((len - self.position) / self.step).ceil() as usize }
+    }
+}
+
+impl Iterator for SamplePlayer {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let samples = &self.sample.samples;
+        let index = self.position as usize;
+        let current = *samples.get(index)?;
+        // Linear interpolation between neighbours when playing off the original rate
+        let fraction = (self.position - index as f64) as f32;
+        let next = samples.get(index + 1).copied().unwrap_or(0.0);
+        self.position += self.step;
+        Some((current + (next - current) * fraction) * self.gain)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining();
+        (remaining, Some(remaining))
+    }
+}
+
+impl Source for SamplePlayer {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f64(self.remaining() as f64 / self.sample.sample_rate as f64))
+    }
+}
+
+impl JingleGenerator {
+    /// Mix a sample into rendered samples, starting `offset` seconds in
+    ///
+    /// The sample is resampled to the generator's rate and the render is extended
+    /// with silence if the sample runs past its end.
+    pub fn layer_sample(&self, samples: &mut Vec<f32>, sample: &Sample, offset: f32, gain_db: f32) {
+        let sample_rate = self.get_sample_rate();
+        let layer = sample.resampled(sample_rate);
+        let start = (offset.max(0.0) * sample_rate as f32).round() as usize;
+        let end = start + layer.samples.len();
+        if samples.len() < end {
+            samples.resize(end, 0.0);
+        }
+        let gain = db_to_linear(gain_db);
+        for (target, value) in samples[start..end].iter_mut().zip(layer.samples.iter()) {
+            *target += value * gain;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_stereo_wav(name: &str, sample_rate: u32, frames: &[(i16, i16)]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+        for &(left, right) in frames {
+            writer.write_sample(left).unwrap();
+            writer.write_sample(right).unwrap();
+        }
+        writer.finalize().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_from_wav() {
+        let frames = vec![(16384, 0); 100];
+        let path = write_stereo_wav("jinglebells_sample_test.wav", SAMPLE_RATE, &frames);
+        let sample = Sample::from_wav(&path).unwrap();
+        assert_eq!(sample.sample_rate(), SAMPLE_RATE);
+        assert_eq!(sample.samples().len(), 100);
+        assert!((sample.samples()[50] - 0.25).abs() < 1e-3);
+
+        let half = Sample::from_wav_at(&path, SAMPLE_RATE / 2).unwrap();
+        assert!((49..=51).contains(&half.samples().len()));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(Sample::from_wav(std::env::temp_dir().join("jinglebells_missing_sample.wav")).is_err());
+    }
+
+    #[test]
+    fn test_player() {
+        let sample = Sample::from_samples(vec![0.0, 0.5, 1.0, 0.5], 8000);
+        let player = sample.player();
+        assert_eq!(player.sample_rate(), 8000);
+        assert_eq!(player.size_hint(), (4, Some(4)));
+        assert_eq!(player.collect::<Vec<f32>>(), vec![0.0, 0.5, 1.0, 0.5]);
+
+        let octave_up: Vec<f32> = sample.player().with_pitch(12.0).collect();
+        assert_eq!(octave_up, vec![0.0, 1.0]);
+        let octave_down: Vec<f32> = sample.player().with_pitch(-12.0).with_gain_db(-6.0).collect();
+        assert_eq!(octave_down.len(), 8);
+        assert!((octave_down[1] - 0.25 * db_to_linear(-6.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_layering() {
+        let generator = JingleGenerator::with_seed(1);
+        let click = Sample::from_samples(vec![0.5; 10], SAMPLE_RATE);
+        let mut samples = vec![0.25; 15];
+        generator.layer_sample(&mut samples, &click, 10.0 / SAMPLE_RATE as f32, 0.0);
+        assert_eq!(samples.len(), 20);
+        assert_eq!(samples[9], 0.25);
+        assert_eq!(samples[10], 0.75);
+        assert_eq!(samples[19], 0.5);
+
+        let mix = generator.mixer().track(click.to_track("click").with_pan(-1.0)).render();
+        assert_eq!(mix[0].len(), 10);
+    }
+}
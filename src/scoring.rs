@@ -0,0 +1,298 @@
+//! Scoring rendered candidates and keeping the best of several seeds
+
+use crate::{
+    SAMPLE_RATE,
+    presets::JinglePreset,
+    spec::JingleSpec,
+    export::JingleGenerator,
+    progress::ProgressPhase,
+    convolution::real_spectrum,
+    error::{JingleError, Result},
+};
+
+/// Analysis frame length in samples (about 46 ms)
+const FRAME_SIZE: usize = 2048;
+/// Strongest spectral peaks per frame used for the consonance estimate
+const FRAME_PEAKS: usize = 6;
+/// Spectral centroid (Hz) of a well-balanced notification sound
+const BALANCED_CENTROID: f32 = 1500.0;
+/// Maximum of the Plomp-Levelt roughness curve, used to scale it to 0.0 - 1.0
+const MAX_ROUGHNESS: f32 = 0.181;
+
+/// Scores rendered samples, higher is better
+///
+/// Implemented for `HeuristicScorer` and for any `FnMut(&[f32]) -> f32` closure.
+pub trait Scorer {
+    /// Score a candidate's samples
+    fn score(&mut self, samples: &[f32]) -> f32;
+
+    /// Score a candidate rendered at `sample_rate` Hz; scorers that don't depend on the rate use `score`
+    fn score_at(&mut self, samples: &[f32], _sample_rate: u32) -> f32 {
+        self.score(samples)
+    }
+}
+
+impl<F> Scorer for F
+where
+    F: FnMut(&[f32]) -> f32,
+{
+    fn score(&mut self, samples: &[f32]) -> f32 {
+        self(samples)
+    }
+}
+
+/// Built-in heuristics, each from 0.0 (worst) to 1.0 (best)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoreBreakdown {
+    /// Lack of roughness between simultaneous partials
+    pub consonance: f32,
+    /// How close the spectral centroid is to the middle of the audible range
+    pub spectral_balance: f32,
+    /// Share of samples below full scale
+    pub clipping: f32,
+    /// How close the length is to the target duration
+    pub duration_fit: f32,
+}
+
+impl ScoreBreakdown {
+    /// Measure every heuristic for samples at the library sample rate meant to last `target_duration` seconds
+    pub fn measure(samples: &[f32], target_duration: f32) -> Self {
+        Self::measure_at(samples, target_duration, SAMPLE_RATE)
+    }
+
+    /// Measure every heuristic for samples at the given sample rate
+    pub fn measure_at(samples: &[f32], target_duration: f32, sample_rate: u32) -> Self {
+        let (consonance, spectral_balance) = spectral_scores(samples, sample_rate);
+        let clipped = samples.iter().filter(|s| s.abs() >= 0.999).count();
+        let clipping = (1.0 - clipped as f32 / samples.len().max(1) as f32 * 100.0).max(0.0);
+        let duration = samples.len() as f32 / sample_rate as f32;
+        let duration_fit = if duration > 0.0 && target_duration > 0.0 {
+            (-(duration / target_duration).ln().abs() * 2.0).exp()
+        } else {
+            0.0
+        };
+        Self { consonance, spectral_balance, clipping, duration_fit }
+    }
+
+    /// Weighted mean of the heuristics; clipping counts double
+    pub fn total(&self) -> f32 {
+        (self.consonance + self.spectral_balance + self.clipping * 2.0 + self.duration_fit) / 5.0
+    }
+}
+
+/// Scores candidates with `ScoreBreakdown::total`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeuristicScorer {
+    /// Duration in seconds candidates should last
+    pub target_duration: f32,
+}
+
+impl HeuristicScorer {
+    /// Create a scorer for candidates meant to last `target_duration` seconds
+    pub fn new(target_duration: f32) -> Self {
+        Self { target_duration }
+    }
+
+    /// Create a scorer targeting a preset's default duration
+    pub fn for_preset(preset: JinglePreset) -> Self {
+        Self::new(preset.default_duration())
+    }
+}
+
+impl Scorer for HeuristicScorer {
+    fn score(&mut self, samples: &[f32]) -> f32 {
+        ScoreBreakdown::measure(samples, self.target_duration).total()
+    }
+
+    fn score_at(&mut self, samples: &[f32], sample_rate: u32) -> f32 {
+        ScoreBreakdown::measure_at(samples, self.target_duration, sample_rate).total()
+    }
+}
+
+/// The winning render of `generate_best_of`
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    /// Seed that reproduces the samples with `JingleSpec::with_seed`
+    pub seed: u64,
+    pub samples: Vec<f32>,
+    pub score: f32,
+}
+
+/// Consonance and spectral balance from the spectra of successive frames
+fn spectral_scores(samples: &[f32], sample_rate: u32) -> (f32, f32) {
+    let window: Vec<f32> = (0..FRAME_SIZE)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FRAME_SIZE as f32).cos())
+        .collect();
+    let bin_width = sample_rate as f32 / FRAME_SIZE as f32;
+
+    let (mut roughness, mut roughness_weight) = (0.0, 0.0);
+    let (mut centroid, mut energy) = (0.0, 0.0);
+    for frame in samples.chunks(FRAME_SIZE) {
+        let windowed: Vec<f32> = frame.iter().zip(&window).map(|(sample, w)| sample * w).collect();
+        let magnitudes: Vec<f32> = real_spectrum(&windowed, FRAME_SIZE)[..FRAME_SIZE / 2]
+            .iter()
+            .map(|bin| (bin.re * bin.re + bin.im * bin.im).sqrt())
+            .collect();
+        let frame_energy: f32 = magnitudes.iter().map(|m| m * m).sum();
+        if frame_energy < 1e-6 {
+            continue;
+        }
+        centroid += magnitudes.iter().enumerate().map(|(bin, m)| bin as f32 * bin_width * m * m).sum::<f32>();
+        energy += frame_energy;
+
+        // Local maxima within 20 dB of the loudest, strongest first
+        let loudest = magnitudes.iter().copied().fold(0.0, f32::max);
+        let mut peaks: Vec<(f32, f32)> = (1..magnitudes.len() - 1)
+            .filter(|&bin| magnitudes[bin] > loudest * 0.1 && magnitudes[bin] >= magnitudes[bin - 1] && magnitudes[bin] > magnitudes[bin + 1])
+            .map(|bin| (bin as f32 * bin_width, magnitudes[bin]))
+            .collect();
+        peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+        peaks.truncate(FRAME_PEAKS);
+
+        for (i, &(f1, a1)) in peaks.iter().enumerate() {
+            for &(f2, a2) in &peaks[i + 1..] {
+                roughness += a1 * a2 * plomp_levelt(f1, f2);
+                roughness_weight += a1 * a2;
+            }
+        }
+    }
+
+    let consonance = if roughness_weight > 0.0 {
+        1.0 - (roughness / roughness_weight / MAX_ROUGHNESS).min(1.0)
+    } else {
+        1.0
+    };
+    let spectral_balance = if energy > 0.0 {
+        let octaves = (centroid / energy / BALANCED_CENTROID).log2();
+        (-octaves * octaves / 4.5).exp()
+    } else {
+        0.0
+    };
+    (consonance, spectral_balance)
+}
+
+/// Sensory roughness of two sine partials (Plomp and Levelt, as parameterized by Sethares)
+fn plomp_levelt(f1: f32, f2: f32) -> f32 {
+    let scale = 0.24 / (0.021 * f1.min(f2) + 19.0);
+    let distance = scale * (f1 - f2).abs();
+    (-3.5 * distance).exp() - (-5.75 * distance).exp()
+}
+
+impl JingleGenerator {
+    /// Render `n` seeded candidates of a preset and keep the one the scorer likes best
+    ///
+    /// Pass `HeuristicScorer::for_preset(preset)` for the built-in heuristics or any
+    /// closure scoring the samples. Candidate seeds are drawn from this generator,
+    /// so the same generator seed picks the same winner.
+    ///
+    /// ```rust
+    /// use jinglebells::{JingleGenerator, JinglePreset, HeuristicScorer};
+    ///
+    /// let mut generator = JingleGenerator::with_seed(1);
+    /// let best = generator.generate_best_of(JinglePreset::Success, 5, HeuristicScorer::for_preset(JinglePreset::Success)).unwrap();
+    /// let loudest = generator.generate_best_of(JinglePreset::Success, 5, |samples: &[f32]| samples.iter().map(|s| s.abs()).sum()).unwrap();
+    /// assert!(best.score <= 1.0 && loudest.score > 0.0);
+    /// ```
+    pub fn generate_best_of(&mut self, preset: JinglePreset, n: usize, scorer: impl Scorer) -> Result<Candidate> {
+        self.render_best_of(&JingleSpec::new(preset), n, scorer)
+    }
+
+    /// Render `n` candidates of a spec with different seeds and keep the best scoring one
+    ///
+    /// Candidates are scored with `Scorer::score_at` at the generator's sample rate.
+    pub fn render_best_of(&mut self, spec: &JingleSpec, n: usize, mut scorer: impl Scorer) -> Result<Candidate> {
+        if n == 0 {
+            return Err(JingleError::InvalidParameter("best-of needs at least one candidate".to_string()));
+        }
+
+        let mut best: Option<Candidate> = None;
+        for i in 0..n {
+            self.check_cancelled()?;
+            let seed = self.random_seed();
+            let samples = self.render(&spec.clone().with_seed(seed));
+            let score = scorer.score_at(&samples, self.get_sample_rate());
+            if best.as_ref().is_none_or(|best| score > best.score) {
+                best = Some(Candidate { seed, samples, score });
+            }
+            self.report_progress(ProgressPhase::Rendering, i + 1, n);
+        }
+        Ok(best.expect("at least one candidate is rendered"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::WaveForm;
+
+    fn tones(frequencies: &[f32], duration: f32) -> Vec<f32> {
+        tones_at(frequencies, duration, SAMPLE_RATE)
+    }
+
+    fn tones_at(frequencies: &[f32], duration: f32, sample_rate: u32) -> Vec<f32> {
+        (0..(duration * sample_rate as f32) as usize)
+            .map(|i| {
+                let time = i as f32 / sample_rate as f32;
+                frequencies.iter().map(|f| (2.0 * std::f32::consts::PI * f * time).sin()).sum::<f32>() * 0.3
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_heuristics() {
+        let fifth = ScoreBreakdown::measure(&tones(&[440.0, 660.0], 0.5), 0.5);
+        let semitone = ScoreBreakdown::measure(&tones(&[440.0, 466.2], 0.5), 0.5);
+        assert!(fifth.consonance > semitone.consonance + 0.3, "{:?} vs {:?}", fifth, semitone);
+        assert!((fifth.duration_fit - 1.0).abs() < 0.01);
+        assert!(ScoreBreakdown::measure(&tones(&[440.0, 660.0], 0.25), 0.5).duration_fit < 0.5);
+
+        let balanced = ScoreBreakdown::measure(&tones(&[1500.0], 0.5), 0.5);
+        let rumble = ScoreBreakdown::measure(&tones(&[60.0], 0.5), 0.5);
+        assert!(balanced.spectral_balance > 0.95 && rumble.spectral_balance < 0.2);
+
+        let clipped: Vec<f32> = tones(&[440.0], 0.5).iter().map(|s| (s * 10.0).clamp(-1.0, 1.0)).collect();
+        assert_eq!(ScoreBreakdown::measure(&clipped, 0.5).clipping, 0.0);
+        assert_eq!(balanced.clipping, 1.0);
+    }
+
+    #[test]
+    fn test_heuristics_at_sample_rate() {
+        let balanced = ScoreBreakdown::measure_at(&tones_at(&[1500.0], 0.5, 22050), 0.5, 22050);
+        assert!((balanced.duration_fit - 1.0).abs() < 0.01);
+        assert!(balanced.spectral_balance > 0.95, "{:?}", balanced);
+        // Read at the wrong rate, the same samples sound half as long and an octave low
+        let misread = ScoreBreakdown::measure(&tones_at(&[1500.0], 0.5, 22050), 0.5);
+        assert!(misread.duration_fit < 0.5 && misread.spectral_balance < balanced.spectral_balance);
+
+        // Best-of scores candidates at the generator's rate
+        let config = crate::export::GeneratorConfig::default().with_sample_rate(22050);
+        let mut generator = JingleGenerator::new().with_config(config).unwrap();
+        let preset = JinglePreset::Success;
+        let best = generator.generate_best_of(preset, 3, HeuristicScorer::for_preset(preset)).unwrap();
+        assert_eq!(best.score, ScoreBreakdown::measure_at(&best.samples, preset.default_duration(), 22050).total());
+    }
+
+    #[test]
+    fn test_best_of_is_reproducible() {
+        let preset = JinglePreset::Notification;
+        let mut scores = Vec::new();
+        let scorer = |samples: &[f32]| {
+            let score = samples.len() as f32;
+            scores.push(score);
+            score
+        };
+        let best = JingleGenerator::with_seed(4).generate_best_of(preset, 6, scorer).unwrap();
+        assert_eq!(scores.len(), 6);
+        assert_eq!(best.score, scores.iter().copied().fold(f32::MIN, f32::max));
+
+        // The winning seed renders the same samples again
+        let spec = JingleSpec::new(preset).with_seed(best.seed);
+        assert_eq!(JingleGenerator::new().render(&spec), best.samples);
+
+        let again = JingleGenerator::with_seed(4).generate_best_of(preset, 6, HeuristicScorer::for_preset(preset)).unwrap();
+        let repeat = JingleGenerator::with_seed(4).generate_best_of(preset, 6, HeuristicScorer::for_preset(preset)).unwrap();
+        assert_eq!(again.seed, repeat.seed);
+        assert!(JingleGenerator::new().generate_best_of(preset, 0, HeuristicScorer::new(1.0)).is_err());
+        assert!(JingleGenerator::new().render_best_of(&preset.spec(WaveForm::Square, None, None), 1, HeuristicScorer::new(1.0)).is_ok());
+    }
+}
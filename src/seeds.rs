@@ -0,0 +1,213 @@
+//! Human-friendly seed slugs and a catalog of seeds that render well
+//!
+//! A slug spells a seed as `adjective-color-animal`, so `1984` is shared as
+//! `brave-teal-otter`. The three words cover seeds below 131072; larger seeds
+//! keep their remaining bits as a numeric suffix (`brave-teal-otter-7`), so
+//! every `u64` round-trips exactly.
+//!
+//! ```rust
+//! use jinglebells::{seed_to_slug, slug_to_seed};
+//!
+//! assert_eq!(seed_to_slug(1984), "brave-teal-otter");
+//! assert_eq!(slug_to_seed("brave-teal-otter").unwrap(), 1984);
+//! assert_eq!(slug_to_seed(&seed_to_slug(u64::MAX)).unwrap(), u64::MAX);
+//! ```
+
+use crate::{
+    export::JingleGenerator,
+    presets::JinglePreset,
+    spec::JingleSpec,
+    error::{JingleError, Result},
+};
+
+const ADJECTIVES: [&str; 64] = [
+    "brave", "calm", "clever", "cozy", "crisp", "daring", "eager", "fancy",
+    "fearless", "gentle", "glad", "happy", "humble", "jolly", "keen", "kind",
+    "lively", "lucky", "merry", "mighty", "nimble", "noble", "plucky", "polite",
+    "proud", "quick", "quiet", "rapid", "silly", "sleepy", "smart", "snappy",
+    "sunny", "swift", "tidy", "witty", "zesty", "bold", "bright", "cheerful",
+    "curious", "dapper", "fuzzy", "graceful", "hardy", "jazzy", "loyal", "mellow",
+    "modest", "peppy", "perky", "playful", "shiny", "spry", "steady", "sturdy",
+    "sweet", "tender", "upbeat", "vivid", "warm", "wise", "zippy", "breezy",
+];
+
+const COLORS: [&str; 32] = [
+    "amber", "aqua", "azure", "beige", "black", "blue", "bronze", "coral",
+    "cream", "crimson", "cyan", "gold", "gray", "green", "indigo", "ivory",
+    "jade", "khaki", "lemon", "lilac", "lime", "magenta", "maroon", "mint",
+    "navy", "olive", "peach", "pink", "plum", "ruby", "silver", "teal",
+];
+
+const ANIMALS: [&str; 64] = [
+    "otter", "badger", "beaver", "bison", "camel", "cat", "cobra", "crane",
+    "crow", "deer", "dingo", "dolphin", "dove", "eagle", "falcon", "ferret",
+    "finch", "fox", "frog", "gecko", "goat", "goose", "hare", "hawk",
+    "heron", "hippo", "ibis", "jackal", "koala", "lemur", "lion", "llama",
+    "lynx", "magpie", "marmot", "mole", "moose", "moth", "mouse", "newt",
+    "owl", "panda", "parrot", "pelican", "penguin", "puffin", "quail", "rabbit",
+    "raven", "robin", "salmon", "seal", "shark", "sloth", "sparrow", "squid",
+    "swan", "tiger", "toad", "turtle", "walrus", "whale", "wolf", "wombat",
+];
+
+/// Bits of the seed spelled by the three words
+const WORD_BITS: u32 = 17;
+
+/// Spell a seed as `adjective-color-animal`, with a numeric suffix for seeds of 131072 and above
+pub fn seed_to_slug(seed: u64) -> String {
+    let words = seed & ((1 << WORD_BITS) - 1);
+    let animal = ANIMALS[(words % 64) as usize];
+    let color = COLORS[(words / 64 % 32) as usize];
+    let adjective = ADJECTIVES[(words / 2048) as usize];
+    match seed >> WORD_BITS {
+        0 => format!("{}-{}-{}", adjective, color, animal),
+        rest => format!("{}-{}-{}-{}", adjective, color, animal, rest),
+    }
+}
+
+/// Read a slug written by `seed_to_slug` back into its seed, ignoring case
+///
+/// Fails with `InvalidParameter` for unknown words or a suffix too large for a `u64` seed.
+pub fn slug_to_seed(slug: &str) -> Result<u64> {
+    let slug = slug.trim().to_ascii_lowercase();
+    let parts: Vec<&str> = slug.split('-').collect();
+    if !(3..=4).contains(&parts.len()) {
+        return Err(JingleError::InvalidParameter(format!("seed slug '{}' should look like 'brave-teal-otter'", slug)));
+    }
+
+    let index = |words: &[&str], word: &str, kind: &str| {
+        words.iter().position(|&w| w == word).map(|i| i as u64).ok_or_else(|| {
+            JingleError::InvalidParameter(format!("'{}' in seed slug '{}' is not a known {}", word, slug, kind))
+        })
+    };
+    let words = index(&ADJECTIVES, parts[0], "adjective")? * 2048
+        + index(&COLORS, parts[1], "color")? * 64
+        + index(&ANIMALS, parts[2], "animal")?;
+
+    let rest = match parts.get(3) {
+        Some(suffix) => suffix.parse::<u64>().ok().filter(|rest| rest >> (64 - WORD_BITS) == 0).ok_or_else(|| {
+            JingleError::InvalidParameter(format!("suffix '{}' in seed slug '{}' must be a number below {}", suffix, slug, 1u64 << (64 - WORD_BITS)))
+        })?,
+        None => 0,
+    };
+    Ok(rest << WORD_BITS | words)
+}
+
+/// A seed known to render a preset nicely with the preset's own waveform
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CatalogSeed {
+    pub preset: JinglePreset,
+    pub seed: u64,
+}
+
+impl CatalogSeed {
+    /// The seed spelled as a slug
+    pub fn slug(&self) -> String {
+        seed_to_slug(self.seed)
+    }
+
+    /// A spec rendering this entry with `JingleGenerator::render`
+    pub fn spec(&self) -> JingleSpec {
+        JingleSpec::new(self.preset).with_waveform(self.preset.style().waveform).with_seed(self.seed)
+    }
+}
+
+/// Curated seeds, three per melodic preset, best first
+///
+/// The top `HeuristicScorer` results among the three-word seeds. Countdown is
+/// left out because its ticks don't depend on the seed.
+/// Rendering them relies on `DefaultRng` staying stable.
+pub const SEED_CATALOG: &[CatalogSeed] = &[
+    CatalogSeed { preset: JinglePreset::Notification, seed: 105283 },
+    CatalogSeed { preset: JinglePreset::Notification, seed: 108077 },
+    CatalogSeed { preset: JinglePreset::Notification, seed: 61468 },
+    CatalogSeed { preset: JinglePreset::Success, seed: 65278 },
+    CatalogSeed { preset: JinglePreset::Success, seed: 97917 },
+    CatalogSeed { preset: JinglePreset::Success, seed: 72136 },
+    CatalogSeed { preset: JinglePreset::Alert, seed: 97917 },
+    CatalogSeed { preset: JinglePreset::Alert, seed: 117729 },
+    CatalogSeed { preset: JinglePreset::Alert, seed: 39751 },
+    CatalogSeed { preset: JinglePreset::Error, seed: 55626 },
+    CatalogSeed { preset: JinglePreset::Error, seed: 72771 },
+    CatalogSeed { preset: JinglePreset::Error, seed: 62103 },
+    CatalogSeed { preset: JinglePreset::Startup, seed: 72136 },
+    CatalogSeed { preset: JinglePreset::Startup, seed: 58166 },
+    CatalogSeed { preset: JinglePreset::Startup, seed: 53086 },
+    CatalogSeed { preset: JinglePreset::Shutdown, seed: 61468 },
+    CatalogSeed { preset: JinglePreset::Shutdown, seed: 34163 },
+    CatalogSeed { preset: JinglePreset::Shutdown, seed: 96012 },
+    CatalogSeed { preset: JinglePreset::Message, seed: 89535 },
+    CatalogSeed { preset: JinglePreset::Message, seed: 93218 },
+    CatalogSeed { preset: JinglePreset::Message, seed: 96901 },
+    CatalogSeed { preset: JinglePreset::Completion, seed: 33655 },
+    CatalogSeed { preset: JinglePreset::Completion, seed: 98806 },
+    CatalogSeed { preset: JinglePreset::Completion, seed: 122936 },
+    CatalogSeed { preset: JinglePreset::Ringtone, seed: 29718 },
+    CatalogSeed { preset: JinglePreset::Ringtone, seed: 47371 },
+    CatalogSeed { preset: JinglePreset::Ringtone, seed: 113919 },
+    CatalogSeed { preset: JinglePreset::Doorbell, seed: 47879 },
+    CatalogSeed { preset: JinglePreset::Doorbell, seed: 254 },
+    CatalogSeed { preset: JinglePreset::Doorbell, seed: 79756 },
+    CatalogSeed { preset: JinglePreset::Reminder, seed: 90932 },
+    CatalogSeed { preset: JinglePreset::Reminder, seed: 112903 },
+    CatalogSeed { preset: JinglePreset::Reminder, seed: 51943 },
+    CatalogSeed { preset: JinglePreset::Holiday, seed: 54737 },
+    CatalogSeed { preset: JinglePreset::Holiday, seed: 50800 },
+    CatalogSeed { preset: JinglePreset::Holiday, seed: 109347 },
+];
+
+/// Curated seeds for one preset, best first
+pub fn catalog_seeds(preset: JinglePreset) -> impl Iterator<Item = &'static CatalogSeed> {
+    SEED_CATALOG.iter().filter(move |entry| entry.preset == preset)
+}
+
+impl JingleGenerator {
+    /// Create a reproducible generator from a seed slug such as `brave-teal-otter`
+    pub fn with_slug(slug: &str) -> Result<Self> {
+        Ok(Self::with_seed(slug_to_seed(slug)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slug_round_trip() {
+        for seed in [0, 1, 63, 64, 1984, (1 << WORD_BITS) - 1, 1 << WORD_BITS, 42 << WORD_BITS | 7, u64::MAX] {
+            assert_eq!(slug_to_seed(&seed_to_slug(seed)).unwrap(), seed);
+        }
+        assert_eq!(seed_to_slug(0), "brave-amber-otter");
+        assert_eq!(seed_to_slug((1 << WORD_BITS) + 1984), "brave-teal-otter-1");
+        assert_eq!(slug_to_seed(" Brave-Teal-OTTER ").unwrap(), 1984);
+    }
+
+    #[test]
+    fn test_word_lists_are_unique() {
+        let mut words: Vec<&str> = ADJECTIVES.iter().chain(&COLORS).chain(&ANIMALS).copied().collect();
+        words.sort();
+        words.dedup();
+        assert_eq!(words.len(), 64 + 32 + 64);
+        assert!(words.iter().all(|word| !word.contains('-')));
+    }
+
+    #[test]
+    fn test_invalid_slugs() {
+        for slug in ["", "brave", "brave-teal", "brave-teal-unicorn", "brave-teal-otter-x", "brave-teal-otter-1-2"] {
+            assert!(matches!(slug_to_seed(slug), Err(JingleError::InvalidParameter(_))), "{} accepted", slug);
+        }
+        let too_large = format!("brave-teal-otter-{}", 1u64 << (64 - WORD_BITS));
+        assert!(slug_to_seed(&too_large).is_err());
+    }
+
+    #[test]
+    fn test_catalog() {
+        for preset in JinglePreset::all().into_iter().take(12) {
+            assert_eq!(catalog_seeds(preset).count(), 3, "{} needs catalog seeds", preset.name());
+        }
+        let entry = catalog_seeds(JinglePreset::Success).next().unwrap();
+        let mut generator = JingleGenerator::with_slug(&entry.slug()).unwrap();
+        assert_eq!(generator.get_current_seed(), Some(entry.seed));
+        let samples = generator.render(&entry.spec());
+        assert!(samples.iter().any(|&s| s != 0.0));
+    }
+}
@@ -0,0 +1,377 @@
+//! Timeline of notes, chords and samples placed at absolute or beat times on lanes
+//!
+//! Lanes play at the same time, and the events of a lane may overlap: its
+//! notes are played by a `VoiceAllocator`, each with its own envelope. Lanes
+//! carry their own gain, pan and effect chain, and can automate their level
+//! and a low-pass cutoff over the timeline.
+//!
+//! ```rust
+//! use jinglebells::{Automation, JingleGenerator, Note, Sequence, SequenceLane, SequenceTime, WaveForm};
+//!
+//! let generator = JingleGenerator::with_seed(1);
+//! let sequence = Sequence::new()
+//!     .with_tempo(120.0)
+//!     .lane(SequenceLane::new("beeps")
+//!         .note(SequenceTime::Beats(0.0), 880.0, SequenceTime::Beats(0.5), WaveForm::Sine)
+//!         .note(SequenceTime::Beats(1.0), 880.0, SequenceTime::Beats(0.5), WaveForm::Sine))
+//!     .lane(SequenceLane::new("pad")
+//!         .chord(SequenceTime::Seconds(0.25), jinglebells::Chord::major(Note::C), 4, SequenceTime::Beats(1.5), WaveForm::Triangle)
+//!         .automate_gain_db(Automation::linear(-12.0, 0.0, 0.75)));
+//! let samples = generator.render_sequence(&sequence).unwrap();
+//! assert!(samples.len() >= (0.75 * generator.get_sample_rate() as f32) as usize);
+//! ```
+
+use crate::{
+    audio::WaveForm,
+    automation::{Automated, Automation},
+    chain::EffectChain,
+    effects::{LowPassFilter, SampleProcessor},
+    export::JingleGenerator,
+    math::db_to_linear,
+    mixer::{Mixer, Track},
+    music::Chord,
+    sample::Sample,
+    voices::{VoiceNote, DEFAULT_MAX_VOICES},
+    error::Result,
+};
+
+/// Tempo of a new sequence in beats per minute
+pub const DEFAULT_TEMPO: f32 = 120.0;
+
+/// A point or length on the timeline, in seconds or in beats of the sequence tempo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SequenceTime {
+    Seconds(f32),
+    Beats(f32),
+}
+
+impl SequenceTime {
+    /// Convert to seconds at a tempo in beats per minute
+    pub fn to_seconds(self, tempo: f32) -> f32 {
+        match self {
+            SequenceTime::Seconds(seconds) => seconds,
+            SequenceTime::Beats(beats) => beats * 60.0 / tempo,
+        }
+        .max(0.0)
+    }
+}
+
+impl From<f32> for SequenceTime {
+    fn from(seconds: f32) -> Self {
+        SequenceTime::Seconds(seconds)
+    }
+}
+
+/// Something that sounds on a lane
+#[derive(Clone, Debug)]
+pub enum SequenceEvent {
+    /// A tone with the generator's envelope, held for `duration` and then released
+    Note { frequency: f32, duration: SequenceTime, waveform: WaveForm },
+    /// Every note of a chord at once, one voice each
+    Chord { chord: Chord, octave: i32, duration: SequenceTime, waveform: WaveForm },
+    /// An imported one-shot, played to its end
+    Sample { sample: Sample, gain_db: f32 },
+}
+
+/// Parameter a lane automates over the timeline
+#[derive(Clone, Debug)]
+enum LaneAutomation {
+    GainDb(Automation),
+    LowPass(Automation),
+}
+
+/// One voice of a sequence: timed events plus level, pan, effects and automation
+#[derive(Clone, Debug)]
+pub struct SequenceLane {
+    name: String,
+    events: Vec<(SequenceTime, SequenceEvent)>,
+    gain_db: f32,
+    pan: f32,
+    effects: EffectChain,
+    automation: Vec<LaneAutomation>,
+    max_voices: usize,
+}
+
+impl SequenceLane {
+    /// Create an empty lane
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            events: Vec::new(),
+            gain_db: 0.0,
+            pan: 0.0,
+            effects: EffectChain::new(),
+            automation: Vec::new(),
+            max_voices: DEFAULT_MAX_VOICES,
+        }
+    }
+
+    /// Place an event at a time; events may overlap
+    pub fn event(mut self, at: SequenceTime, event: SequenceEvent) -> Self {
+        self.events.push((at, event));
+        self
+    }
+
+    /// Place a tone at a time
+    pub fn note(self, at: SequenceTime, frequency: f32, duration: SequenceTime, waveform: WaveForm) -> Self {
+        self.event(at, SequenceEvent::Note { frequency, duration, waveform })
+    }
+
+    /// Place a chord at a time
+    pub fn chord(self, at: SequenceTime, chord: Chord, octave: i32, duration: SequenceTime, waveform: WaveForm) -> Self {
+        self.event(at, SequenceEvent::Chord { chord, octave, duration, waveform })
+    }
+
+    /// Place an imported sample at a time
+    pub fn sample(self, at: SequenceTime, sample: Sample, gain_db: f32) -> Self {
+        self.event(at, SequenceEvent::Sample { sample, gain_db })
+    }
+
+    /// Set the lane level in dB
+    pub fn with_gain_db(mut self, gain_db: f32) -> Self {
+        self.gain_db = gain_db;
+        self
+    }
+
+    /// Place the lane from -1.0 (left) to 1.0 (right) when mixed with `JingleGenerator::sequence_mixer`
+    pub fn with_pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+        self
+    }
+
+    /// Run the lane through an effect chain
+    pub fn with_effects(mut self, effects: EffectChain) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// Limit how many notes the lane sounds at once (defaults to `DEFAULT_MAX_VOICES`)
+    ///
+    /// A note beyond the limit takes over the oldest released voice, or the oldest held one.
+    pub fn with_max_voices(mut self, max_voices: usize) -> Self {
+        self.max_voices = max_voices.max(1);
+        self
+    }
+
+    /// Change the lane level over the timeline, in dB at times in seconds
+    pub fn automate_gain_db(mut self, automation: Automation) -> Self {
+        self.automation.push(LaneAutomation::GainDb(automation));
+        self
+    }
+
+    /// Sweep a low-pass filter over the lane, with the cutoff in Hz at times in seconds
+    pub fn automate_lowpass(mut self, automation: Automation) -> Self {
+        self.automation.push(LaneAutomation::LowPass(automation));
+        self
+    }
+
+    /// Get the lane name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the events in the order they were placed
+    pub fn events(&self) -> &[(SequenceTime, SequenceEvent)] {
+        &self.events
+    }
+}
+
+/// Lanes of timed events sharing a tempo
+#[derive(Clone, Debug)]
+pub struct Sequence {
+    lanes: Vec<SequenceLane>,
+    tempo: f32,
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sequence {
+    /// Create an empty sequence at `DEFAULT_TEMPO`
+    pub fn new() -> Self {
+        Self {
+            lanes: Vec::new(),
+            tempo: DEFAULT_TEMPO,
+        }
+    }
+
+    /// Set the tempo beat times are measured in (20 - 400 BPM)
+    pub fn with_tempo(mut self, bpm: f32) -> Self {
+        self.tempo = bpm.clamp(20.0, 400.0);
+        self
+    }
+
+    /// Add a lane
+    pub fn lane(mut self, lane: SequenceLane) -> Self {
+        self.lanes.push(lane);
+        self
+    }
+
+    /// Get the tempo in beats per minute
+    pub fn tempo(&self) -> f32 {
+        self.tempo
+    }
+
+    /// Get the lanes in the order they were added
+    pub fn lanes(&self) -> &[SequenceLane] {
+        &self.lanes
+    }
+
+    /// Convert a timeline position to seconds at the sequence tempo
+    pub fn seconds(&self, time: SequenceTime) -> f32 {
+        time.to_seconds(self.tempo)
+    }
+}
+
+impl JingleGenerator {
+    /// Render a sequence, summing its lanes into mono samples
+    ///
+    /// Pan is ignored; use `sequence_mixer` for a stereo mix. Fails with
+    /// `JingleError::Cancelled` if the generator's cancel token is cancelled.
+    pub fn render_sequence(&self, sequence: &Sequence) -> Result<Vec<f32>> {
+        let mut out: Vec<f32> = Vec::new();
+        for lane in &sequence.lanes {
+            let samples = self.render_lane(sequence, lane)?;
+            let gain = db_to_linear(lane.gain_db);
+            if out.len() < samples.len() {
+                out.resize(samples.len(), 0.0);
+            }
+            for (mixed, sample) in out.iter_mut().zip(samples) {
+                *mixed += sample * gain;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Turn each lane of a sequence into a mixer track with the lane's gain and pan
+    pub fn sequence_mixer(&self, sequence: &Sequence) -> Result<Mixer> {
+        sequence.lanes.iter().try_fold(self.mixer(), |mixer, lane| {
+            Ok(mixer.track(Track::new(&lane.name, self.render_lane(sequence, lane)?)
+                .with_sample_rate(self.get_sample_rate())
+                .with_gain_db(lane.gain_db)
+                .with_pan(lane.pan)))
+        })
+    }
+
+    /// Render a lane's events, effects and automation, before its gain
+    fn render_lane(&self, sequence: &Sequence, lane: &SequenceLane) -> Result<Vec<f32>> {
+        self.check_cancelled()?;
+        let sample_rate = self.get_sample_rate();
+        // Notes and chords share the lane's voices, so overlaps keep each note's own envelope
+        let mut notes = Vec::new();
+        let mut layers = Vec::new();
+        for (at, event) in &lane.events {
+            let start = sequence.seconds(*at);
+            match event {
+                SequenceEvent::Note { frequency, duration, waveform } => {
+                    notes.push(VoiceNote::new(start, *frequency, sequence.seconds(*duration), *waveform));
+                }
+                SequenceEvent::Chord { chord, octave, duration, waveform } => {
+                    let root = chord.root.frequency(*octave);
+                    for &semitones in &chord.intervals {
+                        let frequency = root * (2.0_f32).powf(semitones as f32 / 12.0);
+                        notes.push(VoiceNote::new(start, frequency, sequence.seconds(*duration), *waveform));
+                    }
+                }
+                SequenceEvent::Sample { sample, gain_db } => layers.push((start, sample, *gain_db)),
+            }
+        }
+
+        let mut out = if notes.is_empty() { Vec::new() } else { self.render_polyphonic(&notes, lane.max_voices)? };
+        for (start, sample, gain_db) in layers {
+            self.layer_sample(&mut out, sample, start, gain_db);
+        }
+
+        let mut out = self.apply_effects(&out, &lane.effects);
+        for automation in &lane.automation {
+            match automation {
+                LaneAutomation::GainDb(automation) => {
+                    for (i, sample) in out.iter_mut().enumerate() {
+                        *sample *= db_to_linear(automation.value_at(i as f32 / sample_rate as f32));
+                    }
+                }
+                LaneAutomation::LowPass(automation) => {
+                    let start = automation.value_at(0.0);
+                    let mut filter = Automated::new(LowPassFilter::new(start, sample_rate as f32), sample_rate as f32)
+                        .automate(automation.clone(), LowPassFilter::set_cutoff);
+                    out = filter.process(out);
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::Note;
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn test_times() {
+        let sequence = Sequence::new().with_tempo(90.0);
+        assert_eq!(sequence.seconds(SequenceTime::Beats(3.0)), 2.0);
+        assert_eq!(sequence.seconds(0.5.into()), 0.5);
+        assert_eq!(SequenceTime::Seconds(-1.0).to_seconds(120.0), 0.0);
+    }
+
+    #[test]
+    fn test_events_overlap() {
+        let generator = JingleGenerator::with_seed(1);
+        let rate = generator.get_sample_rate() as f32;
+        let tone = generator.render_polyphonic(&[VoiceNote::new(0.0, 440.0, 0.2, WaveForm::Sine)], 1).unwrap();
+
+        // Two notes 0.1 s apart on one lane overlap and sum
+        let lane = SequenceLane::new("a")
+            .note(SequenceTime::Seconds(0.0), 440.0, SequenceTime::Seconds(0.2), WaveForm::Sine)
+            .note(SequenceTime::Seconds(0.1), 440.0, SequenceTime::Seconds(0.2), WaveForm::Sine);
+        let samples = generator.render_sequence(&Sequence::new().lane(lane.clone())).unwrap();
+        let offset = (0.1 * rate).round() as usize;
+        assert_eq!(samples.len(), offset + tone.len());
+        assert!((samples[offset + 10] - (tone[offset + 10] + tone[10])).abs() < 1e-5);
+
+        // With one voice the second note takes over from the first
+        let mono = generator.render_sequence(&Sequence::new().lane(lane.with_max_voices(1))).unwrap();
+        assert!((mono[offset + 10] - tone[10]).abs() < 1e-5);
+
+        // Lanes play at the same time, each at its own level
+        let sequence = Sequence::new()
+            .lane(SequenceLane::new("a").note(SequenceTime::Beats(0.0), 440.0, SequenceTime::Seconds(0.2), WaveForm::Sine))
+            .lane(SequenceLane::new("b").note(SequenceTime::Beats(0.0), 440.0, SequenceTime::Seconds(0.2), WaveForm::Sine).with_gain_db(-120.0));
+        let samples = generator.render_sequence(&sequence).unwrap();
+        assert!((peak(&samples) - peak(&tone)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_chords_samples_and_automation() {
+        let generator = JingleGenerator::with_seed(1);
+        let chord = Sequence::new().lane(SequenceLane::new("pad")
+            .chord(SequenceTime::Seconds(0.0), Chord::major(Note::C), 4, SequenceTime::Seconds(0.5), WaveForm::Sine));
+        assert!(peak(&generator.render_sequence(&chord).unwrap()) <= 1.0);
+
+        let click = Sample::from_samples(vec![0.5; 10], generator.get_sample_rate());
+        let sequence = Sequence::new().with_tempo(60.0).lane(SequenceLane::new("clicks").sample(SequenceTime::Beats(1.0), click, 0.0));
+        let samples = generator.render_sequence(&sequence).unwrap();
+        let start = generator.get_sample_rate() as usize;
+        assert_eq!(samples.len(), start + 10);
+        assert_eq!(samples[start], 0.5);
+
+        let fade = Sequence::new().lane(SequenceLane::new("fade")
+            .note(SequenceTime::Seconds(0.0), 440.0, SequenceTime::Seconds(1.0), WaveForm::Sine)
+            .automate_gain_db(Automation::linear(0.0, -60.0, 1.0)));
+        let samples = generator.render_sequence(&fade).unwrap();
+        let half = samples.len() / 2;
+        assert!(peak(&samples[half..]) < peak(&samples[..half]) * 0.1);
+
+        let mix = generator.sequence_mixer(&fade).unwrap().render();
+        assert_eq!(mix.len(), 2);
+        assert_eq!(mix[0].len(), samples.len());
+    }
+}
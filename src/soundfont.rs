@@ -0,0 +1,705 @@
+//! SoundFont (SF2) instruments for rendering melodies with sampled sounds
+//!
+//! Load a `.sf2` file, pick a preset (a piano, a marimba, a glockenspiel...)
+//! and render melodies with it instead of the raw oscillators. The player
+//! covers what instrument banks rely on to sound right: key and velocity
+//! splits, root keys and tuning, loops, attenuation and the volume envelope.
+//! Modulators, filters and the modulation envelope are ignored, and stereo
+//! zones are folded to mono.
+//!
+//! ```rust,no_run
+//! use jinglebells::{JingleGenerator, Melody, Note, SoundFont};
+//!
+//! let soundfont = SoundFont::from_file("instruments.sf2").unwrap();
+//! let marimba = soundfont.instrument("Marimba").unwrap();
+//! let melody = Melody { notes: vec![(Note::C, 0.2), (Note::E, 0.2), (Note::G, 0.4)] };
+//! let samples = JingleGenerator::new().generate_melody_samples_with_instrument(&melody, 5, &marimba);
+//! ```
+
+use std::path::Path;
+use std::sync::Arc;
+use crate::{
+    audio::MAX_DURATION,
+    effects::apply_gain_db,
+    export::{JingleGenerator, MelodyRenderOptions},
+    progress::ProgressPhase,
+    error::{JingleError, Result},
+};
+
+// Generator operators used by the player, from the SF2 2.04 specification
+const START_OFFSET: u16 = 0;
+const END_OFFSET: u16 = 1;
+const START_LOOP_OFFSET: u16 = 2;
+const END_LOOP_OFFSET: u16 = 3;
+const START_COARSE_OFFSET: u16 = 4;
+const END_COARSE_OFFSET: u16 = 12;
+const DELAY_VOL_ENV: u16 = 33;
+const ATTACK_VOL_ENV: u16 = 34;
+const HOLD_VOL_ENV: u16 = 35;
+const DECAY_VOL_ENV: u16 = 36;
+const SUSTAIN_VOL_ENV: u16 = 37;
+const RELEASE_VOL_ENV: u16 = 38;
+const INSTRUMENT: u16 = 41;
+const KEY_RANGE: u16 = 43;
+const VEL_RANGE: u16 = 44;
+const START_LOOP_COARSE_OFFSET: u16 = 45;
+const INITIAL_ATTENUATION: u16 = 48;
+const END_LOOP_COARSE_OFFSET: u16 = 50;
+const COARSE_TUNE: u16 = 51;
+const FINE_TUNE: u16 = 52;
+const SAMPLE_ID: u16 = 53;
+const SAMPLE_MODES: u16 = 54;
+const SCALE_TUNING: u16 = 56;
+const OVERRIDING_ROOT_KEY: u16 = 58;
+const GENERATOR_COUNT: usize = 61;
+
+/// Generators a preset zone may offset; the rest belong to instruments only
+const ADDITIVE_IN_PRESETS: [u16; 9] = [
+    DELAY_VOL_ENV, ATTACK_VOL_ENV, HOLD_VOL_ENV, DECAY_VOL_ENV, SUSTAIN_VOL_ENV,
+    RELEASE_VOL_ENV, INITIAL_ATTENUATION, COARSE_TUNE, FINE_TUNE,
+];
+
+/// Velocity used when a melody doesn't say how hard a note is played
+pub const DEFAULT_VELOCITY: u8 = 100;
+
+/// Generator amounts of one zone, `None` where the zone leaves the default
+#[derive(Clone, Debug)]
+struct Generators([Option<i16>; GENERATOR_COUNT]);
+
+impl Default for Generators {
+    fn default() -> Self {
+        Self([None; GENERATOR_COUNT])
+    }
+}
+
+impl Generators {
+    fn get(&self, operator: u16) -> Option<i16> {
+        self.0.get(operator as usize).copied().flatten()
+    }
+
+    fn amount(&self, operator: u16, default: i16) -> i32 {
+        self.get(operator).unwrap_or(default) as i32
+    }
+
+    /// Low and high bytes of a range generator, inclusive
+    fn range(&self, operator: u16) -> (u8, u8) {
+        self.get(operator).map_or((0, 127), |amount| {
+            let [low, high] = (amount as u16).to_le_bytes();
+            (low, high)
+        })
+    }
+
+    fn contains(&self, key: u8, velocity: u8) -> bool {
+        let (key_low, key_high) = self.range(KEY_RANGE);
+        let (vel_low, vel_high) = self.range(VEL_RANGE);
+        (key_low..=key_high).contains(&key) && (vel_low..=vel_high).contains(&velocity)
+    }
+
+    /// Zone generators on top of the global zone's
+    fn over(&self, global: &Generators) -> Generators {
+        let mut merged = global.clone();
+        for (slot, amount) in merged.0.iter_mut().zip(self.0.iter()) {
+            if amount.is_some() {
+                *slot = *amount;
+            }
+        }
+        merged
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Zone {
+    generators: Generators,
+}
+
+#[derive(Clone, Debug)]
+struct SampleHeader {
+    start: u32,
+    end: u32,
+    loop_start: u32,
+    loop_end: u32,
+    sample_rate: u32,
+    original_pitch: u8,
+    pitch_correction: i8,
+    sample_type: u16,
+}
+
+#[derive(Clone, Debug)]
+struct InstrumentData {
+    global: Generators,
+    zones: Vec<Zone>,
+}
+
+#[derive(Clone, Debug)]
+struct PresetData {
+    header: SoundFontPreset,
+    global: Generators,
+    zones: Vec<Zone>,
+}
+
+/// Name, bank and program number of a preset in a SoundFont
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SoundFontPreset {
+    pub name: String,
+    pub bank: u16,
+    pub program: u16,
+}
+
+/// A parsed SF2 file: sample data plus its presets and instruments
+#[derive(Clone, Debug)]
+pub struct SoundFont {
+    samples: Vec<f32>,
+    sample_headers: Vec<SampleHeader>,
+    instruments: Vec<InstrumentData>,
+    presets: Vec<PresetData>,
+}
+
+/// Little-endian reader over a chunk's bytes
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self.data.get(self.position..self.position + len)
+            .ok_or_else(|| JingleError::SoundFontError("unexpected end of file".to_string()))?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let bytes = self.bytes(20)?;
+        let end = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+        Ok(String::from_utf8_lossy(&bytes[..end]).trim().to_string())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    /// Next RIFF chunk as its id and body, skipping the pad byte after odd sizes
+    fn chunk(&mut self) -> Result<([u8; 4], &'a [u8])> {
+        let id: [u8; 4] = self.bytes(4)?.try_into().unwrap();
+        let len = self.u32()? as usize;
+        let body = self.bytes(len)?;
+        if len % 2 == 1 && !self.is_empty() {
+            self.position += 1;
+        }
+        Ok((id, body))
+    }
+}
+
+/// Find the `LIST` chunk of a type among chunks
+fn list<'a>(chunks: &[([u8; 4], &'a [u8])], kind: &[u8; 4]) -> Result<Vec<([u8; 4], &'a [u8])>> {
+    let body = chunks.iter()
+        .find(|(id, body)| id == b"LIST" && body.get(..4) == Some(kind.as_slice()))
+        .map(|(_, body)| &body[4..])
+        .ok_or_else(|| JingleError::SoundFontError(format!("missing '{}' list", String::from_utf8_lossy(kind))))?;
+    sub_chunks(body)
+}
+
+fn sub_chunks(body: &[u8]) -> Result<Vec<([u8; 4], &[u8])>> {
+    let mut reader = Reader::new(body);
+    let mut chunks = Vec::new();
+    while !reader.is_empty() {
+        chunks.push(reader.chunk()?);
+    }
+    Ok(chunks)
+}
+
+fn find<'a>(chunks: &[([u8; 4], &'a [u8])], id: &[u8; 4]) -> Result<&'a [u8]> {
+    chunks.iter()
+        .find(|(chunk_id, _)| chunk_id == id)
+        .map(|(_, body)| *body)
+        .ok_or_else(|| JingleError::SoundFontError(format!("missing '{}' chunk", String::from_utf8_lossy(id))))
+}
+
+/// Split a chunk into fixed-size records
+fn records(body: &[u8], size: usize) -> Result<Vec<Reader<'_>>> {
+    if !body.len().is_multiple_of(size) {
+        return Err(JingleError::SoundFontError(format!("record chunk of {} bytes is not a multiple of {}", body.len(), size)));
+    }
+    Ok(body.chunks(size).map(Reader::new).collect())
+}
+
+/// Read `(operator, amount)` generator records
+fn read_generators(body: &[u8]) -> Result<Vec<(u16, i16)>> {
+    records(body, 4)?.into_iter().map(|mut record| Ok((record.u16()?, record.u16()? as i16))).collect()
+}
+
+/// Read bag records as the index of each zone's first generator
+fn read_bags(body: &[u8]) -> Result<Vec<usize>> {
+    records(body, 4)?.into_iter().map(|mut record| Ok(record.u16()? as usize)).collect()
+}
+
+/// Group generators into zones, splitting off a leading global zone (one without the terminal generator)
+fn read_zones(bags: &[usize], generators: &[(u16, i16)], first_bag: usize, end_bag: usize, terminal: u16) -> Result<(Generators, Vec<Zone>)> {
+    let mut global = Generators::default();
+    let mut zones = Vec::new();
+    for bag in first_bag..end_bag {
+        let (Some(&start), Some(&end)) = (bags.get(bag), bags.get(bag + 1)) else {
+            return Err(JingleError::SoundFontError("zone index out of range".to_string()));
+        };
+        let zone_generators = generators.get(start..end)
+            .ok_or_else(|| JingleError::SoundFontError("generator index out of range".to_string()))?;
+        let mut zone = Generators::default();
+        for &(operator, amount) in zone_generators {
+            if let Some(slot) = zone.0.get_mut(operator as usize) {
+                *slot = Some(amount);
+            }
+        }
+        if zone.get(terminal).is_some() {
+            zones.push(Zone { generators: zone });
+        } else if bag == first_bag {
+            global = zone;
+        }
+    }
+    Ok((global, zones))
+}
+
+impl SoundFont {
+    /// Load and parse an SF2 file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Parse SF2 data held in memory
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(data);
+        let (id, body) = reader.chunk()?;
+        if &id != b"RIFF" || body.get(..4) != Some(b"sfbk".as_slice()) {
+            return Err(JingleError::SoundFontError("not a SoundFont 2 file".to_string()));
+        }
+        let chunks = sub_chunks(&body[4..])?;
+
+        let sdta = list(&chunks, b"sdta")?;
+        let smpl = find(&sdta, b"smpl")?;
+        let samples = smpl.chunks_exact(2)
+            .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0)
+            .collect();
+
+        let pdta = list(&chunks, b"pdta")?;
+        let sample_headers = records(find(&pdta, b"shdr")?, 46)?.into_iter().map(|mut record| {
+            record.name()?;
+            Ok(SampleHeader {
+                start: record.u32()?,
+                end: record.u32()?,
+                loop_start: record.u32()?,
+                loop_end: record.u32()?,
+                sample_rate: record.u32()?,
+                original_pitch: record.u8()?,
+                pitch_correction: record.u8()? as i8,
+                sample_type: { record.u16()?; record.u16()? },
+            })
+        }).collect::<Result<Vec<_>>>()?;
+
+        let instrument_bags = read_bags(find(&pdta, b"ibag")?)?;
+        let instrument_generators = read_generators(find(&pdta, b"igen")?)?;
+        let instrument_headers = records(find(&pdta, b"inst")?, 22)?.into_iter()
+            .map(|mut record| { record.name()?; Ok(record.u16()? as usize) })
+            .collect::<Result<Vec<_>>>()?;
+        let instruments = instrument_headers.windows(2).map(|bags| {
+            let (global, zones) = read_zones(&instrument_bags, &instrument_generators, bags[0], bags[1], SAMPLE_ID)?;
+            Ok(InstrumentData { global, zones })
+        }).collect::<Result<Vec<_>>>()?;
+
+        let preset_bags = read_bags(find(&pdta, b"pbag")?)?;
+        let preset_generators = read_generators(find(&pdta, b"pgen")?)?;
+        let preset_headers = records(find(&pdta, b"phdr")?, 38)?.into_iter()
+            .map(|mut record| Ok((record.name()?, record.u16()?, record.u16()?, record.u16()? as usize)))
+            .collect::<Result<Vec<_>>>()?;
+        // The last header of each list is a terminator marking where the previous entry's zones end
+        let presets = preset_headers.windows(2).map(|headers| {
+            let (name, program, bank, first_bag) = headers[0].clone();
+            let (global, zones) = read_zones(&preset_bags, &preset_generators, first_bag, headers[1].3, INSTRUMENT)?;
+            Ok(PresetData { header: SoundFontPreset { name, bank, program }, global, zones })
+        }).collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { samples, sample_headers, instruments, presets })
+    }
+
+    /// List the presets, in file order
+    pub fn presets(&self) -> Vec<SoundFontPreset> {
+        self.presets.iter().map(|preset| preset.header.clone()).collect()
+    }
+
+    /// Pick a preset by name, ignoring case
+    pub fn instrument(&self, name: &str) -> Result<SoundFontInstrument> {
+        self.presets.iter()
+            .position(|preset| preset.header.name.eq_ignore_ascii_case(name.trim()))
+            .map(|preset| SoundFontInstrument { soundfont: Arc::new(self.clone()), preset })
+            .ok_or_else(|| JingleError::InvalidParameter(format!("SoundFont has no preset named '{}'", name)))
+    }
+
+    /// Pick a preset by General MIDI bank and program number
+    pub fn instrument_by_program(&self, bank: u16, program: u16) -> Result<SoundFontInstrument> {
+        self.presets.iter()
+            .position(|preset| preset.header.bank == bank && preset.header.program == program)
+            .map(|preset| SoundFontInstrument { soundfont: Arc::new(self.clone()), preset })
+            .ok_or_else(|| JingleError::InvalidParameter(format!("SoundFont has no preset at bank {} program {}", bank, program)))
+    }
+}
+
+/// Convert timecents to seconds
+fn timecents(amount: i32) -> f32 {
+    2.0_f32.powf(amount as f32 / 1200.0)
+}
+
+/// Convert centibels of attenuation to a linear gain
+fn centibels(amount: i32) -> f32 {
+    10.0_f32.powf(-(amount.max(0) as f32) / 200.0)
+}
+
+/// One sample playing for one note
+struct Voice<'a> {
+    data: &'a [f32],
+    loop_range: Option<(f64, f64)>,
+    loop_after_release: bool,
+    step: f64,
+    gain: f32,
+    delay: f32,
+    attack: f32,
+    hold: f32,
+    decay: f32,
+    sustain: f32,
+    release: f32,
+}
+
+impl Voice<'_> {
+    /// Envelope level `time` seconds after the note starts, released at `held`
+    fn envelope(&self, time: f32, held: f32) -> f32 {
+        let level = |time: f32| {
+            let time = time - self.delay;
+            if time < 0.0 {
+                0.0
+            } else if time < self.attack {
+                time / self.attack
+            } else if time < self.attack + self.hold {
+                1.0
+            } else {
+                // Decay runs in dB, so it sounds even; the sustain level is reached at `decay`
+                let progress = ((time - self.attack - self.hold) / self.decay).min(1.0);
+                self.sustain.max(1e-5).powf(progress)
+            }
+        };
+        if time < held {
+            level(time)
+        } else {
+            let progress = (time - held) / self.release;
+            if progress >= 1.0 { 0.0 } else { level(held) * (1e-5_f32).powf(progress) }
+        }
+    }
+
+    fn render(&self, held: f32, sample_rate: u32, out: &mut [f32]) {
+        let mut position = 0.0_f64;
+        for (i, sample) in out.iter_mut().enumerate() {
+            let time = i as f32 / sample_rate as f32;
+            if let Some((loop_start, loop_end)) = self.loop_range
+                && position >= loop_end
+                && (time < held || self.loop_after_release)
+            {
+                position = loop_start + (position - loop_end) % (loop_end - loop_start);
+            }
+            let index = position as usize;
+            let Some(&current) = self.data.get(index) else { break };
+            let next = self.data.get(index + 1).copied().unwrap_or(0.0);
+            let fraction = (position - index as f64) as f32;
+            *sample += (current + (next - current) * fraction) * self.gain * self.envelope(time, held);
+            position += self.step;
+        }
+    }
+}
+
+/// A preset of a loaded SoundFont, ready to play notes
+#[derive(Clone, Debug)]
+pub struct SoundFontInstrument {
+    soundfont: Arc<SoundFont>,
+    preset: usize,
+}
+
+impl SoundFontInstrument {
+    /// Name of the preset
+    pub fn name(&self) -> &str {
+        &self.soundfont.presets[self.preset].header.name
+    }
+
+    /// Voices of every zone the key and velocity fall in
+    fn voices(&self, frequency: f32, velocity: u8, sample_rate: u32) -> Vec<Voice<'_>> {
+        let soundfont = &*self.soundfont;
+        let preset = &soundfont.presets[self.preset];
+        let pitch = 69.0 + 12.0 * (frequency / 440.0).log2();
+        let key = pitch.round().clamp(0.0, 127.0) as u8;
+        let mut voices = Vec::new();
+
+        for preset_zone in &preset.zones {
+            let preset_generators = preset_zone.generators.over(&preset.global);
+            if !preset_generators.contains(key, velocity) {
+                continue;
+            }
+            let Some(instrument) = preset_generators.get(INSTRUMENT).and_then(|index| soundfont.instruments.get(index as u16 as usize)) else {
+                continue;
+            };
+            for zone in &instrument.zones {
+                let mut generators = zone.generators.over(&instrument.global);
+                if !generators.contains(key, velocity) {
+                    continue;
+                }
+                for operator in ADDITIVE_IN_PRESETS {
+                    if let Some(offset) = preset_generators.get(operator) {
+                        let amount = generators.amount(operator, 0) + offset as i32;
+                        generators.0[operator as usize] = Some(amount.clamp(i16::MIN as i32, i16::MAX as i32) as i16);
+                    }
+                }
+                if let Some(voice) = self.voice(&generators, pitch, velocity, sample_rate) {
+                    voices.push(voice);
+                }
+            }
+        }
+        voices
+    }
+
+    fn voice(&self, generators: &Generators, pitch: f32, velocity: u8, sample_rate: u32) -> Option<Voice<'_>> {
+        let soundfont = &*self.soundfont;
+        let header = soundfont.sample_headers.get(generators.get(SAMPLE_ID)? as u16 as usize)?;
+        let offset = |fine: u16, coarse: u16| generators.amount(fine, 0) as i64 + generators.amount(coarse, 0) as i64 * 32768;
+        let start = (header.start as i64 + offset(START_OFFSET, START_COARSE_OFFSET)).max(0) as usize;
+        let end = ((header.end as i64 + offset(END_OFFSET, END_COARSE_OFFSET)).max(0) as usize).min(soundfont.samples.len());
+        if start >= end {
+            return None;
+        }
+        let loop_start = header.loop_start as i64 + offset(START_LOOP_OFFSET, START_LOOP_COARSE_OFFSET) - start as i64;
+        let loop_end = header.loop_end as i64 + offset(END_LOOP_OFFSET, END_LOOP_COARSE_OFFSET) - start as i64;
+        let mode = generators.amount(SAMPLE_MODES, 0) & 3;
+        let loop_range = (mode == 1 || mode == 3)
+            .then_some((loop_start as f64, loop_end as f64))
+            .filter(|&(loop_start, loop_end)| loop_start >= 0.0 && loop_end > loop_start + 1.0 && loop_end <= (end - start) as f64);
+
+        let root = match generators.amount(OVERRIDING_ROOT_KEY, -1) {
+            root if root >= 0 => root as f32,
+            _ => header.original_pitch.min(127) as f32,
+        };
+        let cents = (pitch - root) * generators.amount(SCALE_TUNING, 100) as f32
+            + generators.amount(COARSE_TUNE, 0) as f32 * 100.0
+            + generators.amount(FINE_TUNE, 0) as f32
+            + header.pitch_correction as f32;
+        let step = 2.0_f64.powf(cents as f64 / 1200.0) * header.sample_rate.max(1) as f64 / sample_rate as f64;
+
+        // Stereo halves (right 2, left 4) are summed to mono, so each contributes half
+        let stereo = if header.sample_type & 6 != 0 { 0.5 } else { 1.0 };
+        let velocity_gain = (velocity as f32 / 127.0).powi(2);
+        Some(Voice {
+            data: &soundfont.samples[start..end],
+            loop_range,
+            loop_after_release: mode == 1,
+            step,
+            gain: centibels(generators.amount(INITIAL_ATTENUATION, 0)) * velocity_gain * stereo,
+            delay: timecents(generators.amount(DELAY_VOL_ENV, -12000)),
+            attack: timecents(generators.amount(ATTACK_VOL_ENV, -12000)),
+            hold: timecents(generators.amount(HOLD_VOL_ENV, -12000)),
+            decay: timecents(generators.amount(DECAY_VOL_ENV, -12000)),
+            sustain: centibels(generators.amount(SUSTAIN_VOL_ENV, 0).min(1440)),
+            release: timecents(generators.amount(RELEASE_VOL_ENV, -12000)),
+        })
+    }
+
+    /// Render a note held for `duration` seconds, followed by its release
+    ///
+    /// Unlooped samples stop when they run out, even while held.
+    pub fn render_note(&self, frequency: f32, velocity: u8, duration: f32, sample_rate: u32) -> Vec<f32> {
+        let duration = duration.clamp(0.0, MAX_DURATION);
+        let voices = self.voices(frequency, velocity.min(127), sample_rate);
+        let release = voices.iter().map(|voice| voice.release).fold(0.0f32, f32::max).min(MAX_DURATION);
+        let mut out = vec![0.0; ((duration + release) * sample_rate as f32) as usize];
+        for voice in &voices {
+            voice.render(duration, sample_rate, &mut out);
+        }
+        // Drop the silence after unlooped samples end
+        let len = out.iter().rposition(|&sample| sample != 0.0).map_or(0, |last| last + 1);
+        out.truncate(len);
+        out
+    }
+}
+
+impl JingleGenerator {
+    /// Render a melody with a SoundFont instrument instead of the oscillators
+    pub fn generate_melody_samples_with_instrument(&self, melody: &crate::music::Melody, octave: i32, instrument: &SoundFontInstrument) -> Vec<f32> {
+        self.generate_melody_samples_with_instrument_options(melody, octave, instrument, &MelodyRenderOptions::default())
+    }
+
+    /// Render a melody with a SoundFont instrument, phrased with articulation and per-note gains
+    ///
+    /// Each note is held for its articulated duration and its release rings over
+    /// the following notes. The envelope and overlap options don't apply: the
+    /// instrument brings its own envelope.
+    pub fn generate_melody_samples_with_instrument_options(&self, melody: &crate::music::Melody, octave: i32, instrument: &SoundFontInstrument, options: &MelodyRenderOptions) -> Vec<f32> {
+        let sample_rate = self.get_sample_rate();
+        let articulation = options.articulation.clamp(0.05, 1.0);
+        let total = melody.notes.len();
+        let mut out = Vec::new();
+        let mut start = 0;
+        for (i, (note, duration)) in melody.notes.iter().enumerate() {
+            let mut voice = instrument.render_note(self.tuned(note.frequency(octave)), DEFAULT_VELOCITY, duration * articulation, sample_rate);
+            if let Some(&gain_db) = options.note_gains_db.get(i % options.note_gains_db.len().max(1)) {
+                apply_gain_db(&mut voice, gain_db);
+            }
+            if out.len() < start + voice.len() {
+                out.resize(start + voice.len(), 0.0);
+            }
+            for (mixed, sample) in out[start..].iter_mut().zip(&voice) {
+                *mixed += sample;
+            }
+            start += (duration.max(0.0) * sample_rate as f32) as usize;
+            if out.len() < start {
+                out.resize(start, 0.0);
+            }
+            self.report_progress(ProgressPhase::Rendering, i + 1, total);
+        }
+        self.master(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::music::{Melody, Note};
+
+    fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut data = id.to_vec();
+        data.extend((body.len() as u32).to_le_bytes());
+        data.extend(body);
+        if body.len() % 2 == 1 {
+            data.push(0);
+        }
+        data
+    }
+
+    fn list_chunk(kind: &[u8; 4], chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = kind.to_vec();
+        chunks.iter().for_each(|chunk| body.extend(chunk));
+        chunk(b"LIST", &body)
+    }
+
+    fn name(name: &str) -> Vec<u8> {
+        let mut bytes = name.as_bytes().to_vec();
+        bytes.resize(20, 0);
+        bytes
+    }
+
+    fn generator(operator: u16, amount: i16) -> Vec<u8> {
+        [operator.to_le_bytes(), amount.to_le_bytes()].concat()
+    }
+
+    fn bag(generator: u16) -> Vec<u8> {
+        [generator.to_le_bytes(), 0u16.to_le_bytes()].concat()
+    }
+
+    /// A one-preset SoundFont holding a looped 440 Hz sine recorded at 44100 Hz
+    fn test_soundfont() -> Vec<u8> {
+        let period = 44100.0 / 440.0;
+        let wave: Vec<u8> = (0..4410)
+            .flat_map(|i| (((i as f32 * 2.0 * std::f32::consts::PI / period).sin() * 16000.0) as i16).to_le_bytes())
+            .collect();
+        let mut smpl = wave;
+        smpl.extend([0u8; 92]); // 46 zero samples after the data
+
+        let sample = |name_: &str, start: u32, end: u32, loop_start: u32, loop_end: u32, pitch: u8| -> Vec<u8> {
+            let mut record = name(name_);
+            for value in [start, end, loop_start, loop_end, 44100] {
+                record.extend(value.to_le_bytes());
+            }
+            record.extend([pitch, 0]);
+            record.extend(0u16.to_le_bytes());
+            record.extend(if end > 0 { 1u16 } else { 0 }.to_le_bytes());
+            record
+        };
+        // Loop 40 whole periods so the wave continues smoothly
+        let shdr = [sample("sine", 0, 4410, 0, (40.0 * period) as u32, 69), sample("EOS", 0, 0, 0, 0, 0)].concat();
+
+        let igen = [
+            generator(RELEASE_VOL_ENV, -1200), // global zone: 0.5 s release
+            generator(SAMPLE_MODES, 1),
+            generator(SAMPLE_ID, 0),
+        ].concat();
+        let ibag = [bag(0), bag(1), bag(3)].concat();
+        let inst = [[name("Sine"), 0u16.to_le_bytes().to_vec()].concat(), [name("EOI"), 2u16.to_le_bytes().to_vec()].concat()].concat();
+
+        let pgen = [generator(INITIAL_ATTENUATION, 60), generator(INSTRUMENT, 0)].concat();
+        let pbag = [bag(0), bag(2)].concat();
+        let preset = |name_: &str, program: u16, bag: u16| [name(name_), program.to_le_bytes().to_vec(), 0u16.to_le_bytes().to_vec(), bag.to_le_bytes().to_vec(), vec![0; 12]].concat();
+        let phdr = [preset("Test Bell", 9, 0), preset("EOP", 0, 1)].concat();
+
+        let pdta = list_chunk(b"pdta", &[
+            chunk(b"phdr", &phdr), chunk(b"pbag", &pbag), chunk(b"pmod", &[0; 10]), chunk(b"pgen", &pgen),
+            chunk(b"inst", &inst), chunk(b"ibag", &ibag), chunk(b"imod", &[0; 10]), chunk(b"igen", &igen),
+            chunk(b"shdr", &shdr),
+        ]);
+        let info = list_chunk(b"INFO", &[chunk(b"ifil", &[2, 0, 4, 0])]);
+        let sdta = list_chunk(b"sdta", &[chunk(b"smpl", &smpl)]);
+        chunk(b"RIFF", &[b"sfbk".to_vec(), info, sdta, pdta].concat())
+    }
+
+    /// Estimate the pitch of a render from its upward zero crossings
+    fn frequency(samples: &[f32], sample_rate: u32) -> f32 {
+        let crossings = samples.windows(2).filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0).count();
+        crossings as f32 * sample_rate as f32 / samples.len() as f32
+    }
+
+    #[test]
+    fn test_parse_presets() {
+        let soundfont = SoundFont::from_bytes(&test_soundfont()).unwrap();
+        assert_eq!(soundfont.presets(), vec![SoundFontPreset { name: "Test Bell".to_string(), bank: 0, program: 9 }]);
+        assert_eq!(soundfont.instrument("test bell").unwrap().name(), "Test Bell");
+        assert!(soundfont.instrument_by_program(0, 9).is_ok());
+        assert!(matches!(soundfont.instrument("Piano"), Err(JingleError::InvalidParameter(_))));
+
+        assert!(matches!(SoundFont::from_bytes(b"RIFF\x04\x00\x00\x00WAVE"), Err(JingleError::SoundFontError(_))));
+        let mut truncated = test_soundfont();
+        truncated.truncate(100);
+        assert!(SoundFont::from_bytes(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_render_note() {
+        let instrument = SoundFont::from_bytes(&test_soundfont()).unwrap().instrument("Test Bell").unwrap();
+
+        // The loop keeps the note sounding past the 0.1 s sample, then it releases
+        let note = instrument.render_note(880.0, 127, 1.0, 44100);
+        assert!(note.len() > 44100 && note.len() <= 66150);
+        assert!((frequency(&note[..44100], 44100) - 880.0).abs() < 5.0);
+        let held = note[..44100].iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!((held - 0.49 * centibels(60)).abs() < 0.02);
+        assert!(note[note.len() - 100..].iter().all(|s| s.abs() < held * 0.01));
+
+        let soft = instrument.render_note(880.0, 64, 0.5, 44100);
+        assert!(soft.iter().fold(0.0f32, |peak, s| peak.max(s.abs())) < held * 0.5);
+    }
+
+    #[test]
+    fn test_melody_with_instrument() {
+        let instrument = SoundFont::from_bytes(&test_soundfont()).unwrap().instrument("Test Bell").unwrap();
+        let generator = JingleGenerator::new();
+        let melody = Melody { notes: vec![(Note::A, 0.25), (Note::E, 0.25)] };
+        let samples = generator.generate_melody_samples_with_instrument(&melody, 4, &instrument);
+        let rate = generator.get_sample_rate();
+        // The last note's release rings past the melody
+        assert!(samples.len() > (0.5 * rate as f32) as usize);
+        assert!((frequency(&samples[..rate as usize / 5], rate) - 440.0).abs() < 10.0);
+    }
+}
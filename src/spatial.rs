@@ -0,0 +1,164 @@
+//! Stereo rendering from a direction using interaural time and level differences
+//!
+//! A lightweight stand-in for HRTF convolution: the far ear hears the sound
+//! later and with the highs shadowed by the head, and sounds from behind are
+//! darkened as the outer ear would. Best heard on headphones.
+
+use std::path::Path;
+use crate::{
+    channels::ChannelLayout,
+    filter::{Biquad, BUTTERWORTH_Q},
+    export::JingleGenerator,
+    spec::JingleSpec,
+    effects::db_to_linear,
+    error::Result,
+};
+
+/// Radius of an average adult head in meters
+const HEAD_RADIUS: f32 = 0.0875;
+/// Speed of sound in air at room temperature (m/s)
+const SPEED_OF_SOUND: f32 = 343.0;
+/// Corner above which the head shadows the far ear
+const HEAD_SHADOW_CORNER: f32 = 1500.0;
+/// Corner above which the outer ear shadows sounds from behind
+const PINNA_SHADOW_CORNER: f32 = 4000.0;
+
+/// Where a sound comes from on the horizontal plane
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Direction {
+    /// Degrees clockwise from straight ahead: 90 is right, -90 left and 180 behind
+    pub azimuth: f32,
+}
+
+impl Direction {
+    pub const FRONT: Direction = Direction { azimuth: 0.0 };
+    pub const RIGHT: Direction = Direction { azimuth: 90.0 };
+    pub const BEHIND: Direction = Direction { azimuth: 180.0 };
+    pub const LEFT: Direction = Direction { azimuth: -90.0 };
+
+    /// Create a direction from an azimuth in degrees, wrapped to -180 - 180
+    pub fn new(azimuth: f32) -> Self {
+        let wrapped = (azimuth + 180.0).rem_euclid(360.0) - 180.0;
+        Self { azimuth: if wrapped == -180.0 { 180.0 } else { wrapped } }
+    }
+
+    /// How far to the side the sound is, from -1.0 (left) to 1.0 (right)
+    pub fn lateral(&self) -> f32 {
+        self.azimuth.to_radians().sin()
+    }
+
+    /// How far behind the listener the sound is, from 0.0 (beside or ahead) to 1.0 (directly behind)
+    pub fn rear(&self) -> f32 {
+        (-self.azimuth.to_radians().cos()).max(0.0)
+    }
+
+    /// Time in seconds by which the near ear hears the sound first (Woodworth's formula)
+    ///
+    /// Positive when the right ear is nearer.
+    pub fn interaural_delay(&self) -> f32 {
+        let angle = self.lateral().asin();
+        HEAD_RADIUS / SPEED_OF_SOUND * (angle + angle.sin())
+    }
+}
+
+/// Render mono samples as planar `[left, right]` channels heard from a direction
+///
+/// The channels are longer than the input by the interaural delay.
+/// - sample_rate: rate (Hz) of the samples
+pub fn spatialize(samples: &[f32], direction: Direction, sample_rate: u32) -> Vec<Vec<f32>> {
+    let sample_rate = sample_rate as f32;
+    let lateral = direction.lateral().abs();
+    let delay = direction.interaural_delay().abs() * sample_rate;
+    let length = samples.len() + delay.ceil() as usize;
+    let mut padded = samples.to_vec();
+    padded.resize(length, 0.0);
+
+    // The head shadows the highs more than the lows and dips the far ear overall
+    let mut far: Vec<f32> = (0..length)
+        .map(|i| {
+            let position = i as f32 - delay;
+            let index = position.floor();
+            let fraction = position - index;
+            let at = |index: f32| if index < 0.0 { 0.0 } else { padded.get(index as usize).copied().unwrap_or(0.0) };
+            at(index) * (1.0 - fraction) + at(index + 1.0) * fraction
+        })
+        .collect();
+    Biquad::high_shelf(HEAD_SHADOW_CORNER, BUTTERWORTH_Q, -12.0 * lateral, sample_rate).process(&mut far);
+    let far_gain = db_to_linear(-3.0 * lateral);
+    far.iter_mut().for_each(|sample| *sample *= far_gain);
+    let mut near = padded;
+
+    let rear = direction.rear();
+    if rear > 0.0 {
+        for ear in [&mut near, &mut far] {
+            Biquad::high_shelf(PINNA_SHADOW_CORNER, BUTTERWORTH_Q, -6.0 * rear, sample_rate).process(ear);
+        }
+    }
+
+    if direction.lateral() >= 0.0 {
+        vec![far, near]
+    } else {
+        vec![near, far]
+    }
+}
+
+impl JingleGenerator {
+    /// Render a spec as planar `[left, right]` channels heard from a direction
+    pub fn render_spatial(&mut self, spec: &JingleSpec, direction: Direction) -> Vec<Vec<f32>> {
+        spatialize(&self.render(spec), direction, self.get_sample_rate())
+    }
+
+    /// Export mono samples to a stereo WAV file heard from a direction
+    pub fn export_spatial_wav<P: AsRef<Path>>(&self, samples: &[f32], direction: Direction, path: P) -> Result<()> {
+        self.export_multichannel_wav(&spatialize(samples, direction, self.get_sample_rate()), ChannelLayout::Stereo, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SAMPLE_RATE, audio::WaveForm};
+
+    fn energy(samples: &[f32]) -> f32 {
+        samples.iter().map(|s| s * s).sum()
+    }
+
+    fn onset(samples: &[f32]) -> usize {
+        samples.iter().position(|s| s.abs() > 1e-4).unwrap()
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Direction::new(270.0), Direction::LEFT);
+        assert_eq!(Direction::new(-180.0), Direction::BEHIND);
+        assert!(Direction::FRONT.interaural_delay().abs() < 1e-6);
+        // About two thirds of a millisecond for a sound directly to one side
+        let delay = Direction::RIGHT.interaural_delay();
+        assert!((0.0006..0.0007).contains(&delay), "{}", delay);
+        assert_eq!(Direction::LEFT.interaural_delay(), -delay);
+    }
+
+    #[test]
+    fn test_spatialize_cues() {
+        let tone = JingleGenerator::with_seed(1).generate_tone(2000.0, 0.1, WaveForm::Sawtooth);
+
+        let front = spatialize(&tone, Direction::FRONT, SAMPLE_RATE);
+        assert_eq!(front[0], front[1]);
+        assert_eq!(front[0], tone);
+
+        let right = spatialize(&tone, Direction::RIGHT, SAMPLE_RATE);
+        assert!(energy(&right[1]) > energy(&right[0]) * 2.0);
+        let mut click = vec![0.0; 100];
+        click[10] = 1.0;
+        let click = spatialize(&click, Direction::RIGHT, SAMPLE_RATE);
+        let lag = onset(&click[0]) - onset(&click[1]);
+        assert!((27..=30).contains(&lag), "{}", lag);
+
+        let left = spatialize(&tone, Direction::LEFT, SAMPLE_RATE);
+        assert_eq!(left[0], right[1]);
+
+        let behind = spatialize(&tone, Direction::BEHIND, SAMPLE_RATE);
+        assert!(behind[0].iter().zip(&behind[1]).all(|(l, r)| (l - r).abs() < 1e-4));
+        assert!(energy(&behind[0]) < energy(&front[0]));
+    }
+}
@@ -0,0 +1,458 @@
+//! Declarative description of a jingle to render
+
+use std::path::Path;
+use crate::{
+    SAMPLE_RATE,
+    audio::{WaveForm, ADSR, MAX_DURATION},
+    music::{Scale, MelodyPattern},
+    presets::JinglePreset,
+    chain::EffectChain,
+    export::JingleGenerator,
+    notation::Phrase,
+    error::{Result, check_positive},
+};
+#[cfg(feature = "serde")]
+use crate::error::JingleError;
+
+/// File formats a spec can be saved in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecFormat {
+    Toml,
+    Json,
+}
+
+impl SpecFormat {
+    /// Pick the format from a file extension, defaulting to TOML
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => SpecFormat::Json,
+            _ => SpecFormat::Toml,
+        }
+    }
+}
+
+/// Everything needed to render a preset jingle, consumed by `JingleGenerator::render`
+///
+/// Unset options fall back to the preset's own (randomized) choices.
+///
+/// ```rust
+/// use jinglebells::{JingleGenerator, JinglePreset, JingleSpec, EffectChain, Scale, WaveForm};
+///
+/// let spec = JingleSpec::new(JinglePreset::Success)
+///     .with_waveform(WaveForm::Triangle)
+///     .with_scale(Scale::Pentatonic)
+///     .with_duration(0.6)
+///     .with_effects(EffectChain::new().reverb(0.8, 0.4, 0.2))
+///     .with_seed(42);
+/// let samples = JingleGenerator::new().render(&spec);
+/// assert!(!samples.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(deny_unknown_fields))]
+pub struct JingleSpec {
+    pub preset: JinglePreset,
+    #[cfg_attr(feature = "serde", serde(default = "default_waveform"))]
+    pub waveform: WaveForm,
+    /// Base duration in seconds
+    pub duration: Option<f32>,
+    /// Base frequency in Hz
+    pub frequency: Option<f32>,
+    pub scale: Option<Scale>,
+    pub pattern: Option<MelodyPattern>,
+    /// Envelope for oscillator notes
+    pub adsr: Option<ADSR>,
+    /// Effects applied after rendering
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub effects: EffectChain,
+    /// Seed for this render alone, leaving the generator's random sequence untouched
+    pub seed: Option<u64>,
+}
+
+impl JingleSpec {
+    /// Create a spec for a preset with a sine waveform and no overrides
+    pub fn new(preset: JinglePreset) -> Self {
+        Self {
+            preset,
+            waveform: WaveForm::Sine,
+            duration: None,
+            frequency: None,
+            scale: None,
+            pattern: None,
+            adsr: None,
+            effects: EffectChain::new(),
+            seed: None,
+        }
+    }
+
+    /// Set the waveform
+    pub fn with_waveform(mut self, waveform: WaveForm) -> Self {
+        self.waveform = waveform;
+        self
+    }
+
+    /// Set the base duration in seconds
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Set the base frequency in Hz
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Use a scale instead of letting the preset choose one
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Use a melody pattern instead of letting the preset choose one
+    pub fn with_pattern(mut self, pattern: MelodyPattern) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    /// Set the envelope for oscillator notes
+    pub fn with_adsr(mut self, adsr: ADSR) -> Self {
+        self.adsr = Some(adsr);
+        self
+    }
+
+    /// Run the rendered jingle through an effect chain
+    pub fn with_effects(mut self, effects: EffectChain) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// Render from a fixed seed instead of the generator's random sequence
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Check the duration, base frequency, envelope and effects, as `JingleGenerator::try_render` does
+    pub fn validate(&self) -> Result<()> {
+        if let Some(duration) = self.duration {
+            check_positive("duration (s)", duration, MAX_DURATION)?;
+        }
+        if let Some(frequency) = self.frequency {
+            check_positive("base frequency (Hz)", frequency, SAMPLE_RATE as f32 / 2.0)?;
+        }
+        if let Some(adsr) = self.adsr {
+            adsr.validate()?;
+        }
+        self.effects.validate()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl JingleSpec {
+    /// Load a spec from a TOML or JSON file, chosen by its extension
+    ///
+    /// Files hold the spec's fields in its serde form; only `preset` is required:
+    ///
+    /// ```toml
+    /// preset = "success"
+    /// waveform = "triangle"
+    /// duration = 0.6
+    /// scale = "pentatonic"
+    /// seed = 42
+    ///
+    /// [adsr]
+    /// attack = 0.01
+    /// decay = 0.1
+    /// sustain = 0.7
+    /// release = 0.2
+    ///
+    /// [[effects]]
+    /// effect = { type = "reverb", room_size = 0.8, damping = 0.4, mix = 0.2 }
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let text = std::fs::read_to_string(&path)?;
+        Self::parse(&text, SpecFormat::from_path(path))
+    }
+
+    /// Save the spec as TOML or JSON, chosen by the file extension
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(&path, self.format(SpecFormat::from_path(&path))?)?;
+        Ok(())
+    }
+
+    /// Parse a spec from TOML or JSON text, failing on values `validate` rejects
+    pub fn parse(text: &str, format: SpecFormat) -> Result<Self> {
+        let spec: Self = match format {
+            SpecFormat::Toml => toml::from_str(text).map_err(|err| invalid(err.to_string()))?,
+            SpecFormat::Json => serde_json::from_str(text).map_err(|err| invalid(err.to_string()))?,
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Format the spec as TOML or JSON text
+    ///
+    /// TOML integers are signed, so seeds above `i64::MAX` can only be written as JSON.
+    pub fn format(&self, format: SpecFormat) -> Result<String> {
+        match format {
+            SpecFormat::Toml if self.seed.is_some_and(|seed| seed > i64::MAX as u64) => {
+                Err(invalid("TOML integers can't hold seeds above i64::MAX; save the spec as JSON".to_string()))
+            },
+            SpecFormat::Toml => toml::to_string(self).map_err(|err| invalid(err.to_string())),
+            SpecFormat::Json => serde_json::to_string_pretty(self).map_err(|err| invalid(err.to_string())),
+        }
+    }
+}
+
+/// A spec document: a preset spec, or a melody written in note notation
+///
+/// Documents are untagged: melodies have a `notes` field instead of a preset.
+///
+/// ```rust
+/// # #[cfg(feature = "serde")] {
+/// use jinglebells::{JingleGenerator, SpecDocument, SpecFormat};
+///
+/// let text = r#"{"notes": "C5:e E5:e G5:q", "tempo": 140, "waveform": "triangle"}"#;
+/// let document = SpecDocument::parse(text, SpecFormat::Json).unwrap();
+/// assert!(matches!(document, SpecDocument::Melody(_)));
+/// let samples = JingleGenerator::with_seed(1).render_document(&document).unwrap();
+/// assert!(!samples.is_empty());
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(untagged))]
+pub enum SpecDocument {
+    Preset(JingleSpec),
+    Melody(MelodySpec),
+}
+
+/// Melody in `Phrase` notation with its waveform and effects
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(deny_unknown_fields))]
+pub struct MelodySpec {
+    /// Notes in `Phrase` notation, e.g. `"C4:e E4:e G4:q"`
+    pub notes: String,
+    /// Tempo in beats per minute, or the phrase default
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tempo: Option<f32>,
+    #[cfg_attr(feature = "serde", serde(default = "default_waveform"))]
+    pub waveform: WaveForm,
+    /// Effects applied after rendering
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub effects: EffectChain,
+}
+
+impl MelodySpec {
+    /// Parse the notes at the tempo
+    pub fn phrase(&self) -> Result<Phrase> {
+        let phrase = Phrase::parse(&self.notes)?;
+        Ok(match self.tempo {
+            Some(tempo) => phrase.with_tempo(tempo),
+            None => phrase,
+        })
+    }
+}
+
+impl SpecDocument {
+    /// Parse a document from TOML or JSON text, failing on specs `validate` rejects
+    #[cfg(feature = "serde")]
+    pub fn parse(text: &str, format: SpecFormat) -> Result<Self> {
+        let document: Self = match format {
+            SpecFormat::Toml => toml::from_str(text).map_err(|err| invalid(err.to_string()))?,
+            SpecFormat::Json => serde_json::from_str(text).map_err(|err| invalid(err.to_string()))?,
+        };
+        document.validate()?;
+        Ok(document)
+    }
+
+    /// Check the spec, or the melody's notes and effects
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            SpecDocument::Preset(spec) => spec.validate(),
+            SpecDocument::Melody(melody) => {
+                melody.phrase()?;
+                melody.effects.validate()
+            },
+        }
+    }
+}
+
+impl JingleGenerator {
+    /// Render a spec document; preset specs render like `try_render`
+    pub fn render_document(&mut self, document: &SpecDocument) -> Result<Vec<f32>> {
+        match document {
+            SpecDocument::Preset(spec) => self.try_render(spec),
+            SpecDocument::Melody(melody) => {
+                melody.effects.validate()?;
+                let samples = self.render_phrase(&melody.phrase()?, melody.waveform)?;
+                Ok(self.apply_effects(&samples, &melody.effects))
+            },
+        }
+    }
+}
+
+/// Waveform of specs that don't name one
+#[cfg(feature = "serde")]
+fn default_waveform() -> WaveForm {
+    WaveForm::Sine
+}
+
+/// Invalid spec file error
+#[cfg(feature = "serde")]
+fn invalid(message: String) -> JingleError {
+    JingleError::InvalidParameter(format!("spec file: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::JingleError;
+    #[cfg(feature = "serde")]
+    use crate::{Severity, UiSound};
+
+    #[cfg(feature = "serde")]
+    fn full_spec() -> JingleSpec {
+        JingleSpec::new(JinglePreset::Warning(Severity::High))
+            .with_waveform(WaveForm::Triangle)
+            .with_duration(0.6)
+            .with_frequency(523.25)
+            .with_scale(Scale::Pentatonic)
+            .with_pattern(MelodyPattern::ScaleRun)
+            .with_adsr(ADSR { attack: 0.01, decay: 0.1, sustain: 0.7, release: 0.2 })
+            .with_effects(EffectChain::new().reverb(0.8, 0.4, 0.2).tremolo(5.0, 0.5, WaveForm::Square).limiter())
+            .with_seed(i64::MAX as u64)
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_spec_round_trip() {
+        let spec = full_spec();
+        for format in [SpecFormat::Toml, SpecFormat::Json] {
+            let text = spec.format(format).unwrap();
+            let parsed = JingleSpec::parse(&text, format).unwrap();
+            assert_eq!(parsed.format(format).unwrap(), text);
+            assert_eq!(parsed.seed, Some(i64::MAX as u64));
+            assert_eq!(parsed.effects.len(), 3);
+        }
+        
+        // JSON keeps seeds above the TOML integer range
+        let spec = spec.with_seed(u64::MAX - 1);
+        let parsed = JingleSpec::parse(&spec.format(SpecFormat::Json).unwrap(), SpecFormat::Json).unwrap();
+        assert_eq!(parsed.seed, Some(u64::MAX - 1));
+        assert!(spec.format(SpecFormat::Toml).is_err());
+        
+        // Effects the old file syntax couldn't write now round-trip too
+        let distorted = JingleSpec::new(JinglePreset::Success).with_effects(EffectChain::new().distortion(crate::effects::Shaper::Tanh, 2.0, 1.0));
+        let parsed = JingleSpec::parse(&distorted.format(SpecFormat::Toml).unwrap(), SpecFormat::Toml).unwrap();
+        assert_eq!(parsed.effects.len(), 1);
+        
+        let minimal = JingleSpec::new(JinglePreset::Ui(UiSound::Tap));
+        let parsed = JingleSpec::parse(&minimal.format(SpecFormat::Toml).unwrap(), SpecFormat::Toml).unwrap();
+        assert_eq!(parsed.preset, JinglePreset::Ui(UiSound::Tap));
+        assert!(parsed.duration.is_none() && parsed.effects.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_spec_parse_handwritten() {
+        let toml = "# office chime\npreset = \"doorbell\"\nduration = 1.5 # seconds\n\n[[effects]]\neffect = { type = \"echo\", delay_ms = 120, feedback = 0.3, mix = 0.25 }\n";
+        let spec = JingleSpec::parse(toml, SpecFormat::Toml).unwrap();
+        assert_eq!(spec.preset, JinglePreset::Doorbell);
+        assert_eq!(spec.waveform, WaveForm::Sine);
+        assert_eq!(spec.duration, Some(1.5));
+        assert_eq!(spec.effects.len(), 1);
+        
+        let json = r#"{"preset": {"warning": "critical"}, "waveform": "square", "scale": null, "seed": 7}"#;
+        let spec = JingleSpec::parse(json, SpecFormat::Json).unwrap();
+        assert_eq!(spec.preset, JinglePreset::Warning(Severity::Critical));
+        assert_eq!(spec.waveform, WaveForm::Square);
+        assert_eq!(spec.scale, None);
+        assert_eq!(spec.seed, Some(7));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_spec_parse_errors() {
+        for (text, format) in [
+            ("duration = 1.0", SpecFormat::Toml),
+            ("preset = \"fanfare\"", SpecFormat::Toml),
+            ("preset = \"success\"\ntempo = 120", SpecFormat::Toml),
+            ("preset = \"success\"\nduration = -1.0", SpecFormat::Toml),
+            ("preset = \"success\"\n[[effects]]\neffect = { type = \"flanger\" }", SpecFormat::Toml),
+            ("{\"preset\": \"success\"", SpecFormat::Json),
+            ("{preset: \"success\"}", SpecFormat::Json),
+        ] {
+            assert!(matches!(JingleSpec::parse(text, format), Err(JingleError::InvalidParameter(_))), "{}", text);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_spec_files() {
+        let spec = full_spec();
+        for name in ["jinglebells_test_spec.toml", "jinglebells_test_spec.json"] {
+            let path = std::env::temp_dir().join(name);
+            spec.save(&path).unwrap();
+            let loaded = JingleSpec::from_path(&path).unwrap();
+            assert_eq!(loaded.format(SpecFormat::Toml).unwrap(), spec.format(SpecFormat::Toml).unwrap());
+            std::fs::remove_file(&path).ok();
+        }
+        assert_eq!(SpecFormat::from_path("recipe.JSON"), SpecFormat::Json);
+        assert_eq!(SpecFormat::from_path("recipe"), SpecFormat::Toml);
+    }
+
+    #[test]
+    fn test_try_render_validates() {
+        let mut generator = crate::JingleGenerator::with_seed(1);
+        assert!(generator.try_render(&JingleSpec::new(JinglePreset::Success).with_duration(0.5)).is_ok());
+        for spec in [
+            JingleSpec::new(JinglePreset::Success).with_duration(-0.5),
+            JingleSpec::new(JinglePreset::Success).with_duration(f32::NAN),
+            JingleSpec::new(JinglePreset::Success).with_frequency(0.0),
+            JingleSpec::new(JinglePreset::Success).with_adsr(ADSR { decay: f32::NAN, ..ADSR::default() }),
+            JingleSpec::new(JinglePreset::Success).with_effects(EffectChain::new().echo(1e9, 0.3, 0.3)),
+        ] {
+            assert!(matches!(generator.try_render(&spec), Err(JingleError::InvalidParameter(_))));
+            assert!(generator.render_named("success", &spec).is_err());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_spec_document() {
+        let text = full_spec().format(SpecFormat::Json).unwrap();
+        let SpecDocument::Preset(spec) = SpecDocument::parse(&text, SpecFormat::Json).unwrap() else {
+            panic!("expected a preset spec");
+        };
+        assert_eq!(spec.seed, Some(i64::MAX as u64));
+
+        let text = r#"{"notes": "C4:e E4:e G4:h", "tempo": 90, "waveform": "square", "effects": [{"effect": {"type": "gain", "gain_db": -3}}]}"#;
+        let document = SpecDocument::parse(text, SpecFormat::Json).unwrap();
+        let SpecDocument::Melody(melody) = &document else {
+            panic!("expected a melody");
+        };
+        assert_eq!((melody.tempo, melody.waveform, melody.effects.len()), (Some(90.0), WaveForm::Square, 1));
+        let phrase = melody.phrase().unwrap();
+        assert_eq!(phrase.notes().len(), 3);
+        let samples = crate::JingleGenerator::with_seed(1).render_document(&document).unwrap();
+        assert!(samples.len() as f32 >= phrase.seconds() * SAMPLE_RATE as f32);
+
+        // JSON written by serde from the library types reads back as a document
+        let json = serde_json::to_string(&full_spec()).unwrap();
+        let document: SpecDocument = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert!(matches!(document, SpecDocument::Preset(spec) if spec.preset == full_spec().preset));
+        let json = serde_json::to_string(&SpecDocument::Melody(melody.clone())).unwrap();
+        let document: SpecDocument = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert!(matches!(document, SpecDocument::Melody(restored) if restored.notes == melody.notes));
+
+        for text in [
+            r#"{"notes": "C4 H4"}"#,
+            r#"{"notes": "C4", "preset": "success"}"#,
+            r#"{"notes": "C4", "octave": 5}"#,
+            r#"{"notes": "C4", "effects": [{"effect": {"type": "echo", "delay_ms": 1e9, "feedback": 0.3, "mix": 0.3}}]}"#,
+            r#"{"waveform": "sine"}"#,
+        ] {
+            assert!(matches!(SpecDocument::parse(text, SpecFormat::Json), Err(JingleError::InvalidParameter(_))), "{}", text);
+        }
+    }
+}
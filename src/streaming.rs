@@ -0,0 +1,233 @@
+//! Drain-to-disk rendering for long outputs such as hold music
+//!
+//! Rendering a ten-minute loop into a `Vec<f32>` before exporting it keeps
+//! every sample in memory. `StreamingWavWriter` writes 16-bit PCM as blocks
+//! arrive, and the `JingleGenerator::stream_*_to_wav` methods feed it straight
+//! from a streaming renderer so memory stays flat however long the output is.
+//!
+//! ```rust
+//! use jinglebells::{JingleGenerator, VoiceNote, WaveForm};
+//!
+//! let generator = JingleGenerator::with_seed(1);
+//! let pad: Vec<VoiceNote> = (0..8)
+//!     .map(|i| VoiceNote::new(i as f32 * 0.5, [220.0, 277.18, 329.63][i % 3], 1.0, WaveForm::Sine))
+//!     .collect();
+//! let path = std::env::temp_dir().join("jinglebells_doc_hold_music.wav");
+//! let frames = generator.stream_polyphonic_to_wav(&pad, 4, &path).unwrap();
+//! assert!(frames > 44100 * 4);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use hound::{SampleFormat, WavSpec, WavWriter};
+use rodio::source::Source;
+use crate::{
+    cancel::CANCEL_CHECK_INTERVAL,
+    clipping::OutputPolicy,
+    encoder::{EncodeSpec, quantize},
+    export::{Dither, JingleGenerator},
+    music::Melody,
+    audio::WaveForm,
+    progress::ProgressPhase,
+    voices::VoiceNote,
+    error::{JingleError, Result},
+};
+
+/// 16-bit PCM WAV file written block by block
+///
+/// The header's length fields are filled in by `finalize`; a writer dropped
+/// without finalizing leaves a file most players still open up to its last block.
+pub struct StreamingWavWriter {
+    writer: WavWriter<BufWriter<File>>,
+    channels: u16,
+    dither: Dither,
+    dither_seed: u64,
+    blocks: u64,
+    samples: u64,
+}
+
+impl StreamingWavWriter {
+    /// Create a WAV file for interleaved samples in a format
+    pub fn create<P: AsRef<Path>>(path: P, spec: EncodeSpec) -> Result<Self> {
+        if spec.channels == 0 {
+            return Err(JingleError::InvalidParameter("WAV needs at least one channel".to_string()));
+        }
+        let wav_spec = WavSpec {
+            channels: spec.channels,
+            sample_rate: spec.sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        Ok(Self {
+            writer: WavWriter::create(path, wav_spec)?,
+            channels: spec.channels,
+            dither: Dither::None,
+            dither_seed: 0,
+            blocks: 0,
+            samples: 0,
+        })
+    }
+
+    /// Set the dither used for 16-bit conversion and the seed of its noise
+    pub fn with_dither(mut self, dither: Dither, dither_seed: u64) -> Self {
+        self.dither = dither;
+        self.dither_seed = dither_seed;
+        self
+    }
+
+    /// Quantize and append interleaved samples (-1.0 to 1.0)
+    pub fn write(&mut self, samples: &[f32]) -> Result<()> {
+        // A fresh noise seed per block so blocks don't repeat the same dither pattern
+        let seed = self.dither_seed.wrapping_add(self.blocks);
+        for sample in quantize(samples, 1.0, self.dither, seed) {
+            self.writer.write_sample(sample)?;
+        }
+        self.blocks += 1;
+        self.samples += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Whole frames written so far
+    pub fn frames_written(&self) -> u64 {
+        self.samples / self.channels as u64
+    }
+
+    /// Complete the header and close the file, returning the number of frames written
+    pub fn finalize(self) -> Result<u64> {
+        let frames = self.frames_written();
+        self.writer.finalize()?;
+        Ok(frames)
+    }
+}
+
+impl JingleGenerator {
+    /// Create a streaming WAV writer at the generator's sample rate with its dither settings
+    pub fn streaming_wav_writer<P: AsRef<Path>>(&self, path: P, channels: u16) -> Result<StreamingWavWriter> {
+        let spec = EncodeSpec { sample_rate: self.get_sample_rate(), channels };
+        Ok(StreamingWavWriter::create(path, spec)?.with_dither(self.get_dither(), self.get_current_seed().unwrap_or(0)))
+    }
+
+    /// Drain a rodio source into a WAV file block by block, returning the number of frames written
+    ///
+    /// The file keeps the source's sample rate and channel count. The loudness target
+    /// and output sample rate are not applied since both need the whole render, and the
+    /// `Normalize` output policy falls back to clamping for the same reason.
+    pub fn stream_to_wav<S: Source<Item = f32>, P: AsRef<Path>>(&self, mut source: S, path: P) -> Result<u64> {
+        let channels = source.channels();
+        let spec = EncodeSpec { sample_rate: source.sample_rate(), channels };
+        let mut writer = StreamingWavWriter::create(path, spec)?.with_dither(self.get_dither(), self.get_current_seed().unwrap_or(0));
+        let total = source.size_hint().1.map(|samples| samples / channels.max(1) as usize);
+        // Whole frames per block so every block starts on the first channel
+        let block_len = CANCEL_CHECK_INTERVAL.next_multiple_of(channels.max(1) as usize);
+        let mut block = Vec::with_capacity(block_len);
+        loop {
+            self.check_cancelled()?;
+            block.clear();
+            block.extend(source.by_ref().take(block_len));
+            if block.is_empty() {
+                break;
+            }
+            self.write_stream_block(&mut writer, &mut block, total)?;
+        }
+        writer.finalize()
+    }
+
+    /// Stream a melody into a WAV file note by note, without rendering it in memory first
+    ///
+    /// Writes the samples of `melody_source`; see `stream_to_wav` for the settings not applied.
+    pub fn stream_melody_to_wav<P: AsRef<Path>>(&self, melody: &Melody, octave: i32, waveform: WaveForm, path: P) -> Result<u64> {
+        self.stream_to_wav(self.melody_source(melody, octave, waveform), path)
+    }
+
+    /// Render overlapping notes straight into a WAV file, returning the number of frames written
+    ///
+    /// Writes the samples of `render_polyphonic`; see `stream_to_wav` for the settings not applied.
+    pub fn stream_polyphonic_to_wav<P: AsRef<Path>>(&self, notes: &[VoiceNote], max_voices: usize, path: P) -> Result<u64> {
+        let mut writer = self.streaming_wav_writer(path, 1)?;
+        // Estimated length for progress: the last note off plus its release
+        let end = notes.iter().map(|note| note.start.max(0.0) + note.duration.max(0.0)).fold(0.0f32, f32::max);
+        let total = ((end + self.get_adsr().release.max(0.0)) * self.get_sample_rate() as f32) as usize;
+        self.polyphonic_blocks(notes, max_voices, |block| {
+            let mut block = block.to_vec();
+            self.write_stream_block(&mut writer, &mut block, Some(total))
+        })?;
+        writer.finalize()
+    }
+
+    /// Apply the output policy to one block, write it and report progress
+    fn write_stream_block(&self, writer: &mut StreamingWavWriter, block: &mut [f32], total: Option<usize>) -> Result<()> {
+        if self.get_output_policy() == OutputPolicy::Normalize {
+            block.iter_mut().for_each(|sample| *sample = if sample.is_nan() { 0.0 } else { sample.clamp(-1.0, 1.0) });
+        } else {
+            self.apply_output_policy(block)?;
+        }
+        writer.write(block)?;
+        let done = writer.frames_written() as usize;
+        self.report_progress(ProgressPhase::Exporting, done, total.unwrap_or(done).max(done));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cancel::CancelToken, music::Note};
+
+    fn read_wav(path: &Path) -> (hound::WavSpec, Vec<i16>) {
+        let mut reader = hound::WavReader::open(path).unwrap();
+        let samples = reader.samples::<i16>().map(|sample| sample.unwrap()).collect();
+        (reader.spec(), samples)
+    }
+
+    #[test]
+    fn test_writer_appends_blocks() {
+        let path = std::env::temp_dir().join("jinglebells_test_streaming_writer.wav");
+        let mut writer = StreamingWavWriter::create(&path, EncodeSpec { sample_rate: 8000, channels: 2 }).unwrap();
+        writer.write(&[0.5, -0.5, 0.25, -0.25]).unwrap();
+        writer.write(&[1.5, 0.0]).unwrap();
+        assert_eq!(writer.frames_written(), 3);
+        assert_eq!(writer.finalize().unwrap(), 3);
+
+        let (spec, samples) = read_wav(&path);
+        assert_eq!((spec.channels, spec.sample_rate), (2, 8000));
+        assert_eq!(samples, quantize(&[0.5, -0.5, 0.25, -0.25, 1.5, 0.0], 1.0, Dither::None, 0));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(StreamingWavWriter::create(&path, EncodeSpec { sample_rate: 8000, channels: 0 }).is_err());
+    }
+
+    #[test]
+    fn test_streams_match_in_memory_renders() {
+        let generator = JingleGenerator::with_seed(3);
+        let path = std::env::temp_dir().join("jinglebells_test_streaming_render.wav");
+
+        let melody = Melody { notes: vec![(Note::C, 0.1), (Note::G, 0.2)] };
+        let frames = generator.stream_melody_to_wav(&melody, 5, WaveForm::Square, &path).unwrap();
+        let expected: Vec<f32> = generator.melody_source(&melody, 5, WaveForm::Square).collect();
+        assert_eq!(frames as usize, expected.len());
+        assert_eq!(read_wav(&path).1, quantize(&expected, 1.0, Dither::None, 0));
+
+        let notes = vec![
+            VoiceNote::new(0.0, 220.0, 0.3, WaveForm::Sine),
+            VoiceNote::new(0.1, 330.0, 0.2, WaveForm::Triangle),
+        ];
+        let frames = generator.stream_polyphonic_to_wav(&notes, 2, &path).unwrap();
+        let expected = generator.render_polyphonic(&notes, 2).unwrap();
+        assert_eq!(frames as usize, expected.len());
+        assert_eq!(read_wav(&path).1, quantize(&expected, 1.0, Dither::None, 0));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stream_cancelled() {
+        let token = CancelToken::new();
+        token.cancel();
+        let generator = JingleGenerator::with_seed(3).with_cancel_token(token);
+        let path = std::env::temp_dir().join("jinglebells_test_streaming_cancel.wav");
+        let notes = vec![VoiceNote::new(0.0, 220.0, 1.0, WaveForm::Sine)];
+        assert!(matches!(generator.stream_polyphonic_to_wav(&notes, 1, &path), Err(JingleError::Cancelled)));
+        let _ = std::fs::remove_file(&path);
+    }
+}
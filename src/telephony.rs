@@ -0,0 +1,233 @@
+//! DTMF digits and call progress tones for IVR testing and softphone interfaces
+
+use std::f32::consts::PI;
+use crate::{
+    SAMPLE_RATE,
+    error::{JingleError, Result},
+};
+
+/// DTMF row (low group) frequencies in Hz
+pub const DTMF_ROWS: [f32; 4] = [697.0, 770.0, 852.0, 941.0];
+/// DTMF column (high group) frequencies in Hz
+pub const DTMF_COLUMNS: [f32; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+
+/// Keypad layout matching `DTMF_ROWS` and `DTMF_COLUMNS`
+const KEYPAD: [[char; 4]; 4] = [
+    ['1', '2', '3', 'A'],
+    ['4', '5', '6', 'B'],
+    ['7', '8', '9', 'C'],
+    ['*', '0', '#', 'D'],
+];
+
+/// Level of each sine in a tone pair, leaving headroom when both peak together
+const COMPONENT_LEVEL: f32 = 0.35;
+/// Ramp at the start and end of every burst so cadences don't click
+const RAMP_DURATION: f32 = 0.002;
+
+/// Call progress tones played by the network while a call is set up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallProgressTone {
+    /// Line is ready for dialling
+    Dial,
+    /// Called party is busy
+    Busy,
+    /// Called party is being rung
+    Ringback,
+    /// Call can't be completed (fast busy)
+    Reorder,
+}
+
+impl CallProgressTone {
+    /// Get all call progress tones
+    pub fn all() -> Vec<CallProgressTone> {
+        vec![CallProgressTone::Dial, CallProgressTone::Busy, CallProgressTone::Ringback, CallProgressTone::Reorder]
+    }
+
+    /// Get the name of this tone as a string
+    pub fn name(&self) -> &'static str {
+        match self {
+            CallProgressTone::Dial => "dial",
+            CallProgressTone::Busy => "busy",
+            CallProgressTone::Ringback => "ringback",
+            CallProgressTone::Reorder => "reorder",
+        }
+    }
+}
+
+/// Telephone network whose tone plan is followed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ToneRegion {
+    /// North American precise tone plan
+    #[default]
+    NorthAmerica,
+    /// BT tones used in the United Kingdom
+    UnitedKingdom,
+    /// CEPT/ETSI 425 Hz tones used across most of Europe
+    Europe,
+}
+
+/// Frequencies and on/off cadence of a call progress tone
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToneSpec {
+    /// One or two frequencies in Hz played together
+    pub frequencies: Vec<f32>,
+    /// `(on, off)` durations in seconds, repeated; empty for a continuous tone
+    pub cadence: Vec<(f32, f32)>,
+}
+
+impl ToneRegion {
+    /// Frequencies and cadence of a call progress tone in this region
+    pub fn tone(&self, tone: CallProgressTone) -> ToneSpec {
+        let (frequencies, cadence): (&[f32], &[(f32, f32)]) = match (self, tone) {
+            (ToneRegion::NorthAmerica, CallProgressTone::Dial) => (&[350.0, 440.0], &[]),
+            (ToneRegion::NorthAmerica, CallProgressTone::Busy) => (&[480.0, 620.0], &[(0.5, 0.5)]),
+            (ToneRegion::NorthAmerica, CallProgressTone::Ringback) => (&[440.0, 480.0], &[(2.0, 4.0)]),
+            (ToneRegion::NorthAmerica, CallProgressTone::Reorder) => (&[480.0, 620.0], &[(0.25, 0.25)]),
+            (ToneRegion::UnitedKingdom, CallProgressTone::Dial) => (&[350.0, 450.0], &[]),
+            (ToneRegion::UnitedKingdom, CallProgressTone::Busy) => (&[400.0], &[(0.375, 0.375)]),
+            (ToneRegion::UnitedKingdom, CallProgressTone::Ringback) => (&[400.0, 450.0], &[(0.4, 0.2), (0.4, 2.0)]),
+            (ToneRegion::UnitedKingdom, CallProgressTone::Reorder) => (&[400.0], &[(0.4, 0.35), (0.225, 0.525)]),
+            (ToneRegion::Europe, CallProgressTone::Dial) => (&[425.0], &[]),
+            (ToneRegion::Europe, CallProgressTone::Busy) => (&[425.0], &[(0.5, 0.5)]),
+            (ToneRegion::Europe, CallProgressTone::Ringback) => (&[425.0], &[(1.0, 4.0)]),
+            (ToneRegion::Europe, CallProgressTone::Reorder) => (&[425.0], &[(0.25, 0.25)]),
+        };
+        ToneSpec { frequencies: frequencies.to_vec(), cadence: cadence.to_vec() }
+    }
+}
+
+/// Row and column frequencies of a DTMF key (`0`-`9`, `*`, `#`, `A`-`D`)
+pub fn dtmf_frequencies(key: char) -> Option<(f32, f32)> {
+    let key = key.to_ascii_uppercase();
+    KEYPAD.iter().enumerate().find_map(|(row, keys)| {
+        keys.iter().position(|&candidate| candidate == key).map(|column| (DTMF_ROWS[row], DTMF_COLUMNS[column]))
+    })
+}
+
+/// Render one DTMF key for a duration in seconds
+pub fn dtmf_tone(key: char, duration: f32) -> Result<Vec<f32>> {
+    let (row, column) = dtmf_frequencies(key)
+        .ok_or_else(|| JingleError::InvalidParameter(format!("`{}` is not a DTMF key", key)))?;
+    Ok(tone_burst(&[row, column], duration))
+}
+
+/// Render a string of DTMF keys with `tone` seconds per key and `gap` seconds of silence between keys
+///
+/// Spaces, dashes, dots and parentheses are ignored so formatted numbers can be
+/// dialled as written. ITU-T Q.24 asks for at least 40 ms of tone and gap; 100 ms
+/// of each is a common choice.
+pub fn dtmf_sequence(keys: &str, tone: f32, gap: f32) -> Result<Vec<f32>> {
+    let gap_samples = seconds_to_samples(gap);
+    let mut samples = Vec::new();
+    for (i, key) in keys.chars().filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')')).enumerate() {
+        if i > 0 {
+            samples.extend(std::iter::repeat_n(0.0, gap_samples));
+        }
+        samples.extend(dtmf_tone(key, tone)?);
+    }
+    Ok(samples)
+}
+
+/// Render a call progress tone for a duration in seconds, following the region's cadence
+pub fn call_progress_tone(tone: CallProgressTone, region: ToneRegion, duration: f32) -> Vec<f32> {
+    let spec = region.tone(tone);
+    let total = seconds_to_samples(duration);
+    if spec.cadence.is_empty() {
+        return tone_burst(&spec.frequencies, duration);
+    }
+
+    let mut samples = Vec::with_capacity(total);
+    'cadence: loop {
+        for &(on, off) in &spec.cadence {
+            // Bursts start at phase zero so every repetition sounds the same
+            samples.extend(tone_burst(&spec.frequencies, on));
+            samples.extend(std::iter::repeat_n(0.0, seconds_to_samples(off)));
+            if samples.len() >= total {
+                break 'cadence;
+            }
+        }
+    }
+    samples.truncate(total);
+    samples
+}
+
+/// Sum of sines at equal level, with short ramps at both ends
+fn tone_burst(frequencies: &[f32], duration: f32) -> Vec<f32> {
+    let length = seconds_to_samples(duration);
+    let ramp = seconds_to_samples(RAMP_DURATION).min(length / 2).max(1);
+    (0..length)
+        .map(|i| {
+            let time = i as f32 / SAMPLE_RATE as f32;
+            let sum: f32 = frequencies.iter().map(|&frequency| (2.0 * PI * frequency * time).sin()).sum();
+            let envelope = (i.min(length - 1 - i) as f32 / ramp as f32).min(1.0);
+            sum * COMPONENT_LEVEL * envelope
+        })
+        .collect()
+}
+
+fn seconds_to_samples(seconds: f32) -> usize {
+    (seconds.max(0.0) * SAMPLE_RATE as f32).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Signal power at one frequency (Goertzel algorithm)
+    fn power_at(samples: &[f32], frequency: f32) -> f32 {
+        let coefficient = 2.0 * (2.0 * PI * frequency / SAMPLE_RATE as f32).cos();
+        let (mut previous, mut before) = (0.0f32, 0.0f32);
+        for &sample in samples {
+            let current = sample + coefficient * previous - before;
+            before = previous;
+            previous = current;
+        }
+        (previous * previous + before * before - coefficient * previous * before) / samples.len() as f32
+    }
+
+    #[test]
+    fn test_dtmf_frequencies() {
+        assert_eq!(dtmf_frequencies('5'), Some((770.0, 1336.0)));
+        assert_eq!(dtmf_frequencies('#'), Some((941.0, 1477.0)));
+        assert_eq!(dtmf_frequencies('d'), Some((941.0, 1633.0)));
+        assert_eq!(dtmf_frequencies('x'), None);
+        assert!(dtmf_tone('x', 0.1).is_err());
+
+        let tone = dtmf_tone('8', 0.1).unwrap();
+        let expected = power_at(&tone, 852.0).min(power_at(&tone, 1336.0));
+        for other in DTMF_ROWS.iter().chain(&DTMF_COLUMNS).filter(|&&f| f != 852.0 && f != 1336.0) {
+            assert!(power_at(&tone, *other) < expected * 0.05, "{} Hz leaks into key 8", other);
+        }
+        assert!(tone.iter().all(|s| s.abs() <= 0.7 + 1e-6));
+    }
+
+    #[test]
+    fn test_dtmf_sequence() {
+        let samples = dtmf_sequence("(555) 01-2", 0.1, 0.05).unwrap();
+        let keys = 6.0;
+        let expected = keys * 0.1 + (keys - 1.0) * 0.05;
+        assert_eq!(samples.len(), (expected * SAMPLE_RATE as f32).round() as usize);
+        assert!(dtmf_sequence("12x", 0.1, 0.1).is_err());
+    }
+
+    #[test]
+    fn test_call_progress_cadence() {
+        let busy = call_progress_tone(CallProgressTone::Busy, ToneRegion::NorthAmerica, 2.0);
+        assert_eq!(busy.len(), 2 * SAMPLE_RATE as usize);
+        let second = SAMPLE_RATE as usize;
+        // On for the first half second, silent for the next
+        assert!(busy[second / 8..second * 3 / 8].iter().any(|s| s.abs() > 0.1));
+        assert!(busy[second * 5 / 8..second * 7 / 8].iter().all(|&s| s == 0.0));
+
+        let dial = call_progress_tone(CallProgressTone::Dial, ToneRegion::NorthAmerica, 0.5);
+        assert!(power_at(&dial, 350.0) > power_at(&dial, 425.0) * 20.0);
+        let dial = call_progress_tone(CallProgressTone::Dial, ToneRegion::Europe, 0.5);
+        assert!(power_at(&dial, 425.0) > power_at(&dial, 350.0) * 20.0);
+
+        for region in [ToneRegion::NorthAmerica, ToneRegion::UnitedKingdom, ToneRegion::Europe] {
+            for tone in CallProgressTone::all() {
+                assert_eq!(call_progress_tone(tone, region, 3.0).len(), 3 * SAMPLE_RATE as usize, "{} {:?}", tone.name(), region);
+            }
+        }
+    }
+}
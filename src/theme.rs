@@ -0,0 +1,223 @@
+//! Matched sets of preset sounds sharing key, tempo, timbre and effects
+
+use std::path::{Path, PathBuf};
+use crate::{
+    audio::WaveForm,
+    music::{Note, Scale},
+    presets::JinglePreset,
+    export::{JingleGenerator, FamilyTraits},
+    progress::ProgressPhase,
+    manifest::{RenderChoices, json_string, json_debug},
+    chain::EffectChain,
+    error::Result,
+};
+
+/// File name of the manifest written by `JingleGenerator::export_theme`
+pub const THEME_MANIFEST: &str = "theme.json";
+
+/// One sound of a theme with the choices made while rendering it
+#[derive(Clone, Debug)]
+pub struct ThemeSound {
+    pub preset: JinglePreset,
+    pub samples: Vec<f32>,
+    pub choices: RenderChoices,
+}
+
+/// Matched set of notification, alert, success, error, startup, shutdown, message
+/// and completion sounds generated by `JingleGenerator::generate_theme`
+#[derive(Clone, Debug)]
+pub struct SoundTheme {
+    /// Seed the theme was generated from
+    pub seed: u64,
+    /// Key shared by every sound
+    pub root_note: Note,
+    pub scale: Scale,
+    /// Timbre shared by every sound (alerts may still pick a harsher one)
+    pub waveform: WaveForm,
+    /// Effects applied to every sound
+    pub effects: EffectChain,
+    pub sounds: Vec<ThemeSound>,
+}
+
+impl SoundTheme {
+    /// Presets included in every theme, in order
+    pub fn presets() -> Vec<JinglePreset> {
+        vec![
+            JinglePreset::Notification,
+            JinglePreset::Alert,
+            JinglePreset::Success,
+            JinglePreset::Error,
+            JinglePreset::Startup,
+            JinglePreset::Shutdown,
+            JinglePreset::Message,
+            JinglePreset::Completion,
+        ]
+    }
+
+    /// Get the samples of one sound in the theme
+    pub fn get(&self, preset: JinglePreset) -> Option<&[f32]> {
+        self.sounds.iter()
+            .find(|sound| sound.preset == preset)
+            .map(|sound| sound.samples.as_slice())
+    }
+
+    /// File name of a sound when exported, e.g. `notification.wav`
+    pub fn file_name(preset: JinglePreset, extension: &str) -> String {
+        format!("{}.{}", preset.name(), extension)
+    }
+
+    /// Describe the theme and the file of each sound as a JSON object
+    fn manifest_json(&self, generator: &JingleGenerator, extension: &str) -> String {
+        let sounds: Vec<String> = self.sounds.iter().map(|sound| format!(
+            "    {{\"preset\": {}, \"file\": {}, \"pattern\": {}, \"used_waveform\": {}}}",
+            json_string(sound.preset.name()),
+            json_string(&Self::file_name(sound.preset, extension)),
+            sound.choices.pattern.map(|pattern| json_debug(&pattern)).unwrap_or_else(|| "null".to_string()),
+            sound.choices.waveform.map(|waveform| json_debug(&waveform)).unwrap_or_else(|| "null".to_string()),
+        )).collect();
+        format!(
+            "{{\n  \"seed\": {},\n  \"generation_version\": {},\n  \"root_note\": {},\n  \"scale\": {},\n  \"waveform\": {},\n  \"sample_rate\": {},\n  \"sounds\": [\n{}\n  ]\n}}\n",
+            self.seed,
+            json_string(generator.get_generation_version().name()),
+            json_debug(&self.root_note),
+            json_debug(&self.scale),
+            json_debug(&self.waveform),
+            generator.get_output_sample_rate(),
+            sounds.join(",\n"),
+        )
+    }
+}
+
+impl JingleGenerator {
+    /// Generate a matched set of preset sounds from a seed
+    ///
+    /// Key, scale, tempo, timbre and effect chain are drawn once from the seed and
+    /// shared by every sound, so the set sounds like it belongs together. Each preset
+    /// keeps its own character and default register. The generator's settings apply,
+    /// but its random sequence is left untouched.
+    ///
+    /// ```rust
+    /// use jinglebells::{JingleGenerator, JinglePreset};
+    ///
+    /// let theme = JingleGenerator::new().generate_theme(7);
+    /// assert_eq!(theme.sounds.len(), 8);
+    /// assert!(theme.get(JinglePreset::Success).is_some());
+    /// ```
+    pub fn generate_theme(&self, seed: u64) -> SoundTheme {
+        let mut parent = JingleGenerator::with_seed(seed).with_settings_from(self);
+        let root_note = Note::from_frequency(Note::C.frequency(4) * (2.0_f32).powf(parent.random_range(0..12) as f32 / 12.0));
+        let scale = match parent.random_range(0..3) {
+            0 => Scale::Major,
+            1 => Scale::Pentatonic,
+            _ => Scale::Minor,
+        };
+        let waveform = parent.random_waveform();
+        let traits = FamilyTraits {
+            variation: parent.random_float_range(0.8..=1.2),
+            pitch_offset: 0.0,
+            rhythm: parent.random_float_range(0.8..=1.25),
+            note_count_offset: parent.random_range(-1..2),
+            waveform: Some(waveform),
+        };
+        let effects = if parent.random_bool(0.6) {
+            EffectChain::new().reverb(parent.random_float_range(0.3..=0.8), 0.5, parent.random_float_range(0.15..=0.3))
+        } else {
+            EffectChain::new().echo(parent.random_float_range(80.0..=160.0), 0.25, 0.2)
+        };
+
+        let sounds = SoundTheme::presets()
+            .into_iter()
+            .enumerate()
+            .map(|(i, preset)| {
+                let mut member = JingleGenerator::with_seed(seed.wrapping_add(i as u64 + 1)).with_settings_from(self);
+                member.clear_progress();
+                member.set_family(Some(traits));
+                // The theme's key in the register the preset normally uses
+                let default_frequency = preset.default_frequency();
+                let octave = (default_frequency / root_note.frequency(4)).log2().round() as i32 + 4;
+                let spec = preset.spec(waveform, None, Some(root_note.frequency(octave)))
+                    .with_scale(scale)
+                    .with_effects(effects.clone());
+                let samples = member.render(&spec);
+                self.report_progress(ProgressPhase::Rendering, i + 1, SoundTheme::presets().len());
+                ThemeSound { preset, samples, choices: member.last_choices().clone() }
+            })
+            .collect();
+
+        SoundTheme { seed, root_note, scale, waveform, effects, sounds }
+    }
+
+    /// Generate a theme and export every sound into a directory, with a `theme.json` manifest
+    ///
+    /// Sounds are named after their preset (`SoundTheme::file_name`) and encoded by
+    /// extension like `export_to_file`. Returns the theme and the written audio paths.
+    pub fn export_theme<P: AsRef<Path>>(&self, seed: u64, dir: P, extension: &str) -> Result<(SoundTheme, Vec<PathBuf>)> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        let theme = self.generate_theme(seed);
+        // Progress is reported per file rather than per sample
+        let mut generator = JingleGenerator::with_seed(seed).with_settings_from(self);
+        generator.clear_progress();
+
+        let mut written = Vec::with_capacity(theme.sounds.len());
+        for sound in &theme.sounds {
+            self.check_cancelled()?;
+            let path = dir.join(SoundTheme::file_name(sound.preset, extension));
+            generator.export_to_file(&sound.samples, &path)?;
+            written.push(path);
+            self.report_progress(ProgressPhase::Exporting, written.len(), theme.sounds.len());
+        }
+        std::fs::write(dir.join(THEME_MANIFEST), theme.manifest_json(self, extension))?;
+        Ok((theme, written))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_is_coherent() {
+        let theme = JingleGenerator::new().generate_theme(11);
+        assert_eq!(theme.sounds.len(), SoundTheme::presets().len());
+        for sound in &theme.sounds {
+            assert!(!sound.samples.is_empty(), "{} is empty", sound.preset.name());
+            if let Some(root) = sound.choices.root_note {
+                assert_eq!(root, theme.root_note, "{} is in another key", sound.preset.name());
+            }
+            if let Some(scale) = sound.choices.scale {
+                assert_eq!(scale, theme.scale);
+            }
+        }
+        assert!(theme.get(JinglePreset::Doorbell).is_none());
+    }
+
+    #[test]
+    fn test_export_theme() {
+        let dir = std::env::temp_dir().join("jinglebells_test_theme_export");
+        let generator = JingleGenerator::new();
+        let (theme, written) = generator.export_theme(5, &dir, "wav").unwrap();
+        assert_eq!(written.len(), SoundTheme::presets().len());
+        assert_eq!(written[0], dir.join("notification.wav"));
+        assert!(written.iter().all(|path| path.exists()));
+        assert_eq!(theme.get(JinglePreset::Alert), generator.generate_theme(5).get(JinglePreset::Alert));
+
+        let manifest = std::fs::read_to_string(dir.join(THEME_MANIFEST)).unwrap();
+        assert!(manifest.contains("\"seed\": 5"));
+        assert!(manifest.contains("\"file\": \"completion.wav\""));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_theme_is_reproducible() {
+        let mut generator = JingleGenerator::with_seed(3);
+        let theme = generator.generate_theme(11);
+        generator.create_alert_jingle(WaveForm::Square, None, None);
+        let again = generator.generate_theme(11);
+        assert_eq!(again.get(JinglePreset::Startup), theme.get(JinglePreset::Startup));
+        assert_eq!(again.root_note, theme.root_note);
+
+        let other = generator.generate_theme(12);
+        assert_ne!(other.get(JinglePreset::Startup), theme.get(JinglePreset::Startup));
+    }
+}
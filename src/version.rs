@@ -0,0 +1,131 @@
+//! Versions of the generation algorithm, so a (seed, preset) pair keeps rendering the same sound
+//!
+//! Any change to preset logic that alters what a seed renders lands as a new
+//! `GenerationVersion`; the code for older versions stays, selected with
+//! `JingleGenerator::set_generation_version`. Exported manifests record the
+//! version alongside the seed, so a sound chosen today renders the same
+//! samples after upgrading. Encoding (dither, rounding, formats) is not versioned.
+
+use crate::{export::JingleGenerator, error::{JingleError, Result}};
+
+/// Revision of the preset and composition logic a generator renders with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "lowercase"))]
+pub enum GenerationVersion {
+    /// The algorithm of the 0.1 releases, which render the original presets sample for sample
+    #[default]
+    V1,
+    /// Oscillators advance a phase accumulator instead of computing the phase from the sample time.
+    /// Ringtones repeat only the cycles that fit, intensity boosts past full scale are normalized,
+    /// declicking fades arrays out before gaps, and morphs compose one melody from both presets.
+    V2,
+}
+
+impl GenerationVersion {
+    /// The newest algorithm, used by generators unless another version is selected
+    pub const LATEST: GenerationVersion = GenerationVersion::V2;
+
+    /// Every version, oldest first
+    pub fn all() -> Vec<GenerationVersion> {
+        vec![GenerationVersion::V1, GenerationVersion::V2]
+    }
+
+    /// Name recorded in manifests (e.g. "v1")
+    pub fn name(&self) -> &'static str {
+        match self {
+            GenerationVersion::V1 => "v1",
+            GenerationVersion::V2 => "v2",
+        }
+    }
+
+    /// Look up a version by the name recorded in manifests
+    pub fn from_name(name: &str) -> Result<Self> {
+        Self::all()
+            .into_iter()
+            .find(|version| version.name().eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| JingleError::InvalidParameter(format!("unknown generation version '{}', this release supports up to '{}'", name, Self::LATEST.name())))
+    }
+}
+
+impl JingleGenerator {
+    /// Pin the algorithm presets are rendered with, so stored seeds keep their sound
+    pub fn set_generation_version(&mut self, version: GenerationVersion) {
+        self.generation_version = version;
+    }
+
+    /// Get the algorithm presets are rendered with
+    pub fn get_generation_version(&self) -> GenerationVersion {
+        self.generation_version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(not(feature = "f64"))]
+    use crate::{encoder::quantize, export::Dither, presets::{JinglePreset, Severity, UiSound, GameSfx, SciFiSound}};
+
+    #[test]
+    fn test_names() {
+        for version in GenerationVersion::all() {
+            assert_eq!(GenerationVersion::from_name(version.name()).unwrap(), version);
+        }
+        assert_eq!(GenerationVersion::from_name(" V1 ").unwrap(), GenerationVersion::V1);
+        assert!(GenerationVersion::from_name("v0").is_err());
+        assert_eq!(JingleGenerator::new().get_generation_version(), GenerationVersion::LATEST);
+    }
+
+    /// FNV-1a hash of the 16-bit samples an export would contain
+    #[cfg(not(feature = "f64"))]
+    fn checksum(samples: &[f32]) -> u64 {
+        quantize(samples, 1.0, Dither::None, 0).iter().fold(0xcbf29ce484222325, |hash, sample| {
+            sample.to_le_bytes().iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+        })
+    }
+
+    // Fails when preset logic changes what V1 renders: keep V1 as it was and add a new version instead.
+    // The eight original presets match the 0.1 release sample for sample.
+    #[cfg(not(feature = "f64"))]
+    #[test]
+    fn test_v1_renders_are_stable() {
+        let expected = [
+            (JinglePreset::Notification, 6850907066506319612),
+            (JinglePreset::Success, 13011993341855871843),
+            (JinglePreset::Alert, 15224083902468694942),
+            (JinglePreset::Error, 8982640601838688743),
+            (JinglePreset::Startup, 14954170552251792379),
+            (JinglePreset::Shutdown, 4440621957127056729),
+            (JinglePreset::Message, 8519516471294330321),
+            (JinglePreset::Completion, 12857175833161573389),
+            (JinglePreset::Ringtone, 9397721979755155623),
+            (JinglePreset::Doorbell, 14734170548896846436),
+            (JinglePreset::Reminder, 2516263339566714165),
+            (JinglePreset::Holiday, 7203751896688956488),
+            (JinglePreset::Countdown, 8751823476608870807),
+            (JinglePreset::Warning(Severity::Low), 12631160677653543797),
+            (JinglePreset::Warning(Severity::Medium), 5448552009590486849),
+            (JinglePreset::Warning(Severity::High), 3358752836727383248),
+            (JinglePreset::Warning(Severity::Critical), 9044020258089384502),
+            (JinglePreset::Ui(UiSound::Click), 477107530094622966),
+            (JinglePreset::Ui(UiSound::Tap), 3684185633766180003),
+            (JinglePreset::Ui(UiSound::Toggle), 1725017128428436416),
+            (JinglePreset::Ui(UiSound::Swipe), 9535757400221262106),
+            (JinglePreset::Game(GameSfx::Coin), 3616652903259946756),
+            (JinglePreset::Game(GameSfx::PowerUp), 17942365932447119632),
+            (JinglePreset::Game(GameSfx::Jump), 12105918833597424943),
+            (JinglePreset::Game(GameSfx::Laser), 17750894873363201900),
+            (JinglePreset::Game(GameSfx::Explosion), 15935894202841176512),
+            (JinglePreset::SciFi(SciFiSound::Sweep), 16335131835828969631),
+            (JinglePreset::SciFi(SciFiSound::Zap), 10357986368411208340),
+            (JinglePreset::SciFi(SciFiSound::Scan), 2290110745406719637),
+            (JinglePreset::SciFi(SciFiSound::Shimmer), 4703105462000388715),
+        ];
+        assert_eq!(expected.len(), JinglePreset::all().len());
+        for (preset, expected) in expected {
+            let mut generator = JingleGenerator::with_seed(7);
+            generator.set_generation_version(GenerationVersion::V1);
+            let samples = preset.generate(&mut generator, preset.style().waveform);
+            assert_eq!(checksum(&samples), expected, "{} changed under v1", preset.name());
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Polyphonic voice allocation for overlapping notes
+//!
+//! A `VoiceAllocator` plays any number of notes at once, up to a voice limit.
+//! Every voice has its own gated envelope: it attacks and decays on
+//! `note_on`, holds the sustain level while the note is down and releases
+//! from wherever it is on `note_off`, so a release tail keeps ringing under
+//! the notes that follow instead of being cut off by them.
+//!
+//! ```rust
+//! use jinglebells::{VoiceAllocator, WaveForm};
+//!
+//! let mut voices = VoiceAllocator::new(4);
+//! let root = voices.note_on(261.63, WaveForm::Triangle);
+//! let fifth = voices.note_on(392.0, WaveForm::Triangle);
+//! let mut block = vec![0.0; 4410];
+//! voices.fill(&mut block);
+//! voices.note_off(root);
+//! voices.note_off(fifth);
+//! assert_eq!(voices.active_voices(), 2);
+//! ```
+
+use core::f32::consts::PI;
+use crate::{
+    SAMPLE_RATE,
+    audio::{ADSR, OSCILLATOR_HEADROOM, WaveForm},
+    cancel::CANCEL_CHECK_INTERVAL,
+    chiptune,
+    export::JingleGenerator,
+    math::db_to_linear,
+    error::Result,
+};
+
+/// Voice limit of allocators created by `JingleGenerator` and the sequencer
+pub const DEFAULT_MAX_VOICES: usize = 16;
+
+/// Handle for releasing a note started with `VoiceAllocator::note_on`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VoiceId(u64);
+
+/// One sounding note
+#[derive(Clone, Debug)]
+struct Voice {
+    id: VoiceId,
+    frequency: f32,
+    waveform: WaveForm,
+    gain: f32,
+    phase: f32,
+    /// Seconds since the note started
+    time: f32,
+    /// Seconds since release and the level the release started from
+    release: Option<(f32, f32)>,
+}
+
+impl Voice {
+    /// Level of the held part of the envelope
+    fn held_level(&self, adsr: &ADSR) -> f32 {
+        if self.time < adsr.attack {
+            self.time / adsr.attack
+        } else if self.time < adsr.attack + adsr.decay {
+            1.0 - (self.time - adsr.attack) / adsr.decay * (1.0 - adsr.sustain)
+        } else {
+            adsr.sustain
+        }
+    }
+
+    /// Envelope level now, or `None` once the release has finished
+    fn level(&self, adsr: &ADSR) -> Option<f32> {
+        match self.release {
+            None => Some(self.held_level(adsr)),
+            Some((elapsed, from)) if elapsed < adsr.release => Some(from * (1.0 - elapsed / adsr.release)),
+            Some(_) => None,
+        }
+    }
+
+    fn wave(&self) -> f32 {
+        match self.waveform {
+            WaveForm::Sine => (self.phase * 2.0 * PI).sin(),
+            WaveForm::Triangle => if self.phase < 0.5 { 4.0 * self.phase - 1.0 } else { 3.0 - 4.0 * self.phase },
+            WaveForm::Sawtooth => 2.0 * self.phase - 1.0,
+            WaveForm::Square => if self.phase < 0.5 { 1.0 } else { -1.0 },
+        }
+    }
+}
+
+/// Plays overlapping notes, each with its own envelope, up to a voice limit
+///
+/// When every voice is busy, `note_on` takes over the oldest released voice,
+/// or the oldest held one if none is releasing.
+#[derive(Clone, Debug)]
+pub struct VoiceAllocator {
+    voices: Vec<Voice>,
+    max_voices: usize,
+    adsr: ADSR,
+    sample_rate: u32,
+    gain: f32,
+    next_id: u64,
+}
+
+impl VoiceAllocator {
+    /// Create an allocator playing at most `max_voices` notes at once (at least one)
+    pub fn new(max_voices: usize) -> Self {
+        Self {
+            voices: Vec::new(),
+            max_voices: max_voices.max(1),
+            adsr: ADSR::default(),
+            sample_rate: SAMPLE_RATE,
+            gain: OSCILLATOR_HEADROOM,
+            next_id: 0,
+        }
+    }
+
+    /// Set the envelope of every voice; `release` runs after `note_off`
+    pub fn with_adsr(mut self, adsr: ADSR) -> Self {
+        self.adsr = adsr;
+        self
+    }
+
+    /// Set the rate (Hz) samples are generated at (defaults to `SAMPLE_RATE`)
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate.max(1);
+        self
+    }
+
+    /// Set the level of each voice in dB (defaults to the linear `OSCILLATOR_HEADROOM`)
+    pub fn with_gain_db(mut self, gain_db: f32) -> Self {
+        self.gain = db_to_linear(gain_db);
+        self
+    }
+
+    /// Start a note at full voice level
+    pub fn note_on(&mut self, frequency: f32, waveform: WaveForm) -> VoiceId {
+        self.note_on_with_gain(frequency, waveform, 0.0)
+    }
+
+    /// Start a note with its own level in dB, relative to the voice level
+    pub fn note_on_with_gain(&mut self, frequency: f32, waveform: WaveForm, gain_db: f32) -> VoiceId {
+        if self.voices.len() >= self.max_voices {
+            // Voices are kept oldest first
+            let stolen = self.voices.iter().position(|voice| voice.release.is_some()).unwrap_or(0);
+            self.voices.remove(stolen);
+        }
+        let id = VoiceId(self.next_id);
+        self.next_id += 1;
+        self.voices.push(Voice {
+            id,
+            frequency,
+            waveform,
+            gain: db_to_linear(gain_db),
+            phase: 0.0,
+            time: 0.0,
+            release: None,
+        });
+        id
+    }
+
+    /// Release a note; it fades out over the envelope's release time
+    ///
+    /// Does nothing if the note already ended or its voice was stolen.
+    pub fn note_off(&mut self, id: VoiceId) {
+        let adsr = self.adsr;
+        if let Some(voice) = self.voices.iter_mut().find(|voice| voice.id == id && voice.release.is_none()) {
+            voice.release = Some((0.0, voice.held_level(&adsr)));
+        }
+    }
+
+    /// Release every held note
+    pub fn release_all(&mut self) {
+        let ids: Vec<VoiceId> = self.voices.iter().map(|voice| voice.id).collect();
+        ids.into_iter().for_each(|id| self.note_off(id));
+    }
+
+    /// Number of notes held or still releasing
+    pub fn active_voices(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Check whether every note has finished releasing
+    pub fn is_idle(&self) -> bool {
+        self.voices.is_empty()
+    }
+
+    /// Get the voice limit
+    pub fn max_voices(&self) -> usize {
+        self.max_voices
+    }
+
+    /// Mix every voice into the next sample
+    pub fn next_sample(&mut self) -> f32 {
+        let step = 1.0 / self.sample_rate as f32;
+        let adsr = self.adsr;
+        let gain = self.gain;
+        let mut mixed = 0.0;
+        self.voices.retain_mut(|voice| {
+            let Some(level) = voice.level(&adsr) else { return false };
+            mixed += voice.wave() * level * voice.gain * gain;
+            voice.phase = (voice.phase + voice.frequency * step).fract();
+            voice.time += step;
+            if let Some((elapsed, _)) = &mut voice.release {
+                *elapsed += step;
+            }
+            true
+        });
+        mixed
+    }
+
+    /// Fill a buffer with the mix of every voice
+    pub fn fill(&mut self, buffer: &mut [f32]) {
+        buffer.iter_mut().for_each(|sample| *sample = self.next_sample());
+    }
+}
+
+/// A note for `JingleGenerator::render_polyphonic`, timed in seconds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VoiceNote {
+    pub start: f32,
+    pub frequency: f32,
+    /// Seconds the note is held; its release rings on after this
+    pub duration: f32,
+    pub waveform: WaveForm,
+    pub gain_db: f32,
+}
+
+impl VoiceNote {
+    /// Create a note at full voice level
+    pub fn new(start: f32, frequency: f32, duration: f32, waveform: WaveForm) -> Self {
+        Self { start, frequency, duration, waveform, gain_db: 0.0 }
+    }
+
+    /// Set the note level in dB
+    pub fn with_gain_db(mut self, gain_db: f32) -> Self {
+        self.gain_db = gain_db;
+        self
+    }
+}
+
+impl JingleGenerator {
+    /// Create a voice allocator with the generator's envelope and sample rate
+    pub fn voice_allocator(&self, max_voices: usize) -> VoiceAllocator {
+        VoiceAllocator::new(max_voices)
+            .with_adsr(self.get_adsr())
+            .with_sample_rate(self.get_sample_rate())
+    }
+
+    /// Render notes that may overlap, each with its own envelope, until the last release ends
+    ///
+    /// Notes are tuned, chiptune-mapped and scaled by the master gain like `generate_tone`.
+    /// Fails with `JingleError::Cancelled` if the generator's cancel token is cancelled.
+    pub fn render_polyphonic(&self, notes: &[VoiceNote], max_voices: usize) -> Result<Vec<f32>> {
+        let mut out = Vec::new();
+        self.polyphonic_blocks(notes, max_voices, |block| {
+            out.extend_from_slice(block);
+            Ok(())
+        })?;
+        Ok(out)
+    }
+
+    /// Render overlapping notes in blocks of `CANCEL_CHECK_INTERVAL` samples, handing each to `emit`
+    pub(crate) fn polyphonic_blocks<F: FnMut(&[f32]) -> Result<()>>(&self, notes: &[VoiceNote], max_voices: usize, mut emit: F) -> Result<()> {
+        let sample_rate = self.get_sample_rate() as f32;
+        let chip = self.get_chiptune();
+        let to_sample = |seconds: f32| (seconds.max(0.0) * sample_rate).round() as usize;
+
+        // (sample, is note-on, note index), note-offs first when they coincide with note-ons
+        let mut events: Vec<(usize, bool, usize)> = notes.iter().enumerate()
+            .flat_map(|(i, note)| [(to_sample(note.start), true, i), (to_sample(note.start + note.duration.max(0.0)), false, i)])
+            .collect();
+        events.sort_by_key(|&(at, on, i)| (at, on, i));
+
+        let mut voices = self.voice_allocator(max_voices);
+        let mut ids = vec![None; notes.len()];
+        let mut block = Vec::with_capacity(CANCEL_CHECK_INTERVAL);
+        let mut position = 0;
+        let mut pending = events.into_iter().peekable();
+        while pending.peek().is_some() || !voices.is_idle() {
+            if block.is_empty() {
+                self.check_cancelled()?;
+            }
+            while let Some(&(_, on, i)) = pending.peek().filter(|&&(at, _, _)| at <= position) {
+                pending.next();
+                let note = &notes[i];
+                if on {
+                    let waveform = if chip { chiptune::chip_waveform(note.waveform) } else { note.waveform };
+                    ids[i] = Some(voices.note_on_with_gain(self.tuned(note.frequency), waveform, note.gain_db));
+                } else if let Some(id) = ids[i] {
+                    voices.note_off(id);
+                }
+            }
+            block.push(voices.next_sample());
+            position += 1;
+            if block.len() == CANCEL_CHECK_INTERVAL {
+                self.emit_polyphonic_block(&mut block, &mut emit)?;
+            }
+        }
+        if !block.is_empty() {
+            self.emit_polyphonic_block(&mut block, &mut emit)?;
+        }
+        Ok(())
+    }
+
+    /// Finish a block with the chiptune DAC and master gain, hand it on and clear it
+    fn emit_polyphonic_block<F: FnMut(&[f32]) -> Result<()>>(&self, block: &mut Vec<f32>, emit: &mut F) -> Result<()> {
+        if self.get_chiptune() {
+            chiptune::quantize(block);
+        }
+        self.apply_master(block);
+        emit(block)?;
+        block.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn test_gated_envelope() {
+        let adsr = ADSR { attack: 0.01, decay: 0.01, sustain: 0.5, release: 0.1 };
+        let mut voices = VoiceAllocator::new(4).with_adsr(adsr).with_gain_db(0.0);
+        let id = voices.note_on(441.0, WaveForm::Square);
+        let mut held = vec![0.0; 4410];
+        voices.fill(&mut held);
+        assert!((held[4000].abs() - 0.5).abs() < 1e-3);
+
+        voices.note_off(id);
+        let mut tail = vec![0.0; 4500];
+        voices.fill(&mut tail);
+        // The release starts from the sustain level and ends within its time
+        assert!(tail[0].abs() <= 0.5 && tail[0].abs() > 0.45);
+        assert_eq!(peak(&tail[4420..]), 0.0);
+        assert!(voices.is_idle());
+    }
+
+    #[test]
+    fn test_voice_stealing() {
+        let mut voices = VoiceAllocator::new(2);
+        let first = voices.note_on(220.0, WaveForm::Sine);
+        let second = voices.note_on(330.0, WaveForm::Sine);
+        voices.note_off(second);
+        voices.note_on(440.0, WaveForm::Sine);
+        assert_eq!(voices.active_voices(), 2);
+        // The released voice was taken rather than the held one
+        voices.note_off(first);
+        assert!(voices.voices.iter().any(|voice| voice.id == first && voice.release.is_some()));
+        voices.note_on(550.0, WaveForm::Sine);
+        voices.note_on(660.0, WaveForm::Sine);
+        assert!(voices.voices.iter().all(|voice| voice.id != first));
+    }
+
+    #[test]
+    fn test_render_polyphonic() {
+        let generator = JingleGenerator::with_seed(1);
+        let rate = generator.get_sample_rate() as f32;
+        let release = generator.get_adsr().release;
+
+        let single = generator.render_polyphonic(&[VoiceNote::new(0.0, 440.0, 0.3, WaveForm::Sine)], DEFAULT_MAX_VOICES).unwrap();
+        assert!((single.len() as f32 - (0.3 + release) * rate).abs() <= 2.0);
+
+        // A block chord sums its voices
+        let chord: Vec<VoiceNote> = [261.63, 329.63, 392.0].iter().map(|&f| VoiceNote::new(0.0, f, 0.3, WaveForm::Sine)).collect();
+        let chord = generator.render_polyphonic(&chord, DEFAULT_MAX_VOICES).unwrap();
+        assert!(peak(&chord) > peak(&single) * 1.5);
+
+        // A note's release rings under the next one
+        let overlapping = generator.render_polyphonic(&[
+            VoiceNote::new(0.0, 440.0, 0.2, WaveForm::Sine),
+            VoiceNote::new(0.2, 660.0, 0.2, WaveForm::Sine).with_gain_db(-6.0),
+        ], 1).unwrap();
+        let limited = generator.render_polyphonic(&[
+            VoiceNote::new(0.0, 440.0, 0.2, WaveForm::Sine),
+            VoiceNote::new(0.2, 660.0, 0.2, WaveForm::Sine).with_gain_db(-6.0),
+        ], 2).unwrap();
+        let at = (0.21 * rate) as usize;
+        assert!(peak(&limited[at..at + 100]) > peak(&overlapping[at..at + 100]));
+    }
+}
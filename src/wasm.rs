@@ -0,0 +1,58 @@
+//! JavaScript bindings for previewing and downloading jingles in the browser
+//!
+//! Build with `cargo rustc --lib --release --target wasm32-unknown-unknown
+//! --no-default-features --features wasm --crate-type cdylib` and run
+//! `wasm-bindgen` on the output. Samples come back as a `Float32Array`
+//! at `sample_rate()`, ready for an `AudioBuffer`.
+
+use wasm_bindgen::prelude::*;
+use crate::{SAMPLE_RATE, export::JingleGenerator, presets::JinglePreset};
+
+/// Look up a preset by name, or fail with a JavaScript error listing the valid names
+fn preset(name: &str) -> Result<JinglePreset, JsError> {
+    JinglePreset::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = JinglePreset::all().iter().map(JinglePreset::name).collect();
+        JsError::new(&format!("Unknown preset '{}', expected one of: {}", name, names.join(", ")))
+    })
+}
+
+/// Render a preset with its own waveform; the same name and seed always give the same samples
+#[wasm_bindgen]
+pub fn generate_preset(name: &str, seed: u64) -> Result<Vec<f32>, JsError> {
+    let preset = preset(name)?;
+    Ok(preset.generate(&mut JingleGenerator::with_seed(seed), preset.style().waveform))
+}
+
+/// Render a preset as the bytes of a 16-bit WAV file, for downloading what was previewed
+#[wasm_bindgen]
+pub fn preset_wav(name: &str, seed: u64) -> Result<Vec<u8>, JsError> {
+    JingleGenerator::with_seed(seed).encode_wav(&generate_preset(name, seed)?).map_err(|error| JsError::new(&error.to_string()))
+}
+
+/// Names of the built-in presets
+#[wasm_bindgen]
+pub fn preset_names() -> Vec<String> {
+    JinglePreset::all().iter().map(|preset| preset.name().to_string()).collect()
+}
+
+/// Rate (Hz) of the samples returned by `generate_preset`
+#[wasm_bindgen]
+pub fn sample_rate() -> u32 {
+    SAMPLE_RATE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_preset() {
+        for name in preset_names() {
+            let samples = generate_preset(&name, 7).unwrap();
+            assert!(!samples.is_empty(), "{} failed", name);
+            assert_eq!(samples, generate_preset(&name, 7).unwrap());
+        }
+        let wav = preset_wav("notification", 7).unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+    }
+}